@@ -0,0 +1,183 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+};
+
+use glam::Vec3;
+
+/// Neighbours and centers of every tile of a sphere, for walking across it.
+/// Shared by [crate::HexSphere] and the particle sphere so neither needs its own flood fills.
+#[derive(Clone)]
+pub struct TileGraph {
+    /// Indices to the tiles sharing a vertex with each tile, not including the tile itself
+    neighbors: Vec<Vec<usize>>,
+    /// Unit sphere normal of each tile center
+    normals: Vec<Vec3>,
+}
+
+/// Entry of the [TileGraph::shortest_path] open set, reversed so the [BinaryHeap] pops the lowest estimate first
+struct Candidate {
+    estimate: f32,
+    tile: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl TileGraph {
+    /// `neighbors` may include the tile itself, like [crate::Tile::adjacent], it is left out of the graph
+    pub fn new(mut neighbors: Vec<Vec<usize>>, normals: Vec<Vec3>) -> Self {
+        for (tile, tile_neighbors) in neighbors.iter_mut().enumerate() {
+            tile_neighbors.retain(|&neighbor| neighbor != tile);
+        }
+        TileGraph {
+            neighbors,
+            normals: normals.into_iter().map(Vec3::normalize_or_zero).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neighbors.is_empty()
+    }
+
+    pub fn neighbors(&self, tile: usize) -> &[usize] {
+        &self.neighbors[tile]
+    }
+
+    pub fn normal(&self, tile: usize) -> Vec3 {
+        self.normals[tile]
+    }
+
+    /// Great circle distance between two tile centers in radians, on the unit sphere
+    pub fn geodesic_distance(&self, a: usize, b: usize) -> f32 {
+        self.normals[a].angle_between(self.normals[b])
+    }
+
+    /// The tile with its center closest to `normal`, found by walking towards it from the first tile.
+    /// Tiles cover the sphere without gaps, so there is a closer neighbour until the closest tile is reached.
+    /// Panics on an empty graph.
+    pub fn nearest_tile(&self, normal: Vec3) -> usize {
+        let normal = normal.normalize_or_zero();
+        let mut current = 0;
+        let mut current_dot = self.normals[current].dot(normal);
+        loop {
+            let Some((next, next_dot)) = self.neighbors[current]
+                .iter()
+                .map(|&neighbor| (neighbor, self.normals[neighbor].dot(normal)))
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            else {
+                return current;
+            };
+            if next_dot <= current_dot {
+                return current;
+            }
+            current = next;
+            current_dot = next_dot;
+        }
+    }
+
+    /// Every tile reachable from the tile under `normal` through tiles whose centers are within `radius` radians of it.
+    /// Walking through neighbours keeps the region connected, so it matches the geodesic disc on the unit sphere.
+    /// The tile under `normal` is always included, even when the radius is smaller than the tile.
+    pub fn tiles_within_geodesic(&self, normal: Vec3, radius: f32) -> Vec<usize> {
+        let normal = normal.normalize_or_zero();
+        let start = self.nearest_tile(normal);
+        let mut visited = vec![false; self.len()];
+        visited[start] = true;
+        let mut tiles = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(tile) = queue.pop_front() {
+            for &neighbor in &self.neighbors[tile] {
+                if !visited[neighbor] && self.normals[neighbor].angle_between(normal) <= radius {
+                    visited[neighbor] = true;
+                    tiles.push(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        tiles
+    }
+
+    /// Tiles exactly `k` neighbour steps away from `tile`, `k = 0` is the tile itself
+    pub fn bfs_ring(&self, tile: usize, k: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.len()];
+        visited[tile] = true;
+        let mut ring = vec![tile];
+        for _ in 0..k {
+            let mut next_ring = Vec::new();
+            for &ring_tile in &ring {
+                for &neighbor in &self.neighbors[ring_tile] {
+                    if !visited[neighbor] {
+                        visited[neighbor] = true;
+                        next_ring.push(neighbor);
+                    }
+                }
+            }
+            if next_ring.is_empty() {
+                break;
+            }
+            ring = next_ring;
+        }
+        ring
+    }
+
+    /// Tiles from `a` to `b`, both included, along the shortest chain of tile centers by great circle distance.
+    /// A* with the great circle distance to `b` as the heuristic, `None` if `b` can not be reached.
+    pub fn shortest_path(&self, a: usize, b: usize) -> Option<Vec<usize>> {
+        let mut cost = vec![f32::INFINITY; self.len()];
+        let mut came_from = vec![usize::MAX; self.len()];
+        let mut closed = vec![false; self.len()];
+        cost[a] = 0.;
+        let mut open = BinaryHeap::from([Candidate {
+            estimate: self.geodesic_distance(a, b),
+            tile: a,
+        }]);
+        while let Some(Candidate { tile, .. }) = open.pop() {
+            if tile == b {
+                let mut path = vec![b];
+                while let Some(&last) = path.last().filter(|&&last| last != a) {
+                    path.push(came_from[last]);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if closed[tile] {
+                continue;
+            }
+            closed[tile] = true;
+            for &neighbor in &self.neighbors[tile] {
+                let neighbor_cost = cost[tile] + self.geodesic_distance(tile, neighbor);
+                if neighbor_cost < cost[neighbor] {
+                    cost[neighbor] = neighbor_cost;
+                    came_from[neighbor] = tile;
+                    open.push(Candidate {
+                        estimate: neighbor_cost + self.geodesic_distance(neighbor, b),
+                        tile: neighbor,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
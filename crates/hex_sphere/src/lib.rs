@@ -0,0 +1,199 @@
+pub mod graph;
+
+use std::num::NonZero;
+
+use glam::Vec3;
+use subsphere::{Face, Sphere, Vertex, proj::Fuller};
+
+pub use graph::TileGraph;
+
+/// A helper for the modified faces with a central vertex
+#[derive(Clone)]
+pub struct Tile {
+    /// Index to [subsphere::hex::Face<Fuller>] (same index in wrapper and subsphere)
+    pub index: usize,
+    /// Index to the central vertex in HexSphere.vertices
+    pub center: usize,
+    /// Indices to corner vertices in HexSphere.vertices
+    pub vertices: Vec<usize>,
+    /// Height of the tile center
+    pub height: f32,
+    /// Indices to adjacent tiles, including the tile itself
+    pub adjacent: Vec<usize>,
+    /// Tile face normal
+    pub normal: Vec3,
+}
+
+/// Tiles and mesh buffers of a subdivided icosahedron, every tile is a fan of triangles around its own center vertex
+pub struct HexSphere {
+    /// The [subsphere::HexSphere<Fuller>] [HexSphere] wraps around
+    pub subsphere: subsphere::HexSphere<Fuller>,
+    /// Mesh vertices
+    pub vertices: Vec<[f32; 3]>,
+    /// Mesh triangle list, indices into [HexSphere::vertices]
+    pub triangles: Vec<u32>,
+    /// Essentially a wrapper around [subsphere::hex::Face<Fuller>], modified with a central vertex and height
+    pub tiles: Vec<Tile>,
+    /// For each vertex, the indices of the tiles it is adjacent to
+    pub vertices_to_tiles: Vec<Vec<usize>>,
+    /// Tile neighbours and centers for neighbourhood and path queries
+    pub graph: TileGraph,
+}
+
+/// The subsphere every hex sphere of `subdivisions` is built on
+pub fn subsphere(subdivisions: u32) -> subsphere::HexSphere<Fuller> {
+    let c = subdivisions % 3;
+    subsphere::HexSphere::from_kis(subsphere::TriSphere::new(
+        subsphere::BaseTriSphere::Icosa,
+        subsphere::proj::Fuller,
+        NonZero::new(subdivisions).unwrap(),
+        c,
+    ))
+    .unwrap()
+}
+
+/// Sorted indices of the faces sharing a vertex with `face`, including `face` itself
+pub fn adjacent_faces(face: &subsphere::hex::Face<Fuller>) -> Vec<usize> {
+    let mut adjacent = face
+        .vertices()
+        // Need explicit collect or we run into a infinite type recursion for some reason
+        .flat_map(|v| v.faces().map(|f| f.index()).collect::<Vec<usize>>())
+        .collect::<Vec<usize>>();
+    adjacent.sort_unstable();
+    adjacent.dedup();
+    adjacent
+}
+
+/// Returns [Tile] from unit sphere normal
+pub fn tile_at<'a>(
+    subsphere: &subsphere::HexSphere<Fuller>,
+    tiles: &'a [Tile],
+    at: Vec3,
+) -> &'a Tile {
+    let at: [f32; 3] = at.into();
+    &tiles[subsphere.face_at(at.map(|val| val as f64)).index()]
+}
+
+/// Returns the indices of all tiles connected to `start` through adjacent tiles matching `predicate`.
+/// `visited` is shared between calls so repeated fills over the same sphere never revisit a tile.
+pub fn flood_fill<P>(tiles: &[Tile], start: usize, visited: &mut [bool], predicate: P) -> Vec<usize>
+where
+    P: Fn(&Tile) -> bool,
+{
+    let mut filled = Vec::new();
+    if visited[start] || !predicate(&tiles[start]) {
+        return filled;
+    }
+    visited[start] = true;
+    let mut stack = vec![start];
+    while let Some(tile_index) = stack.pop() {
+        filled.push(tile_index);
+        for &adjacent in &tiles[tile_index].adjacent {
+            if !visited[adjacent] && predicate(&tiles[adjacent]) {
+                visited[adjacent] = true;
+                stack.push(adjacent);
+            }
+        }
+    }
+    filled
+}
+
+impl HexSphere {
+    /// Builds the tiles and mesh buffers with each tile center at `tile_heights`.
+    /// Without heights tiles sit at the length of their subsphere face center, on the unit sphere.
+    pub fn new(subdivisions: u32, tile_heights: Option<&[f32]>) -> Self {
+        let subsphere = subsphere(subdivisions);
+        let num_pentagons = 12;
+        let num_hexagons = subsphere.num_faces() - num_pentagons;
+        let num_vertices = num_pentagons * 6 + num_hexagons * 7;
+        let num_faces = subsphere.num_faces();
+
+        let tile_heights: Vec<f32> = match tile_heights {
+            Some(tile_heights) => tile_heights.to_vec(),
+            None => subsphere
+                .faces()
+                .map(|face| Vec3::from(face.center().pos().map(|f| f as f32)).length())
+                .collect(),
+        };
+
+        let mut vertices: Vec<[f32; 3]> = Vec::with_capacity(num_vertices);
+        let mut vertices_to_tiles: Vec<Vec<usize>> = vec![Vec::new(); num_vertices];
+        let mut tiles: Vec<Tile> = Vec::with_capacity(num_faces);
+        let mut triangles: Vec<u32> = Vec::with_capacity(num_hexagons * 6 + num_pentagons + 5);
+
+        for (i, face) in subsphere.faces().enumerate() {
+            // Build triangles, we want each face to be triangular slices around the center point
+            let face_normal = face.center().pos().map(|f| f as f32);
+            let face_center = face_normal.map(|f| f * tile_heights[i]);
+            let face_vertex_count = if face.is_hex() { 7 } else { 6 };
+
+            // For each face vertex excluding the center, interpolate between adjacent tile centers
+            vertices.extend(face.vertices().map(|v| {
+                let interpolated_pos: [f32; 3] = v
+                    .faces()
+                    .map(|face| {
+                        face.center()
+                            .pos()
+                            .map(|val| val as f32 * tile_heights[face.index()] / 3.)
+                    })
+                    .reduce(|acc, e| [acc[0] + e[0], acc[1] + e[1], acc[2] + e[2]])
+                    .unwrap();
+                interpolated_pos
+            }));
+            vertices.push(face_center);
+            let face_center_index: usize = vertices.len() - 1;
+
+            let face_vertex_indices: Vec<usize> =
+                (face_center_index + 1 - face_vertex_count..=face_center_index).collect();
+
+            let mut face_triangles: Vec<u32> = face_vertex_indices[..face_vertex_indices.len() - 1]
+                .iter()
+                .flat_map(move |i| vec![*i as u32, face_center_index as u32, *i as u32])
+                .collect();
+            face_triangles.rotate_right(1);
+            triangles.extend(face_triangles);
+
+            vertices_to_tiles[face_center_index] = vec![];
+            for (i, vertex) in face.vertices().enumerate() {
+                vertices_to_tiles[face_vertex_indices[i]] =
+                    vertex.faces().map(|f| f.index()).collect::<Vec<usize>>();
+            }
+
+            tiles.push(Tile {
+                index: i,
+                center: face_center_index,
+                vertices: face_vertex_indices[..face_vertex_indices.len() - 1].into(),
+                height: tile_heights[i],
+                adjacent: adjacent_faces(&face),
+                normal: face_normal.into(),
+            });
+        }
+
+        let graph = TileGraph::new(
+            tiles.iter().map(|tile| tile.adjacent.clone()).collect(),
+            tiles.iter().map(|tile| tile.normal).collect(),
+        );
+
+        HexSphere {
+            subsphere,
+            vertices,
+            triangles,
+            tiles,
+            vertices_to_tiles,
+            graph,
+        }
+    }
+
+    /// Returns [Tile] from unit sphere normal
+    pub fn tile_at(&self, at: Vec3) -> &Tile {
+        tile_at(&self.subsphere, &self.tiles, at)
+    }
+
+    /// See [flood_fill]
+    pub fn flood_fill<P>(&self, start: usize, visited: &mut [bool], predicate: P) -> Vec<usize>
+    where
+        P: Fn(&Tile) -> bool,
+    {
+        flood_fill(&self.tiles, start, visited, predicate)
+    }
+}
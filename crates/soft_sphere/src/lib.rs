@@ -1,10 +1,13 @@
 pub mod collision;
 pub mod frame;
 pub mod point_mass;
+pub mod precision;
 pub mod shape;
+pub mod solver;
 pub mod spring;
 
 pub use frame::Frame;
 pub use point_mass::PointMass;
-pub use shape::Shape;
+pub use shape::{PointMassId, RemovedPointMass, Shape, ShapeBuilder, ShapeError};
+pub use solver::Solver;
 pub use spring::Spring;
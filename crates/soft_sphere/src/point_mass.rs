@@ -1,6 +1,8 @@
 use glam::Vec3;
 
-#[derive(PartialEq)]
+use crate::precision::angle_between;
+
+#[derive(PartialEq, Clone)]
 pub struct PointMass {
     pub position: Vec3,
     pub velocity: Vec3,
@@ -20,6 +22,6 @@ impl PointMass {
         }
     }
     pub fn geodesic_distance(&self, other: &Self) -> f32 {
-        f32::acos(self.position.dot(other.position).clamp(-1., 1.))
+        angle_between(self.position, other.position)
     }
 }
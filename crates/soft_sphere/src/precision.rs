@@ -0,0 +1,82 @@
+#[cfg(not(feature = "f64"))]
+use glam::Quat;
+use glam::Vec3;
+#[cfg(feature = "f64")]
+use glam::{DQuat, DVec3};
+
+/// Scalar the precision sensitive math is done in, `f64` with the `f64` feature.
+/// Point masses are stored in `f32` either way, so only the math between loading and storing them gains precision.
+#[cfg(not(feature = "f64"))]
+pub type Real = f32;
+#[cfg(feature = "f64")]
+pub type Real = f64;
+
+/// Vector counterpart of [Real]
+#[cfg(not(feature = "f64"))]
+pub type RealVec3 = Vec3;
+#[cfg(feature = "f64")]
+pub type RealVec3 = DVec3;
+
+/// Rotation counterpart of [Real]
+#[cfg(not(feature = "f64"))]
+pub type RealQuat = Quat;
+#[cfg(feature = "f64")]
+pub type RealQuat = DQuat;
+
+#[cfg(not(feature = "f64"))]
+#[inline]
+pub fn widen_scalar(value: f32) -> Real {
+    value
+}
+#[cfg(feature = "f64")]
+#[inline]
+pub fn widen_scalar(value: f32) -> Real {
+    f64::from(value)
+}
+
+#[cfg(not(feature = "f64"))]
+#[inline]
+pub fn widen(vector: Vec3) -> RealVec3 {
+    vector
+}
+#[cfg(feature = "f64")]
+#[inline]
+pub fn widen(vector: Vec3) -> RealVec3 {
+    vector.as_dvec3()
+}
+
+#[cfg(not(feature = "f64"))]
+#[inline]
+pub fn narrow(vector: RealVec3) -> Vec3 {
+    vector
+}
+#[cfg(feature = "f64")]
+#[inline]
+pub fn narrow(vector: RealVec3) -> Vec3 {
+    vector.as_vec3()
+}
+
+#[cfg(not(feature = "f64"))]
+#[inline]
+pub fn narrow_scalar(value: Real) -> f32 {
+    value
+}
+#[cfg(feature = "f64")]
+#[inline]
+pub fn narrow_scalar(value: Real) -> f32 {
+    value as f32
+}
+
+/// Angle in radians between two vectors, from the arctangent of the cross and dot products.
+/// Unlike the arccosine of the dot product this never leaves its domain, and it keeps full relative precision near 0 and pi,
+/// where the arccosine is flat and rounds every angle below about 3.5e-4 radians in `f32`.
+#[inline]
+pub fn real_angle_between(a: RealVec3, b: RealVec3) -> Real {
+    a.cross(b).length().atan2(a.dot(b))
+}
+
+/// [real_angle_between] for stored `f32` vectors
+#[inline]
+pub fn angle_between(a: Vec3, b: Vec3) -> f32 {
+    narrow_scalar(real_angle_between(widen(a), widen(b)))
+}
@@ -1,19 +1,156 @@
-use glam::{Quat, Vec3};
+use glam::Vec3;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt, sync::OnceLock};
 
-use crate::{point_mass::PointMass, spring::Spring};
+use crate::{
+    point_mass::PointMass,
+    precision::{RealQuat, RealVec3, angle_between, narrow, widen},
+    solver::Solver,
+    spring::Spring,
+};
 
+#[derive(Debug, PartialEq)]
+pub enum ShapeError {
+    /// A spring anchor is not the index of a point mass in the shape
+    AnchorOutOfBounds {
+        anchor: usize,
+        point_mass_count: usize,
+    },
+    /// Both anchors of a spring are the same point mass
+    SameAnchors(usize),
+    /// A spring already connects the two point masses, in either direction
+    DuplicateSpring { anchor_a: usize, anchor_b: usize },
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::AnchorOutOfBounds {
+                anchor,
+                point_mass_count,
+            } => write!(
+                f,
+                "Spring anchor {anchor} is out of bounds for {point_mass_count} point masses"
+            ),
+            ShapeError::SameAnchors(anchor) => {
+                write!(f, "Spring has point mass {anchor} as both anchors")
+            }
+            ShapeError::DuplicateSpring { anchor_a, anchor_b } => write!(
+                f,
+                "A spring between point masses {anchor_a} and {anchor_b} already exists"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// Handle to a point mass of a [Shape] that stays valid while other point masses are removed, unlike its index.
+/// Handles are never reused within a shape, so one of a removed point mass finds nothing rather than another point mass.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct PointMassId(u64);
+
+/// A point mass taken out of a [Shape] by [Shape::remove_point_mass] or [Shape::remove_point_mass_ordered]
+pub struct RemovedPointMass {
+    pub point_mass: PointMass,
+    pub id: PointMassId,
+    /// Previous index of the point mass moved into the removed one's place, `None` if the last point mass was removed.
+    /// Always `None` after [Shape::remove_point_mass_ordered], which shifts every later point mass down by one instead.
+    pub moved_from: Option<usize>,
+}
+
+/// Builds a [Shape] one point mass and spring at a time, rejecting springs that would leave it inconsistent
+pub struct ShapeBuilder {
+    shape: Shape,
+}
+
+impl ShapeBuilder {
+    pub fn new() -> Self {
+        ShapeBuilder {
+            shape: Shape::new(),
+        }
+    }
+
+    /// Point masses added so far, for measuring rest lengths of the springs between them
+    pub fn point_masses(&self) -> &[PointMass] {
+        &self.shape.point_masses
+    }
+
+    /// Adds a point mass and returns its index, the anchor springs refer to it by
+    pub fn point_mass(&mut self, point_mass: PointMass) -> usize {
+        self.shape.add_point_mass(point_mass);
+        self.shape.point_masses.len() - 1
+    }
+
+    /// Adds a spring between two point masses already in the builder
+    pub fn spring(&mut self, spring: Spring) -> Result<&mut Self, ShapeError> {
+        self.shape.add_spring(spring)?;
+        Ok(self)
+    }
+
+    /// Integration scheme of [Shape::update_substepped], [Solver::VelocityVerlet] if not set
+    pub fn solver(&mut self, solver: Solver) -> &mut Self {
+        self.shape.solver = solver;
+        self
+    }
+
+    pub fn build(mut self) -> Shape {
+        self.shape.update_bounds();
+        self.shape
+    }
+}
+
+impl Default for ShapeBuilder {
+    fn default() -> Self {
+        ShapeBuilder::new()
+    }
+}
+
+/// Point masses on the unit sphere held together by springs.
+/// Constructed through [ShapeBuilder], so every spring anchors two distinct point masses of the shape and the spring map stays in sync.
+#[derive(Clone)]
 pub struct Shape {
-    pub point_masses: Vec<PointMass>,
-    pub springs: Vec<Spring>,
+    point_masses: Vec<PointMass>,
+    springs: Vec<Spring>,
     centroid: Vec3,
     bounding_distance: f32,
     /// Hashmap from PointMass index to Spring indices
     spring_map: HashMap<usize, Vec<usize>>,
+    /// Handle of every point mass, in the same order as the point masses
+    ids: Vec<PointMassId>,
+    /// Current index of every handle, kept in sync with [Shape::ids] on removal
+    id_indices: HashMap<PointMassId, usize>,
+    next_id: u64,
+    /// [Shape::boundary], walked on first use and dropped when point masses or springs are added or removed
+    outline: OnceLock<Vec<usize>>,
+    solver: Solver,
+    /// Spring force evaluations skipped because [Spring::apply_force] found the spring degenerate
+    degenerate_springs: usize,
+}
+
+/// Moves `position` along the great circle in the tangent part of `displacement`, staying on the unit sphere
+fn move_on_sphere(position: Vec3, displacement: Vec3) -> Vec3 {
+    let (position, displacement) = (widen(position), widen(displacement));
+    // Project displacement onto tangent plane of point mass
+    let tangent_disp = displacement - displacement.dot(position) * position;
+
+    let angle = tangent_disp.length();
+    if angle > 0.0 {
+        // Vanishes when position is not on the unit sphere and the displacement points along it
+        let axis = position.cross(tangent_disp).normalize_or_zero();
+        if axis == RealVec3::ZERO {
+            return narrow(position);
+        }
+        let rot = RealQuat::from_axis_angle(axis, angle);
+        // Normalize to avoid error build up, point masses are constrained to the unit sphere
+        narrow((rot * position).normalize())
+    } else {
+        narrow(position)
+    }
 }
 
 impl Shape {
+    /// A shape without point masses, see [ShapeBuilder] for filling one
     pub fn new() -> Self {
         Shape {
             point_masses: Vec::new(),
@@ -21,28 +158,236 @@ impl Shape {
             centroid: Vec3::NAN,
             bounding_distance: f32::NAN,
             spring_map: HashMap::<usize, Vec<usize>>::new(),
+            ids: Vec::new(),
+            id_indices: HashMap::new(),
+            next_id: 0,
+            outline: OnceLock::new(),
+            solver: Solver::default(),
+            degenerate_springs: 0,
         }
     }
 
-    pub fn add_point_mass(&mut self, point_mass: PointMass) {
+    pub fn point_masses(&self) -> &[PointMass] {
+        &self.point_masses
+    }
+
+    /// Point masses can be moved but not added or removed, which would invalidate spring anchors
+    pub fn point_masses_mut(&mut self) -> &mut [PointMass] {
+        &mut self.point_masses
+    }
+
+    pub fn springs(&self) -> &[Spring] {
+        &self.springs
+    }
+
+    pub fn solver(&self) -> Solver {
+        self.solver
+    }
+
+    pub fn set_solver(&mut self, solver: Solver) {
+        self.solver = solver;
+    }
+
+    /// Replaces the stiffness and damping of every spring, leaving anchors and rest lengths alone
+    pub fn set_spring_parameters(&mut self, spring_constant: f32, damping_coefficient: f32) {
+        for spring in &mut self.springs {
+            spring.spring_constant = spring_constant;
+            spring.damping_coefficient = damping_coefficient;
+        }
+    }
+
+    /// Stable handle of the point mass at `index`
+    pub fn point_mass_id(&self, index: usize) -> PointMassId {
+        self.ids[index]
+    }
+
+    /// Current index of the point mass behind `id`, `None` once it has been removed
+    pub fn index_of(&self, id: PointMassId) -> Option<usize> {
+        self.id_indices.get(&id).copied()
+    }
+
+    fn add_point_mass(&mut self, point_mass: PointMass) {
+        self.outline.take();
+        let id = PointMassId(self.next_id);
+        self.next_id += 1;
+        self.id_indices.insert(id, self.point_masses.len());
+        self.ids.push(id);
         self.spring_map.insert(self.point_masses.len(), vec![]);
         self.point_masses.push(point_mass);
     }
 
-    pub fn add_spring(&mut self, spring: Spring) {
-        if let Some(entry) = self.spring_map.get_mut(&spring.anchor_a) {
-            entry.push(self.springs.len());
-        } else {
-            self.spring_map
-                .insert(spring.anchor_a, vec![self.springs.len()]);
+    /// Adds a spring between two point masses of the shape, such as a suture after [Shape::merge]
+    pub fn add_spring(&mut self, spring: Spring) -> Result<(), ShapeError> {
+        for anchor in [spring.anchor_a, spring.anchor_b] {
+            if anchor >= self.point_masses.len() {
+                return Err(ShapeError::AnchorOutOfBounds {
+                    anchor,
+                    point_mass_count: self.point_masses.len(),
+                });
+            }
+        }
+        if spring.anchor_a == spring.anchor_b {
+            return Err(ShapeError::SameAnchors(spring.anchor_a));
         }
-        if let Some(entry) = self.spring_map.get_mut(&spring.anchor_b) {
-            entry.push(self.springs.len());
-        } else {
+        let duplicate = self.spring_map[&spring.anchor_a]
+            .iter()
+            .any(|&spring_index| {
+                let existing = &self.springs[spring_index];
+                existing.anchor_a == spring.anchor_b || existing.anchor_b == spring.anchor_b
+            });
+        if duplicate {
+            return Err(ShapeError::DuplicateSpring {
+                anchor_a: spring.anchor_a,
+                anchor_b: spring.anchor_b,
+            });
+        }
+        for anchor in [spring.anchor_a, spring.anchor_b] {
             self.spring_map
-                .insert(spring.anchor_b, vec![self.springs.len()]);
+                .get_mut(&anchor)
+                .expect("Every point mass has a spring_map entry")
+                .push(self.springs.len());
         }
+        self.outline.take();
         self.springs.push(spring);
+        Ok(())
+    }
+
+    /// Moves all point masses and springs of `other` into this shape.
+    /// Returns the index of the first point mass of `other` in this shape, add springs across the seam with it.
+    /// The moved point masses get new [PointMassId]s, handles into `other` find nothing in this shape.
+    pub fn merge(&mut self, other: Shape) -> usize {
+        self.outline.take();
+        let offset = self.point_masses.len();
+        self.degenerate_springs += other.degenerate_springs;
+        for point_mass in other.point_masses {
+            self.add_point_mass(point_mass);
+        }
+        for spring in other.springs {
+            self.add_spring(Spring {
+                anchor_a: spring.anchor_a + offset,
+                anchor_b: spring.anchor_b + offset,
+                ..spring
+            })
+            .expect("Springs of a valid shape stay valid after offsetting");
+        }
+        self.update_centroid();
+        self.update_bounding_distance();
+        offset
+    }
+
+    /// Removes the point mass at `index` along with its springs, moving the last point mass into its place like [Vec::swap_remove].
+    /// Only the moved point mass changes index, so maps from point mass indices only need [RemovedPointMass::moved_from] updated.
+    /// Maps holding [PointMassId]s need no update at all.
+    pub fn remove_point_mass(&mut self, index: usize) -> RemovedPointMass {
+        self.outline.take();
+        self.remove_springs_of(index);
+        let point_mass = self.point_masses.swap_remove(index);
+        let id = self.ids.swap_remove(index);
+        self.id_indices.remove(&id);
+        self.spring_map.remove(&index);
+        let last = self.point_masses.len();
+        let moved_from = (index != last).then(|| {
+            self.id_indices.insert(self.ids[index], index);
+            let springs = self
+                .spring_map
+                .remove(&last)
+                .expect("Every point mass has a spring_map entry");
+            for &spring_index in &springs {
+                let spring = &mut self.springs[spring_index];
+                if spring.anchor_a == last {
+                    spring.anchor_a = index;
+                }
+                if spring.anchor_b == last {
+                    spring.anchor_b = index;
+                }
+            }
+            self.spring_map.insert(index, springs);
+            last
+        });
+        self.update_bounds();
+        RemovedPointMass {
+            point_mass,
+            id,
+            moved_from,
+        }
+    }
+
+    /// [Shape::remove_point_mass] by handle, `None` if the point mass was already removed
+    pub fn remove_point_mass_by_id(&mut self, id: PointMassId) -> Option<RemovedPointMass> {
+        self.index_of(id).map(|index| self.remove_point_mass(index))
+    }
+
+    /// Removes the point mass at `index` along with its springs, keeping the order of the remaining point masses like [Vec::remove].
+    /// Every point mass after `index` shifts down by one, slower than [Shape::remove_point_mass] but the order is kept.
+    /// Their [PointMassId]s stay valid, [Shape::index_of] finds the shifted indices.
+    pub fn remove_point_mass_ordered(&mut self, index: usize) -> RemovedPointMass {
+        self.outline.take();
+        self.remove_springs_of(index);
+        let point_mass = self.point_masses.remove(index);
+        let id = self.ids.remove(index);
+        self.id_indices.remove(&id);
+        for (shifted, &id) in self.ids.iter().enumerate().skip(index) {
+            self.id_indices.insert(id, shifted);
+        }
+        for spring in &mut self.springs {
+            if spring.anchor_a > index {
+                spring.anchor_a -= 1;
+            }
+            if spring.anchor_b > index {
+                spring.anchor_b -= 1;
+            }
+        }
+        self.spring_map = (0..self.point_masses.len())
+            .map(|point_mass_index| (point_mass_index, Vec::new()))
+            .collect();
+        for (spring_index, spring) in self.springs.iter().enumerate() {
+            for anchor in [spring.anchor_a, spring.anchor_b] {
+                self.spring_map
+                    .get_mut(&anchor)
+                    .expect("Every point mass has a spring_map entry")
+                    .push(spring_index);
+            }
+        }
+        self.update_bounds();
+        RemovedPointMass {
+            point_mass,
+            id,
+            moved_from: None,
+        }
+    }
+
+    /// Removes every spring anchored to the point mass at `index`
+    fn remove_springs_of(&mut self, index: usize) {
+        let mut spring_indices = self.spring_map[&index].clone();
+        // Removing from the back first keeps the remaining indices valid, the spring swapped in always comes after them
+        spring_indices.sort_unstable_by(|a, b| b.cmp(a));
+        for spring_index in spring_indices {
+            let removed = self.springs.swap_remove(spring_index);
+            for anchor in [removed.anchor_a, removed.anchor_b] {
+                if let Some(entry) = self.spring_map.get_mut(&anchor) {
+                    entry.retain(|&other| other != spring_index);
+                }
+            }
+            let moved_from = self.springs.len();
+            if spring_index != moved_from {
+                let moved = &self.springs[spring_index];
+                for anchor in [moved.anchor_a, moved.anchor_b] {
+                    if let Some(entry) = self.spring_map.get_mut(&anchor) {
+                        for other in entry.iter_mut().filter(|other| **other == moved_from) {
+                            *other = spring_index;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Centroid and bounding distance are left as they are once the last point mass is gone
+    fn update_bounds(&mut self) {
+        if !self.point_masses.is_empty() {
+            self.update_centroid();
+            self.update_bounding_distance();
+        }
     }
 
     fn zero_forces(&mut self) {
@@ -54,10 +399,17 @@ impl Shape {
 
     pub fn apply_spring_forces(&mut self) {
         for spring in &self.springs {
-            spring.apply_force(&mut self.point_masses);
+            if !spring.apply_force(&mut self.point_masses) {
+                self.degenerate_springs += 1;
+            }
         }
     }
 
+    /// Spring force evaluations skipped so far because their point masses coincided or were antipodal, which would otherwise give NaN forces
+    pub fn degenerate_springs(&self) -> usize {
+        self.degenerate_springs
+    }
+
     pub fn apply_external_force<F>(&mut self, function: F)
     where
         F: Fn(&PointMass) -> Vec3,
@@ -73,18 +425,7 @@ impl Shape {
             let old_acc = point_mass.prev_force / point_mass.mass;
             let new_acc = point_mass.force / point_mass.mass;
             let displacement = point_mass.velocity * timestep + 0.5 * old_acc * timestep.powi(2);
-
-            // Project displacement onto tangent plane of point mass
-            let tangent_disp =
-                displacement - displacement.dot(point_mass.position) * point_mass.position;
-
-            let angle = tangent_disp.length();
-            if angle > 0.0 {
-                let axis = point_mass.position.cross(tangent_disp).normalize();
-                let rot = Quat::from_axis_angle(axis, angle);
-                // Normalize to avoid error build up, point masses are constrained to the unit sphere
-                point_mass.position = (rot * point_mass.position).normalize();
-            }
+            point_mass.position = move_on_sphere(point_mass.position, displacement);
             point_mass.velocity = point_mass.velocity + (old_acc + new_acc) / 2. * timestep;
         }
         self.zero_forces();
@@ -92,6 +433,106 @@ impl Shape {
         self.update_bounding_distance();
     }
 
+    /// Integrates `timestep` in `substeps` equal steps with the shape's [Solver], evaluating spring forces at every step.
+    /// Forces accumulated before the call, such as from [Shape::apply_external_force], are held constant over the whole timestep.
+    /// Spring forces must not be applied beforehand, unlike with [Shape::update].
+    pub fn update_substepped(&mut self, timestep: f32, substeps: usize) {
+        let substeps = substeps.max(1);
+        let step = timestep / substeps as f32;
+        let external: Vec<Vec3> = self
+            .point_masses
+            .iter()
+            .map(|point_mass| point_mass.force)
+            .collect();
+        for _ in 0..substeps {
+            match self.solver {
+                Solver::SemiImplicitEuler => {
+                    let accelerations = self.accelerations(&external);
+                    for (point_mass, acceleration) in
+                        self.point_masses.iter_mut().zip(accelerations)
+                    {
+                        point_mass.velocity += acceleration * step;
+                        point_mass.position =
+                            move_on_sphere(point_mass.position, point_mass.velocity * step);
+                    }
+                }
+                Solver::VelocityVerlet => {
+                    let old_accelerations = self.accelerations(&external);
+                    for (point_mass, acceleration) in
+                        self.point_masses.iter_mut().zip(&old_accelerations)
+                    {
+                        let displacement =
+                            point_mass.velocity * step + 0.5 * *acceleration * step.powi(2);
+                        point_mass.position = move_on_sphere(point_mass.position, displacement);
+                    }
+                    let new_accelerations = self.accelerations(&external);
+                    for ((point_mass, old_acc), new_acc) in self
+                        .point_masses
+                        .iter_mut()
+                        .zip(old_accelerations)
+                        .zip(new_accelerations)
+                    {
+                        point_mass.velocity += (old_acc + new_acc) / 2. * step;
+                    }
+                }
+                Solver::RK4 => self.rk4_step(&external, step),
+            }
+        }
+        // Leaves the forces as [Shape::update] does, so the two can be mixed
+        let final_accelerations = self.accelerations(&external);
+        for (point_mass, acceleration) in self.point_masses.iter_mut().zip(final_accelerations) {
+            point_mass.prev_force = acceleration * point_mass.mass;
+            point_mass.force = Vec3::ZERO;
+        }
+        self.update_bounds();
+    }
+
+    /// Spring forces plus `external` divided by mass for every point mass, at their current positions and velocities
+    fn accelerations(&mut self, external: &[Vec3]) -> Vec<Vec3> {
+        for (point_mass, force) in self.point_masses.iter_mut().zip(external) {
+            point_mass.force = *force;
+        }
+        self.apply_spring_forces();
+        self.point_masses
+            .iter()
+            .map(|point_mass| point_mass.force / point_mass.mass)
+            .collect()
+    }
+
+    /// One classic Runge-Kutta step, intermediate positions are reached by moving along the sphere from the start of the step
+    fn rk4_step(&mut self, external: &[Vec3], step: f32) {
+        let start: Vec<(Vec3, Vec3)> = self
+            .point_masses
+            .iter()
+            .map(|point_mass| (point_mass.position, point_mass.velocity))
+            .collect();
+        let mut velocity_sum = vec![Vec3::ZERO; start.len()];
+        let mut acceleration_sum = vec![Vec3::ZERO; start.len()];
+        let mut velocities: Vec<Vec3> = start.iter().map(|(_, velocity)| *velocity).collect();
+        for (weight, fraction) in [(1., 0.5), (2., 0.5), (2., 1.), (1., 0.)] {
+            let accelerations = self.accelerations(external);
+            for (i, point_mass) in self.point_masses.iter_mut().enumerate() {
+                velocity_sum[i] += weight * velocities[i];
+                acceleration_sum[i] += weight * accelerations[i];
+                // The last evaluation only contributes to the sums
+                let (position, velocity) = start[i];
+                point_mass.position = move_on_sphere(position, velocities[i] * step * fraction);
+                point_mass.velocity = velocity + accelerations[i] * step * fraction;
+                velocities[i] = point_mass.velocity;
+            }
+        }
+        for (i, point_mass) in self.point_masses.iter_mut().enumerate() {
+            let (position, velocity) = start[i];
+            point_mass.position = move_on_sphere(position, velocity_sum[i] * step / 6.);
+            point_mass.velocity = velocity + acceleration_sum[i] * step / 6.;
+        }
+    }
+
+    /// Average point mass position as of the last [Shape::update_centroid], inside the sphere rather than on it
+    pub fn centroid(&self) -> Vec3 {
+        self.centroid
+    }
+
     /// Calculate the shapes average point
     pub fn update_centroid(&mut self) {
         self.centroid = Vec3::ZERO;
@@ -104,13 +545,13 @@ impl Shape {
         self.bounding_distance = self
             .point_masses
             .iter()
-            .map(|pm| f32::acos(pm.position.dot(self.centroid).clamp(-1., 1.)))
+            .map(|pm| angle_between(pm.position, self.centroid))
             .max_by(|a, b| a.partial_cmp(b).unwrap())
             .unwrap()
     }
 
     pub fn within_bounding_spherical_cap(&self, position: Vec3) -> bool {
-        f32::acos(position.dot(self.centroid).clamp(-1., 1.)) < self.bounding_distance
+        angle_between(position, self.centroid) < self.bounding_distance
     }
 
     /// Returns an iterator going over each point mass and the springs it is an anchor for.
@@ -149,7 +590,135 @@ impl Shape {
         })
     }
 
-    // pub fn apply frame force
+    /// Sum of half mass times squared speed over all point masses
+    pub fn kinetic_energy(&self) -> f32 {
+        self.point_masses
+            .iter()
+            .map(|point_mass| 0.5 * point_mass.mass * point_mass.velocity.length_squared())
+            .sum()
+    }
+
+    /// Energy stored in the springs, from how far their geodesic length is from rest
+    pub fn spring_potential_energy(&self) -> f32 {
+        self.springs
+            .iter()
+            .map(|spring| {
+                let length = self.point_masses[spring.anchor_a]
+                    .geodesic_distance(&self.point_masses[spring.anchor_b]);
+                0.5 * spring.spring_constant * (length - spring.rest_length).powi(2)
+            })
+            .sum()
+    }
 
-    // pub fn get shape/hull from grahams method
+    /// Relative elongation of every spring, in the same order as [Shape::springs]
+    pub fn iter_spring_strains(&self) -> impl Iterator<Item = (&Spring, f32)> {
+        self.springs
+            .iter()
+            .map(|spring| (spring, spring.strain(&self.point_masses)))
+    }
+
+    /// Point mass indices around the outline of the shape, counterclockwise seen from outside the sphere.
+    /// The outline follows the springs, so it hugs concave plates instead of spanning them like a convex hull would.
+    /// It is walked from the point mass farthest from the centroid, only that point mass's connected part is outlined when the shape is split.
+    /// A point mass is listed once per visit, so one joining two lobes of the shape appears twice.
+    /// Walked once and kept until point masses or springs are added or removed, moving point masses does not change which springs it runs along.
+    pub fn boundary(&self) -> &[usize] {
+        self.outline.get_or_init(|| self.walk_boundary())
+    }
+
+    fn walk_boundary(&self) -> Vec<usize> {
+        let Some(start) = self
+            .point_masses
+            .iter()
+            .enumerate()
+            .map(|(i, point_mass)| (i, angle_between(point_mass.position, self.centroid)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+        else {
+            return Vec::new();
+        };
+        // Pretend the walk arrived at the start from outside the shape, away from the centroid
+        let outward = self.point_masses[start].position - self.centroid;
+        let Some(first) = self.next_boundary_point_mass(start, outward) else {
+            return vec![start];
+        };
+
+        let mut boundary = vec![start];
+        let (mut previous, mut current) = (start, first);
+        // Every spring is walked at most once in each direction
+        for _ in 0..2 * self.springs.len() {
+            let back = self.point_masses[previous].position - self.point_masses[current].position;
+            let next = self
+                .next_boundary_point_mass(current, back)
+                .expect("The walk arrived over a spring, so it can leave over one");
+            if (current, next) == (start, first) {
+                break;
+            }
+            boundary.push(current);
+            (previous, current) = (current, next);
+        }
+        boundary
+    }
+
+    /// Whether the unit vector `normal` lies inside the shape, from the winding number of its [Shape::boundary] around it.
+    /// Holes in the shape count as inside, and a shape wide enough to cover two antipodal points contains neither of them.
+    pub fn contains(&self, normal: Vec3) -> bool {
+        // Cheap rejection when asking every plate about a tile, the cap is as of the last update
+        if self.point_masses.is_empty()
+            || angle_between(normal, self.centroid) > self.bounding_distance
+        {
+            return false;
+        }
+        let boundary = self.boundary();
+        // Fewer point masses enclose nothing, the outline runs back over itself
+        if boundary.len() < 3 {
+            return false;
+        }
+        let tangent = |position: Vec3| position - position.dot(normal) * normal;
+        let winding: f32 = boundary
+            .iter()
+            .zip(boundary.iter().cycle().skip(1))
+            .map(|(&a, &b)| {
+                let a = tangent(self.point_masses[a].position);
+                let b = tangent(self.point_masses[b].position);
+                normal.dot(a.cross(b)).atan2(a.dot(b))
+            })
+            .sum();
+        // Tau when the counterclockwise outline goes around `normal`, 0 when it does not and minus tau when it goes around the antipode
+        winding > std::f32::consts::PI
+    }
+
+    /// Neighbor of `index` reached first turning counterclockwise from `back` around the point mass, seen from outside the sphere.
+    /// Walking a spring and then always taking this neighbor of the direction walked from keeps the shape on the left.
+    fn next_boundary_point_mass(&self, index: usize, back: Vec3) -> Option<usize> {
+        let normal = self.point_masses[index].position;
+        let tangent = |direction: Vec3| direction - direction.dot(normal) * normal;
+        let back = tangent(back);
+        self.spring_map[&index]
+            .iter()
+            .map(|&spring_index| {
+                let spring = &self.springs[spring_index];
+                let neighbor = if spring.anchor_a == index {
+                    spring.anchor_b
+                } else {
+                    spring.anchor_a
+                };
+                let towards = tangent(self.point_masses[neighbor].position - normal);
+                let angle = normal
+                    .dot(back.cross(towards))
+                    .atan2(back.dot(towards))
+                    .rem_euclid(std::f32::consts::TAU);
+                // Straight back is the last way out, not the first
+                let angle = if angle == 0. {
+                    std::f32::consts::TAU
+                } else {
+                    angle
+                };
+                (neighbor, angle)
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(neighbor, _)| neighbor)
+    }
+
+    // pub fn apply frame force
 }
@@ -0,0 +1,12 @@
+/// Integration scheme used by [crate::Shape::update_substepped]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Solver {
+    /// Second order and one extra force evaluation per substep, the default
+    #[default]
+    VelocityVerlet,
+    /// First order and a single force evaluation per substep, cheapest but needs the most substeps with stiff springs
+    SemiImplicitEuler,
+    /// Fourth order Runge-Kutta, four force evaluations per substep but stable with stiff springs and long timesteps
+    RK4,
+}
@@ -1,5 +1,14 @@
-use crate::point_mass::PointMass;
+use crate::{
+    point_mass::PointMass,
+    precision::{Real, narrow, real_angle_between, widen, widen_scalar},
+};
 
+/// Springs shorter than this have no usable direction between their point masses
+const MIN_DISTANCE: Real = 1e-9;
+/// Springs longer than this are close enough to antipodal that the direction between their point masses is radial and the tangent force vanishes
+const MAX_DISTANCE: Real = std::f64::consts::PI as Real - 1e-6;
+
+#[derive(Clone)]
 pub struct Spring {
     /// Index to PointMass
     pub anchor_a: usize,
@@ -11,29 +20,49 @@ pub struct Spring {
 }
 
 impl Spring {
-    /// Calculate the spring-dampener system force on [self]
-    pub fn apply_force(&self, point_masses: &mut Vec<PointMass>) {
+    /// Calculate the spring-dampener system force on [self].
+    /// Returns false without applying a force when the spring is degenerate: its point masses coincide or are antipodal,
+    /// so the direction between them is undefined, or the force is not finite.
+    pub fn apply_force(&self, point_masses: &mut Vec<PointMass>) -> bool {
         let point_a = &point_masses[self.anchor_a];
         let point_b = &point_masses[self.anchor_b];
+        // Nearly coincident point masses lose the most precision, see [crate::precision::angle_between]
+        let (position_a, position_b) = (widen(point_a.position), widen(point_b.position));
 
-        let distance = point_a.geodesic_distance(&point_b);
-        if distance == 0.0 {
-            return;
+        let distance = real_angle_between(position_a, position_b);
+        if !(MIN_DISTANCE..=MAX_DISTANCE).contains(&distance) {
+            return false;
         }
 
-        let direction = (point_a.position - point_b.position) / distance;
-        let relative_velocity = point_a.velocity - point_b.velocity;
+        let direction = (position_a - position_b) / distance;
+        let relative_velocity = widen(point_a.velocity) - widen(point_b.velocity);
         let velocity_towards = relative_velocity.dot(direction);
 
-        let force = (-self.spring_constant * (distance - self.rest_length)
-            - self.damping_coefficient * velocity_towards)
+        let force = (-widen_scalar(self.spring_constant)
+            * (distance - widen_scalar(self.rest_length))
+            - widen_scalar(self.damping_coefficient) * velocity_towards)
             * direction;
 
         // Project force onto point_a tangent plane
-        let force_on_a = force - force.dot(point_a.position) * point_a.position;
-        let force_on_b = (-force) - (-force).dot(point_b.position) * point_b.position;
+        let force_on_a = force - force.dot(position_a) * position_a;
+        let force_on_b = (-force) - (-force).dot(position_b) * position_b;
 
+        let (force_on_a, force_on_b) = (narrow(force_on_a), narrow(force_on_b));
+        if !force_on_a.is_finite() || !force_on_b.is_finite() {
+            return false;
+        }
         point_masses[self.anchor_a].force += force_on_a;
         point_masses[self.anchor_b].force += force_on_b;
+        true
+    }
+
+    /// Relative elongation of the spring, positive when stretched and negative when compressed.
+    /// 0 for a degenerate spring without a positive rest length to measure the elongation against.
+    pub fn strain(&self, point_masses: &[PointMass]) -> f32 {
+        if self.rest_length.is_nan() || self.rest_length <= 0. {
+            return 0.;
+        }
+        let length = point_masses[self.anchor_a].geodesic_distance(&point_masses[self.anchor_b]);
+        (length - self.rest_length) / self.rest_length
     }
 }
@@ -0,0 +1,162 @@
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec3;
+use soft_sphere::{PointMass, Shape, ShapeBuilder, Spring};
+
+const SIZE: i32 = 6;
+
+/// Triangulated grid of point masses around +X, with the cells `include` rejects left out.
+/// Returns the shape and the index of every included cell.
+fn grid(include: impl Fn(i32, i32) -> bool) -> (Shape, HashMap<(i32, i32), usize>) {
+    let mut shape = ShapeBuilder::new();
+    let mut indices = HashMap::new();
+    for row in 0..SIZE {
+        for column in 0..SIZE {
+            if include(row, column) {
+                let position = Vec3::new(1., 0.05 * row as f32, 0.05 * column as f32).normalize();
+                indices.insert(
+                    (row, column),
+                    shape.point_mass(PointMass::new(position, 1.)),
+                );
+            }
+        }
+    }
+    for (&(row, column), &anchor_a) in &indices {
+        for neighbor in [(row, column + 1), (row + 1, column), (row + 1, column + 1)] {
+            if let Some(&anchor_b) = indices.get(&neighbor) {
+                let point_masses = shape.point_masses();
+                let rest_length = point_masses[anchor_a].geodesic_distance(&point_masses[anchor_b]);
+                shape
+                    .spring(Spring {
+                        anchor_a,
+                        anchor_b,
+                        rest_length,
+                        spring_constant: 1.,
+                        damping_coefficient: 0.,
+                    })
+                    .expect("Each grid edge once");
+            }
+        }
+    }
+    (shape.build(), indices)
+}
+
+/// Checks the boundary is a closed ring along springs, once around the shape counterclockwise seen from outside
+fn assert_ring(shape: &Shape, boundary: &[usize]) {
+    let unique: HashSet<_> = boundary.iter().collect();
+    assert_eq!(
+        unique.len(),
+        boundary.len(),
+        "{boundary:?} repeats a point mass"
+    );
+    let positions: Vec<Vec3> = boundary
+        .iter()
+        .map(|&index| shape.point_masses()[index].position)
+        .collect();
+    let mut area = 0.;
+    for (i, &index) in boundary.iter().enumerate() {
+        let next = boundary[(i + 1) % boundary.len()];
+        assert!(
+            shape.springs().iter().any(|spring| {
+                (spring.anchor_a, spring.anchor_b) == (index, next)
+                    || (spring.anchor_a, spring.anchor_b) == (next, index)
+            }),
+            "No spring between consecutive boundary point masses {index} and {next}"
+        );
+        area += positions[i]
+            .cross(positions[(i + 1) % positions.len()])
+            .dot(shape.centroid());
+    }
+    assert!(area > 0., "{boundary:?} is clockwise");
+}
+
+/// Included cells on the edge of the grid or next to a left out cell, over the springs of [grid]
+fn edge_cells(
+    indices: &HashMap<(i32, i32), usize>,
+    include: impl Fn(i32, i32) -> bool,
+) -> HashSet<usize> {
+    indices
+        .iter()
+        .filter(|&(&(row, column), _)| {
+            [(0, 1), (1, 0), (1, 1), (0, -1), (-1, 0), (-1, -1)]
+                .iter()
+                .any(|(row_step, column_step)| {
+                    let (row, column) = (row + row_step, column + column_step);
+                    !(0..SIZE).contains(&row)
+                        || !(0..SIZE).contains(&column)
+                        || !include(row, column)
+                })
+        })
+        .map(|(_, &index)| index)
+        .collect()
+}
+
+#[test]
+fn square_boundary_is_its_edge_cells() {
+    let include = |_, _| true;
+    let (shape, indices) = grid(include);
+    let boundary = shape.boundary();
+    assert_ring(&shape, boundary);
+    assert_eq!(
+        boundary.iter().copied().collect::<HashSet<_>>(),
+        edge_cells(&indices, include)
+    );
+}
+
+#[test]
+fn concave_boundary_follows_the_notch() {
+    // An L, a convex hull would cut across the notch and skip its corner
+    let include = |row, column| row < 3 || column < 3;
+    let (shape, indices) = grid(include);
+    let boundary = shape.boundary();
+    assert_ring(&shape, boundary);
+    assert_eq!(
+        boundary.iter().copied().collect::<HashSet<_>>(),
+        edge_cells(&indices, include)
+    );
+    assert!(boundary.contains(&indices[&(2, 2)]));
+    assert!(!boundary.contains(&indices[&(1, 1)]));
+}
+
+#[test]
+fn contains_only_points_inside_the_outline() {
+    let (shape, indices) = grid(|row, column| row < 3 || column < 3);
+    let position = |row: f32, column: f32| Vec3::new(1., 0.05 * row, 0.05 * column).normalize();
+    let contains = |normal: Vec3| shape.contains(normal);
+
+    assert!(contains(position(1.5, 1.5)));
+    assert!(contains(position(4.5, 0.5)));
+    assert!(contains(shape.point_masses()[indices[&(1, 1)]].position));
+    // In the notch, inside the bounding cap and a convex hull but not the plate
+    assert!(!contains(position(4., 4.)));
+    assert!(!contains(position(-1., 2.)));
+    assert!(!contains(-position(1.5, 1.5)));
+    assert!(!contains(Vec3::Y));
+}
+
+#[test]
+fn outline_follows_removed_point_masses() {
+    let (mut shape, indices) = grid(|_, _| true);
+    let position = |row: f32, column: f32| Vec3::new(1., 0.05 * row, 0.05 * column).normalize();
+    assert!(shape.contains(position(0.4, 0.2)));
+
+    // Takes both triangles of the corner cell with it, the outline has to be walked again
+    shape.remove_point_mass(indices[&(0, 0)]);
+    assert_ring(&shape, shape.boundary());
+    assert!(!shape.contains(position(0.4, 0.2)));
+    assert!(shape.contains(position(1.5, 1.5)));
+}
+
+#[test]
+fn tiny_shapes_have_tiny_boundaries() {
+    assert!(Shape::new().boundary().is_empty());
+
+    let (single, _) = grid(|row, column| (row, column) == (0, 0));
+    assert_eq!(single.boundary(), vec![0]);
+
+    let (pair, _) = grid(|row, column| row == 0 && column < 2);
+    let mut boundary = pair.boundary().to_vec();
+    boundary.sort_unstable();
+    assert_eq!(boundary, vec![0, 1]);
+    assert!(!pair.contains(pair.centroid().normalize()));
+}
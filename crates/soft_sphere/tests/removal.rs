@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use glam::Vec3;
+use soft_sphere::{PointMass, PointMassId, Shape, ShapeBuilder, Spring};
+
+const SPRINGS: [(usize, usize); 7] = [(0, 1), (1, 2), (2, 3), (3, 4), (0, 2), (2, 4), (1, 3)];
+
+fn position(index: usize) -> Vec3 {
+    Vec3::new(1., 0.03 * (index % 2) as f32, 0.05 * index as f32).normalize()
+}
+
+/// Five point masses in a strip of triangles, the middle one anchoring four springs
+fn strip() -> Shape {
+    let mut shape = ShapeBuilder::new();
+    for index in 0..5 {
+        shape.point_mass(PointMass::new(position(index), 1.));
+    }
+    for (anchor_a, anchor_b) in SPRINGS {
+        let point_masses = shape.point_masses();
+        let rest_length = point_masses[anchor_a].geodesic_distance(&point_masses[anchor_b]);
+        shape
+            .spring(Spring {
+                anchor_a,
+                anchor_b,
+                rest_length,
+                spring_constant: 1.,
+                damping_coefficient: 0.,
+            })
+            .expect("Each strip edge once");
+    }
+    shape.build()
+}
+
+/// Springs as the pair of original point mass indices they connect, found from the positions of their anchors
+fn physical_springs(shape: &Shape) -> HashSet<(usize, usize)> {
+    let original = |anchor: usize| {
+        (0..5)
+            .find(|&index| position(index) == shape.point_masses()[anchor].position)
+            .expect("Point masses keep their positions")
+    };
+    shape
+        .springs()
+        .iter()
+        .map(|spring| {
+            let (a, b) = (original(spring.anchor_a), original(spring.anchor_b));
+            (a.min(b), a.max(b))
+        })
+        .collect()
+}
+
+/// The spring map lists every spring under exactly its two anchors, and the centroid matches the point masses
+fn assert_consistent(shape: &Shape) {
+    let mut listed = 0;
+    for (index, (_, springs)) in shape.iter_point_masses_with_springs().enumerate() {
+        for spring in springs {
+            assert!(
+                spring.anchor_a == index || spring.anchor_b == index,
+                "Point mass {index} lists a spring between {} and {}",
+                spring.anchor_a,
+                spring.anchor_b
+            );
+            listed += 1;
+        }
+    }
+    assert_eq!(listed, 2 * shape.springs().len());
+
+    let centroid = shape
+        .point_masses()
+        .iter()
+        .map(|point_mass| point_mass.position)
+        .sum::<Vec3>()
+        / shape.point_masses().len() as f32;
+    assert!(shape.centroid().distance(centroid) < 1e-6);
+    for point_mass in shape.point_masses() {
+        let inside = point_mass.position.lerp(shape.centroid().normalize(), 1e-3);
+        assert!(shape.within_bounding_spherical_cap(inside));
+    }
+}
+
+/// Springs of the strip that survive removing the original point mass `removed`
+fn expected_springs(removed: usize) -> HashSet<(usize, usize)> {
+    SPRINGS
+        .into_iter()
+        .filter(|&(a, b)| a != removed && b != removed)
+        .collect()
+}
+
+#[test]
+fn swap_remove_keeps_springs_on_the_same_point_masses() {
+    let mut shape = strip();
+    let removed = shape.remove_point_mass(2);
+    assert_eq!(removed.point_mass.position, position(2));
+    // The last point mass fills the gap
+    assert_eq!(removed.moved_from, Some(4));
+    assert_eq!(shape.point_masses()[2].position, position(4));
+    assert_eq!(physical_springs(&shape), expected_springs(2));
+    assert_consistent(&shape);
+
+    let removed = shape.remove_point_mass(shape.point_masses().len() - 1);
+    assert_eq!(removed.point_mass.position, position(3));
+    assert_eq!(removed.moved_from, None);
+    assert_consistent(&shape);
+}
+
+#[test]
+fn ordered_remove_keeps_springs_on_the_same_point_masses() {
+    let mut shape = strip();
+    let ids: Vec<PointMassId> = (0..5).map(|index| shape.point_mass_id(index)).collect();
+    let removed = shape.remove_point_mass_ordered(2);
+    assert_eq!(removed.point_mass.position, position(2));
+    assert_eq!(removed.id, ids[2]);
+    assert_eq!(removed.moved_from, None);
+    let positions: Vec<Vec3> = shape
+        .point_masses()
+        .iter()
+        .map(|point_mass| point_mass.position)
+        .collect();
+    assert_eq!(positions, [0, 1, 3, 4].map(position));
+    // Point masses after the removed one shift down, their ids follow them
+    let indices: Vec<Option<usize>> = ids.iter().map(|&id| shape.index_of(id)).collect();
+    assert_eq!(indices, [Some(0), Some(1), None, Some(2), Some(3)]);
+    assert_eq!(physical_springs(&shape), expected_springs(2));
+    assert_consistent(&shape);
+}
+
+#[test]
+fn ids_follow_point_masses_through_removals() {
+    let mut shape = strip();
+    let ids: Vec<PointMassId> = (0..5).map(|index| shape.point_mass_id(index)).collect();
+
+    let removed = shape
+        .remove_point_mass_by_id(ids[1])
+        .expect("Not removed yet");
+    assert_eq!(removed.id, ids[1]);
+    assert_eq!(removed.point_mass.position, position(1));
+    shape.remove_point_mass_ordered(shape.index_of(ids[0]).expect("Not removed yet"));
+
+    for (original, id) in ids.iter().enumerate() {
+        match shape.index_of(*id) {
+            Some(index) => {
+                assert_eq!(shape.point_masses()[index].position, position(original));
+                assert_eq!(shape.point_mass_id(index), *id);
+            }
+            None => assert!(original < 2, "Point mass {original} lost its id"),
+        }
+    }
+    assert!(shape.remove_point_mass_by_id(ids[1]).is_none());
+    assert_eq!(
+        physical_springs(&shape),
+        HashSet::from([(2, 3), (3, 4), (2, 4)])
+    );
+    assert_consistent(&shape);
+}
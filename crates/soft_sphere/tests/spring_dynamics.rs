@@ -0,0 +1,269 @@
+use std::f32::consts::TAU;
+
+use glam::{Quat, Vec3};
+use soft_sphere::{PointMass, Shape, ShapeBuilder, Solver, Spring};
+
+const REST_LENGTH: f32 = 0.1;
+const TIMESTEP: f32 = 1e-3;
+
+/// Two point masses on the equator joined by one spring, at rest and stretched a tenth past its rest length
+fn oscillator(mass_a: f32, mass_b: f32, spring_constant: f32, damping_coefficient: f32) -> Shape {
+    let stretch = 0.01;
+    let half_length = (REST_LENGTH + stretch) / 2.;
+    let mut shape = ShapeBuilder::new();
+    let anchor_a = shape.point_mass(PointMass::new(
+        Quat::from_rotation_y(half_length) * Vec3::X,
+        mass_a,
+    ));
+    let anchor_b = shape.point_mass(PointMass::new(
+        Quat::from_rotation_y(-half_length) * Vec3::X,
+        mass_b,
+    ));
+    shape
+        .spring(Spring {
+            anchor_a,
+            anchor_b,
+            rest_length: REST_LENGTH,
+            spring_constant,
+            damping_coefficient,
+        })
+        .expect("Two distinct point masses");
+    shape.build()
+}
+
+/// One step the way tectonics drives a plate
+fn step(shape: &mut Shape) {
+    shape.apply_spring_forces();
+    shape.update(TIMESTEP);
+}
+
+/// Geodesic length of the first spring minus its rest length
+fn extension(shape: &Shape) -> f32 {
+    let spring = &shape.springs()[0];
+    let point_masses = shape.point_masses();
+    point_masses[spring.anchor_a].geodesic_distance(&point_masses[spring.anchor_b])
+        - spring.rest_length
+}
+
+/// Extension of the first spring after every `advance`, starting with the initial one
+fn extensions(shape: &mut Shape, steps: usize, advance: impl Fn(&mut Shape)) -> Vec<f32> {
+    let mut extensions = vec![extension(shape)];
+    for _ in 0..steps {
+        advance(shape);
+        extensions.push(extension(shape));
+    }
+    extensions
+}
+
+/// Average time between the spring passing its rest length while stretching, interpolated between samples `interval` apart
+fn period(extensions: &[f32], interval: f32) -> f32 {
+    let crossings: Vec<f32> = extensions
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| pair[0] < 0. && pair[1] >= 0.)
+        .map(|(i, pair)| (i as f32 + pair[0] / (pair[0] - pair[1])) * interval)
+        .collect();
+    assert!(crossings.len() >= 3, "Only {} crossings", crossings.len());
+    (crossings[crossings.len() - 1] - crossings[0]) / (crossings.len() - 1) as f32
+}
+
+fn reduced_mass(mass_a: f32, mass_b: f32) -> f32 {
+    mass_a * mass_b / (mass_a + mass_b)
+}
+
+fn assert_relative(actual: f32, expected: f32, tolerance: f32) {
+    assert!(
+        actual.is_finite() && ((actual - expected) / expected).abs() <= tolerance,
+        "{actual} is not within {tolerance} relative of {expected}"
+    );
+}
+
+#[test]
+fn undamped_spring_oscillates_with_the_analytic_period() {
+    let (mass_a, mass_b, spring_constant) = (1., 3., 4.);
+    let expected = TAU * (reduced_mass(mass_a, mass_b) / spring_constant).sqrt();
+    let mut shape = oscillator(mass_a, mass_b, spring_constant, 0.);
+    let extensions = extensions(&mut shape, (4. * expected / TIMESTEP) as usize, step);
+    // The chord and tangent projection soften the spring slightly on the sphere
+    assert_relative(period(&extensions, TIMESTEP), expected, 5e-3);
+
+    // Positions move with the force of the previous step, which pumps in energy at a relative rate of about
+    // spring_constant / reduced_mass * timestep / 2 per second, under 3% over these four periods
+    let amplitude = extensions[0];
+    assert!(
+        extensions
+            .iter()
+            .all(|extension| extension.abs() <= amplitude * 1.05),
+        "An undamped spring gained amplitude"
+    );
+}
+
+#[test]
+fn damped_spring_decays_at_the_analytic_rate() {
+    let (mass_a, mass_b, spring_constant, damping_coefficient) = (1., 3., 4., 0.2);
+    let expected = damping_coefficient / (2. * reduced_mass(mass_a, mass_b));
+    let mut shape = oscillator(mass_a, mass_b, spring_constant, damping_coefficient);
+    let extensions = extensions(&mut shape, (10. / TIMESTEP) as usize, step);
+
+    // Peaks of the stretch, the envelope decays as exp(-rate * t)
+    let peaks: Vec<(f32, f32)> = extensions
+        .windows(3)
+        .enumerate()
+        .filter(|(_, triple)| triple[1] > 0. && triple[0] < triple[1] && triple[1] >= triple[2])
+        .map(|(i, triple)| ((i + 1) as f32 * TIMESTEP, triple[1]))
+        .collect();
+    assert!(peaks.len() >= 3, "Only {} peaks", peaks.len());
+    let (first_time, first) = peaks[0];
+    let (last_time, last) = peaks[peaks.len() - 1];
+    let rate = (first / last).ln() / (last_time - first_time);
+    // Slightly slower than analytic, the same energy the undamped spring gains is taken off the decay
+    assert_relative(rate, expected, 3e-2);
+}
+
+#[test]
+fn every_solver_oscillates_with_the_analytic_period() {
+    let (mass_a, mass_b, spring_constant) = (1., 3., 4.);
+    let expected = TAU * (reduced_mass(mass_a, mass_b) / spring_constant).sqrt();
+    let timestep = 0.02;
+    for solver in [
+        Solver::VelocityVerlet,
+        Solver::SemiImplicitEuler,
+        Solver::RK4,
+    ] {
+        let mut shape = oscillator(mass_a, mass_b, spring_constant, 0.);
+        shape.set_solver(solver);
+        let extensions = extensions(&mut shape, (4. * expected / timestep) as usize, |shape| {
+            shape.update_substepped(timestep, 4)
+        });
+        assert_relative(period(&extensions, timestep), expected, 5e-3);
+        // Unlike [Shape::update] none of them gains energy at this step size
+        let amplitude = extensions[0];
+        assert!(
+            extensions
+                .iter()
+                .all(|extension| extension.abs() <= amplitude * 1.01),
+            "{solver:?} gained amplitude"
+        );
+    }
+}
+
+#[test]
+fn degenerate_spring_has_no_strain() {
+    for rest_length in [0., -REST_LENGTH, f32::NAN] {
+        let mut shape = ShapeBuilder::new();
+        let anchor_a = shape.point_mass(PointMass::new(Vec3::X, 1.));
+        let anchor_b = shape.point_mass(PointMass::new(Vec3::Y, 1.));
+        shape
+            .spring(Spring {
+                anchor_a,
+                anchor_b,
+                rest_length,
+                spring_constant: 1.,
+                damping_coefficient: 0.,
+            })
+            .expect("Two distinct point masses");
+        let shape = shape.build();
+        let strains: Vec<f32> = shape
+            .iter_spring_strains()
+            .map(|(_, strain)| strain)
+            .collect();
+        assert_eq!(strains, [0.], "Rest length {rest_length}");
+    }
+
+    let shape = oscillator(1., 1., 1., 0.);
+    let (_, strain) = shape.iter_spring_strains().next().expect("One spring");
+    assert_relative(strain, 0.1, 1e-3);
+}
+
+/// Kinetic energy plus the energy stored in the springs
+fn energy(shape: &Shape) -> f32 {
+    shape.kinetic_energy() + shape.spring_potential_energy()
+}
+
+#[test]
+fn substeps_keep_a_stiff_spring_stable() {
+    // The angular frequency times the timestep is about 2.8, past the limit of 2 a velocity verlet step is stable up to
+    let (spring_constant, timestep) = (400., 0.1);
+    let mut single = oscillator(1., 1., spring_constant, 0.);
+    let mut substepped = oscillator(1., 1., spring_constant, 0.);
+    let initial = energy(&single);
+    for _ in 0..20 {
+        single.update_substepped(timestep, 1);
+        substepped.update_substepped(timestep, 20);
+    }
+
+    let diverged = energy(&single);
+    assert!(
+        diverged.is_nan() || diverged >= 100. * initial,
+        "A single step per timestep kept the energy at {diverged} from {initial}"
+    );
+    assert_relative(energy(&substepped), initial, 2e-2);
+}
+
+/// Point masses in a small strip of triangles with springs along every edge, all moving in different directions
+fn strip(rotation: Quat) -> Shape {
+    let mut shape = ShapeBuilder::new();
+    for i in 0..6 {
+        let position = Vec3::new(1., 0.05 * (i % 2) as f32, 0.04 * i as f32).normalize();
+        let velocity = Vec3::new(0.01 * i as f32, -0.02, 0.015 * (i % 3) as f32);
+        // Velocities are projected onto the tangent plane like forces are
+        let velocity = velocity - velocity.dot(position) * position;
+        let mut point_mass = PointMass::new(rotation * position, 1. + 0.5 * i as f32);
+        point_mass.velocity = rotation * velocity;
+        shape.point_mass(point_mass);
+    }
+    for (anchor_a, anchor_b) in [
+        (0, 1),
+        (0, 2),
+        (1, 2),
+        (1, 3),
+        (2, 3),
+        (2, 4),
+        (3, 4),
+        (3, 5),
+        (4, 5),
+    ] {
+        let point_masses = shape.point_masses();
+        // Compressed from the initial lengths so the springs push from the first step
+        let rest_length = 0.8 * point_masses[anchor_a].geodesic_distance(&point_masses[anchor_b]);
+        shape
+            .spring(Spring {
+                anchor_a,
+                anchor_b,
+                rest_length,
+                spring_constant: 5.,
+                damping_coefficient: 0.3,
+            })
+            .expect("Each edge of the strip once");
+    }
+    shape.build()
+}
+
+#[test]
+fn update_commutes_with_rotation() {
+    let rotation = Quat::from_axis_angle(Vec3::new(0.3, -0.8, 0.5).normalize(), 2.1);
+    let mut shape = strip(Quat::IDENTITY);
+    let mut rotated = strip(rotation);
+    for _ in 0..1000 {
+        step(&mut shape);
+        step(&mut rotated);
+    }
+
+    for (point_mass, rotated_point_mass) in shape.point_masses().iter().zip(rotated.point_masses())
+    {
+        let position = rotation * point_mass.position;
+        assert!(
+            position.distance(rotated_point_mass.position) < 1e-4,
+            "{position} rotated drifted to {}",
+            rotated_point_mass.position
+        );
+        let velocity = rotation * point_mass.velocity;
+        assert!(
+            velocity.distance(rotated_point_mass.velocity) < 1e-4,
+            "{velocity} rotated drifted to {}",
+            rotated_point_mass.velocity
+        );
+    }
+    assert_eq!(shape.degenerate_springs(), 0);
+    assert_eq!(rotated.degenerate_springs(), 0);
+}
@@ -0,0 +1,183 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+    time::Duration,
+};
+
+use suz_sim::{
+    Planet, PlanetGenerator,
+    config::SimulationConfig,
+    observer::{Control, SimulationObserver, Stage},
+    plate_import::load_plates,
+};
+#[cfg(feature = "gpu")]
+use suz_sim::{generator::GenerateError, gpu::GpuError};
+
+const USAGE: &str = "Usage: suz_cli --config <config.toml> --output <heights.csv> [--seed <u64>] [--gpu] [--plates <plates.geojson> [--continental <name,name,...>]]";
+
+struct Args {
+    config: PathBuf,
+    output: PathBuf,
+    seed: u64,
+    /// Run the tectonic simulation on the GPU, needs the `gpu` feature
+    gpu: bool,
+    /// Start from the plate polygons of a GeoJSON file instead of random plates
+    plates: Option<PathBuf>,
+    /// Names of the imported plates that are continental, see [suz_sim::plate_import::plates_from_geojson]
+    continental: Vec<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut config = None;
+    let mut output = None;
+    let mut seed = None;
+    let mut gpu = false;
+    let mut plates = None;
+    let mut continental = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value for {arg}"));
+        match arg.as_str() {
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--seed" => {
+                let value = value()?;
+                seed = Some(
+                    value
+                        .parse::<u64>()
+                        .map_err(|e| format!("Invalid seed {value}: {e}"))?,
+                )
+            }
+            "--gpu" => gpu = true,
+            "--plates" => plates = Some(PathBuf::from(value()?)),
+            "--continental" => {
+                continental = value()?
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .collect()
+            }
+            _ => return Err(format!("Unknown argument {arg}")),
+        }
+    }
+    Ok(Args {
+        config: config.ok_or("Missing --config")?,
+        output: output.ok_or("Missing --output")?,
+        seed: seed.unwrap_or_else(rand::random::<u64>),
+        gpu,
+        plates,
+        continental,
+    })
+}
+
+/// Prints how long each stage of generation took
+struct StageTimes;
+
+impl SimulationObserver for StageTimes {
+    fn on_stage_complete(&mut self, stage: Stage, duration: Duration) -> Control {
+        println!("{stage}: {:.3}s", duration.as_secs_f32());
+        Control::Continue
+    }
+}
+
+/// Generates the planet, returns whether the tectonic simulation ran on the GPU.
+/// Falls back to the CPU if no GPU device can be set up.
+#[cfg(feature = "gpu")]
+fn generate(generator: PlanetGenerator, gpu: bool) -> Result<(Planet, bool), String> {
+    let generator = generator.gpu(gpu);
+    match generator.generate_observed(&mut StageTimes) {
+        Err(GenerateError::Gpu(e @ (GpuError::NoAdapter | GpuError::RequestDevice(_)))) => {
+            eprintln!("{e}, falling back to the CPU");
+            generate(generator.gpu(false), false)
+        }
+        result => result
+            .map(|planet| (planet, gpu))
+            .map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(not(feature = "gpu"))]
+fn generate(generator: PlanetGenerator, gpu: bool) -> Result<(Planet, bool), String> {
+    if gpu {
+        return Err("suz_cli was built without the gpu feature".to_string());
+    }
+    generator
+        .generate_observed(&mut StageTimes)
+        .map(|planet| (planet, false))
+        .map_err(|e| e.to_string())
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let config = SimulationConfig::load(&args.config)
+        .map_err(|e| format!("Failed to load config {}: {e}", args.config.display()))?;
+    println!("Seed: {}", args.seed);
+    let mut generator = PlanetGenerator::new(config, args.seed)
+        .map_err(|e| format!("Invalid config {}: {e}", args.config.display()))?;
+    if let Some(path) = &args.plates {
+        let plates = load_plates(path, &args.continental)
+            .map_err(|e| format!("Failed to import plates {}: {e}", path.display()))?;
+        println!("Imported {} plates from {}", plates.len(), path.display());
+        generator = generator.plates(plates);
+    }
+
+    let (planet, on_gpu) = generate(generator, args.gpu)?;
+    let tectonics = &planet.tectonics;
+    if let Some(invalid_state) = tectonics.halted {
+        return Err(format!(
+            "Tectonic simulation halted at iteration {}: {invalid_state}",
+            tectonics.iteration
+        ));
+    }
+    if tectonics.repaired_point_masses > 0 {
+        eprintln!(
+            "Repaired {} invalid point masses",
+            tectonics.repaired_point_masses
+        );
+    }
+    println!(
+        "Tectonics: {} iterations ({:.2} simulated time) on the {}, final kinetic energy {:.5}",
+        tectonics.iteration,
+        tectonics.simulated_time,
+        if on_gpu { "GPU" } else { "CPU" },
+        tectonics.kinetic_energy()
+    );
+
+    let file = File::create(&args.output)
+        .map_err(|e| format!("Failed to create {}: {e}", args.output.display()))?;
+    let mut writer = BufWriter::new(file);
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {e}", args.output.display());
+    writeln!(writer, "tile,x,y,z,height,elevation_m").map_err(write_error)?;
+    for (tile, elevation) in planet.tiles.iter().zip(planet.elevations_m()) {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            tile.index, tile.normal.x, tile.normal.y, tile.normal.z, tile.height, elevation
+        )
+        .map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)?;
+    println!(
+        "Wrote the heights of {} tiles to {}",
+        planet.tiles.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
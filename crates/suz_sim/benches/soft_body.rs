@@ -1,8 +1,21 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use glam::{Quat, Vec3};
 use rand::SeedableRng;
+use soft_sphere::Solver;
 use suz_sim::{
+    config::SimulationConfig,
+    crust::CrustNoise,
+    interpolation::{height_updates, interpolate_tile_heights},
     particle_sphere::{ParticleSphere, ParticleSphereConfig},
+    sphere_tree::SphereTree,
+    spherical_grid::SphericalGrid,
     tectonics::{Tectonics, TectonicsConfiguration},
+    trails::TrailConfig,
+    units::EARTH_RADIUS_KM,
+    vec_utils::geodesic_distance,
+    vertex_interpolation::{apply_height_updates, move_tile_vertices},
 };
 
 const ITERATIONS: usize = 100;
@@ -20,12 +33,28 @@ fn tectonics_benchmark(c: &mut Criterion) {
         plate_force_modifier: 0.02,
         plate_rotation_drift_rate: 0.001,
         timestep: 0.3,
+        substeps: 0,
+        solver: Solver::VelocityVerlet,
         iterations: 500,
         friction_coefficient: 0.5,
+        merge_iterations: 0,
+        merge_speed: 0.,
+        adaptive_timestep: None,
+        duration: 0.,
+        strict_plate_count: false,
+        min_seed_separation: 0.,
+        planet_radius_km: EARTH_RADIUS_KM,
+        crust_noise: CrustNoise::default(),
+        isostasy: None,
+        trails: TrailConfig::default(),
+        validation: None,
+        torque: None,
+        driving_forces: None,
     };
     let mut rng = rand::rngs::StdRng::seed_from_u64(0);
-    let particle_sphere = ParticleSphere::from_config(ParticleSphereConfig { subdivisions: 32 });
-    let mut tectonics = Tectonics::from_config(tectonics_config, &particle_sphere, &mut rng);
+    let particle_sphere = ParticleSphere::from_config(ParticleSphereConfig::regular(32), &mut rng);
+    let mut tectonics = Tectonics::from_config(tectonics_config, &particle_sphere, &mut rng)
+        .expect("Invalid tectonics config");
     c.bench_function("Tectonics soft body simulation", |b| {
         b.iter(|| {
             for _ in 0..ITERATIONS {
@@ -35,5 +64,164 @@ fn tectonics_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, tectonics_benchmark);
+/// Compares a single thread against the global rayon pool at the default particle sphere resolution
+fn parallel_plates_benchmark(c: &mut Criterion) {
+    let config = SimulationConfig::default();
+    let particle_sphere = ParticleSphere::from_config(
+        ParticleSphereConfig::regular(64),
+        &mut rand::rngs::StdRng::seed_from_u64(0),
+    );
+    let mut group = c.benchmark_group("Tectonics step at 64 subdivisions");
+    group.sample_size(10);
+    for threads in [1, rayon::current_num_threads()] {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("Failed to build thread pool");
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        let mut tectonics = Tectonics::from_config(config.tectonics, &particle_sphere, &mut rng)
+            .expect("Invalid tectonics config");
+        group.bench_function(format!("{threads} threads"), |b| {
+            b.iter(|| pool.install(|| tectonics.simulate(&mut rng)));
+        });
+    }
+    group.finish();
+}
+
+/// Interpolation queries around every tile and a refresh after one step, for the tree and the fixed latitude/longitude grid it replaced
+fn point_mass_index_benchmark(c: &mut Criterion) {
+    let config = SimulationConfig::default();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let particle_sphere = ParticleSphere::from_config(ParticleSphereConfig::regular(64), &mut rng);
+    let mut tectonics = Tectonics::from_config(config.tectonics, &particle_sphere, &mut rng)
+        .expect("Invalid tectonics config");
+    let before = tectonics.plates.clone();
+    tectonics.simulate(&mut rng);
+    let radius = config.tectonics.vertex_interpolation_radius;
+    let mut group = c.benchmark_group("Point mass index at 64 subdivisions");
+    group.sample_size(10);
+    let grid = SphericalGrid::new(&tectonics.plates);
+    let tree = SphereTree::new(&tectonics.plates);
+    group.bench_function("SphericalGrid query_within", |b| {
+        b.iter(|| {
+            for tile in &particle_sphere.tiles {
+                black_box(grid.query_within(tile.normal, radius));
+            }
+        });
+    });
+    group.bench_function("SphereTree query_within", |b| {
+        b.iter(|| {
+            for tile in &particle_sphere.tiles {
+                black_box(tree.query_within(tile.normal, radius));
+            }
+        });
+    });
+    group.bench_function("SphericalGrid refresh", |b| {
+        b.iter_batched(
+            || SphericalGrid::new(&before),
+            |mut grid| grid.refresh(&tectonics.plates),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("SphereTree refresh", |b| {
+        b.iter_batched(
+            || SphereTree::new(&before),
+            |mut tree| tree.refresh(&tectonics.plates),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("SphereTree incremental refresh", |b| {
+        b.iter_batched(
+            || SphereTree::new(&before),
+            |mut tree| tree.refresh_incremental(&tectonics.plates),
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+/// Building the hex sphere tiles and mesh buffers, as done once per generated planet
+fn hex_sphere_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("HexSphere construction");
+    group.sample_size(10);
+    for subdivisions in [16, 32, 64] {
+        group.bench_function(format!("{subdivisions} subdivisions"), |b| {
+            b.iter(|| black_box(hex_sphere::HexSphere::new(subdivisions, None)));
+        });
+    }
+    group.finish();
+}
+
+/// Heights interpolated from the point masses and the mesh vertices they move, what the viewer does to show a step
+fn vertex_interpolation_benchmark(c: &mut Criterion) {
+    let config = SimulationConfig::default();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let particle_sphere = ParticleSphere::from_config(ParticleSphereConfig::regular(64), &mut rng);
+    let mut tectonics = Tectonics::from_config(config.tectonics, &particle_sphere, &mut rng)
+        .expect("Invalid tectonics config");
+    for _ in 0..ITERATIONS {
+        tectonics.simulate(&mut rng);
+    }
+    let hex_sphere = hex_sphere::HexSphere::new(64, None);
+    let normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+    let heights = interpolate_tile_heights(&tectonics, &normals);
+    let mut group = c.benchmark_group("Vertex interpolation at 64 subdivisions");
+    group.sample_size(10);
+    group.bench_function("interpolate_tile_heights", |b| {
+        b.iter(|| black_box(interpolate_tile_heights(&tectonics, &normals)));
+    });
+    group.bench_function("move_tile_vertices", |b| {
+        b.iter_batched(
+            || (hex_sphere.tiles.clone(), hex_sphere.vertices.clone()),
+            |(mut tiles, mut vertices)| {
+                let moved = apply_height_updates(&mut tiles, &height_updates(&tiles, &heights, 0.));
+                move_tile_vertices(&tiles, &mut vertices, &hex_sphere.vertices_to_tiles, &moved)
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+/// Distances between nearly coincident point masses, where precision is hardest to keep.
+/// Run with and without `--features f64` to compare, the tectonics benchmarks above show the cost on a whole step.
+/// Prints the largest error against a double precision atan2 reference.
+fn geodesic_precision_benchmark(c: &mut Criterion) {
+    let a = Vec3::new(0.3, 0.5, 0.8).normalize();
+    let pairs: Vec<(Vec3, Vec3)> = (1..=1000)
+        .map(|k| {
+            let angle = k as f32 * 1e-6;
+            (
+                a,
+                Quat::from_axis_angle(a.any_orthonormal_vector(), angle) * a,
+            )
+        })
+        .collect();
+    let max_error = pairs
+        .iter()
+        .map(|&(a, b)| {
+            let (a64, b64) = (a.as_dvec3(), b.as_dvec3());
+            let reference = a64.cross(b64).length().atan2(a64.dot(b64));
+            (f64::from(geodesic_distance(a, b)) - reference).abs()
+        })
+        .fold(0., f64::max);
+    println!("Largest geodesic distance error below 1e-3 radians: {max_error:e}");
+    c.bench_function("Geodesic distance of nearly coincident point masses", |b| {
+        b.iter(|| {
+            for &(a, b) in &pairs {
+                black_box(geodesic_distance(a, b));
+            }
+        });
+    });
+}
+
+criterion_group!(
+    benches,
+    tectonics_benchmark,
+    parallel_plates_benchmark,
+    point_mass_index_benchmark,
+    hex_sphere_benchmark,
+    vertex_interpolation_benchmark,
+    geodesic_precision_benchmark
+);
 criterion_main!(benches);
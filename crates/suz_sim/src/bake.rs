@@ -0,0 +1,159 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use glam::{Vec2, Vec3};
+use rayon::prelude::*;
+
+use crate::export::{ProjectedLayer, Projection, TileLayer, project_layer};
+
+/// Pixel steps of the directions searched for occluding terrain
+const OCCLUSION_DIRECTIONS: [(i64, i64); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// Parameters for baking textures from tile heights, heights are in planet radii like the tile heights themselves
+#[derive(Clone, Copy)]
+pub struct BakeConfig {
+    /// Scales slopes before they tilt the normal, higher exaggerates relief
+    pub normal_strength: f32,
+    /// Box blur radius in pixels applied to the heights first, so the steps between flat tiles do not show as creases
+    pub smoothing: u32,
+    /// Distance in pixels searched for occluding terrain in each direction
+    pub occlusion_radius: u32,
+    /// Scales the horizon slopes before they occlude, higher darkens valleys more
+    pub occlusion_strength: f32,
+}
+
+impl Default for BakeConfig {
+    fn default() -> Self {
+        BakeConfig {
+            normal_strength: 20.,
+            smoothing: 2,
+            occlusion_radius: 8,
+            occlusion_strength: 20.,
+        }
+    }
+}
+
+/// Equirectangular heights, smoothed
+struct HeightField {
+    width: u32,
+    height: u32,
+    heights: Vec<f32>,
+}
+
+impl HeightField {
+    fn new(layer: &TileLayer<f32>, resolution: u32, smoothing: u32) -> Self {
+        let projected = project_layer(layer, Projection::Equirectangular, resolution);
+        let mut field = HeightField {
+            width: projected.width,
+            height: projected.height,
+            // Equirectangular maps cover every pixel
+            heights: projected.pixels.into_iter().flatten().collect(),
+        };
+        let radius = smoothing as i64;
+        for offset in [(1, 0), (0, 1)] {
+            let heights: Vec<f32> = (0..field.height as i64)
+                .flat_map(|y| (0..field.width as i64).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    let sum: f32 = (-radius..=radius)
+                        .map(|step| field.at(x + step * offset.0, y + step * offset.1))
+                        .sum();
+                    sum / (2 * radius + 1) as f32
+                })
+                .collect();
+            field.heights = heights;
+        }
+        field
+    }
+
+    /// Height of pixel (`x`, `y`), wrapping around in longitude and clamped at the poles
+    fn at(&self, x: i64, y: i64) -> f32 {
+        let x = x.rem_euclid(self.width as i64);
+        let y = y.clamp(0, self.height as i64 - 1);
+        self.heights[(y * self.width as i64 + x) as usize]
+    }
+
+    /// Angular distance covered by a pixel of row `y` to the east and to the north
+    fn spacing(&self, y: u32) -> Vec2 {
+        let latitude = FRAC_PI_2 - (y as f32 + 0.5) / self.height as f32 * PI;
+        let north = PI / self.height as f32;
+        // Pixels shrink towards the poles, never to nothing
+        let east = 2. * PI / self.width as f32 * latitude.cos().max(north);
+        Vec2::new(east, north)
+    }
+
+    /// Every pixel mapped by `bake`, row by row from the north pole
+    fn bake<T: Send>(&self, bake: impl Fn(i64, i64, Vec2) -> T + Sync) -> ProjectedLayer<T> {
+        let pixels = (0..self.height)
+            .into_par_iter()
+            .flat_map_iter(|y| {
+                let spacing = self.spacing(y);
+                let bake = &bake;
+                (0..self.width).map(move |x| Some(bake(x as i64, y as i64, spacing)))
+            })
+            .collect();
+        ProjectedLayer {
+            width: self.width,
+            height: self.height,
+            pixels,
+        }
+    }
+}
+
+/// Bakes an equirectangular tangent-space normal map from the tile `heights`, `resolution` wide and half as tall.
+/// The tangent points along the image's x axis and the bitangent up the image towards the north pole, packed into RGB as `normal * 0.5 + 0.5`.
+pub fn bake_normal_map(
+    heights: &TileLayer<f32>,
+    resolution: u32,
+    config: &BakeConfig,
+) -> ProjectedLayer<[u8; 3]> {
+    let field = HeightField::new(heights, resolution, config.smoothing);
+    field.bake(|x, y, spacing| {
+        let slope = Vec2::new(
+            (field.at(x + 1, y) - field.at(x - 1, y)) / (2. * spacing.x),
+            (field.at(x, y - 1) - field.at(x, y + 1)) / (2. * spacing.y),
+        );
+        let normal = (-slope * config.normal_strength).extend(1.).normalize();
+        ((normal * 0.5 + Vec3::splat(0.5)) * 255.)
+            .round()
+            .to_array()
+            .map(|channel| channel as u8)
+    })
+}
+
+/// Bakes an equirectangular ambient occlusion map from the tile `heights`, `resolution` wide and half as tall, 255 being unoccluded.
+/// Each pixel looks for the highest horizon in eight directions within [BakeConfig::occlusion_radius] and darkens by how much sky they hide.
+pub fn bake_ambient_occlusion(
+    heights: &TileLayer<f32>,
+    resolution: u32,
+    config: &BakeConfig,
+) -> ProjectedLayer<u8> {
+    let field = HeightField::new(heights, resolution, config.smoothing);
+    field.bake(|x, y, spacing| {
+        let height = field.at(x, y);
+        let occlusion: f32 = OCCLUSION_DIRECTIONS
+            .iter()
+            .map(|&(dx, dy)| {
+                let horizon = (1..=config.occlusion_radius as i64)
+                    .map(|step| {
+                        let distance =
+                            (Vec2::new(dx as f32, dy as f32) * spacing * step as f32).length();
+                        (field.at(x + dx * step, y + dy * step) - height) / distance
+                    })
+                    .fold(0f32, f32::max);
+                // Sine of the horizon angle, the fraction of this direction's sky it hides
+                let slope = horizon * config.occlusion_strength;
+                slope / slope.hypot(1.)
+            })
+            .sum::<f32>()
+            / OCCLUSION_DIRECTIONS.len() as f32;
+        ((1. - occlusion) * 255.).round() as u8
+    })
+}
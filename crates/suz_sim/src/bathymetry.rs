@@ -0,0 +1,169 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    boundaries::{BoundaryType, PlateBoundaries},
+    distance_field::nearest_sources,
+    plate::PlateType,
+    tectonics::Tectonics,
+};
+
+/// Iterations between crust age updates when generating without the viewer, matching how often it classifies boundaries
+pub const CRUST_AGE_UPDATE_INTERVAL: usize = 10;
+
+/// Ocean floor heights from the age of the crust, after the half-space cooling model.
+/// New crust sits high at mid-ocean ridges and sinks with the square root of its age as it cools, until it levels off in the abyssal plains.
+/// Ages are in simulated time units, heights in tile height units.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct BathymetryConfig {
+    /// Height of crust formed just now, at mid-ocean ridges
+    pub ridge_height: f32,
+    /// Height lost per square root of crust age
+    pub subsidence_rate: f32,
+    /// Lowest height cooling sinks the ocean floor to
+    pub abyssal_height: f32,
+    /// Height removed from oceanic tiles on a convergent boundary, where they subduct into trenches.
+    /// Plates converging slower than [BathymetryConfig::trench_speed] get proportionally shallower trenches.
+    pub trench_depth: f32,
+    /// Geodesic distance from the boundary into the oceanic plate over which trenches taper off
+    #[serde(default = "default_trench_width")]
+    pub trench_width: f32,
+    /// Convergence speed at which trenches reach the full [BathymetryConfig::trench_depth]
+    #[serde(default = "default_trench_speed")]
+    pub trench_speed: f32,
+}
+
+fn default_trench_width() -> f32 {
+    0.02
+}
+
+fn default_trench_speed() -> f32 {
+    0.05
+}
+
+impl Default for BathymetryConfig {
+    fn default() -> Self {
+        BathymetryConfig {
+            ridge_height: 0.975,
+            subsidence_rate: 0.0035,
+            abyssal_height: 0.945,
+            trench_depth: 0.03,
+            trench_width: default_trench_width(),
+            trench_speed: default_trench_speed(),
+        }
+    }
+}
+
+impl BathymetryConfig {
+    /// Height of the ocean floor on crust of `age`
+    pub fn floor_height(&self, age: f32) -> f32 {
+        (self.ridge_height - self.subsidence_rate * age.max(0.).sqrt()).max(self.abyssal_height)
+    }
+
+    /// Depth of a trench right on a boundary where the plates converge at `speed`
+    pub fn boundary_trench_depth(&self, speed: f32) -> f32 {
+        self.trench_depth * (speed / self.trench_speed).clamp(0., 1.)
+    }
+}
+
+/// Simulated time each tile's crust formed at, renewed whenever the tile sits on a divergent boundary
+#[derive(Clone, Default)]
+pub struct CrustAge {
+    formed_at: Vec<f32>,
+    /// Age of each tile's crust at the last update
+    pub ages: Vec<f32>,
+}
+
+impl CrustAge {
+    /// Tiles on a divergent boundary get new crust at `simulated_time`, tiles never updated before count as formed at the start
+    pub fn update(&mut self, boundaries: &[Option<BoundaryType>], simulated_time: f32) {
+        self.formed_at.resize(boundaries.len(), 0.);
+        for (formed_at, boundary) in self.formed_at.iter_mut().zip(boundaries) {
+            if *boundary == Some(BoundaryType::Divergent) {
+                *formed_at = simulated_time;
+            }
+        }
+        self.ages = self
+            .formed_at
+            .iter()
+            .map(|formed_at| simulated_time - formed_at)
+            .collect();
+    }
+}
+
+/// Depth of the trench under every tile, in a band on the oceanic side of each convergent boundary.
+/// Trenches are deepest on the boundary, as deep as [BathymetryConfig::trench_depth] allows for the convergence speed there,
+/// and taper off over [BathymetryConfig::trench_width] measured along the oceanic tiles given by `adjacent`.
+pub fn trench_depths<'a>(
+    config: &BathymetryConfig,
+    boundaries: &PlateBoundaries,
+    oceanic: &[bool],
+    normals: &[Vec3],
+    adjacent: impl Fn(usize) -> &'a [usize],
+) -> Vec<f32> {
+    let mut boundary_depths = vec![0.; normals.len()];
+    for segment in &boundaries.segments {
+        if segment.boundary_type != BoundaryType::Convergent {
+            continue;
+        }
+        let depth = config.boundary_trench_depth(-segment.normal_speed);
+        for tile in segment.tiles {
+            if oceanic[tile] {
+                boundary_depths[tile] = f32::max(boundary_depths[tile], depth);
+            }
+        }
+    }
+    let trenches = boundary_depths
+        .iter()
+        .enumerate()
+        .filter(|(_, depth)| **depth > 0.)
+        .map(|(tile, depth)| (tile, *depth));
+    nearest_sources(trenches, normals, adjacent, config.trench_width, |tile| {
+        oceanic[tile]
+    })
+    .into_iter()
+    .map(|nearest| match nearest {
+        Some((distance, depth)) if distance > 0. => depth * (1. - distance / config.trench_width),
+        // The boundary tiles themselves, the only ones reached with a width of 0
+        Some((_, depth)) => depth,
+        None => 0.,
+    })
+    .collect()
+}
+
+/// Replaces the flat `oceanic_height` of `oceanic` tiles with the floor height of their `crust_age`, and deepens them by their `trench_depths`.
+/// Offsets from the flat height like compression and crust thickness are kept. Tiles without an age yet are left as they are.
+/// `oceanic_height` is [crate::tectonics::TectonicsConfiguration::plate_height] of oceanic crust.
+pub fn apply_bathymetry(
+    config: &BathymetryConfig,
+    oceanic_height: f32,
+    heights: &mut [f32],
+    oceanic: &[bool],
+    crust_age: &[f32],
+    trench_depths: &[f32],
+) {
+    for (((height, &oceanic), &age), trench_depth) in heights
+        .iter_mut()
+        .zip(oceanic)
+        .zip(crust_age)
+        .zip(trench_depths)
+    {
+        if !oceanic {
+            continue;
+        }
+        *height += config.floor_height(age) - oceanic_height - trench_depth;
+    }
+}
+
+/// Whether each tile lies on an oceanic plate, given the index into [Tectonics::plates] of the plate under each tile
+pub fn oceanic_tiles(tectonics: &Tectonics, tile_plates: &[usize]) -> Vec<bool> {
+    tile_plates
+        .iter()
+        .map(|&plate| {
+            tectonics
+                .plates
+                .get(plate)
+                .is_some_and(|plate| plate.plate_type == PlateType::Oceanic)
+        })
+        .collect()
+}
@@ -0,0 +1,123 @@
+use glam::Vec3;
+use rayon::prelude::*;
+
+use crate::tectonics::Tectonics;
+
+/// How two plates move relative to each other where they touch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryType {
+    /// Plates move towards each other, builds mountains and subduction zones
+    Convergent,
+    /// Plates move apart, builds rifts and ridges
+    Divergent,
+    /// Plates slide past each other
+    Transform,
+}
+
+impl std::fmt::Display for BoundaryType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoundaryType::Convergent => write!(f, "Convergent"),
+            BoundaryType::Divergent => write!(f, "Divergent"),
+            BoundaryType::Transform => write!(f, "Transform"),
+        }
+    }
+}
+
+/// The contact between two adjacent tiles owned by different plates
+#[derive(Clone, Copy)]
+pub struct BoundarySegment {
+    pub tiles: [usize; 2],
+    /// Plate of each tile, indices into [Tectonics::plates]
+    pub plates: [usize; 2],
+    pub boundary_type: BoundaryType,
+    /// Relative velocity along the line between the tiles, negative when the plates converge
+    pub normal_speed: f32,
+    /// Relative velocity across the line between the tiles
+    pub tangential_speed: f32,
+}
+
+/// Every plate boundary segment of a tiling, with the strongest boundary type touching each tile
+#[derive(Default)]
+pub struct PlateBoundaries {
+    pub segments: Vec<BoundarySegment>,
+    /// One entry per tile, `None` for tiles away from any boundary
+    pub tiles: Vec<Option<BoundaryType>>,
+}
+
+impl PlateBoundaries {
+    /// Classifies the boundaries between tiles given by their `normals` and `adjacent` tiles.
+    /// Each tile takes the plate and velocity of its nearest point mass, a boundary is convergent or divergent
+    /// when the relative velocity is mostly along the line between the tiles and transform otherwise.
+    pub fn classify<'a>(
+        tectonics: &Tectonics,
+        normals: &[Vec3],
+        adjacent: impl Fn(usize) -> &'a [usize],
+    ) -> Self {
+        let nearest: Vec<(usize, Vec3)> = normals
+            .par_iter()
+            .map(|normal| {
+                tectonics
+                    .tree
+                    .nearest(*normal)
+                    .map_or((0, Vec3::ZERO), |handle| {
+                        let point_mass =
+                            &tectonics.plates[handle.plate].shape.point_masses()[handle.point_mass];
+                        (handle.plate, point_mass.velocity)
+                    })
+            })
+            .collect();
+
+        let mut segments = Vec::new();
+        let mut tiles = vec![None; normals.len()];
+        for tile_a in 0..normals.len() {
+            let (plate_a, velocity_a) = nearest[tile_a];
+            // Each pair is visited from its lower index only
+            for &tile_b in adjacent(tile_a).iter().filter(|&&tile_b| tile_b > tile_a) {
+                let (plate_b, velocity_b) = nearest[tile_b];
+                if plate_a == plate_b {
+                    continue;
+                }
+                let direction = (normals[tile_b] - normals[tile_a]).normalize_or_zero();
+                let relative_velocity = velocity_b - velocity_a;
+                let normal_speed = relative_velocity.dot(direction);
+                let tangential_speed = (relative_velocity - direction * normal_speed).length();
+                let boundary_type = if tangential_speed > normal_speed.abs() {
+                    BoundaryType::Transform
+                } else if normal_speed < 0. {
+                    BoundaryType::Convergent
+                } else {
+                    BoundaryType::Divergent
+                };
+                for tile in [tile_a, tile_b] {
+                    tiles[tile] = Some(match (tiles[tile], boundary_type) {
+                        // Convergence shapes the crust the most, transform the least
+                        (Some(BoundaryType::Convergent), _) | (_, BoundaryType::Convergent) => {
+                            BoundaryType::Convergent
+                        }
+                        (Some(BoundaryType::Divergent), _) | (_, BoundaryType::Divergent) => {
+                            BoundaryType::Divergent
+                        }
+                        _ => BoundaryType::Transform,
+                    });
+                }
+                segments.push(BoundarySegment {
+                    tiles: [tile_a, tile_b],
+                    plates: [plate_a, plate_b],
+                    boundary_type,
+                    normal_speed,
+                    tangential_speed,
+                });
+            }
+        }
+        PlateBoundaries { segments, tiles }
+    }
+
+    /// How many segments of the given type there are
+    pub fn count(&self, boundary_type: BoundaryType) -> usize {
+        self.segments
+            .iter()
+            .filter(|segment| segment.boundary_type == boundary_type)
+            .count()
+    }
+}
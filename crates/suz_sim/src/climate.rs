@@ -0,0 +1,163 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::vec_utils;
+
+/// Axial tilt in degrees that [ClimateConfig::equator_temperature] and [ClimateConfig::pole_temperature] are given for
+const EARTH_AXIAL_TILT: f32 = 23.44;
+
+/// Mean annual surface temperature from latitude and elevation, and precipitation from wind-blown moisture
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ClimateConfig {
+    /// Angle between the rotation axis and the orbital plane normal, in degrees.
+    /// Larger tilts spread sunlight towards the poles
+    pub axial_tilt: f32,
+    /// Sea level temperature at the equator in °C, for an Earth-like tilt
+    pub equator_temperature: f32,
+    /// Sea level temperature at the poles in °C, for an Earth-like tilt
+    pub pole_temperature: f32,
+    /// Cooling in °C per unit of tile height above sea level
+    pub lapse_rate: f32,
+    /// Moisture picked up by the air over each ocean tile per step
+    pub evaporation: f32,
+    /// [0,1] Fraction of its moisture the air rains out over flat ground per step
+    pub rain_rate: f32,
+    /// Extra fraction rained out per unit of height the air has to climb to the next tiles
+    pub orographic_rate: f32,
+    /// Fraction rained out less per unit of height the air descends, drying the lee of mountains
+    pub rain_shadow: f32,
+    /// How many steps moisture is carried downwind for, roughly how many tiles it can travel inland
+    pub moisture_steps: usize,
+}
+
+impl Default for ClimateConfig {
+    fn default() -> Self {
+        ClimateConfig {
+            axial_tilt: EARTH_AXIAL_TILT,
+            equator_temperature: 27.,
+            pole_temperature: -25.,
+            lapse_rate: 650.,
+            evaporation: 1.,
+            rain_rate: 0.05,
+            orographic_rate: 40.,
+            rain_shadow: 20.,
+            moisture_steps: 60,
+        }
+    }
+}
+
+/// Annual mean sunlight at `sin_latitude`, relative to the global mean, after North (1975).
+/// The second Legendre polynomial term flattens as the tilt grows, and flips sign past 54.7°.
+fn annual_insolation(axial_tilt: f32, sin_latitude: f32) -> f32 {
+    let p2 = |x: f32| (3. * x * x - 1.) / 2.;
+    let s2 = -5. / 8. * p2(axial_tilt.to_radians().cos());
+    1. + s2 * p2(sin_latitude)
+}
+
+/// Temperature of each tile given by its unit sphere `normals` and `heights`, the y axis is the rotation axis.
+/// Temperature is linear in sunlight, calibrated so an Earth-like tilt gives the configured equator and pole temperatures.
+pub fn tile_temperatures(
+    config: &ClimateConfig,
+    normals: &[Vec3],
+    heights: &[f32],
+    sea_level: f32,
+) -> Vec<f32> {
+    let equator_insolation = annual_insolation(EARTH_AXIAL_TILT, 0.);
+    let pole_insolation = annual_insolation(EARTH_AXIAL_TILT, 1.);
+    let degrees_per_insolation = (config.equator_temperature - config.pole_temperature)
+        / (equator_insolation - pole_insolation);
+    normals
+        .iter()
+        .zip(heights)
+        .map(|(normal, height)| {
+            let insolation = annual_insolation(config.axial_tilt, normal.y.clamp(-1., 1.));
+            let sea_level_temperature =
+                config.pole_temperature + (insolation - pole_insolation) * degrees_per_insolation;
+            sea_level_temperature - config.lapse_rate * (height - sea_level).max(0.)
+        })
+        .collect()
+}
+
+/// Direction the air moves in at `normal`, from the three circulation cells of each hemisphere.
+/// Trade winds and polar easterlies blow west and towards the equator, the westerlies between them blow east and poleward.
+pub fn prevailing_wind(normal: Vec3) -> Vec3 {
+    let (east, north) = vec_utils::tangent_frame(normal);
+    let latitude = vec_utils::vec3_to_lat_long(normal).0.to_degrees();
+    let poleward = north * latitude.signum();
+    let wind = if (30.0..60.0).contains(&latitude.abs()) {
+        east + 0.5 * poleward
+    } else {
+        -east - 0.5 * poleward
+    };
+    wind.normalize_or_zero()
+}
+
+/// Mean precipitation per step of each tile given by its unit sphere `normals`, `heights` and `adjacent` tiles.
+/// Air picks up moisture over the ocean and is carried downwind by [prevailing_wind], raining out a fraction of it on every tile.
+/// Air rising onto higher tiles rains out more and air descending rains out less, leaving rain shadows behind mountains.
+pub fn tile_precipitation<'a>(
+    config: &ClimateConfig,
+    normals: &[Vec3],
+    heights: &[f32],
+    sea_level: f32,
+    adjacent: impl Fn(usize) -> &'a [usize],
+) -> Vec<f32> {
+    // Share of the outgoing air each downwind neighbour receives, by how well it lines up with the wind
+    let downwind: Vec<Vec<(usize, f32)>> = normals
+        .iter()
+        .enumerate()
+        .map(|(tile, normal)| {
+            let wind = prevailing_wind(*normal);
+            let alignments: Vec<(usize, f32)> = adjacent(tile)
+                .iter()
+                .filter(|&&other| other != tile)
+                .filter_map(|&other| {
+                    let alignment = (normals[other] - *normal).normalize_or_zero().dot(wind);
+                    (alignment > 0.).then_some((other, alignment))
+                })
+                .collect();
+            let total: f32 = alignments.iter().map(|(_, alignment)| alignment).sum();
+            alignments
+                .into_iter()
+                .map(|(other, alignment)| (other, alignment / total))
+                .collect()
+        })
+        .collect();
+    let rain_fractions: Vec<f32> = downwind
+        .iter()
+        .enumerate()
+        .map(|(tile, neighbours)| {
+            let slope: f32 = neighbours
+                .iter()
+                .map(|&(other, share)| share * (heights[other] - heights[tile]))
+                .sum();
+            let orographic = if slope > 0. {
+                config.orographic_rate * slope
+            } else {
+                config.rain_shadow * slope
+            };
+            (config.rain_rate + orographic).clamp(0., 1.)
+        })
+        .collect();
+
+    let mut moisture = vec![0.; normals.len()];
+    let mut precipitation = vec![0.; normals.len()];
+    for _ in 0..config.moisture_steps {
+        let mut carried = vec![0.; normals.len()];
+        for tile in 0..normals.len() {
+            let mut air = moisture[tile];
+            if heights[tile] < sea_level {
+                air += config.evaporation;
+            }
+            let rain = air * rain_fractions[tile];
+            precipitation[tile] += rain;
+            air -= rain;
+            for &(other, share) in &downwind[tile] {
+                carried[other] += air * share;
+            }
+        }
+        moisture = carried;
+    }
+    let steps = config.moisture_steps.max(1) as f32;
+    precipitation.iter().map(|rain| rain / steps).collect()
+}
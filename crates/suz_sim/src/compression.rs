@@ -0,0 +1,112 @@
+use glam::Vec3;
+use rayon::prelude::*;
+
+use crate::{plate::Plate, sphere_tree::SphereTree};
+
+/// Updates between full rebuilds of a [CompressionField], so rounding in the incremental sums cannot build up
+const REBUILD_INTERVAL: usize = 64;
+
+/// Summed compression of the springs anchored to each point mass, kept between interpolations instead of being summed from scratch.
+/// [CompressionField::update] only touches the point masses of springs whose compression changed.
+#[derive(Clone, Default)]
+pub struct CompressionField {
+    /// Compression of every spring as of the last update, per plate in spring order
+    springs: Vec<Vec<f32>>,
+    /// Summed compression of every point mass, per plate in point mass order
+    point_masses: Vec<Vec<f32>>,
+    updates_since_rebuild: usize,
+}
+
+impl CompressionField {
+    pub fn new(plates: &[Plate]) -> Self {
+        let mut field = CompressionField::default();
+        field.rebuild(plates);
+        field
+    }
+
+    /// Compression of every point mass, per plate in the same order as [crate::tectonics::Tectonics::plates]
+    pub fn point_mass_compression(&self) -> &[Vec<f32>] {
+        &self.point_masses
+    }
+
+    /// Catches up with the springs of `plates`.
+    /// Plates whose springs or point masses were added or removed are summed again from scratch, as is every plate now and then.
+    pub fn update(&mut self, plates: &[Plate]) {
+        self.updates_since_rebuild += 1;
+        let changed_plates = self.springs.len() != plates.len()
+            || plates
+                .iter()
+                .zip(&self.springs)
+                .zip(&self.point_masses)
+                .any(|((plate, springs), point_masses)| {
+                    springs.len() != plate.shape.springs().len()
+                        || point_masses.len() != plate.shape.point_masses().len()
+                });
+        if changed_plates || self.updates_since_rebuild >= REBUILD_INTERVAL {
+            self.rebuild(plates);
+            return;
+        }
+        self.springs
+            .par_iter_mut()
+            .zip(&mut self.point_masses)
+            .zip(plates)
+            .for_each(|((springs, point_masses), plate)| {
+                for (compression, (spring, new_compression)) in
+                    springs.iter_mut().zip(spring_compressions(plate))
+                {
+                    let change = new_compression - *compression;
+                    if change != 0. {
+                        point_masses[spring.anchor_a] += change;
+                        point_masses[spring.anchor_b] += change;
+                        *compression = new_compression;
+                    }
+                }
+            });
+    }
+
+    fn rebuild(&mut self, plates: &[Plate]) {
+        self.updates_since_rebuild = 0;
+        (self.springs, self.point_masses) = plates
+            .par_iter()
+            .map(|plate| {
+                let mut point_masses = vec![0.; plate.shape.point_masses().len()];
+                let springs = spring_compressions(plate)
+                    .map(|(spring, compression)| {
+                        point_masses[spring.anchor_a] += compression;
+                        point_masses[spring.anchor_b] += compression;
+                        compression
+                    })
+                    .collect();
+                (springs, point_masses)
+            })
+            .unzip();
+    }
+
+    /// Point masses within `radius` of `normal`, with their distance and compression
+    pub fn query_within<'a>(
+        &'a self,
+        tree: &'a SphereTree,
+        normal: Vec3,
+        radius: f32,
+    ) -> impl Iterator<Item = (f32, f32)> + 'a {
+        tree.query_within(normal, radius)
+            .into_iter()
+            .filter_map(|(handle, distance)| {
+                let compression = self
+                    .point_masses
+                    .get(handle.plate)?
+                    .get(handle.point_mass)?;
+                Some((distance, *compression))
+            })
+    }
+}
+
+/// Rest length minus the current length of every spring of `plate`, positive when compressed
+fn spring_compressions(plate: &Plate) -> impl Iterator<Item = (&soft_sphere::Spring, f32)> + '_ {
+    let point_masses = plate.shape.point_masses();
+    plate.shape.springs().iter().map(move |spring| {
+        let pm_a = &point_masses[spring.anchor_a];
+        let pm_b = &point_masses[spring.anchor_b];
+        (spring, spring.rest_length - pm_a.geodesic_distance(pm_b))
+    })
+}
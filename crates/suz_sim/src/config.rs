@@ -0,0 +1,451 @@
+use std::{fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
+use soft_sphere::Solver;
+
+use crate::{
+    bathymetry::BathymetryConfig, climate::ClimateConfig, crust::CrustNoise, detail::DetailConfig,
+    epochs::EpochConfig, flexure::FlexureConfig, ice::IceConfig, island_arcs::IslandArcConfig,
+    particle_sphere::ParticleSphereConfig, shelf::ShelfConfig, tectonics::TectonicsConfiguration,
+    trails::TrailConfig, units::EARTH_RADIUS_KM,
+};
+
+/// Configuration of the rendered hex sphere mesh
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct HexSphereConfig {
+    pub subdivisions: u32,
+}
+
+/// Every parameter of the planet generation pipeline, loadable from a single TOML file.
+/// Sections added after the first config format are `#[serde(default)]`, so older configs still load.
+/// Those that are an `Option` skip their pass when missing, the rest fall back to their [Default].
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    pub hex_sphere: HexSphereConfig,
+    pub particle_sphere: ParticleSphereConfig,
+    pub tectonics: TectonicsConfiguration,
+    /// Foreland basins and rims flexed into the crust next to mountain belts
+    #[serde(default)]
+    pub flexure: FlexureConfig,
+    /// Temperature and precipitation of every tile
+    #[serde(default)]
+    pub climate: ClimateConfig,
+    /// Glaciers and the valleys they carve
+    #[serde(default)]
+    pub ice: IceConfig,
+    /// Rounds of tectonics and erosion, the default is a single epoch
+    #[serde(default)]
+    pub epochs: EpochConfig,
+    /// Ocean floor depth from crust age, without it the ocean floor keeps the flat oceanic height
+    #[serde(default)]
+    pub bathymetry: Option<BathymetryConfig>,
+    /// Continental shelves and slopes, without it coasts drop straight from continental to oceanic height
+    #[serde(default)]
+    pub shelf: Option<ShelfConfig>,
+    /// Island arcs raised over ocean-ocean subduction zones
+    #[serde(default)]
+    pub island_arcs: Option<IslandArcConfig>,
+    /// Ridged noise layered onto the finished terrain, the default amplitude of 0 skips it
+    #[serde(default)]
+    pub detail: DetailConfig,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Serialize(toml::ser::Error),
+    /// A field was parsed but holds a value the simulation can not run with
+    Invalid {
+        field: &'static str,
+        reason: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{e}"),
+            ConfigError::Parse(e) => write!(f, "{e}"),
+            ConfigError::Serialize(e) => write!(f, "{e}"),
+            ConfigError::Invalid { field, reason } => write!(f, "Invalid `{field}`: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            hex_sphere: HexSphereConfig { subdivisions: 128 },
+            particle_sphere: ParticleSphereConfig::regular(64),
+            tectonics: TectonicsConfiguration {
+                major_plate_fraction: 0.3,
+                major_tile_fraction: 0.4,
+                plate_goal: 30,
+                continental_rate: 0.4,
+                min_plate_size: 15,
+                vertex_interpolation_radius: 0.10,
+                spring_constant: 2.0,
+                dampener_coefficient: 0.5,
+                plate_force_modifier: 0.04,
+                plate_rotation_drift_rate: 0.001,
+                timestep: 0.10,
+                substeps: 0,
+                solver: Solver::VelocityVerlet,
+                iterations: 200,
+                friction_coefficient: 0.6,
+                merge_iterations: 0,
+                merge_speed: 0.01,
+                adaptive_timestep: None,
+                duration: 0.,
+                strict_plate_count: false,
+                min_seed_separation: 0.,
+                planet_radius_km: EARTH_RADIUS_KM,
+                crust_noise: CrustNoise::default(),
+                isostasy: None,
+                trails: TrailConfig::default(),
+                validation: None,
+                torque: None,
+                driving_forces: None,
+            },
+            flexure: FlexureConfig::default(),
+            climate: ClimateConfig::default(),
+            ice: IceConfig::default(),
+            epochs: EpochConfig::default(),
+            bathymetry: None,
+            shelf: None,
+            island_arcs: None,
+            detail: DetailConfig::default(),
+        }
+    }
+}
+
+/// A named starting point for planet generation
+#[derive(Clone)]
+pub struct Preset {
+    pub name: String,
+    pub description: String,
+    pub config: SimulationConfig,
+}
+
+impl Preset {
+    fn new(name: &str, description: &str, config: SimulationConfig) -> Self {
+        Preset {
+            name: name.to_string(),
+            description: description.to_string(),
+            config,
+        }
+    }
+
+    /// The built-in presets, the first one uses [SimulationConfig::default]
+    pub fn all() -> Vec<Preset> {
+        let default = SimulationConfig::default();
+        vec![
+            Preset::new(
+                "Earth-like",
+                "A mix of major and minor plates, roughly 40% continental",
+                default,
+            ),
+            Preset::new(
+                "Pangaea",
+                "Few large plates with most continental crust gathered together",
+                SimulationConfig {
+                    tectonics: TectonicsConfiguration {
+                        plate_goal: 12,
+                        major_plate_fraction: 0.5,
+                        major_tile_fraction: 0.8,
+                        continental_rate: 0.45,
+                        ..default.tectonics
+                    },
+                    ..default
+                },
+            ),
+            Preset::new(
+                "Archipelago",
+                "Many small plates scattering continental crust into islands",
+                SimulationConfig {
+                    tectonics: TectonicsConfiguration {
+                        plate_goal: 60,
+                        major_plate_fraction: 0.2,
+                        major_tile_fraction: 0.2,
+                        continental_rate: 0.25,
+                        ..default.tectonics
+                    },
+                    ..default
+                },
+            ),
+            Preset::new(
+                "Water world",
+                "Almost entirely oceanic crust with a handful of small landmasses",
+                SimulationConfig {
+                    tectonics: TectonicsConfiguration {
+                        continental_rate: 0.1,
+                        ..default.tectonics
+                    },
+                    ..default
+                },
+            ),
+        ]
+    }
+}
+
+fn unit_interval(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if (0.0..=1.0).contains(&value) {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: format!("{value} is not within [0, 1]"),
+        })
+    }
+}
+
+fn open_unit_interval(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if value > 0.0 && value < 1.0 {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: format!("{value} is not within (0, 1)"),
+        })
+    }
+}
+
+fn positive(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if value > 0.0 {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: format!("{value} must be larger than 0"),
+        })
+    }
+}
+
+fn non_negative(field: &'static str, value: f32) -> Result<(), ConfigError> {
+    if value >= 0.0 {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: format!("{value} must not be negative"),
+        })
+    }
+}
+
+fn non_zero(field: &'static str, value: usize) -> Result<(), ConfigError> {
+    if value > 0 {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid {
+            field,
+            reason: "must be larger than 0".to_string(),
+        })
+    }
+}
+
+impl SimulationConfig {
+    pub fn from_toml_str(input: &str) -> Result<Self, ConfigError> {
+        let config: SimulationConfig = toml::from_str(input).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, ConfigError> {
+        toml::to_string_pretty(self).map_err(ConfigError::Serialize)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Self::from_toml_str(&std::fs::read_to_string(path).map_err(ConfigError::Io)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        std::fs::write(path, self.to_toml_string()?).map_err(ConfigError::Io)
+    }
+
+    /// Checks that every field is within the range the simulation expects
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        non_zero(
+            "hex_sphere.subdivisions",
+            self.hex_sphere.subdivisions as usize,
+        )?;
+        non_zero(
+            "particle_sphere.subdivisions",
+            self.particle_sphere.subdivisions as usize,
+        )?;
+        // Further and tiles can swap places, which the rebuilt adjacency does not account for
+        if !(0.0..=0.5).contains(&self.particle_sphere.jitter) {
+            return Err(ConfigError::Invalid {
+                field: "particle_sphere.jitter",
+                reason: format!("{} is not within [0, 0.5]", self.particle_sphere.jitter),
+            });
+        }
+        self.tectonics.validate()?;
+        let flexure = &self.flexure;
+        non_negative("flexure.deflection_ratio", flexure.deflection_ratio)?;
+        positive("flexure.flexural_parameter", flexure.flexural_parameter)?;
+        unit_interval("flexure.sediment_fill", flexure.sediment_fill)?;
+        let climate = &self.climate;
+        if !(0.0..=90.0).contains(&climate.axial_tilt) {
+            return Err(ConfigError::Invalid {
+                field: "climate.axial_tilt",
+                reason: format!("{} is not within [0, 90] degrees", climate.axial_tilt),
+            });
+        }
+        non_negative("climate.lapse_rate", climate.lapse_rate)?;
+        non_negative("climate.evaporation", climate.evaporation)?;
+        unit_interval("climate.rain_rate", climate.rain_rate)?;
+        non_negative("climate.orographic_rate", climate.orographic_rate)?;
+        non_negative("climate.rain_shadow", climate.rain_shadow)?;
+        let ice = &self.ice;
+        non_negative("ice.accumulation_rate", ice.accumulation_rate)?;
+        non_negative("ice.melt_rate", ice.melt_rate)?;
+        unit_interval("ice.flow_fraction", ice.flow_fraction)?;
+        non_negative("ice.erosion_rate", ice.erosion_rate)?;
+        non_zero("epochs.epochs", self.epochs.epochs)?;
+        if let Some(bathymetry) = &self.bathymetry {
+            non_negative("bathymetry.subsidence_rate", bathymetry.subsidence_rate)?;
+            non_negative("bathymetry.trench_depth", bathymetry.trench_depth)?;
+            non_negative("bathymetry.trench_width", bathymetry.trench_width)?;
+            positive("bathymetry.trench_speed", bathymetry.trench_speed)?;
+            if bathymetry.abyssal_height > bathymetry.ridge_height {
+                return Err(ConfigError::Invalid {
+                    field: "bathymetry.abyssal_height",
+                    reason: format!(
+                        "{} is above ridge_height {}",
+                        bathymetry.abyssal_height, bathymetry.ridge_height
+                    ),
+                });
+            }
+        }
+        if let Some(shelf) = &self.shelf {
+            non_negative("shelf.width", shelf.width)?;
+            non_negative("shelf.break_depth", shelf.break_depth)?;
+            non_negative("shelf.slope_width", shelf.slope_width)?;
+        }
+        if let Some(island_arcs) = &self.island_arcs {
+            non_negative("island_arcs.offset", island_arcs.offset)?;
+            positive("island_arcs.width", island_arcs.width)?;
+            non_negative("island_arcs.uplift_rate", island_arcs.uplift_rate)?;
+            non_negative("island_arcs.max_uplift", island_arcs.max_uplift)?;
+        }
+        non_negative("detail.amplitude", self.detail.amplitude)?;
+        positive("detail.frequency", self.detail.frequency)?;
+        unit_interval("detail.flat_roughness", self.detail.flat_roughness)?;
+        Ok(())
+    }
+}
+
+impl TectonicsConfiguration {
+    /// Checks every tectonics parameter and the ones that depend on each other, so plate generation cannot fail halfway
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        non_zero("tectonics.plate_goal", self.plate_goal)?;
+        // Plate seeding divides by both fractions and their complements, so neither plate kind may be left out
+        open_unit_interval("tectonics.major_plate_fraction", self.major_plate_fraction)?;
+        open_unit_interval("tectonics.major_tile_fraction", self.major_tile_fraction)?;
+        unit_interval("tectonics.continental_rate", self.continental_rate)?;
+        positive(
+            "tectonics.vertex_interpolation_radius",
+            self.vertex_interpolation_radius,
+        )?;
+        non_negative("tectonics.spring_constant", self.spring_constant)?;
+        non_negative("tectonics.dampener_coefficient", self.dampener_coefficient)?;
+        non_negative(
+            "tectonics.plate_rotation_drift_rate",
+            self.plate_rotation_drift_rate,
+        )?;
+        positive("tectonics.timestep", self.timestep)?;
+        non_negative("tectonics.friction_coefficient", self.friction_coefficient)?;
+        non_negative("tectonics.merge_speed", self.merge_speed)?;
+        non_negative("tectonics.duration", self.duration)?;
+        non_negative("tectonics.min_seed_separation", self.min_seed_separation)?;
+        positive("tectonics.planet_radius_km", self.planet_radius_km)?;
+        non_negative(
+            "tectonics.crust_noise.amplitude",
+            self.crust_noise.amplitude,
+        )?;
+        positive(
+            "tectonics.crust_noise.frequency",
+            self.crust_noise.frequency,
+        )?;
+        if let Some(isostasy) = &self.isostasy {
+            non_negative(
+                "tectonics.isostasy.continental_thickness",
+                isostasy.continental_thickness,
+            )?;
+            non_negative(
+                "tectonics.isostasy.oceanic_thickness",
+                isostasy.oceanic_thickness,
+            )?;
+            non_negative(
+                "tectonics.isostasy.compression_thickening",
+                isostasy.compression_thickening,
+            )?;
+            positive(
+                "tectonics.isostasy.continental_density",
+                isostasy.continental_density,
+            )?;
+            positive(
+                "tectonics.isostasy.oceanic_density",
+                isostasy.oceanic_density,
+            )?;
+            let densest_crust = isostasy.continental_density.max(isostasy.oceanic_density);
+            if isostasy.mantle_density <= densest_crust {
+                return Err(ConfigError::Invalid {
+                    field: "tectonics.isostasy.mantle_density",
+                    reason: format!(
+                        "{} must be larger than the densest crust {densest_crust}",
+                        isostasy.mantle_density
+                    ),
+                });
+            }
+        }
+        non_zero("tectonics.trails.interval", self.trails.interval)?;
+        if let Some(validation) = &self.validation {
+            positive("tectonics.validation.max_speed", validation.max_speed)?;
+            positive(
+                "tectonics.validation.max_radius_error",
+                validation.max_radius_error,
+            )?;
+        }
+        if let Some(torque) = &self.torque {
+            non_negative(
+                "tectonics.torque.collision_resistance",
+                torque.collision_resistance,
+            )?;
+            non_negative("tectonics.torque.slab_pull", torque.slab_pull)?;
+            positive("tectonics.torque.max_spin", torque.max_spin)?;
+        }
+        if let Some(driving) = &self.driving_forces {
+            non_negative("tectonics.driving_forces.slab_pull", driving.slab_pull)?;
+            non_negative("tectonics.driving_forces.ridge_push", driving.ridge_push)?;
+            positive("tectonics.driving_forces.range", driving.range)?;
+            unit_interval(
+                "tectonics.driving_forces.rotation_forcing",
+                driving.rotation_forcing,
+            )?;
+        }
+        if let Some(adaptive) = &self.adaptive_timestep {
+            positive(
+                "tectonics.adaptive_timestep.target_displacement",
+                adaptive.target_displacement,
+            )?;
+            positive(
+                "tectonics.adaptive_timestep.min_timestep",
+                adaptive.min_timestep,
+            )?;
+            if adaptive.max_timestep < adaptive.min_timestep {
+                return Err(ConfigError::Invalid {
+                    field: "tectonics.adaptive_timestep.max_timestep",
+                    reason: format!(
+                        "{} is below min_timestep {}",
+                        adaptive.max_timestep, adaptive.min_timestep
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use glam::Vec3;
+use noise::{Fbm, MultiFractal, NoiseFn, Simplex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Variation of the initial crust thickness of every point mass, on top of the flat height of its plate type
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct CrustNoise {
+    /// Largest height offset from the plate type height, 0 gives every plate a flat crust
+    pub amplitude: f32,
+    /// Frequency of the first octave on the unit sphere, higher gives smaller features
+    pub frequency: f32,
+    /// Octaves of simplex noise summed, each at twice the frequency and half the amplitude of the last
+    pub octaves: usize,
+}
+
+impl Default for CrustNoise {
+    fn default() -> Self {
+        CrustNoise {
+            amplitude: 0.,
+            frequency: 2.,
+            octaves: 4,
+        }
+    }
+}
+
+/// Samples [CrustNoise] at point mass positions
+pub struct CrustSampler {
+    amplitude: f32,
+    /// `None` when the crust is flat
+    noise: Option<Fbm<Simplex>>,
+}
+
+impl CrustSampler {
+    /// Only draws a noise seed from `rng` when [CrustNoise::amplitude] is positive, so a flat crust keeps the random sequence unchanged
+    pub fn new(config: &CrustNoise, rng: &mut rand::rngs::StdRng) -> Self {
+        let noise = (config.amplitude > 0.).then(|| {
+            Fbm::<Simplex>::new(rng.random())
+                .set_octaves(config.octaves.max(1))
+                .set_frequency(config.frequency as f64)
+        });
+        CrustSampler {
+            amplitude: config.amplitude,
+            noise,
+        }
+    }
+
+    /// Height offset of crust starting at the unit sphere `position`
+    pub fn thickness(&self, position: Vec3) -> f32 {
+        match &self.noise {
+            Some(noise) => noise.get(position.as_dvec3().to_array()) as f32 * self.amplitude,
+            None => 0.,
+        }
+    }
+}
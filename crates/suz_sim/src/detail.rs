@@ -0,0 +1,62 @@
+use glam::Vec3;
+use noise::{MultiFractal, NoiseFn, RidgedMulti, Simplex};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Ridged noise layered onto the finished terrain, breaking up the smooth interpolated heights at high subdivisions
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct DetailConfig {
+    /// Largest height offset, reached where tectonics raised the crust the most. 0 disables the pass
+    pub amplitude: f32,
+    /// Frequency of the first octave on the unit sphere, higher gives smaller ridges
+    pub frequency: f32,
+    /// Octaves of ridged noise summed, each at twice the frequency of the last
+    pub octaves: usize,
+    /// Uplift at and above which the detail has its full [DetailConfig::amplitude]
+    pub full_uplift: f32,
+    /// [0,1] Fraction of the amplitude left on crust tectonics did not raise, like abyssal plains
+    pub flat_roughness: f32,
+}
+
+impl Default for DetailConfig {
+    fn default() -> Self {
+        DetailConfig {
+            amplitude: 0.,
+            frequency: 24.,
+            octaves: 6,
+            full_uplift: 0.02,
+            flat_roughness: 0.1,
+        }
+    }
+}
+
+impl DetailConfig {
+    pub fn enabled(&self) -> bool {
+        self.amplitude > 0.
+    }
+}
+
+/// Adds seeded ridged multifractal noise to `heights`, scaled between [DetailConfig::flat_roughness] and the full amplitude by each tile's `uplift`.
+/// `uplift` holds one value per tile, see [crate::interpolation::interpolate_tile_uplift].
+pub fn apply_detail(
+    config: &DetailConfig,
+    heights: &mut [f32],
+    normals: &[Vec3],
+    uplift: &[f32],
+    seed: u32,
+) {
+    let noise = RidgedMulti::<Simplex>::new(seed)
+        .set_octaves(config.octaves.max(1))
+        .set_frequency(config.frequency as f64);
+    let full_uplift = config.full_uplift.max(f32::EPSILON);
+    heights
+        .par_iter_mut()
+        .zip(normals)
+        .zip(uplift)
+        .for_each(|((height, normal), uplift)| {
+            let roughness = config.flat_roughness
+                + (1. - config.flat_roughness) * (uplift / full_uplift).clamp(0., 1.);
+            *height +=
+                noise.get(normal.as_dvec3().to_array()) as f32 * config.amplitude * roughness;
+        });
+}
@@ -0,0 +1,78 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+use glam::Vec3;
+
+use crate::vec_utils;
+
+struct Frontier<T> {
+    distance: f32,
+    tile: usize,
+    source: T,
+}
+
+impl<T> PartialEq for Frontier<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Frontier<T> {}
+
+impl<T> PartialOrd for Frontier<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Frontier<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance.total_cmp(&other.distance)
+    }
+}
+
+/// Distance to the closest of `sources` for every tile, along the tile graph given by `adjacent`, with the value of that source.
+/// The search only spreads into tiles for which `passable` holds and stops short of `max_distance`, tiles it does not reach are `None`.
+pub fn nearest_sources<'a, T: Copy>(
+    sources: impl IntoIterator<Item = (usize, T)>,
+    normals: &[Vec3],
+    adjacent: impl Fn(usize) -> &'a [usize],
+    max_distance: f32,
+    passable: impl Fn(usize) -> bool,
+) -> Vec<Option<(f32, T)>> {
+    let mut nearest: Vec<Option<(f32, T)>> = vec![None; normals.len()];
+    let mut frontier: BinaryHeap<_> = sources
+        .into_iter()
+        .map(|(tile, source)| {
+            Reverse(Frontier {
+                distance: 0.,
+                tile,
+                source,
+            })
+        })
+        .collect();
+    while let Some(Reverse(Frontier {
+        distance,
+        tile,
+        source,
+    })) = frontier.pop()
+    {
+        if nearest[tile].is_some() {
+            continue;
+        }
+        nearest[tile] = Some((distance, source));
+        for &next in adjacent(tile) {
+            let distance = distance + vec_utils::geodesic_distance(normals[tile], normals[next]);
+            if nearest[next].is_none() && distance < max_distance && passable(next) {
+                frontier.push(Reverse(Frontier {
+                    distance,
+                    tile: next,
+                    source,
+                }));
+            }
+        }
+    }
+    nearest
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tectonics::Tectonics;
+
+/// Alternating rounds of tectonics and erosion, mountains raised in a later epoch stay sharper than older ones
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct EpochConfig {
+    /// Rounds of tectonics followed by erosion, the tectonic simulation is split evenly between them.
+    /// 1 runs the tectonic simulation once and erodes the result, as without epochs.
+    pub epochs: usize,
+    /// Erosion passes at the end of every epoch
+    pub erosion_passes: usize,
+}
+
+impl Default for EpochConfig {
+    fn default() -> Self {
+        EpochConfig {
+            epochs: 1,
+            erosion_passes: 1,
+        }
+    }
+}
+
+/// Tracks which epoch the pipeline is in and what erosion took away in the finished ones
+#[derive(Clone)]
+pub struct EpochSchedule {
+    pub config: EpochConfig,
+    /// Epochs finished so far
+    pub epoch: usize,
+    /// Height each tile lost to erosion in finished epochs, empty until the first erosion is recorded
+    pub eroded: Vec<f32>,
+}
+
+impl EpochSchedule {
+    pub fn new(config: EpochConfig) -> Self {
+        EpochSchedule {
+            config,
+            epoch: 0,
+            eroded: Vec::new(),
+        }
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.epoch + 1 >= self.config.epochs
+    }
+
+    /// Whether the tectonic simulation reached the end of the current epoch, the last epoch runs until [Tectonics::finished]
+    pub fn tectonics_finished(&self, tectonics: &Tectonics) -> bool {
+        if self.is_last() || tectonics.halted.is_some() {
+            tectonics.finished()
+        } else {
+            tectonics.progress() >= (self.epoch + 1) as f32 / self.config.epochs as f32
+        }
+    }
+
+    /// Moves on to the tectonics of the next epoch
+    pub fn advance(&mut self) {
+        self.epoch += 1;
+    }
+
+    /// Stores how far erosion lowered each tile from `before` to `after`, for [EpochSchedule::apply_erosion] in later epochs
+    pub fn record_erosion(&mut self, before: &[f32], after: &[f32]) {
+        self.eroded.resize(before.len(), 0.);
+        for ((eroded, before), after) in self.eroded.iter_mut().zip(before).zip(after) {
+            *eroded += before - after;
+        }
+    }
+
+    /// Lowers tectonic `heights` by the erosion of previous epochs.
+    /// Erosion stays with the tile rather than the crust that was eroded, so it drifts away from the plates that carried it.
+    pub fn apply_erosion(&self, heights: &mut [f32]) {
+        for (height, eroded) in heights.iter_mut().zip(&self.eroded) {
+            *height -= eroded;
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use glam::Vec3;
+use rayon::prelude::*;
+use subsphere::{Face, Sphere, proj::Fuller};
+
+use crate::vec_utils;
+
+/// Pole at the center of an [Projection::Azimuthal] map
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pole {
+    North,
+    South,
+}
+
+/// Map projections a tile layer can be resampled to with [project_layer]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// Latitude and longitude grid from the north pole down, `resolution` wide and half as tall
+    Equirectangular,
+    /// Six square faces of `resolution` pixels side by side, in the order +X, -X, +Y, -Y, +Z, -Z.
+    /// Each face is seen from outside the planet, side faces with north up, polar faces with -X or +X up.
+    CubeMap,
+    /// Azimuthal equidistant disc around `Pole` reaching to the equator, `resolution` pixels square.
+    /// Pixels outside the disc are `None`.
+    Azimuthal(Pole),
+}
+
+impl Projection {
+    /// Width and height of the image for `resolution`
+    pub fn size(&self, resolution: u32) -> (u32, u32) {
+        let resolution = resolution.max(1);
+        match self {
+            Projection::Equirectangular => (resolution, (resolution / 2).max(1)),
+            Projection::CubeMap => (resolution * 6, resolution),
+            Projection::Azimuthal(_) => (resolution, resolution),
+        }
+    }
+
+    /// Unit sphere direction under the center of pixel (`x`, `y`), with `y` counted from the top
+    fn direction(&self, resolution: u32, x: u32, y: u32) -> Option<Vec3> {
+        let resolution = resolution.max(1);
+        let (width, height) = self.size(resolution);
+        match self {
+            Projection::Equirectangular => {
+                let latitude = FRAC_PI_2 - (y as f32 + 0.5) / height as f32 * PI;
+                let longitude = (x as f32 + 0.5) / width as f32 * 2. * PI - PI;
+                Some(vec_utils::lat_long_to_vec3(latitude, longitude))
+            }
+            Projection::CubeMap => {
+                let face = x / resolution;
+                // Face coordinates in [-1, 1], up is positive
+                let u = ((x % resolution) as f32 + 0.5) / resolution as f32 * 2. - 1.;
+                let v = 1. - (y as f32 + 0.5) / resolution as f32 * 2.;
+                let (forward, up) = match face {
+                    0 => (Vec3::X, Vec3::Y),
+                    1 => (Vec3::NEG_X, Vec3::Y),
+                    2 => (Vec3::Y, Vec3::NEG_X),
+                    3 => (Vec3::NEG_Y, Vec3::X),
+                    4 => (Vec3::Z, Vec3::Y),
+                    _ => (Vec3::NEG_Z, Vec3::Y),
+                };
+                let right = up.cross(forward);
+                Some((forward + right * u + up * v).normalize())
+            }
+            Projection::Azimuthal(pole) => {
+                let u = (x as f32 + 0.5) / width as f32 * 2. - 1.;
+                let v = 1. - (y as f32 + 0.5) / height as f32 * 2.;
+                let radius = u.hypot(v);
+                if radius > 1. {
+                    return None;
+                }
+                // Distance from the center is the angle from the pole
+                let latitude = FRAC_PI_2 * (1. - radius);
+                let latitude = match pole {
+                    Pole::North => latitude,
+                    Pole::South => -latitude,
+                };
+                Some(vec_utils::lat_long_to_vec3(latitude, v.atan2(u)))
+            }
+        }
+    }
+}
+
+/// Per tile values of a planet, such as height, biome or plate ID, with the sphere they tile.
+/// `values` is indexed like the faces of `subsphere`.
+pub struct TileLayer<'a, T> {
+    pub subsphere: &'a subsphere::HexSphere<Fuller>,
+    pub values: &'a [T],
+}
+
+/// A tile layer resampled to a [Projection], row by row from the top
+pub struct ProjectedLayer<T> {
+    pub width: u32,
+    pub height: u32,
+    /// `None` for pixels the projection does not cover
+    pub pixels: Vec<Option<T>>,
+}
+
+/// Resamples `layer` to `projection`, each pixel takes the value of the tile under its center.
+/// `resolution` is the width of an equirectangular or azimuthal map, or the side of a cube map face.
+pub fn project_layer<T: Copy + Send + Sync>(
+    layer: &TileLayer<T>,
+    projection: Projection,
+    resolution: u32,
+) -> ProjectedLayer<T> {
+    let (width, height) = projection.size(resolution);
+    let pixels = (0..height)
+        .into_par_iter()
+        .flat_map_iter(|y| {
+            (0..width).map(move |x| {
+                projection.direction(resolution, x, y).map(|direction| {
+                    let at: [f32; 3] = direction.into();
+                    layer.values[layer.subsphere.face_at(at.map(f64::from)).index()]
+                })
+            })
+        })
+        .collect();
+    ProjectedLayer {
+        width,
+        height,
+        pixels,
+    }
+}
@@ -0,0 +1,71 @@
+use std::f32::consts::PI;
+
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::{distance_field::nearest_sources, tectonics::CONTINENTAL_HEIGHT};
+
+/// Elastic plate flexure next to mountain belts, a depressed foreland basin followed by a raised rim
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct FlexureConfig {
+    /// Depth of the basin right next to a range as a fraction of the range height, 0 disables flexure
+    pub deflection_ratio: f32,
+    /// Geodesic distance controlling the width of the flexure.
+    /// The basin reaches out to 3π/4 times this, and the rim peaks at π times this
+    pub flexural_parameter: f32,
+    /// [0,1] Fraction of the basin depth filled back in with sediment
+    pub sediment_fill: f32,
+}
+
+impl Default for FlexureConfig {
+    fn default() -> Self {
+        FlexureConfig {
+            deflection_ratio: 0.3,
+            flexural_parameter: 0.03,
+            sediment_fill: 0.5,
+        }
+    }
+}
+
+/// Deflection of a broken elastic plate at distance `x` from a line load, in units of the deflection under the load.
+/// Negative close to the load and slightly positive past 3π/4, which forms the rim.
+fn flexure_profile(x: f32) -> f32 {
+    -(-x).exp() * (x.cos() + x.sin())
+}
+
+/// Bends the crust around mountain belts and fills the resulting basins with sediment.
+/// Tiles above [CONTINENTAL_HEIGHT] act as loads, every other tile is deflected according to the closest load,
+/// measured along the tile graph given by `adjacent`.
+pub fn apply_flexure<'a>(
+    config: &FlexureConfig,
+    heights: &mut [f32],
+    normals: &[Vec3],
+    adjacent: impl Fn(usize) -> &'a [usize],
+) {
+    if config.deflection_ratio <= 0.0 {
+        return;
+    }
+    // The profile is negligible past one full wavelength
+    let max_distance = 2. * PI * config.flexural_parameter;
+
+    let loads = heights
+        .iter()
+        .enumerate()
+        .filter(|(_, height)| **height > CONTINENTAL_HEIGHT)
+        .map(|(tile, height)| (tile, height - CONTINENTAL_HEIGHT));
+    let nearest_load = nearest_sources(loads, normals, adjacent, max_distance, |_| true);
+
+    for (height, nearest_load) in heights.iter_mut().zip(nearest_load) {
+        // Loads themselves are left as they are, the ranges already sit at their final height
+        if let Some((distance, load)) = nearest_load.filter(|(distance, _)| *distance > 0.) {
+            let deflection = config.deflection_ratio
+                * load
+                * flexure_profile(distance / config.flexural_parameter);
+            *height += if deflection < 0. {
+                deflection * (1. - config.sediment_fill)
+            } else {
+                deflection
+            };
+        }
+    }
+}
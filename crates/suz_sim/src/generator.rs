@@ -0,0 +1,392 @@
+use std::{fmt, time::Instant};
+
+use glam::Vec3;
+use rand::{Rng, SeedableRng};
+
+#[cfg(feature = "gpu")]
+use crate::gpu::{GpuError, GpuTectonics};
+use crate::{
+    bathymetry::{
+        CRUST_AGE_UPDATE_INTERVAL, CrustAge, apply_bathymetry, oceanic_tiles, trench_depths,
+    },
+    boundaries::PlateBoundaries,
+    climate::{tile_precipitation, tile_temperatures},
+    config::{ConfigError, SimulationConfig},
+    detail::apply_detail,
+    epochs::EpochSchedule,
+    flexure::apply_flexure,
+    ice::glaciate,
+    interpolation::{containing_plates, interpolate_tile_heights, interpolate_tile_uplift},
+    island_arcs::{IslandArcs, apply_island_arcs},
+    observer::{Control, SimulationObserver, Stage},
+    particle_sphere::ParticleSphere,
+    plate::PlateType,
+    plate_import::{ImportedPlate, assign_tiles},
+    serialize::{PlanetSnapshot, PlateSnapshot},
+    shelf::apply_shelf,
+    tectonics::Tectonics,
+};
+
+/// Fraction of tiles placed below sea level unless [PlanetGenerator::ocean_fraction] says otherwise
+pub const DEFAULT_OCEAN_FRACTION: f32 = 0.7;
+
+/// Picks the height below which `ocean_fraction` of the `heights` lie
+pub fn pick_sea_level(heights: &[f32], ocean_fraction: f32) -> f32 {
+    if heights.is_empty() {
+        return 0.;
+    }
+    let mut heights = heights.to_vec();
+    let ocean_tiles = (ocean_fraction.clamp(0., 1.) * heights.len() as f32).round() as usize;
+    if ocean_tiles == heights.len() {
+        return heights.iter().cloned().fold(f32::MIN, f32::max) + f32::EPSILON;
+    }
+    *heights
+        .select_nth_unstable_by(ocean_tiles, |a, b| a.total_cmp(b))
+        .1
+}
+
+/// Per tile values computed on top of the heights
+#[derive(Clone)]
+pub struct PlanetLayers {
+    /// Mean annual temperature in °C
+    pub temperature: Vec<f32>,
+    pub precipitation: Vec<f32>,
+    /// Tiles are glaciated where this is above zero
+    pub ice_thickness: Vec<f32>,
+}
+
+/// A generated planet, every per tile `Vec` is indexed like [Planet::tiles]
+pub struct Planet {
+    pub seed: u64,
+    pub config: SimulationConfig,
+    /// Tiles of the hex sphere, [hex_sphere::Tile::height] holds the final height
+    pub tiles: Vec<hex_sphere::Tile>,
+    pub heights: Vec<f32>,
+    /// Index into [Planet::plates] for each tile
+    pub tile_plates: Vec<u32>,
+    pub plates: Vec<PlateSnapshot>,
+    /// Tiles below this height are ocean
+    pub sea_level: f32,
+    pub layers: PlanetLayers,
+    /// The tectonic simulation as it was when it finished
+    pub tectonics: Tectonics,
+}
+
+impl Planet {
+    /// Elevation of every tile in metres above [Planet::sea_level], see [crate::units::PhysicalUnits]
+    pub fn elevations_m(&self) -> Vec<f32> {
+        let units = self.config.tectonics.units();
+        self.heights
+            .iter()
+            .map(|&height| units.elevation_m(height, self.sea_level))
+            .collect()
+    }
+
+    pub fn to_snapshot(&self) -> PlanetSnapshot {
+        PlanetSnapshot {
+            seed: self.seed,
+            config: self.config,
+            tile_heights: self.heights.clone(),
+            tile_plates: self.tile_plates.clone(),
+            plates: self.plates.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GenerateError {
+    /// The observer returned [Control::Abort], the planet is discarded
+    Aborted,
+    /// None of the tiles lies inside the plates given to [PlanetGenerator::plates]
+    NoTileInPlates,
+    #[cfg(feature = "gpu")]
+    Gpu(GpuError),
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::Aborted => write!(f, "Generation was aborted"),
+            GenerateError::NoTileInPlates => write!(f, "No tile lies inside the imported plates"),
+            #[cfg(feature = "gpu")]
+            GenerateError::Gpu(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+#[cfg(feature = "gpu")]
+impl From<GpuError> for GenerateError {
+    fn from(e: GpuError) -> Self {
+        GenerateError::Gpu(e)
+    }
+}
+
+/// Steps the tectonic simulation on the CPU, or on the GPU with [PlanetGenerator::gpu]
+struct Simulator {
+    #[cfg(feature = "gpu")]
+    gpu: Option<GpuTectonics>,
+}
+
+impl Simulator {
+    fn simulate(
+        &mut self,
+        tectonics: &mut Tectonics,
+        rng: &mut rand::rngs::StdRng,
+    ) -> Result<(), GenerateError> {
+        #[cfg(feature = "gpu")]
+        if let Some(gpu) = &mut self.gpu {
+            return gpu.simulate(tectonics, rng).map_err(GenerateError::Gpu);
+        }
+        tectonics.simulate(rng);
+        Ok(())
+    }
+
+    /// Brings the point masses of `tectonics` up to date at the end of an epoch
+    fn finish_epoch(&self, _tectonics: &mut Tectonics) -> Result<(), GenerateError> {
+        #[cfg(feature = "gpu")]
+        if let Some(gpu) = &self.gpu {
+            gpu.read_back(_tectonics)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reports `stage` as taking the time since `start`, then restarts `start` for the next stage
+fn complete_stage(
+    observer: &mut impl SimulationObserver,
+    stage: Stage,
+    start: &mut Instant,
+) -> Control {
+    let control = observer.on_stage_complete(stage, start.elapsed());
+    *start = Instant::now();
+    control
+}
+
+/// Runs the whole generation pipeline in one call, for using suz_sim without the planet viewer.
+/// Every epoch of [SimulationConfig::epochs] runs its share of the tectonic simulation, then climate and glaciation.
+pub struct PlanetGenerator {
+    config: SimulationConfig,
+    seed: u64,
+    ocean_fraction: f32,
+    plates: Option<Vec<ImportedPlate>>,
+    #[cfg(feature = "gpu")]
+    gpu: bool,
+}
+
+impl PlanetGenerator {
+    /// Returns an error if `config` does not pass [SimulationConfig::validate]
+    pub fn new(config: SimulationConfig, seed: u64) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(PlanetGenerator {
+            config,
+            seed,
+            ocean_fraction: DEFAULT_OCEAN_FRACTION,
+            plates: None,
+            #[cfg(feature = "gpu")]
+            gpu: false,
+        })
+    }
+
+    /// Fraction of tiles placed below sea level
+    pub fn ocean_fraction(mut self, ocean_fraction: f32) -> Self {
+        self.ocean_fraction = ocean_fraction;
+        self
+    }
+
+    /// Starts from imported plates instead of seeding them at random, see [crate::plate_import::load_plates]
+    pub fn plates(mut self, plates: Vec<ImportedPlate>) -> Self {
+        self.plates = Some(plates);
+        self
+    }
+
+    /// Runs the tectonic simulation on the GPU, with the limits listed on [GpuTectonics].
+    /// Point masses are only read back when crust age needs them and at the end of every epoch,
+    /// so [SimulationObserver::on_iteration] sees them as they were at the last readback.
+    #[cfg(feature = "gpu")]
+    pub fn gpu(mut self, gpu: bool) -> Self {
+        self.gpu = gpu;
+        self
+    }
+
+    pub fn generate(&self) -> Result<Planet, GenerateError> {
+        self.generate_observed(&mut ())
+    }
+
+    /// Like [PlanetGenerator::generate], reporting progress to `observer`
+    pub fn generate_observed(
+        &self,
+        observer: &mut impl SimulationObserver,
+    ) -> Result<Planet, GenerateError> {
+        let config = &self.config;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(self.seed);
+        let mut start = Instant::now();
+
+        let particle_sphere = ParticleSphere::from_config(config.particle_sphere, &mut rng);
+        let mut tectonics = match &self.plates {
+            Some(plates) => {
+                let normals: Vec<Vec3> = particle_sphere
+                    .tiles
+                    .iter()
+                    .map(|tile| tile.normal)
+                    .collect();
+                let tile_plates = assign_tiles(plates, &normals, |tile_index| {
+                    particle_sphere.tiles[tile_index].adjacent.as_slice()
+                })
+                .ok_or(GenerateError::NoTileInPlates)?;
+                let plate_types: Vec<PlateType> =
+                    plates.iter().map(|plate| plate.plate_type).collect();
+                Tectonics::from_tile_plates(
+                    config.tectonics,
+                    &particle_sphere,
+                    &tile_plates,
+                    &plate_types,
+                    &mut rng,
+                )
+            }
+            None => Tectonics::from_config(config.tectonics, &particle_sphere, &mut rng),
+        }
+        .expect("Config is validated by PlanetGenerator::new");
+        // Crust age is only needed for bathymetry and island arcs, classifying boundaries every few iterations is not free
+        let mut crust_age =
+            (config.bathymetry.is_some() || config.island_arcs.is_some()).then(CrustAge::default);
+        let mut simulator = Simulator {
+            #[cfg(feature = "gpu")]
+            gpu: self
+                .gpu
+                .then(|| {
+                    let readback_interval = if crust_age.is_some() {
+                        CRUST_AGE_UPDATE_INTERVAL
+                    } else {
+                        config.tectonics.iterations
+                    };
+                    GpuTectonics::new(&tectonics, readback_interval)
+                })
+                .transpose()?,
+        };
+        if complete_stage(observer, Stage::ParticleSphere, &mut start) == Control::Abort {
+            return Err(GenerateError::Aborted);
+        }
+        let mut tiles = hex_sphere::HexSphere::new(config.hex_sphere.subdivisions, None).tiles;
+        let normals: Vec<Vec3> = tiles.iter().map(|tile| tile.normal).collect();
+        let adjacent = |tile_index: usize| tiles[tile_index].adjacent.as_slice();
+        if complete_stage(observer, Stage::HexSphere, &mut start) == Control::Abort {
+            return Err(GenerateError::Aborted);
+        }
+
+        let mut epoch_schedule = EpochSchedule::new(config.epochs);
+        let mut island_arcs = config.island_arcs.map(IslandArcs::new);
+        loop {
+            while !epoch_schedule.tectonics_finished(&tectonics) {
+                simulator.simulate(&mut tectonics, &mut rng)?;
+                if let Some(crust_age) = &mut crust_age {
+                    if tectonics.iteration % CRUST_AGE_UPDATE_INTERVAL == 0 {
+                        let boundaries = PlateBoundaries::classify(&tectonics, &normals, adjacent);
+                        crust_age.update(&boundaries.tiles, tectonics.simulated_time);
+                        if let Some(island_arcs) = &mut island_arcs {
+                            island_arcs.update(
+                                &tectonics,
+                                &boundaries,
+                                &containing_plates(&tectonics, &normals),
+                                &crust_age.ages,
+                                &normals,
+                                adjacent,
+                            );
+                        }
+                    }
+                }
+                if observer.on_iteration(tectonics.iteration, &tectonics) == Control::Abort {
+                    return Err(GenerateError::Aborted);
+                }
+            }
+            simulator.finish_epoch(&mut tectonics)?;
+            if complete_stage(observer, Stage::Tectonics, &mut start) == Control::Abort {
+                return Err(GenerateError::Aborted);
+            }
+            let mut heights = interpolate_tile_heights(&tectonics, &normals);
+            apply_flexure(&config.flexure, &mut heights, &normals, adjacent);
+            if let (Some(bathymetry), Some(crust_age)) = (&config.bathymetry, &mut crust_age) {
+                let boundaries = PlateBoundaries::classify(&tectonics, &normals, adjacent);
+                crust_age.update(&boundaries.tiles, tectonics.simulated_time);
+                let oceanic = oceanic_tiles(&tectonics, &containing_plates(&tectonics, &normals));
+                let trench_depths =
+                    trench_depths(bathymetry, &boundaries, &oceanic, &normals, adjacent);
+                apply_bathymetry(
+                    bathymetry,
+                    tectonics.config.plate_height(PlateType::Oceanic),
+                    &mut heights,
+                    &oceanic,
+                    &crust_age.ages,
+                    &trench_depths,
+                );
+            }
+            if let Some(island_arcs) = &island_arcs {
+                apply_island_arcs(&mut heights, &island_arcs.uplift);
+            }
+            if let Some(shelf) = &config.shelf {
+                apply_shelf(shelf, &mut heights, &normals, adjacent);
+            }
+            epoch_schedule.apply_erosion(&mut heights);
+            // Before the sea level is picked so the ocean fraction holds for the final heights, glaciation never cuts land below it
+            if epoch_schedule.is_last() && config.detail.enabled() {
+                let uplift = interpolate_tile_uplift(&tectonics, &normals);
+                apply_detail(
+                    &config.detail,
+                    &mut heights,
+                    &normals,
+                    &uplift,
+                    rng.random(),
+                );
+            }
+
+            let sea_level = pick_sea_level(&heights, self.ocean_fraction);
+            let temperature = tile_temperatures(&config.climate, &normals, &heights, sea_level);
+            let precipitation =
+                tile_precipitation(&config.climate, &normals, &heights, sea_level, adjacent);
+            let before = heights.clone();
+            let mut ice_thickness = Vec::new();
+            for _ in 0..config.epochs.erosion_passes.max(1) {
+                ice_thickness = glaciate(
+                    &config.ice,
+                    &mut heights,
+                    &temperature,
+                    &precipitation,
+                    sea_level,
+                    adjacent,
+                );
+            }
+            epoch_schedule.record_erosion(&before, &heights);
+            if complete_stage(observer, Stage::Erosion, &mut start) == Control::Abort {
+                return Err(GenerateError::Aborted);
+            }
+            if !epoch_schedule.is_last() {
+                epoch_schedule.advance();
+                continue;
+            }
+
+            let tile_plates = containing_plates(&tectonics, &normals)
+                .into_iter()
+                .map(|plate| plate as u32)
+                .collect();
+            for (tile, &height) in tiles.iter_mut().zip(&heights) {
+                tile.height = height;
+            }
+            return Ok(Planet {
+                seed: self.seed,
+                config: *config,
+                tiles,
+                heights,
+                tile_plates,
+                plates: PlateSnapshot::from_tectonics(&tectonics),
+                sea_level,
+                layers: PlanetLayers {
+                    temperature,
+                    precipitation,
+                    ice_thickness,
+                },
+                tectonics,
+            });
+        }
+    }
+}
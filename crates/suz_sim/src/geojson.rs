@@ -0,0 +1,441 @@
+use std::fmt;
+
+use glam::Vec3;
+
+use crate::vec_utils;
+
+/// Longitude and latitude in degrees, the order of GeoJSON positions
+pub type Position = [f32; 2];
+
+/// The GeoJSON geometries planets are exported as, see RFC 7946
+pub enum Geometry {
+    /// Polygons of an exterior ring followed by its holes
+    MultiPolygon(Vec<Vec<Vec<Position>>>),
+    MultiLineString(Vec<Vec<Position>>),
+}
+
+pub struct Feature {
+    pub geometry: Geometry,
+    /// Property names with their values already encoded as JSON
+    pub properties: Vec<(&'static str, String)>,
+}
+
+fn position(point: Vec3) -> Position {
+    let (latitude, longitude) = vec_utils::vec3_to_lat_long(point.normalize());
+    [longitude.to_degrees(), latitude.to_degrees()]
+}
+
+/// Positions along `points`, with longitudes unwrapped so consecutive positions never jump across the antimeridian.
+/// Lines crossing it continue past ±180 degrees instead, which GIS tools draw as one continuous line.
+pub fn line_positions(points: impl IntoIterator<Item = Vec3>) -> Vec<Position> {
+    let mut positions: Vec<Position> = Vec::new();
+    for point in points {
+        let [mut longitude, latitude] = position(point);
+        if let Some(&[previous, _]) = positions.last() {
+            longitude += ((previous - longitude) / 360.).round() * 360.;
+        }
+        positions.push([longitude, latitude]);
+    }
+    positions
+}
+
+/// Closed counterclockwise ring around a region, from a loop of `points` with the region on its left seen from outside the sphere.
+/// Longitude grows to the west in [vec_utils::vec3_to_lat_long], so the loop is reversed to keep the region on its left on the map.
+/// A loop around a pole ends 360 degrees from where it started and is closed over that pole.
+pub fn ring_positions(points: &[Vec3]) -> Vec<Position> {
+    let mut ring = line_positions(points.iter().rev().copied());
+    let (Some(&first), Some(&last)) = (ring.first(), ring.last()) else {
+        return ring;
+    };
+    // Going east the region lies to the north, going west to the south
+    let winding = ((last[0] - first[0]) / 360.).round();
+    if winding != 0. {
+        let pole = 90f32.copysign(winding);
+        let end = first[0] + winding * 360.;
+        ring.extend([[end, first[1]], [end, pole], [first[0], pole]]);
+    }
+    ring.push(first);
+    ring
+}
+
+/// Shoelace area on the map, positive for counterclockwise rings
+fn signed_area(ring: &[Position]) -> f32 {
+    ring.windows(2)
+        .map(|pair| pair[0][0] * pair[1][1] - pair[1][0] * pair[0][1])
+        .sum::<f32>()
+        / 2.
+}
+
+/// Even-odd test of whether `ring` encloses `point` on the map
+fn contains(ring: &[Position], [longitude, latitude]: Position) -> bool {
+    let mut inside = false;
+    for pair in ring.windows(2) {
+        let ([x_a, y_a], [x_b, y_b]) = (pair[0], pair[1]);
+        if (y_a > latitude) != (y_b > latitude)
+            && longitude < x_a + (latitude - y_a) / (y_b - y_a) * (x_b - x_a)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Polygons of a region from its outline `loops`, see [ring_positions].
+/// Counterclockwise rings are exteriors and clockwise rings holes in the smallest exterior around them.
+/// Holes outside every exterior belong to a region reaching around the whole planet, and are cut from a polygon covering the map.
+pub fn region_polygons(loops: &[Vec<Vec3>]) -> Vec<Vec<Vec<Position>>> {
+    let (exteriors, holes): (Vec<_>, Vec<_>) = loops
+        .iter()
+        .map(|points| ring_positions(points))
+        .filter(|ring| ring.len() >= 4)
+        .partition(|ring| signed_area(ring) > 0.);
+    let mut polygons: Vec<Vec<Vec<Position>>> =
+        exteriors.into_iter().map(|ring| vec![ring]).collect();
+    let mut world = vec![vec![
+        [-180., -90.],
+        [180., -90.],
+        [180., 90.],
+        [-180., 90.],
+        [-180., -90.],
+    ]];
+    for hole in holes {
+        let [longitude, latitude] = hole[0];
+        // Unwrapped rings may lie a full turn away from each other
+        let around = polygons
+            .iter_mut()
+            .filter(|polygon| {
+                [-360., 0., 360.]
+                    .iter()
+                    .any(|shift| contains(&polygon[0], [longitude + shift, latitude]))
+            })
+            .min_by(|a, b| signed_area(&a[0]).total_cmp(&signed_area(&b[0])));
+        match around {
+            Some(polygon) => polygon.push(hole),
+            None => world.push(hole),
+        }
+    }
+    if world.len() > 1 {
+        polygons.push(world);
+    }
+    polygons
+}
+
+fn positions_json(positions: &[Position]) -> String {
+    let positions: Vec<String> = positions
+        .iter()
+        .map(|[longitude, latitude]| format!("[{longitude},{latitude}]"))
+        .collect();
+    format!("[{}]", positions.join(","))
+}
+
+fn lines_json(lines: &[Vec<Position>]) -> String {
+    let lines: Vec<String> = lines.iter().map(|line| positions_json(line)).collect();
+    format!("[{}]", lines.join(","))
+}
+
+/// Encodes `features` as a GeoJSON FeatureCollection
+pub fn feature_collection(features: &[Feature]) -> String {
+    let features: Vec<String> = features
+        .iter()
+        .map(|feature| {
+            let geometry = match &feature.geometry {
+                Geometry::MultiPolygon(polygons) => {
+                    let polygons: Vec<String> =
+                        polygons.iter().map(|polygon| lines_json(polygon)).collect();
+                    format!(
+                        r#"{{"type":"MultiPolygon","coordinates":[{}]}}"#,
+                        polygons.join(",")
+                    )
+                }
+                Geometry::MultiLineString(lines) => format!(
+                    r#"{{"type":"MultiLineString","coordinates":{}}}"#,
+                    lines_json(lines)
+                ),
+            };
+            let properties: Vec<String> = feature
+                .properties
+                .iter()
+                .map(|(name, value)| format!(r#""{name}":{value}"#))
+                .collect();
+            format!(
+                r#"{{"type":"Feature","geometry":{geometry},"properties":{{{}}}}}"#,
+                properties.join(",")
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"type":"FeatureCollection","features":[{}]}}"#,
+        features.join(",")
+    )
+}
+
+/// A parsed JSON value, object members keep the order of the file
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Value of member `key` of an object, `None` for missing members and other values
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GeoJsonError(pub String);
+
+impl fmt::Display for GeoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid GeoJSON: {}", self.0)
+    }
+}
+
+impl std::error::Error for GeoJsonError {}
+
+struct JsonParser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self, reason: &str) -> GeoJsonError {
+        GeoJsonError(format!("{reason} at byte {}", self.position))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), GeoJsonError> {
+        self.skip_whitespace();
+        if self.text[self.position..].starts_with(expected) {
+            self.position += expected.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected {expected}")))
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, GeoJsonError> {
+        match self.peek() {
+            Some(b'{') => {
+                self.position += 1;
+                let mut members = Vec::new();
+                if self.peek() == Some(b'}') {
+                    self.position += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                loop {
+                    let JsonValue::String(name) = self.value()? else {
+                        return Err(self.error("Expected a member name"));
+                    };
+                    self.expect(":")?;
+                    members.push((name, self.value()?));
+                    match self.peek() {
+                        Some(b',') => self.position += 1,
+                        Some(b'}') => {
+                            self.position += 1;
+                            return Ok(JsonValue::Object(members));
+                        }
+                        _ => return Err(self.error("Expected , or }")),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.position += 1;
+                let mut values = Vec::new();
+                if self.peek() == Some(b']') {
+                    self.position += 1;
+                    return Ok(JsonValue::Array(values));
+                }
+                loop {
+                    values.push(self.value()?);
+                    match self.peek() {
+                        Some(b',') => self.position += 1,
+                        Some(b']') => {
+                            self.position += 1;
+                            return Ok(JsonValue::Array(values));
+                        }
+                        _ => return Err(self.error("Expected , or ]")),
+                    }
+                }
+            }
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b't') => self.expect("true").map(|()| JsonValue::Bool(true)),
+            Some(b'f') => self.expect("false").map(|()| JsonValue::Bool(false)),
+            Some(b'n') => self.expect("null").map(|()| JsonValue::Null),
+            Some(_) => {
+                let text = self.text;
+                let rest = &text[self.position..];
+                let length = rest
+                    .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+                    .unwrap_or(rest.len());
+                let number = rest[..length]
+                    .parse()
+                    .map_err(|_| self.error("Expected a value"))?;
+                self.position += length;
+                Ok(JsonValue::Number(number))
+            }
+            None => Err(self.error("Unexpected end")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, GeoJsonError> {
+        self.position += 1;
+        let mut string = String::new();
+        let text = self.text;
+        let mut chars = text[self.position..].char_indices();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.position += offset + 1;
+                    return Ok(string);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            // Surrogate pairs are not combined, names outside the basic plane are not expected
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        Some(c) => c,
+                        None => break,
+                    };
+                    string.push(escaped);
+                }
+                c => string.push(c),
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+}
+
+/// Parses a complete JSON document
+pub fn parse_json(text: &str) -> Result<JsonValue, GeoJsonError> {
+    let mut parser = JsonParser { text, position: 0 };
+    let value = parser.value()?;
+    match parser.peek() {
+        None => Ok(value),
+        Some(_) => Err(parser.error("Trailing characters")),
+    }
+}
+
+/// A feature with a Polygon or MultiPolygon geometry, read by [read_polygon_features]
+pub struct PolygonFeature {
+    /// Polygons of an exterior ring followed by its holes, a Polygon geometry gives a single one
+    pub polygons: Vec<Vec<Vec<Position>>>,
+    /// Empty when the feature has no properties
+    pub properties: JsonValue,
+}
+
+fn ring(value: &JsonValue) -> Result<Vec<Position>, GeoJsonError> {
+    let positions = value
+        .as_array()
+        .ok_or_else(|| GeoJsonError("Expected a ring of positions".to_string()))?;
+    positions
+        .iter()
+        .map(|position| match position.as_array() {
+            Some(
+                [
+                    JsonValue::Number(longitude),
+                    JsonValue::Number(latitude),
+                    ..,
+                ],
+            ) => Ok([*longitude as f32, *latitude as f32]),
+            _ => Err(GeoJsonError(
+                "Expected a position of longitude and latitude".to_string(),
+            )),
+        })
+        .collect()
+}
+
+fn polygon(value: &JsonValue) -> Result<Vec<Vec<Position>>, GeoJsonError> {
+    value
+        .as_array()
+        .ok_or_else(|| GeoJsonError("Expected a polygon of rings".to_string()))?
+        .iter()
+        .map(ring)
+        .collect()
+}
+
+/// Every feature of a FeatureCollection or single Feature with a Polygon or MultiPolygon geometry, other features are skipped
+pub fn read_polygon_features(text: &str) -> Result<Vec<PolygonFeature>, GeoJsonError> {
+    let document = parse_json(text)?;
+    let features = match document.get("type").and_then(JsonValue::as_str) {
+        Some("FeatureCollection") => document
+            .get("features")
+            .and_then(JsonValue::as_array)
+            .ok_or_else(|| GeoJsonError("FeatureCollection without features".to_string()))?,
+        Some("Feature") => std::slice::from_ref(&document),
+        _ => {
+            return Err(GeoJsonError(
+                "Expected a FeatureCollection or Feature".to_string(),
+            ));
+        }
+    };
+    let mut polygon_features = Vec::new();
+    for feature in features {
+        let Some(geometry) = feature.get("geometry") else {
+            continue;
+        };
+        let coordinates = geometry.get("coordinates").unwrap_or(&JsonValue::Null);
+        let polygons = match geometry.get("type").and_then(JsonValue::as_str) {
+            Some("Polygon") => vec![polygon(coordinates)?],
+            Some("MultiPolygon") => coordinates
+                .as_array()
+                .ok_or_else(|| GeoJsonError("Expected a list of polygons".to_string()))?
+                .iter()
+                .map(polygon)
+                .collect::<Result<_, _>>()?,
+            _ => continue,
+        };
+        polygon_features.push(PolygonFeature {
+            polygons,
+            properties: feature
+                .get("properties")
+                .cloned()
+                .unwrap_or(JsonValue::Object(Vec::new())),
+        });
+    }
+    Ok(polygon_features)
+}
@@ -0,0 +1,414 @@
+use std::{borrow::Cow, fmt, sync::mpsc};
+
+use bytemuck::{Pod, Zeroable};
+use glam::Vec4;
+use wgpu::util::DeviceExt;
+
+use crate::tectonics::Tectonics;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct GpuPointMass {
+    position: [f32; 4],
+    velocity: [f32; 4],
+    force: [f32; 4],
+    prev_force: [f32; 4],
+    mass: f32,
+    plate: u32,
+    padding: [u32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct GpuSpring {
+    anchor_a: u32,
+    anchor_b: u32,
+    rest_length: f32,
+    spring_constant: f32,
+    damping_coefficient: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct GpuParams {
+    timestep: f32,
+    plate_force_modifier: f32,
+    friction_coefficient: f32,
+    point_mass_count: u32,
+}
+
+#[derive(Debug)]
+pub enum GpuError {
+    /// No GPU adapter is available, use the CPU path instead
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
+    Readback(wgpu::BufferAsyncError),
+}
+
+impl fmt::Display for GpuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuError::NoAdapter => write!(f, "No GPU adapter found"),
+            GpuError::RequestDevice(e) => write!(f, "Failed to create GPU device: {e}"),
+            GpuError::Readback(e) => write!(f, "Failed to read point masses from GPU: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for GpuError {}
+
+/// Runs the force and velocity verlet steps of [Tectonics::simulate] in wgpu compute shaders.
+/// Point masses live on the GPU and are copied back into the [Tectonics] every [GpuTectonics::readback_interval] iterations.
+/// Plate drift stays on the CPU, plate merging is not supported so [crate::tectonics::TectonicsConfiguration::merge_iterations] is ignored.
+/// [crate::tectonics::TectonicsConfiguration::validation] is ignored too, point masses on the GPU are not checked.
+/// Plates always drift at random, [crate::tectonics::TectonicsConfiguration::torque] needs plate contacts only known on the CPU.
+/// [crate::tectonics::TectonicsConfiguration::driving_forces] is ignored for the same reason.
+/// Every iteration is a single velocity verlet step, [crate::tectonics::TectonicsConfiguration::substeps] and its solver are ignored.
+pub struct GpuTectonics {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    forces_pipeline: wgpu::ComputePipeline,
+    integrate_pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    params_buffer: wgpu::Buffer,
+    point_mass_buffer: wgpu::Buffer,
+    plate_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    point_mass_count: u32,
+    /// Index of the first point mass of each plate in the point mass buffer
+    plate_offsets: Vec<usize>,
+    pub readback_interval: usize,
+}
+
+/// Storage buffers can not be empty, so a single zeroed element stands in for no elements
+fn non_empty<T: Zeroable>(mut values: Vec<T>) -> Vec<T> {
+    if values.is_empty() {
+        values.push(T::zeroed());
+    }
+    values
+}
+
+fn params(tectonics: &Tectonics, point_mass_count: u32) -> GpuParams {
+    GpuParams {
+        timestep: tectonics.timestep,
+        plate_force_modifier: tectonics.config.plate_force_modifier,
+        friction_coefficient: tectonics.config.friction_coefficient,
+        point_mass_count,
+    }
+}
+
+fn plate_axes(tectonics: &Tectonics) -> Vec<[f32; 4]> {
+    tectonics
+        .plates
+        .iter()
+        .map(|plate| plate.axis_of_rotation.extend(0.).to_array())
+        .collect()
+}
+
+impl GpuTectonics {
+    /// Uploads the point masses and springs of `tectonics`, fails if there is no usable GPU
+    pub fn new(tectonics: &Tectonics, readback_interval: usize) -> Result<Self, GpuError> {
+        pollster::block_on(Self::new_async(tectonics, readback_interval))
+    }
+
+    async fn new_async(tectonics: &Tectonics, readback_interval: usize) -> Result<Self, GpuError> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await
+            .ok_or(GpuError::NoAdapter)?;
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    label: Some("tectonics"),
+                    required_features: wgpu::Features::empty(),
+                    required_limits: wgpu::Limits::default(),
+                    memory_hints: wgpu::MemoryHints::Performance,
+                },
+                None,
+            )
+            .await
+            .map_err(GpuError::RequestDevice)?;
+
+        let mut plate_offsets = Vec::with_capacity(tectonics.plates.len());
+        let mut point_masses = Vec::new();
+        let mut springs = Vec::new();
+        for (plate_index, plate) in tectonics.plates.iter().enumerate() {
+            let offset = point_masses.len();
+            plate_offsets.push(offset);
+            point_masses.extend(
+                plate
+                    .shape
+                    .point_masses()
+                    .iter()
+                    .map(|point_mass| GpuPointMass {
+                        position: point_mass.position.extend(0.).to_array(),
+                        velocity: point_mass.velocity.extend(0.).to_array(),
+                        force: point_mass.force.extend(0.).to_array(),
+                        prev_force: point_mass.prev_force.extend(0.).to_array(),
+                        mass: point_mass.mass,
+                        plate: plate_index as u32,
+                        padding: [0; 2],
+                    }),
+            );
+            springs.extend(plate.shape.springs().iter().map(|spring| GpuSpring {
+                anchor_a: (spring.anchor_a + offset) as u32,
+                anchor_b: (spring.anchor_b + offset) as u32,
+                rest_length: spring.rest_length,
+                spring_constant: spring.spring_constant,
+                damping_coefficient: spring.damping_coefficient,
+            }));
+        }
+        // Springs grouped by anchor, so each point mass gathers its own spring forces
+        let mut anchored = vec![Vec::new(); point_masses.len()];
+        for (spring_index, spring) in springs.iter().enumerate() {
+            anchored[spring.anchor_a as usize].push(spring_index as u32);
+            anchored[spring.anchor_b as usize].push(spring_index as u32);
+        }
+        let mut spring_offsets = vec![0u32];
+        let mut spring_indices = Vec::new();
+        for spring_list in anchored {
+            spring_indices.extend(spring_list);
+            spring_offsets.push(spring_indices.len() as u32);
+        }
+        let point_mass_count = point_masses.len() as u32;
+
+        let buffer = |label: &str, contents: &[u8], usage: wgpu::BufferUsages| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage,
+            })
+        };
+        let params_buffer = buffer(
+            "params",
+            bytemuck::bytes_of(&params(tectonics, point_mass_count)),
+            wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        );
+        let point_mass_buffer = buffer(
+            "point masses",
+            bytemuck::cast_slice(&non_empty(point_masses)),
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        );
+        let spring_buffer = buffer(
+            "springs",
+            bytemuck::cast_slice(&non_empty(springs)),
+            wgpu::BufferUsages::STORAGE,
+        );
+        let spring_offset_buffer = buffer(
+            "spring offsets",
+            bytemuck::cast_slice(&spring_offsets),
+            wgpu::BufferUsages::STORAGE,
+        );
+        let spring_index_buffer = buffer(
+            "spring indices",
+            bytemuck::cast_slice(&non_empty(spring_indices)),
+            wgpu::BufferUsages::STORAGE,
+        );
+        let plate_buffer = buffer(
+            "plate axes",
+            bytemuck::cast_slice(&non_empty(plate_axes(tectonics))),
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("point mass readback"),
+            size: point_mass_buffer.size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage = |binding: u32, read_only: bool| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("tectonics"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage(1, false),
+                storage(2, true),
+                storage(3, true),
+                storage(4, true),
+                storage(5, true),
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("tectonics"),
+            layout: &bind_group_layout,
+            entries: &[
+                params_buffer.as_entire_binding(),
+                point_mass_buffer.as_entire_binding(),
+                spring_buffer.as_entire_binding(),
+                spring_offset_buffer.as_entire_binding(),
+                spring_index_buffer.as_entire_binding(),
+                plate_buffer.as_entire_binding(),
+            ]
+            .into_iter()
+            .enumerate()
+            .map(|(binding, resource)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource,
+            })
+            .collect::<Vec<_>>(),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("tectonics"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("tectonics"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("shaders/tectonics.wgsl"))),
+        });
+        let pipeline = |entry_point: &str| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let forces_pipeline = pipeline("forces");
+        let integrate_pipeline = pipeline("integrate");
+
+        Ok(GpuTectonics {
+            device,
+            queue,
+            forces_pipeline,
+            integrate_pipeline,
+            bind_group,
+            params_buffer,
+            point_mass_buffer,
+            plate_buffer,
+            readback_buffer,
+            point_mass_count,
+            plate_offsets,
+            readback_interval: readback_interval.max(1),
+        })
+    }
+
+    /// GPU equivalent of [Tectonics::simulate], the final iteration is always read back
+    pub fn simulate(
+        &mut self,
+        tectonics: &mut Tectonics,
+        rng: &mut rand::rngs::StdRng,
+    ) -> Result<(), GpuError> {
+        assert!(
+            tectonics.plates.len() == self.plate_offsets.len(),
+            "Plate count changed since the GPU buffers were built"
+        );
+        tectonics.iteration += 1;
+        // Velocities are only read back every few steps, so the timestep stays at the one the simulation had when the GPU took over
+        tectonics.simulated_time += tectonics.timestep;
+        // Config is uploaded every step so Tectonics::set_config also applies on the GPU
+        self.queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::bytes_of(&params(tectonics, self.point_mass_count)),
+        );
+        self.queue.write_buffer(
+            &self.plate_buffer,
+            0,
+            bytemuck::cast_slice(&plate_axes(tectonics)),
+        );
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("tectonics step"),
+            });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("tectonics step"),
+                timestamp_writes: None,
+            });
+            let workgroups = self.point_mass_count.div_ceil(WORKGROUP_SIZE);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.set_pipeline(&self.forces_pipeline);
+            pass.dispatch_workgroups(workgroups, 1, 1);
+            pass.set_pipeline(&self.integrate_pipeline);
+            pass.dispatch_workgroups(workgroups, 1, 1);
+        }
+        self.queue.submit(Some(encoder.finish()));
+        tectonics.drift_plates(rng);
+        if tectonics.iteration % self.readback_interval == 0 || tectonics.finished() {
+            self.read_back(tectonics)?;
+        }
+        // Centroids only move on a readback, so trails sampled in between repeat the last position
+        tectonics.record_trails();
+        Ok(())
+    }
+
+    /// Copies point masses from the GPU into `tectonics`, blocking until the GPU is done
+    pub fn read_back(&self, tectonics: &mut Tectonics) -> Result<(), GpuError> {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("point mass readback"),
+            });
+        encoder.copy_buffer_to_buffer(
+            &self.point_mass_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            self.readback_buffer.size(),
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = self.readback_buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            sender.send(result).ok();
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .expect("Readback callback was dropped")
+            .map_err(GpuError::Readback)?;
+        {
+            let data = slice.get_mapped_range();
+            let point_masses: &[GpuPointMass] = bytemuck::cast_slice(&data);
+            for (plate, &offset) in tectonics.plates.iter_mut().zip(&self.plate_offsets) {
+                for (point_mass, gpu_point_mass) in plate
+                    .shape
+                    .point_masses_mut()
+                    .iter_mut()
+                    .zip(&point_masses[offset..])
+                {
+                    point_mass.position = Vec4::from(gpu_point_mass.position).truncate();
+                    point_mass.velocity = Vec4::from(gpu_point_mass.velocity).truncate();
+                    point_mass.force = Vec4::from(gpu_point_mass.force).truncate();
+                    point_mass.prev_force = Vec4::from(gpu_point_mass.prev_force).truncate();
+                }
+                plate.shape.update_centroid();
+                plate.shape.update_bounding_distance();
+            }
+        }
+        self.readback_buffer.unmap();
+        tectonics.tree.refresh_incremental(&tectonics.plates);
+        Ok(())
+    }
+}
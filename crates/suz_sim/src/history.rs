@@ -0,0 +1,184 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use glam::Vec3;
+
+use crate::{
+    serialize::{SnapshotError, read_array, read_f32, read_f32s, read_u32, write_f32s, write_u32},
+    tectonics::Tectonics,
+};
+
+const MAGIC: &[u8; 4] = b"SUZH";
+/// Bumped whenever the binary layout changes, older histories are rejected
+pub const HISTORY_VERSION: u32 = 1;
+
+/// Point mass positions of one plate, each unit sphere component quantized to 16 bits
+#[derive(Clone)]
+pub struct PlateFrame {
+    /// Linear RGBA
+    pub color: [f32; 4],
+    positions: Vec<[i16; 3]>,
+}
+
+impl PlateFrame {
+    pub fn positions(&self) -> impl Iterator<Item = Vec3> + '_ {
+        self.positions.iter().map(|position| {
+            Vec3::from_array(position.map(|component| component as f32 / i16::MAX as f32))
+                .normalize_or_zero()
+        })
+    }
+}
+
+/// The plates of the simulation at a single iteration
+#[derive(Clone)]
+pub struct HistoryFrame {
+    pub iteration: usize,
+    pub simulated_time: f32,
+    pub plates: Vec<PlateFrame>,
+}
+
+impl HistoryFrame {
+    pub fn from_tectonics(tectonics: &Tectonics) -> Self {
+        HistoryFrame {
+            iteration: tectonics.iteration,
+            simulated_time: tectonics.simulated_time,
+            plates: tectonics
+                .plates
+                .iter()
+                .map(|plate| PlateFrame {
+                    color: plate.color,
+                    positions: plate
+                        .shape
+                        .point_masses()
+                        .iter()
+                        .map(|point_mass| {
+                            point_mass
+                                .position
+                                .to_array()
+                                .map(|component| (component * i16::MAX as f32).round() as i16)
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Frames of the tectonic simulation taken at least [TectonicsHistory::interval] iterations apart, for replaying how the planet formed.
+/// Plates merge during the simulation, so frames can hold different numbers of plates.
+#[derive(Clone)]
+pub struct TectonicsHistory {
+    pub interval: usize,
+    /// Oldest first
+    pub frames: Vec<HistoryFrame>,
+}
+
+impl TectonicsHistory {
+    pub fn new(interval: usize) -> Self {
+        TectonicsHistory {
+            interval: interval.max(1),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Adds a frame of `tectonics` if it is the first or [TectonicsHistory::interval] iterations passed since the last frame.
+    /// Returns whether a frame was added.
+    pub fn record(&mut self, tectonics: &Tectonics) -> bool {
+        let due = self
+            .frames
+            .last()
+            .is_none_or(|last| tectonics.iteration >= last.iteration + self.interval);
+        if due {
+            self.frames.push(HistoryFrame::from_tectonics(tectonics));
+        }
+        due
+    }
+
+    /// Layout: magic, version, interval, frames (iteration, simulated time, plates (color, positions))
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), SnapshotError> {
+        writer.write_all(MAGIC)?;
+        write_u32(writer, HISTORY_VERSION)?;
+        write_u32(writer, self.interval as u32)?;
+        write_u32(writer, self.frames.len() as u32)?;
+        for frame in &self.frames {
+            write_u32(writer, frame.iteration as u32)?;
+            write_f32s(writer, &[frame.simulated_time])?;
+            write_u32(writer, frame.plates.len() as u32)?;
+            for plate in &frame.plates {
+                write_f32s(writer, &plate.color)?;
+                write_u32(writer, plate.positions.len() as u32)?;
+                for component in plate.positions.iter().flatten() {
+                    writer.write_all(&component.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, SnapshotError> {
+        if &read_array::<4>(reader)? != MAGIC {
+            return Err(SnapshotError::InvalidMagic);
+        }
+        let version = read_u32(reader)?;
+        if version != HISTORY_VERSION {
+            return Err(SnapshotError::UnsupportedVersion {
+                found: version,
+                expected: HISTORY_VERSION,
+            });
+        }
+        let interval = read_u32(reader)? as usize;
+        let frame_count = read_u32(reader)? as usize;
+        let mut frames = Vec::with_capacity(frame_count);
+        for _ in 0..frame_count {
+            let iteration = read_u32(reader)? as usize;
+            let simulated_time = read_f32(reader)?;
+            let plate_count = read_u32(reader)? as usize;
+            let mut plates = Vec::with_capacity(plate_count);
+            for _ in 0..plate_count {
+                let color = read_f32s(reader)?;
+                let position_count = read_u32(reader)? as usize;
+                let positions = (0..position_count)
+                    .map(|_| {
+                        let mut position = [0; 3];
+                        for component in &mut position {
+                            *component = i16::from_le_bytes(read_array(reader)?);
+                        }
+                        Ok(position)
+                    })
+                    .collect::<std::io::Result<Vec<_>>>()?;
+                plates.push(PlateFrame { color, positions });
+            }
+            if frames
+                .last()
+                .is_some_and(|last: &HistoryFrame| last.iteration > iteration)
+            {
+                return Err(SnapshotError::Corrupt(format!(
+                    "Frame at iteration {iteration} comes after iteration {}",
+                    frames
+                        .last()
+                        .map_or(0, |last: &HistoryFrame| last.iteration)
+                )));
+            }
+            frames.push(HistoryFrame {
+                iteration,
+                simulated_time,
+                plates,
+            });
+        }
+        Ok(TectonicsHistory { interval, frames })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+}
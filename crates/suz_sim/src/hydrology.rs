@@ -0,0 +1,115 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::BinaryHeap,
+};
+
+/// A basin that fills with water up to the level where it spills over into a neighbouring basin
+#[derive(Clone, Debug)]
+pub struct Lake {
+    /// Indices to the tiles under water
+    pub tiles: Vec<usize>,
+    /// Height of the water surface, the lowest height at which the basin overflows
+    pub surface_height: f32,
+}
+
+#[derive(PartialEq)]
+struct Spill {
+    height: f32,
+    tile: usize,
+}
+
+impl Eq for Spill {}
+
+impl PartialOrd for Spill {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Spill {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.height
+            .total_cmp(&other.height)
+            .then(self.tile.cmp(&other.tile))
+    }
+}
+
+/// Raises every depression without an outlet to its spill level, so water can flow downhill from any tile to the ocean.
+/// Flooding starts from the tiles below `sea_level`, or from the lowest tile when there is no ocean (Barnes et al., 2014).
+/// Filled tiles form flats at their spill level, every other tile keeps its height.
+pub fn fill_depressions<'a>(
+    heights: &[f32],
+    sea_level: f32,
+    adjacent: impl Fn(usize) -> &'a [usize],
+) -> Vec<f32> {
+    let mut filled: Vec<Option<f32>> = vec![None; heights.len()];
+    let mut frontier = BinaryHeap::new();
+    for (tile, &height) in heights.iter().enumerate() {
+        if height < sea_level {
+            frontier.push(Reverse(Spill { height, tile }));
+        }
+    }
+    if frontier.is_empty() {
+        if let Some((tile, &height)) = heights
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        {
+            frontier.push(Reverse(Spill { height, tile }));
+        }
+    }
+    while let Some(Reverse(Spill { height, tile })) = frontier.pop() {
+        if filled[tile].is_some() {
+            continue;
+        }
+        filled[tile] = Some(height);
+        for &next in adjacent(tile) {
+            if filled[next].is_none() {
+                frontier.push(Reverse(Spill {
+                    height: heights[next].max(height),
+                    tile: next,
+                }));
+            }
+        }
+    }
+    filled
+        .into_iter()
+        .zip(heights)
+        .map(|(filled, &height)| filled.unwrap_or(height))
+        .collect()
+}
+
+/// Groups the land tiles that [fill_depressions] raised into connected lakes
+pub fn find_lakes<'a>(
+    heights: &[f32],
+    filled: &[f32],
+    sea_level: f32,
+    adjacent: impl Fn(usize) -> &'a [usize],
+) -> Vec<Lake> {
+    let flooded = |tile: usize| heights[tile] >= sea_level && filled[tile] > heights[tile];
+    let mut visited = vec![false; heights.len()];
+    let mut lakes = Vec::new();
+    for start in 0..heights.len() {
+        if visited[start] || !flooded(start) {
+            continue;
+        }
+        visited[start] = true;
+        let mut tiles = Vec::new();
+        let mut stack = vec![start];
+        while let Some(tile) = stack.pop() {
+            tiles.push(tile);
+            for &next in adjacent(tile) {
+                // Neighbouring basins spilling at different levels are separate lakes
+                if !visited[next] && flooded(next) && filled[next] == filled[start] {
+                    visited[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        lakes.push(Lake {
+            tiles,
+            surface_height: filled[start],
+        });
+    }
+    lakes
+}
@@ -0,0 +1,85 @@
+use serde::{Deserialize, Serialize};
+
+/// Glaciers on land cold enough to keep snow through the year, and the valleys they carve
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct IceConfig {
+    /// Tiles with a mean annual temperature below this, in °C, build up ice
+    pub glaciation_temperature: f32,
+    /// Ice gained per pass for each unit of precipitation on a glaciated tile
+    pub accumulation_rate: f32,
+    /// Ice lost per pass for each °C a tile is warmer than [IceConfig::glaciation_temperature]
+    pub melt_rate: f32,
+    /// [0,1] Fraction of a tile's ice that flows to its lowest neighbour each pass
+    pub flow_fraction: f32,
+    /// Height removed from a tile per unit of ice flowing out of it, 0 disables glacial erosion
+    pub erosion_rate: f32,
+    /// How many accumulate, melt and flow passes are run
+    pub passes: usize,
+}
+
+impl Default for IceConfig {
+    fn default() -> Self {
+        IceConfig {
+            glaciation_temperature: -5.,
+            accumulation_rate: 0.01,
+            melt_rate: 0.002,
+            flow_fraction: 0.2,
+            erosion_rate: 0.005,
+            passes: 50,
+        }
+    }
+}
+
+/// Builds up ice on land tiles below [IceConfig::glaciation_temperature] and lets it flow downhill, deepening the valleys it moves through.
+/// `heights` are lowered in place, ice reaching the ocean breaks off.
+/// Returns the ice thickness of every tile, tiles are glaciated where it is above zero.
+pub fn glaciate<'a>(
+    config: &IceConfig,
+    heights: &mut [f32],
+    temperatures: &[f32],
+    precipitation: &[f32],
+    sea_level: f32,
+    adjacent: impl Fn(usize) -> &'a [usize],
+) -> Vec<f32> {
+    let mut thickness = vec![0.; heights.len()];
+    for _ in 0..config.passes {
+        for tile in 0..heights.len() {
+            if heights[tile] < sea_level {
+                thickness[tile] = 0.;
+                continue;
+            }
+            let warmth = temperatures[tile] - config.glaciation_temperature;
+            if warmth < 0. {
+                thickness[tile] += config.accumulation_rate * precipitation[tile];
+            } else {
+                thickness[tile] = (thickness[tile] - config.melt_rate * warmth).max(0.);
+            }
+        }
+
+        let mut inflow = vec![0.; heights.len()];
+        for tile in 0..heights.len() {
+            if thickness[tile] <= 0. {
+                continue;
+            }
+            let surface = |tile: usize| heights[tile] + thickness[tile];
+            let Some(lowest) = adjacent(tile)
+                .iter()
+                .copied()
+                .filter(|&other| other != tile)
+                .min_by(|&a, &b| surface(a).total_cmp(&surface(b)))
+                .filter(|&lowest| surface(lowest) < surface(tile))
+            else {
+                continue;
+            };
+            let outflow = thickness[tile] * config.flow_fraction;
+            thickness[tile] -= outflow;
+            inflow[lowest] += outflow;
+            // Glaciers cut down into their bed, but not below sea level
+            heights[tile] = (heights[tile] - config.erosion_rate * outflow).max(sea_level);
+        }
+        for (thickness, inflow) in thickness.iter_mut().zip(inflow) {
+            *thickness += inflow;
+        }
+    }
+    thickness
+}
@@ -0,0 +1,147 @@
+use glam::Vec3;
+use rayon::prelude::*;
+
+use crate::{compression::CompressionField, plate::PlateType, tectonics::Tectonics};
+
+/// For each tile normal, compute the inverse distance weighted average of the values of nearby point masses.
+/// `point_mass_values` holds one value per point mass, per plate, in the same order as [Tectonics::plates].
+/// Tiles without any point mass within [crate::tectonics::TectonicsConfiguration::vertex_interpolation_radius] get `empty_value`.
+/// Tiles are split across threads but each tile sums its neighbours sequentially in [crate::sphere_tree::SphereTree] order, so the result does not depend on the thread count.
+pub fn interpolate_tile_values(
+    tectonics: &Tectonics,
+    normals: &[Vec3],
+    point_mass_values: &[Vec<f32>],
+    empty_value: f32,
+) -> Vec<f32> {
+    normals
+        .par_iter()
+        .map(|normal| {
+            let mut weighted_sum = 0.0;
+            let mut weight_total = 0.0;
+            for (handle, distance) in tectonics
+                .tree
+                .query_within(*normal, tectonics.config.vertex_interpolation_radius)
+            {
+                let Some(value) = point_mass_values
+                    .get(handle.plate)
+                    .and_then(|values| values.get(handle.point_mass))
+                else {
+                    continue;
+                };
+                let weight = 1.0 / (distance + 0.01); // closer = higher weight, avoid div by zero
+                weighted_sum += value * weight;
+                weight_total += weight;
+            }
+            if weight_total > 0.0 {
+                weighted_sum / weight_total
+            } else {
+                empty_value
+            }
+        })
+        .collect()
+}
+
+/// For each tile normal, compute the height as the inverse distance weighted average of nearby point masses.
+/// Point masses contribute their plate height and crust thickness plus the summed compression of the springs they anchor.
+/// With [crate::tectonics::TectonicsConfiguration::isostasy] the crust thickness and compression thicken the crust instead, which floats to its height.
+pub fn interpolate_tile_heights(tectonics: &Tectonics, normals: &[Vec3]) -> Vec<f32> {
+    let compression = CompressionField::new(&tectonics.plates);
+    interpolate_tile_heights_with(tectonics, &compression, normals)
+}
+
+/// Like [interpolate_tile_heights], with spring compressions from a [CompressionField] kept up to date by the caller
+pub fn interpolate_tile_heights_with(
+    tectonics: &Tectonics,
+    compression: &CompressionField,
+    normals: &[Vec3],
+) -> Vec<f32> {
+    let config = &tectonics.config;
+    let point_mass_heights: Vec<Vec<f32>> = tectonics
+        .plates
+        .iter()
+        .zip(compression.point_mass_compression())
+        .map(|(plate, compression)| {
+            let plate_height = config.plate_height(plate.plate_type);
+            compression
+                .iter()
+                .zip(&plate.crust_thickness)
+                .map(|(&compression, crust_thickness)| match &config.isostasy {
+                    Some(isostasy) => {
+                        isostasy.point_mass_height(plate.plate_type, *crust_thickness, compression)
+                    }
+                    None => plate_height + crust_thickness + compression,
+                })
+                .collect()
+        })
+        .collect();
+    interpolate_tile_values(
+        tectonics,
+        normals,
+        &point_mass_heights,
+        config.plate_height(PlateType::Oceanic),
+    )
+}
+
+/// A tile that moved to a new height, see [height_updates]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeightUpdate {
+    /// Index into the tiles the heights were computed for
+    pub tile: usize,
+    pub height: f32,
+}
+
+/// The tiles whose height in `heights` differs from their current height by more than `epsilon`.
+/// Pure so the same diff can patch a mesh in the viewer or be checked without one.
+pub fn height_updates(
+    tiles: &[hex_sphere::Tile],
+    heights: &[f32],
+    epsilon: f32,
+) -> Vec<HeightUpdate> {
+    tiles
+        .iter()
+        .zip(heights)
+        .filter(|(tile, height)| (tile.height - **height).abs() > epsilon)
+        .map(|(tile, &height)| HeightUpdate {
+            tile: tile.index,
+            height,
+        })
+        .collect()
+}
+
+/// For each tile normal, how far tectonics raised the crust above its starting height, from the compression of nearby springs.
+/// Stretched crust counts as no uplift.
+pub fn interpolate_tile_uplift(tectonics: &Tectonics, normals: &[Vec3]) -> Vec<f32> {
+    let point_mass_uplift: Vec<Vec<f32>> = CompressionField::new(&tectonics.plates)
+        .point_mass_compression()
+        .iter()
+        .map(|compression| {
+            compression
+                .iter()
+                .map(|compression| compression.max(0.))
+                .collect()
+        })
+        .collect();
+    interpolate_tile_values(tectonics, normals, &point_mass_uplift, 0.)
+}
+
+/// For each tile normal, the index of the plate whose outline contains it, see [soft_sphere::Shape::contains].
+/// Where outlines overlap, as where one plate subducts under another, the plate owning the closest point mass wins.
+/// Tiles in the gaps between drifting plates take the plate of the closest point mass.
+pub fn containing_plates(tectonics: &Tectonics, normals: &[Vec3]) -> Vec<usize> {
+    normals
+        .par_iter()
+        .map(|&normal| {
+            let nearest = tectonics.tree.nearest(normal).map(|handle| handle.plate);
+            nearest
+                .filter(|&plate| tectonics.plates[plate].shape.contains(normal))
+                .or_else(|| {
+                    tectonics
+                        .plates
+                        .iter()
+                        .position(|plate| plate.shape.contains(normal))
+                })
+                .or(nearest)
+                .unwrap_or(0)
+        })
+        .collect()
+}
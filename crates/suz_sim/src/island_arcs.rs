@@ -0,0 +1,128 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bathymetry::oceanic_tiles,
+    boundaries::{BoundaryType, PlateBoundaries},
+    distance_field::nearest_sources,
+    tectonics::Tectonics,
+};
+
+/// Volcanic island arcs behind ocean-ocean subduction zones, like Japan or the Philippines.
+/// The younger of two converging oceanic plates overrides the older one, and melt rising from the sinking slab builds an arc of volcanoes
+/// a short distance behind the trench. Distances are geodesic on the unit sphere, heights in tile height units.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct IslandArcConfig {
+    /// Distance from the boundary into the overriding plate where the arc peaks
+    pub offset: f32,
+    /// Distance from the peak at which uplift falls to a third
+    pub width: f32,
+    /// Height gained per simulated time unit at the peak of an arc converging at unit speed
+    pub uplift_rate: f32,
+    /// Highest the uplift of a tile grows
+    pub max_uplift: f32,
+}
+
+impl Default for IslandArcConfig {
+    fn default() -> Self {
+        IslandArcConfig {
+            offset: 0.04,
+            width: 0.015,
+            uplift_rate: 0.02,
+            max_uplift: 0.04,
+        }
+    }
+}
+
+impl IslandArcConfig {
+    /// Fraction of the peak uplift at `distance` from the boundary
+    fn profile(&self, distance: f32) -> f32 {
+        (-((distance - self.offset) / self.width).powi(2)).exp()
+    }
+}
+
+/// Uplift accumulated by the island arcs of each tile, grown every time the plate boundaries are classified
+#[derive(Clone)]
+pub struct IslandArcs {
+    config: IslandArcConfig,
+    /// Height added to each tile so far
+    pub uplift: Vec<f32>,
+    last_update: f32,
+}
+
+impl IslandArcs {
+    pub fn new(config: IslandArcConfig) -> Self {
+        IslandArcs {
+            config,
+            uplift: Vec::new(),
+            last_update: 0.,
+        }
+    }
+
+    /// Grows the arcs behind every convergent boundary between two oceanic tiles, by the simulated time passed since the last update.
+    /// The tile with the younger `crust_age` is on the overriding plate, `tile_plates` from [crate::interpolation::containing_plates] keeps each arc on it.
+    pub fn update<'a>(
+        &mut self,
+        tectonics: &Tectonics,
+        boundaries: &PlateBoundaries,
+        tile_plates: &[usize],
+        crust_age: &[f32],
+        normals: &[Vec3],
+        adjacent: impl Fn(usize) -> &'a [usize],
+    ) {
+        let config = &self.config;
+        self.uplift.resize(normals.len(), 0.);
+        let elapsed = (tectonics.simulated_time - self.last_update).max(0.);
+        self.last_update = tectonics.simulated_time;
+        let oceanic = oceanic_tiles(tectonics, tile_plates);
+
+        // Overriding boundary tiles with the convergence speed and plate of their fastest subduction zone
+        let mut arcs: Vec<Option<(f32, usize)>> = vec![None; normals.len()];
+        for segment in &boundaries.segments {
+            if segment.boundary_type != BoundaryType::Convergent
+                || !segment.tiles.iter().all(|&tile| oceanic[tile])
+            {
+                continue;
+            }
+            let [tile_a, tile_b] = segment.tiles;
+            let age = |tile: usize| crust_age.get(tile).copied().unwrap_or(0.);
+            let overriding = if age(tile_a) <= age(tile_b) {
+                tile_a
+            } else {
+                tile_b
+            };
+            let speed = -segment.normal_speed;
+            if arcs[overriding].is_none_or(|(fastest, _)| speed > fastest) {
+                arcs[overriding] = Some((speed, tile_plates[overriding]));
+            }
+        }
+
+        let sources = arcs
+            .iter()
+            .enumerate()
+            .filter_map(|(tile, arc)| arc.map(|arc| (tile, arc)));
+        let max_distance = config.offset + 2. * config.width;
+        let nearest_arcs = nearest_sources(sources, normals, adjacent, max_distance, |tile| {
+            oceanic[tile]
+        });
+        for (tile, nearest_arc) in nearest_arcs.into_iter().enumerate() {
+            let Some((distance, (speed, plate))) = nearest_arc else {
+                continue;
+            };
+            // The search may wrap around the trench onto the subducting plate, which gets no arc
+            if tile_plates[tile] != plate {
+                continue;
+            }
+            let uplift = &mut self.uplift[tile];
+            *uplift = (*uplift + config.uplift_rate * speed * config.profile(distance) * elapsed)
+                .min(config.max_uplift);
+        }
+    }
+}
+
+/// Raises every tile by its island arc `uplift`, tiles without any yet are left as they are
+pub fn apply_island_arcs(heights: &mut [f32], uplift: &[f32]) {
+    for (height, uplift) in heights.iter_mut().zip(uplift) {
+        *height += uplift;
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use crate::plate::PlateType;
+
+/// Airy isostasy: crust floats on the denser mantle, so thicker or lighter crust stands higher.
+/// Collisions thicken the crust and raise it, stretching thins and lowers it.
+/// Thicknesses and heights are in tile height units, densities only matter relative to each other.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct IsostasyConfig {
+    /// Thickness of new continental crust
+    pub continental_thickness: f32,
+    /// Thickness of new oceanic crust
+    pub oceanic_thickness: f32,
+    pub continental_density: f32,
+    pub oceanic_density: f32,
+    /// Must be denser than both kinds of crust for them to float
+    pub mantle_density: f32,
+    /// Height crust of no thickness would sit at
+    pub mantle_height: f32,
+    /// Crust thickness gained per unit of spring compression
+    pub compression_thickening: f32,
+}
+
+impl Default for IsostasyConfig {
+    /// Close to [crate::tectonics::OCEANIC_HEIGHT] and [crate::tectonics::CONTINENTAL_HEIGHT] for uncompressed crust
+    fn default() -> Self {
+        IsostasyConfig {
+            continental_thickness: 0.27,
+            oceanic_thickness: 0.07,
+            continental_density: 2.7,
+            oceanic_density: 2.9,
+            mantle_density: 3.3,
+            mantle_height: 0.9715,
+            compression_thickening: 5.5,
+        }
+    }
+}
+
+impl IsostasyConfig {
+    pub fn density(&self, plate_type: PlateType) -> f32 {
+        match plate_type {
+            PlateType::Oceanic => self.oceanic_density,
+            PlateType::Continental => self.continental_density,
+        }
+    }
+
+    /// Thickness of new crust of `plate_type`
+    pub fn thickness(&self, plate_type: PlateType) -> f32 {
+        match plate_type {
+            PlateType::Oceanic => self.oceanic_thickness,
+            PlateType::Continental => self.continental_thickness,
+        }
+    }
+
+    /// Height of the surface of crust `thickness` thick, the mantle it displaces holds up all but the part standing above it
+    pub fn surface_height(&self, plate_type: PlateType, thickness: f32) -> f32 {
+        self.mantle_height
+            + thickness.max(0.) * (1. - self.density(plate_type) / self.mantle_density)
+    }
+
+    /// Height of a point mass whose new crust was made `extra_thickness` thicker and then compressed by `compression`
+    pub fn point_mass_height(
+        &self,
+        plate_type: PlateType,
+        extra_thickness: f32,
+        compression: f32,
+    ) -> f32 {
+        self.surface_height(
+            plate_type,
+            self.thickness(plate_type)
+                + extra_thickness
+                + compression * self.compression_thickening,
+        )
+    }
+}
@@ -1,6 +1,41 @@
+pub mod bake;
+pub mod bathymetry;
+pub mod boundaries;
+pub mod climate;
+pub mod compression;
+pub mod config;
+pub mod crust;
+pub mod detail;
+pub mod distance_field;
+pub mod epochs;
+pub mod export;
+pub mod flexure;
+pub mod generator;
+pub mod geojson;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod history;
+pub mod hydrology;
+pub mod ice;
+pub mod interpolation;
+pub mod island_arcs;
+pub mod isostasy;
+pub mod observer;
 pub mod particle_sphere;
 pub mod plate;
+pub mod plate_import;
+pub mod plate_motion;
+pub mod serialize;
+pub mod shelf;
+pub mod sphere_tree;
+pub mod spherical_grid;
+pub mod strain;
 pub mod tectonics;
+pub mod trails;
+pub mod units;
+pub mod validation;
 pub mod vec_utils;
+pub mod vertex_interpolation;
+pub use generator::{Planet, PlanetGenerator};
 pub use soft_sphere::PointMass;
 pub use soft_sphere::Shape;
@@ -0,0 +1,108 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::tectonics::Tectonics;
+
+/// Steps of planet generation reported to [SimulationObserver::on_stage_complete]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    ParticleSphere,
+    HexSphere,
+    /// The tectonics of one epoch
+    Tectonics,
+    /// The erosion of one epoch
+    Erosion,
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stage::ParticleSphere => write!(f, "Particle sphere"),
+            Stage::HexSphere => write!(f, "Hex sphere"),
+            Stage::Tectonics => write!(f, "Tectonics"),
+            Stage::Erosion => write!(f, "Erosion"),
+        }
+    }
+}
+
+/// Returned by every [SimulationObserver] callback, ordered so the max of several is an abort if any of them is
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Control {
+    Continue,
+    /// Stop generating, the planet is discarded
+    Abort,
+}
+
+/// Hooks into planet generation for logging, streaming or aborting it, without changing the loop that runs it.
+/// Both [crate::PlanetGenerator] and the planet viewer call these, every callback continues by default.
+pub trait SimulationObserver {
+    /// Called after tectonic steps with the [Tectonics::iteration] reached.
+    /// The viewer steps in batches and only reports the last step of each.
+    fn on_iteration(&mut self, _iteration: usize, _tectonics: &Tectonics) -> Control {
+        Control::Continue
+    }
+
+    fn on_stage_complete(&mut self, _stage: Stage, _duration: Duration) -> Control {
+        Control::Continue
+    }
+}
+
+/// Observes nothing
+impl SimulationObserver for () {}
+
+/// Both observers see every callback, either can abort
+impl<A: SimulationObserver, B: SimulationObserver> SimulationObserver for (A, B) {
+    fn on_iteration(&mut self, iteration: usize, tectonics: &Tectonics) -> Control {
+        self.0
+            .on_iteration(iteration, tectonics)
+            .max(self.1.on_iteration(iteration, tectonics))
+    }
+
+    fn on_stage_complete(&mut self, stage: Stage, duration: Duration) -> Control {
+        self.0
+            .on_stage_complete(stage, duration)
+            .max(self.1.on_stage_complete(stage, duration))
+    }
+}
+
+/// Cancels generation from anywhere, clones share the same flag.
+/// As an observer it aborts at the next iteration or stage after [AbortHandle::abort], a stage already running is finished first.
+#[derive(Clone, Default, Debug)]
+pub struct AbortHandle(Arc<AtomicBool>);
+
+impl AbortHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn abort(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn control(&self) -> Control {
+        if self.is_aborted() {
+            Control::Abort
+        } else {
+            Control::Continue
+        }
+    }
+}
+
+impl SimulationObserver for AbortHandle {
+    fn on_iteration(&mut self, _iteration: usize, _tectonics: &Tectonics) -> Control {
+        self.control()
+    }
+
+    fn on_stage_complete(&mut self, _stage: Stage, _duration: Duration) -> Control {
+        self.control()
+    }
+}
@@ -1,11 +1,36 @@
-use bevy::prelude::*;
-use subsphere::{Face, Sphere, Vertex, proj::Fuller};
+use glam::Vec3;
+use hex_sphere::TileGraph;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use subsphere::{Face, Sphere, proj::Fuller};
 
 use crate::vec_utils;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct ParticleSphereConfig {
     pub subdivisions: u32,
+    /// Furthest each tile center is moved at random, as a fraction of the distance to its closest neighbour.
+    /// Breaks up the regular subsphere grid so plate boundaries look less artificial, 0 keeps the grid as is.
+    #[serde(default)]
+    pub jitter: f32,
+    /// Passes moving each tile center towards the centroid of its neighbours after jittering, evening out clumps
+    #[serde(default)]
+    pub relaxation_passes: u32,
+}
+
+impl ParticleSphereConfig {
+    /// The unperturbed subsphere grid at `subdivisions`
+    pub fn regular(subdivisions: u32) -> Self {
+        ParticleSphereConfig {
+            subdivisions,
+            jitter: 0.,
+            relaxation_passes: 0,
+        }
+    }
+
+    fn perturbed(&self) -> bool {
+        self.jitter > 0. || self.relaxation_passes > 0
+    }
 }
 
 #[derive(Clone)]
@@ -14,56 +39,135 @@ pub struct ParticleTile {
     pub index: usize,
     /// Indices to adjacent tiles
     pub adjacent: Vec<usize>,
-    /// Tile face normal
+    /// Tile center on the unit sphere, off the subsphere face center when jittered or relaxed
     pub normal: Vec3,
 }
 
-#[derive(Resource)]
 pub struct ParticleSphere {
     pub config: ParticleSphereConfig,
     pub subsphere: subsphere::HexSphere<Fuller>,
     pub tiles: Vec<ParticleTile>,
+    /// Tile neighbours and centers for neighbourhood and path queries
+    pub graph: TileGraph,
 }
 
 impl ParticleSphere {
-    pub fn from_config(config: ParticleSphereConfig) -> Self {
-        let c = config.subdivisions % 3;
-        let subsphere = subsphere::HexSphere::from_kis(subsphere::TriSphere::new(
-            subsphere::BaseTriSphere::Icosa,
-            subsphere::proj::Fuller,
-            std::num::NonZero::new(config.subdivisions).unwrap(),
-            c,
-        ))
-        .unwrap();
+    /// `rng` is only drawn from when [ParticleSphereConfig::jitter] is positive, a regular grid does not change the random sequence
+    pub fn from_config(config: ParticleSphereConfig, rng: &mut rand::rngs::StdRng) -> Self {
+        let subsphere = hex_sphere::subsphere(config.subdivisions);
         let mut tiles: Vec<ParticleTile> = Vec::with_capacity(subsphere.num_faces());
         for (i, face) in subsphere.faces().enumerate() {
             let face_normal = vec_utils::f64_3_to_f32_3(&face.center().pos());
-            let mut adjacent = face
-                .vertices()
-                .flat_map(|v| {
-                    v.faces()
-                        .filter_map(|f| {
-                            if f.index() != face.index() {
-                                Some(f.index())
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<usize>>()
-                })
-                .collect::<Vec<usize>>();
-            adjacent.sort_unstable();
-            adjacent.dedup();
+            let mut adjacent = hex_sphere::adjacent_faces(&face);
+            adjacent.retain(|&f| f != face.index());
             tiles.push(ParticleTile {
                 index: i,
                 adjacent,
                 normal: face_normal.into(),
             });
         }
+        if config.perturbed() {
+            perturb_tiles(&mut tiles, &config, rng);
+        }
+        let graph = TileGraph::new(
+            tiles.iter().map(|tile| tile.adjacent.clone()).collect(),
+            tiles.iter().map(|tile| tile.normal).collect(),
+        );
         ParticleSphere {
             config,
             subsphere,
             tiles,
+            graph,
         }
     }
 }
+
+/// Jitters then relaxes the tile centers and rebuilds adjacency from the moved centers
+fn perturb_tiles(
+    tiles: &mut [ParticleTile],
+    config: &ParticleSphereConfig,
+    rng: &mut rand::rngs::StdRng,
+) {
+    if config.jitter > 0. {
+        let offsets: Vec<Vec3> = tiles
+            .iter()
+            .map(|tile| {
+                let spacing = tile
+                    .adjacent
+                    .iter()
+                    .map(|&adjacent| tile.normal.distance(tiles[adjacent].normal))
+                    .fold(f32::INFINITY, f32::min);
+                let direction = Vec3::new(
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                    rng.random_range(-1.0..1.0),
+                )
+                .reject_from_normalized(tile.normal)
+                .normalize_or_zero();
+                direction * spacing * config.jitter * rng.random::<f32>()
+            })
+            .collect();
+        for (tile, offset) in tiles.iter_mut().zip(offsets) {
+            tile.normal = (tile.normal + offset).normalize();
+        }
+    }
+
+    // The centroid of a tile is roughly halfway between its center and the mean of its neighbours
+    for _ in 0..config.relaxation_passes {
+        let relaxed: Vec<Vec3> = tiles
+            .iter()
+            .map(|tile| {
+                let neighbour_mean = tile
+                    .adjacent
+                    .iter()
+                    .map(|&adjacent| tiles[adjacent].normal)
+                    .sum::<Vec3>()
+                    / tile.adjacent.len() as f32;
+                (tile.normal + neighbour_mean).normalize()
+            })
+            .collect();
+        for (tile, normal) in tiles.iter_mut().zip(relaxed) {
+            tile.normal = normal;
+        }
+    }
+
+    // Centers move less than half a tile, so the new neighbours are all within two steps on the regular grid.
+    // A candidate is kept if no other candidate lies in the sphere spanned by it and the tile (the Gabriel graph),
+    // which on the regular grid is exactly the original adjacency.
+    let adjacent: Vec<Vec<usize>> = tiles
+        .iter()
+        .enumerate()
+        .map(|(tile_index, tile)| {
+            let mut candidates: Vec<usize> = tile
+                .adjacent
+                .iter()
+                .flat_map(|&adjacent| {
+                    std::iter::once(adjacent).chain(tiles[adjacent].adjacent.iter().copied())
+                })
+                .filter(|&candidate| candidate != tile_index)
+                .collect();
+            candidates.sort_unstable();
+            candidates.dedup();
+            candidates
+                .iter()
+                .copied()
+                .filter(|&candidate| {
+                    let candidate_normal = tiles[candidate].normal;
+                    candidates.iter().all(|&other| {
+                        let other_normal = tiles[other].normal;
+                        other == candidate
+                            || (tile.normal - other_normal).dot(candidate_normal - other_normal)
+                                > 0.
+                    })
+                })
+                .collect()
+        })
+        .collect();
+    for (tile_index, tile) in tiles.iter_mut().enumerate() {
+        tile.adjacent = adjacent[tile_index]
+            .iter()
+            .copied()
+            .filter(|&neighbour| adjacent[neighbour].contains(&tile_index))
+            .collect();
+    }
+}
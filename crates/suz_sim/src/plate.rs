@@ -1,4 +1,4 @@
-use bevy::prelude::*;
+use glam::{Vec2, Vec3};
 use rand::Rng;
 
 #[derive(PartialEq, Clone, Copy)]
@@ -7,20 +7,32 @@ pub enum PlateType {
     Continental,
 }
 
+impl std::fmt::Display for PlateType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlateType::Oceanic => write!(f, "Oceanic"),
+            PlateType::Continental => write!(f, "Continental"),
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Plate {
     pub plate_type: PlateType,
-    pub color: Color,
+    /// Linear RGBA
+    pub color: [f32; 4],
     pub axis_of_rotation: Vec3,
     pub drift_direction: Vec2,
     pub shape: soft_sphere::Shape,
+    /// Height offset of each point mass from the plate type height, in the order of the shape point masses
+    pub crust_thickness: Vec<f32>,
 }
 
 impl Plate {
     pub fn random(plate_type: PlateType, rng: &mut rand::rngs::StdRng) -> Self {
-        let plate_color = LinearRgba::new(rng.random(), rng.random(), rng.random(), 1.).into();
         Plate {
             plate_type: plate_type.clone(),
-            color: plate_color,
+            color: [rng.random(), rng.random(), rng.random(), 1.],
             axis_of_rotation: Vec3::new(
                 rng.random_range(-1.0..1.0),
                 rng.random_range(-1.0..1.0),
@@ -30,6 +42,7 @@ impl Plate {
             drift_direction: Vec2::new(rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0))
                 .normalize(),
             shape: soft_sphere::Shape::new(),
+            crust_thickness: Vec::new(),
         }
     }
 }
@@ -0,0 +1,217 @@
+use std::{fmt, path::Path};
+
+use glam::Vec3;
+use rayon::prelude::*;
+
+use crate::{
+    distance_field::nearest_sources,
+    geojson::{GeoJsonError, JsonValue, Position, read_polygon_features},
+    plate::PlateType,
+    vec_utils,
+};
+
+/// Properties read as a plate's name, in order, covering the common present-day plate datasets
+const NAME_PROPERTIES: [&str; 3] = ["name", "PlateName", "Code"];
+
+/// A ring on the unit sphere, counterclockwise on the map, with the smallest cap around it to skip far points quickly
+struct SphericalRing {
+    points: Vec<Vec3>,
+    center: Vec3,
+    /// Cosine of the angular radius of the cap, `None` if the ring is too large for a cap to exclude anything
+    cap: Option<f32>,
+    /// Rings spanning the whole map, like [crate::geojson::region_polygons] writes for plates reaching around the planet
+    whole_map: bool,
+}
+
+impl SphericalRing {
+    fn new(ring: &[Position]) -> Self {
+        let area = signed_area(ring);
+        let mut points: Vec<Vec3> = ring
+            .iter()
+            .map(|[longitude, latitude]| {
+                vec_utils::lat_long_to_vec3(latitude.to_radians(), longitude.to_radians())
+            })
+            .collect();
+        // Winding is not trusted, older GeoJSON does not require exteriors to be counterclockwise
+        if area < 0. {
+            points.reverse();
+        }
+        let center = points.iter().sum::<Vec3>().normalize_or_zero();
+        let cap = points
+            .iter()
+            .map(|point| point.dot(center))
+            .min_by(f32::total_cmp)
+            .filter(|&cap| cap > 0.);
+        SphericalRing {
+            points,
+            center,
+            cap,
+            whole_map: area.abs() >= 360. * 180. * 0.999,
+        }
+    }
+
+    /// Whether the ring winds around `point`, summing the angles at `point` between the great circles to consecutive vertices.
+    /// The sum is a full negative turn inside a counterclockwise ring, as longitude grows to the west in [vec_utils::lat_long_to_vec3].
+    fn contains(&self, point: Vec3) -> bool {
+        if self.whole_map {
+            return true;
+        }
+        if self.cap.is_some_and(|cap| point.dot(self.center) < cap) {
+            return false;
+        }
+        let winding: f32 = self
+            .points
+            .iter()
+            .zip(self.points.iter().cycle().skip(1))
+            .map(|(&a, &b)| {
+                let sin = point.dot(a.cross(b));
+                let cos = a.dot(b) - a.dot(point) * b.dot(point);
+                sin.atan2(cos)
+            })
+            .sum();
+        winding < -std::f32::consts::PI
+    }
+}
+
+/// Shoelace area on the map, positive for counterclockwise rings
+fn signed_area(ring: &[Position]) -> f32 {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(a, b)| a[0] * b[1] - b[0] * a[1])
+        .sum::<f32>()
+        / 2.
+}
+
+/// A plate read from a GeoJSON plate polygon dataset
+pub struct ImportedPlate {
+    pub name: String,
+    pub plate_type: PlateType,
+    /// Exterior ring followed by its holes for each polygon
+    polygons: Vec<Vec<SphericalRing>>,
+}
+
+impl ImportedPlate {
+    /// Whether `point` on the unit sphere lies inside any polygon of the plate and outside its holes
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.polygons.iter().any(|polygon| {
+            polygon
+                .first()
+                .is_some_and(|exterior| exterior.contains(point))
+                && !polygon[1..].iter().any(|hole| hole.contains(point))
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(std::io::Error),
+    GeoJson(GeoJsonError),
+    /// The file holds no Polygon or MultiPolygon features
+    NoPlates,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "{e}"),
+            ImportError::GeoJson(e) => write!(f, "{e}"),
+            ImportError::NoPlates => write!(f, "No plate polygons found"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e: std::io::Error) -> Self {
+        ImportError::Io(e)
+    }
+}
+
+impl From<GeoJsonError> for ImportError {
+    fn from(e: GeoJsonError) -> Self {
+        ImportError::GeoJson(e)
+    }
+}
+
+/// Reads every Polygon and MultiPolygon feature of a GeoJSON FeatureCollection as a plate, such as a present-day Earth plate dataset.
+/// A `plate_type` property of `Continental` or `Oceanic`, as [crate::geojson] exports, sets the type of a plate.
+/// Otherwise plates named in `continental` are continental and the rest oceanic, names come from the first `name`, `PlateName` or `Code` property.
+pub fn plates_from_geojson(
+    text: &str,
+    continental: &[String],
+) -> Result<Vec<ImportedPlate>, ImportError> {
+    let plates: Vec<ImportedPlate> = read_polygon_features(text)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, feature)| {
+            let name = NAME_PROPERTIES
+                .iter()
+                .find_map(|&key| match feature.properties.get(key) {
+                    Some(JsonValue::String(name)) => Some(name.clone()),
+                    Some(JsonValue::Number(number)) => Some(number.to_string()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| format!("Plate {index}"));
+            let plate_type = match feature
+                .properties
+                .get("plate_type")
+                .and_then(JsonValue::as_str)
+            {
+                Some("Continental") => PlateType::Continental,
+                Some("Oceanic") => PlateType::Oceanic,
+                _ if continental.contains(&name) => PlateType::Continental,
+                _ => PlateType::Oceanic,
+            };
+            let polygons = feature
+                .polygons
+                .iter()
+                .map(|polygon| {
+                    polygon
+                        .iter()
+                        .map(|ring| SphericalRing::new(ring))
+                        .collect()
+                })
+                .collect();
+            ImportedPlate {
+                name,
+                plate_type,
+                polygons,
+            }
+        })
+        .collect();
+    if plates.is_empty() {
+        return Err(ImportError::NoPlates);
+    }
+    Ok(plates)
+}
+
+/// Reads the plates of a GeoJSON file, see [plates_from_geojson]
+pub fn load_plates(
+    path: impl AsRef<Path>,
+    continental: &[String],
+) -> Result<Vec<ImportedPlate>, ImportError> {
+    plates_from_geojson(&std::fs::read_to_string(path)?, continental)
+}
+
+/// Index into `plates` for every tile, the first plate whose polygons contain the tile normal.
+/// Datasets rarely meet exactly, tiles in gaps between polygons take the plate of the closest tile inside one.
+/// Returns `None` if no tile lies inside any plate.
+pub fn assign_tiles<'a>(
+    plates: &[ImportedPlate],
+    normals: &[Vec3],
+    adjacent: impl Fn(usize) -> &'a [usize],
+) -> Option<Vec<usize>> {
+    let covered: Vec<Option<usize>> = normals
+        .par_iter()
+        .map(|&normal| plates.iter().position(|plate| plate.contains(normal)))
+        .collect();
+    let sources = covered
+        .iter()
+        .enumerate()
+        .filter_map(|(tile, plate)| plate.map(|plate| (tile, plate)));
+    nearest_sources(sources, normals, adjacent, f32::INFINITY, |_| true)
+        .into_iter()
+        .map(|nearest| nearest.map(|(_, plate)| plate))
+        .collect()
+}
@@ -0,0 +1,209 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    plate::{Plate, PlateType},
+    sphere_tree::SphereTree,
+};
+
+/// Plate rotation driven by the forces where plates meet, instead of drifting at random.
+/// Each plate's [Plate::axis_of_rotation] is treated as its angular velocity, and changes with the net torque of its boundary forces.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct TorqueConfig {
+    /// Force per unit closing speed pushing colliding point masses apart, which slows and deflects the plates
+    pub collision_resistance: f32,
+    /// Force pulling a subducting point mass towards the plate it sinks under
+    pub slab_pull: f32,
+    /// Longest [Plate::axis_of_rotation] may grow, 1 being the fixed length of randomly drifting axes
+    pub max_spin: f32,
+}
+
+impl Default for TorqueConfig {
+    fn default() -> Self {
+        TorqueConfig {
+            collision_resistance: 0.5,
+            slab_pull: 0.02,
+            max_spin: 2.,
+        }
+    }
+}
+
+/// Slab pull and ridge push, forces from the plate boundaries acting directly on the point masses near them.
+/// Each point mass is driven from its own boundaries instead of only by its plate's [Plate::axis_of_rotation].
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct DrivingForceConfig {
+    /// Force per unit mass pulling point masses near a subduction zone towards the plate they sink under
+    pub slab_pull: f32,
+    /// Force per unit mass pushing point masses near a diverging boundary away from the ridge
+    pub ridge_push: f32,
+    /// Geodesic distance from a contact over which both forces fade out
+    pub range: f32,
+    /// [0,1] Share of [crate::tectonics::TectonicsConfiguration::plate_force_modifier] still applied along the plate axis
+    pub rotation_forcing: f32,
+}
+
+impl Default for DrivingForceConfig {
+    fn default() -> Self {
+        DrivingForceConfig {
+            slab_pull: 0.02,
+            ridge_push: 0.01,
+            range: 0.1,
+            rotation_forcing: 0.5,
+        }
+    }
+}
+
+/// Whether `a` sinks under `b` where they converge.
+/// Oceanic crust subducts under continental crust, and of two oceanic point masses the one with thinner crust sinks.
+fn subducts(a: &Plate, a_index: usize, b: &Plate, b_index: usize) -> bool {
+    match (a.plate_type, b.plate_type) {
+        (PlateType::Oceanic, PlateType::Continental) => true,
+        (PlateType::Oceanic, PlateType::Oceanic) => {
+            a.crust_thickness[a_index] < b.crust_thickness[b_index]
+        }
+        _ => false,
+    }
+}
+
+/// Net torque on every plate from its converging `contacts`, given as (plate, point mass) pairs.
+/// Collisions push the point masses apart along the line between them, and a subducting point mass is pulled towards the other plate.
+pub fn boundary_torques(
+    plates: &[Plate],
+    contacts: &[[(usize, usize); 2]],
+    config: &TorqueConfig,
+) -> Vec<Vec3> {
+    let mut torques = vec![Vec3::ZERO; plates.len()];
+    for &[(plate_a, index_a), (plate_b, index_b)] in contacts {
+        let point_mass_a = &plates[plate_a].shape.point_masses()[index_a];
+        let point_mass_b = &plates[plate_b].shape.point_masses()[index_b];
+        // Unit direction from a to b
+        let direction = (point_mass_b.position - point_mass_a.position).normalize_or_zero();
+        let closing_speed = (point_mass_a.velocity - point_mass_b.velocity).dot(direction);
+        if closing_speed <= 0. {
+            continue;
+        }
+        let collision = direction * closing_speed * config.collision_resistance;
+        let mut force_a = -collision;
+        let mut force_b = collision;
+        if subducts(&plates[plate_a], index_a, &plates[plate_b], index_b) {
+            force_a += direction * config.slab_pull;
+        } else if subducts(&plates[plate_b], index_b, &plates[plate_a], index_a) {
+            force_b -= direction * config.slab_pull;
+        }
+        torques[plate_a] += point_mass_a.position.cross(force_a);
+        torques[plate_b] += point_mass_b.position.cross(force_b);
+    }
+    torques
+}
+
+/// Turns each plate's axis of rotation by its `torques` over `elapsed` simulated time.
+/// Point masses sit on the unit sphere, so a plate's moment of inertia is taken as that of a thin shell, two thirds of its mass.
+pub fn apply_torques(plates: &mut [Plate], torques: &[Vec3], elapsed: f32, config: &TorqueConfig) {
+    for (plate, torque) in plates.iter_mut().zip(torques) {
+        let mass: f32 = plate
+            .shape
+            .point_masses()
+            .iter()
+            .map(|point_mass| point_mass.mass)
+            .sum();
+        if mass <= 0. {
+            continue;
+        }
+        let moment_of_inertia = 2. / 3. * mass;
+        plate.axis_of_rotation = (plate.axis_of_rotation + *torque / moment_of_inertia * elapsed)
+            .clamp_length_max(config.max_spin);
+    }
+}
+
+/// Force per unit mass on every point mass of every plate from slab pull and ridge push at its `contacts`, given as (plate, point mass) pairs.
+/// Where a contact converges the subducting side is pulled towards the other plate, where it diverges both sides are pushed away from each other.
+/// Forces fade linearly to nothing at [DrivingForceConfig::range] from the contact, and the sum on a point mass is capped at the larger of the two
+/// so boundaries with many contacts do not pile up. `tree` must be refreshed with `plates`.
+pub fn driving_forces(
+    plates: &[Plate],
+    contacts: &[[(usize, usize); 2]],
+    tree: &SphereTree,
+    config: &DrivingForceConfig,
+) -> Vec<Vec<Vec3>> {
+    let mut forces: Vec<Vec<Vec3>> = plates
+        .iter()
+        .map(|plate| vec![Vec3::ZERO; plate.shape.point_masses().len()])
+        .collect();
+    for &[(plate_a, index_a), (plate_b, index_b)] in contacts {
+        let point_mass_a = &plates[plate_a].shape.point_masses()[index_a];
+        let point_mass_b = &plates[plate_b].shape.point_masses()[index_b];
+        // Unit direction from a to b
+        let direction = (point_mass_b.position - point_mass_a.position).normalize_or_zero();
+        let closing_speed = (point_mass_a.velocity - point_mass_b.velocity).dot(direction);
+        if closing_speed > 0. {
+            if subducts(&plates[plate_a], index_a, &plates[plate_b], index_b) {
+                let force = direction * config.slab_pull;
+                spread(
+                    &mut forces,
+                    plates,
+                    tree,
+                    plate_a,
+                    point_mass_a.position,
+                    force,
+                    config.range,
+                );
+            } else if subducts(&plates[plate_b], index_b, &plates[plate_a], index_a) {
+                let force = -direction * config.slab_pull;
+                spread(
+                    &mut forces,
+                    plates,
+                    tree,
+                    plate_b,
+                    point_mass_b.position,
+                    force,
+                    config.range,
+                );
+            }
+        } else if closing_speed < 0. {
+            let force = direction * config.ridge_push;
+            spread(
+                &mut forces,
+                plates,
+                tree,
+                plate_a,
+                point_mass_a.position,
+                -force,
+                config.range,
+            );
+            spread(
+                &mut forces,
+                plates,
+                tree,
+                plate_b,
+                point_mass_b.position,
+                force,
+                config.range,
+            );
+        }
+    }
+    let max_force = config.slab_pull.max(config.ridge_push);
+    for force in forces.iter_mut().flatten() {
+        *force = force.clamp_length_max(max_force);
+    }
+    forces
+}
+
+/// Adds `force` to the point masses of `plate` within `range` of `centre`, fading linearly with distance and kept tangent to the sphere
+fn spread(
+    forces: &mut [Vec<Vec3>],
+    plates: &[Plate],
+    tree: &SphereTree,
+    plate: usize,
+    centre: Vec3,
+    force: Vec3,
+    range: f32,
+) {
+    for (handle, distance) in tree.query_within(centre, range) {
+        if handle.plate != plate {
+            continue;
+        }
+        let position = plates[plate].shape.point_masses()[handle.point_mass].position;
+        let tangent = force - position * position.dot(force);
+        forces[plate][handle.point_mass] += tangent * (1. - distance / range);
+    }
+}
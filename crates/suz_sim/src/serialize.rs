@@ -0,0 +1,238 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use glam::{Vec2, Vec3};
+
+use crate::{
+    config::{ConfigError, SimulationConfig},
+    plate::PlateType,
+    tectonics::Tectonics,
+};
+
+const MAGIC: &[u8; 4] = b"SUZP";
+/// Bumped whenever the binary layout changes, older snapshots are rejected
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Plate data kept after the simulation, point masses and springs are not stored
+#[derive(Clone)]
+pub struct PlateSnapshot {
+    pub plate_type: PlateType,
+    /// Linear RGBA
+    pub color: [f32; 4],
+    pub axis_of_rotation: Vec3,
+    pub drift_direction: Vec2,
+}
+
+/// The full post-simulation state of a planet
+#[derive(Clone)]
+pub struct PlanetSnapshot {
+    pub seed: u64,
+    pub config: SimulationConfig,
+    /// Height of each hex sphere tile
+    pub tile_heights: Vec<f32>,
+    /// Index into [PlanetSnapshot::plates] for each hex sphere tile
+    pub tile_plates: Vec<u32>,
+    pub plates: Vec<PlateSnapshot>,
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    InvalidMagic,
+    UnsupportedVersion {
+        found: u32,
+        expected: u32,
+    },
+    Config(ConfigError),
+    /// The file was readable but its contents are inconsistent
+    Corrupt(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "{e}"),
+            SnapshotError::InvalidMagic => write!(f, "Not a planet snapshot"),
+            SnapshotError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "Snapshot version {found} is not supported, expected {expected}"
+            ),
+            SnapshotError::Config(e) => write!(f, "Invalid config in snapshot: {e}"),
+            SnapshotError::Corrupt(reason) => write!(f, "Corrupt snapshot: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(e: std::io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+pub(crate) fn write_u32(writer: &mut impl Write, value: u32) -> std::io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_f32s(writer: &mut impl Write, values: &[f32]) -> std::io::Result<()> {
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+pub(crate) fn read_array<const N: usize>(reader: &mut impl Read) -> std::io::Result<[u8; N]> {
+    let mut buffer = [0; N];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer)
+}
+
+pub(crate) fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    Ok(u32::from_le_bytes(read_array(reader)?))
+}
+
+pub(crate) fn read_f32(reader: &mut impl Read) -> std::io::Result<f32> {
+    Ok(f32::from_le_bytes(read_array(reader)?))
+}
+
+pub(crate) fn read_f32s<const N: usize>(reader: &mut impl Read) -> std::io::Result<[f32; N]> {
+    let mut values = [0.; N];
+    for value in &mut values {
+        *value = read_f32(reader)?;
+    }
+    Ok(values)
+}
+
+impl PlateSnapshot {
+    pub fn from_tectonics(tectonics: &Tectonics) -> Vec<PlateSnapshot> {
+        tectonics
+            .plates
+            .iter()
+            .map(|plate| PlateSnapshot {
+                plate_type: plate.plate_type,
+                color: plate.color,
+                axis_of_rotation: plate.axis_of_rotation,
+                drift_direction: plate.drift_direction,
+            })
+            .collect()
+    }
+}
+
+impl PlanetSnapshot {
+    /// Layout: magic, version, config as TOML, seed, tiles (height, plate), plates
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), SnapshotError> {
+        if self.tile_heights.len() != self.tile_plates.len() {
+            return Err(SnapshotError::Corrupt(format!(
+                "{} tile heights but {} tile plates",
+                self.tile_heights.len(),
+                self.tile_plates.len()
+            )));
+        }
+        writer.write_all(MAGIC)?;
+        write_u32(writer, SNAPSHOT_VERSION)?;
+        let config = self
+            .config
+            .to_toml_string()
+            .map_err(SnapshotError::Config)?;
+        write_u32(writer, config.len() as u32)?;
+        writer.write_all(config.as_bytes())?;
+        writer.write_all(&self.seed.to_le_bytes())?;
+
+        write_u32(writer, self.tile_heights.len() as u32)?;
+        write_f32s(writer, &self.tile_heights)?;
+        for plate in &self.tile_plates {
+            write_u32(writer, *plate)?;
+        }
+
+        write_u32(writer, self.plates.len() as u32)?;
+        for plate in &self.plates {
+            writer.write_all(&[match plate.plate_type {
+                PlateType::Oceanic => 0,
+                PlateType::Continental => 1,
+            }])?;
+            write_f32s(writer, &plate.color)?;
+            write_f32s(writer, &plate.axis_of_rotation.to_array())?;
+            write_f32s(writer, &plate.drift_direction.to_array())?;
+        }
+        Ok(())
+    }
+
+    pub fn read(reader: &mut impl Read) -> Result<Self, SnapshotError> {
+        if &read_array::<4>(reader)? != MAGIC {
+            return Err(SnapshotError::InvalidMagic);
+        }
+        let version = read_u32(reader)?;
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion {
+                found: version,
+                expected: SNAPSHOT_VERSION,
+            });
+        }
+        let mut config = vec![0; read_u32(reader)? as usize];
+        reader.read_exact(&mut config)?;
+        let config = String::from_utf8(config)
+            .map_err(|e| SnapshotError::Corrupt(format!("Config is not valid UTF-8: {e}")))?;
+        let config = SimulationConfig::from_toml_str(&config).map_err(SnapshotError::Config)?;
+        let seed = u64::from_le_bytes(read_array(reader)?);
+
+        let tile_count = read_u32(reader)? as usize;
+        let tile_heights = (0..tile_count)
+            .map(|_| read_f32(reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let tile_plates = (0..tile_count)
+            .map(|_| read_u32(reader))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let plate_count = read_u32(reader)? as usize;
+        let mut plates = Vec::with_capacity(plate_count);
+        for _ in 0..plate_count {
+            let plate_type = match read_array::<1>(reader)?[0] {
+                0 => PlateType::Oceanic,
+                1 => PlateType::Continental,
+                other => {
+                    return Err(SnapshotError::Corrupt(format!(
+                        "Unknown plate type {other}"
+                    )));
+                }
+            };
+            plates.push(PlateSnapshot {
+                plate_type,
+                color: read_f32s(reader)?,
+                axis_of_rotation: Vec3::from_array(read_f32s(reader)?),
+                drift_direction: Vec2::from_array(read_f32s(reader)?),
+            });
+        }
+        if let Some(plate) = tile_plates
+            .iter()
+            .find(|&&plate| plate as usize >= plate_count)
+        {
+            return Err(SnapshotError::Corrupt(format!(
+                "Tile assigned to plate {plate} but there are only {plate_count} plates"
+            )));
+        }
+
+        Ok(PlanetSnapshot {
+            seed,
+            config,
+            tile_heights,
+            tile_plates,
+            plates,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SnapshotError> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+}
@@ -0,0 +1,104 @@
+// Per point mass force and velocity verlet steps of Tectonics::simulate, mirroring soft_sphere::Shape
+
+struct Params {
+    timestep: f32,
+    plate_force_modifier: f32,
+    friction_coefficient: f32,
+    point_mass_count: u32,
+}
+
+struct PointMass {
+    position: vec4<f32>,
+    velocity: vec4<f32>,
+    force: vec4<f32>,
+    prev_force: vec4<f32>,
+    mass: f32,
+    plate: u32,
+    padding: vec2<u32>,
+}
+
+struct Spring {
+    anchor_a: u32,
+    anchor_b: u32,
+    rest_length: f32,
+    spring_constant: f32,
+    damping_coefficient: f32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read_write> point_masses: array<PointMass>;
+@group(0) @binding(2) var<storage, read> springs: array<Spring>;
+// Springs anchored to point mass i are spring_indices[spring_offsets[i]..spring_offsets[i + 1]]
+@group(0) @binding(3) var<storage, read> spring_offsets: array<u32>;
+@group(0) @binding(4) var<storage, read> spring_indices: array<u32>;
+@group(0) @binding(5) var<storage, read> plate_axes: array<vec4<f32>>;
+
+fn geodesic_distance(a: vec3<f32>, b: vec3<f32>) -> f32 {
+    // Same as soft_sphere::precision::angle_between, acos(dot(a, b)) rounds small angles to zero
+    return atan2(length(cross(a, b)), dot(a, b));
+}
+
+// Rodrigues rotation of v around a unit axis
+fn rotate(v: vec3<f32>, axis: vec3<f32>, angle: f32) -> vec3<f32> {
+    return v * cos(angle) + cross(axis, v) * sin(angle) + axis * dot(axis, v) * (1.0 - cos(angle));
+}
+
+// Plate driving force, mantle friction and spring forces. Springs are gathered per point mass so no atomics are needed
+@compute @workgroup_size(64)
+fn forces(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= params.point_mass_count {
+        return;
+    }
+    let position = point_masses[i].position.xyz;
+    let velocity = point_masses[i].velocity.xyz;
+    let mass = point_masses[i].mass;
+    let axis = plate_axes[point_masses[i].plate].xyz;
+    var force = cross(axis, position) * params.plate_force_modifier * mass
+        - velocity * mass * params.friction_coefficient;
+    for (var s = spring_offsets[i]; s < spring_offsets[i + 1u]; s++) {
+        let spring = springs[spring_indices[s]];
+        let position_a = point_masses[spring.anchor_a].position.xyz;
+        let position_b = point_masses[spring.anchor_b].position.xyz;
+        let distance = geodesic_distance(position_a, position_b);
+        // Degenerate springs are skipped like in soft_sphere::Spring::apply_force, without counting them
+        if distance < 1e-9 || distance > 3.1415917 {
+            continue;
+        }
+        let direction = (position_a - position_b) / distance;
+        let relative_velocity = point_masses[spring.anchor_a].velocity.xyz
+            - point_masses[spring.anchor_b].velocity.xyz;
+        var spring_force = (-spring.spring_constant * (distance - spring.rest_length)
+            - spring.damping_coefficient * dot(relative_velocity, direction)) * direction;
+        if spring.anchor_b == i {
+            spring_force = -spring_force;
+        }
+        // Project onto the tangent plane
+        force += spring_force - dot(spring_force, position) * position;
+    }
+    point_masses[i].force = vec4<f32>(force, 0.0);
+}
+
+@compute @workgroup_size(64)
+fn integrate(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= params.point_mass_count {
+        return;
+    }
+    var point_mass = point_masses[i];
+    let timestep = params.timestep;
+    let position = point_mass.position.xyz;
+    let old_acc = point_mass.prev_force.xyz / point_mass.mass;
+    let new_acc = point_mass.force.xyz / point_mass.mass;
+    let displacement = point_mass.velocity.xyz * timestep + 0.5 * old_acc * timestep * timestep;
+    let tangent_displacement = displacement - dot(displacement, position) * position;
+    let angle = length(tangent_displacement);
+    if angle > 0.0 {
+        let axis = normalize(cross(position, tangent_displacement));
+        point_mass.position = vec4<f32>(normalize(rotate(position, axis, angle)), 0.0);
+    }
+    point_mass.velocity = vec4<f32>(point_mass.velocity.xyz + (old_acc + new_acc) / 2.0 * timestep, 0.0);
+    point_mass.prev_force = point_mass.force;
+    point_mass.force = vec4<f32>(0.0);
+    point_masses[i] = point_mass;
+}
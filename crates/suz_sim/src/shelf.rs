@@ -0,0 +1,73 @@
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::{distance_field::nearest_sources, tectonics::SEA_LEVEL};
+
+/// Continental shelves, the shallow submerged margin of the continents.
+/// The sea floor next to a coast slopes gently down to the shelf break, then steeply down the continental slope to the ocean floor.
+/// Distances are geodesic on the unit sphere, heights in tile height units.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ShelfConfig {
+    /// Distance from the coast to the shelf break
+    pub width: f32,
+    /// Depth below [SEA_LEVEL] of the shelf break
+    pub break_depth: f32,
+    /// Distance past the shelf break over which the continental slope drops to the ocean floor
+    pub slope_width: f32,
+}
+
+impl Default for ShelfConfig {
+    fn default() -> Self {
+        ShelfConfig {
+            width: 0.03,
+            break_depth: 0.005,
+            slope_width: 0.02,
+        }
+    }
+}
+
+impl ShelfConfig {
+    /// Height of the shelf at `distance` from the coast on a tile whose ocean floor is at `floor_height`.
+    /// `distance` is expected within (0, width + slope_width], which keeps a width of 0 from being divided by.
+    pub fn shelf_height(&self, distance: f32, floor_height: f32) -> f32 {
+        let shelf_break = SEA_LEVEL - self.break_depth;
+        let shelf = if distance <= self.width {
+            SEA_LEVEL - self.break_depth * distance / self.width
+        } else {
+            let t = ((distance - self.width) / self.slope_width).min(1.);
+            shelf_break + (floor_height - shelf_break) * t
+        };
+        // Ridges and islands rising above the shelf are left as they are
+        shelf.max(floor_height)
+    }
+}
+
+/// Raises the sea floor next to every coast into a shelf, replacing the step between continental and oceanic heights.
+/// Tiles at or above [SEA_LEVEL] are land, the distance of every other tile to the closest land is measured along the tile graph given by `adjacent`.
+pub fn apply_shelf<'a>(
+    config: &ShelfConfig,
+    heights: &mut [f32],
+    normals: &[Vec3],
+    adjacent: impl Fn(usize) -> &'a [usize],
+) {
+    let max_distance = config.width + config.slope_width;
+    if max_distance <= 0.0 {
+        return;
+    }
+
+    let land = heights
+        .iter()
+        .enumerate()
+        .filter(|(_, height)| **height >= SEA_LEVEL)
+        .map(|(tile, _)| (tile, ()));
+    // Shelves only spread through the sea, land is already at distance 0
+    let coast_distance = nearest_sources(land, normals, adjacent, max_distance, |tile| {
+        heights[tile] < SEA_LEVEL
+    });
+
+    for (height, coast_distance) in heights.iter_mut().zip(coast_distance) {
+        if let Some((distance, ())) = coast_distance.filter(|(distance, _)| *distance > 0.) {
+            *height = config.shelf_height(distance, *height);
+        }
+    }
+}
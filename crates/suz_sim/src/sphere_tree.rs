@@ -0,0 +1,436 @@
+use std::f32::consts::PI;
+
+use glam::Vec3;
+use soft_sphere::PointMass;
+
+use crate::{plate::Plate, vec_utils};
+
+/// Average point masses per leaf the depth of a [SphereTree] is picked for
+const POINT_MASSES_PER_LEAF: usize = 4;
+/// 20 * 4^8 leaves is enough for over a million point masses
+const MAX_DEPTH: usize = 8;
+/// Leaves a point mass on the edge between two cells may end up in either, so caps are grown a little to still contain it
+const CAP_MARGIN: f32 = 1e-4;
+
+/// Identifies a point mass across all plates, index into [crate::tectonics::Tectonics::plates] and then [soft_sphere::Shape::point_masses]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PointMassHandle {
+    pub plate: usize,
+    pub point_mass: usize,
+}
+
+/// A spherical triangle of the subdivided icosahedron
+#[derive(Clone)]
+struct Cell {
+    corners: [Vec3; 3],
+    /// Normalized centroid of the corners
+    center: Vec3,
+    /// Geodesic radius of the smallest cap around [Cell::center] containing the cell
+    radius: f32,
+}
+
+impl Cell {
+    fn new(corners: [Vec3; 3]) -> Self {
+        let center = (corners[0] + corners[1] + corners[2]).normalize();
+        let radius = corners
+            .iter()
+            .map(|&corner| vec_utils::geodesic_distance(center, corner))
+            .fold(0., f32::max)
+            + CAP_MARGIN;
+        Cell {
+            corners,
+            center,
+            radius,
+        }
+    }
+
+    /// Points on an edge are inside both cells sharing it
+    fn contains(&self, normal: Vec3) -> bool {
+        let [a, b, c] = self.corners;
+        // Works for either winding of the corners
+        let winding = a.dot(b.cross(c)).signum();
+        [(a, b), (b, c), (c, a)]
+            .iter()
+            .all(|(from, to)| normal.dot(from.cross(*to)) * winding >= 0.)
+    }
+
+    /// Cosine of the geodesic distance from `normal` to the closest great circle through an edge.
+    /// A point inside the cell that moves less than that distance is still inside.
+    fn slack_cos(&self, normal: Vec3) -> f32 {
+        let [a, b, c] = self.corners;
+        let edge_sin = [(a, b), (b, c), (c, a)]
+            .iter()
+            .map(|(from, to)| normal.dot(from.cross(*to).normalize()).abs())
+            .fold(1., f32::min);
+        (1. - edge_sin * edge_sin).max(0.).sqrt()
+    }
+
+    /// The four cells splitting this one at the midpoints of its edges
+    fn children(&self) -> [Cell; 4] {
+        let [a, b, c] = self.corners;
+        let ab = (a + b).normalize();
+        let bc = (b + c).normalize();
+        let ca = (c + a).normalize();
+        [
+            Cell::new([a, ab, ca]),
+            Cell::new([ab, b, bc]),
+            Cell::new([ca, bc, c]),
+            Cell::new([ab, bc, ca]),
+        ]
+    }
+}
+
+fn icosahedron() -> Vec<Cell> {
+    let t = (1. + 5f32.sqrt()) / 2.;
+    let corners = [
+        Vec3::new(-1., t, 0.),
+        Vec3::new(1., t, 0.),
+        Vec3::new(-1., -t, 0.),
+        Vec3::new(1., -t, 0.),
+        Vec3::new(0., -1., t),
+        Vec3::new(0., 1., t),
+        Vec3::new(0., -1., -t),
+        Vec3::new(0., 1., -t),
+        Vec3::new(t, 0., -1.),
+        Vec3::new(t, 0., 1.),
+        Vec3::new(-t, 0., -1.),
+        Vec3::new(-t, 0., 1.),
+    ]
+    .map(Vec3::normalize);
+    [
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ]
+    .iter()
+    .map(|face| Cell::new(face.map(|corner| corners[corner])))
+    .collect()
+}
+
+/// Where a point mass is binned in a [SphereTree]
+#[derive(Clone, Copy)]
+struct BinnedPointMass {
+    leaf: usize,
+    position: Vec3,
+    /// Position the leaf was last checked at
+    binned_at: Vec3,
+    /// [Cell::slack_cos] of the leaf at [BinnedPointMass::binned_at]
+    slack_cos: f32,
+}
+
+/// Shallowest depth with at most [POINT_MASSES_PER_LEAF] point masses per leaf on average
+fn depth_for(point_masses: usize) -> usize {
+    (0..MAX_DEPTH)
+        .find(|&depth| 20 * 4usize.pow(depth as u32) * POINT_MASSES_PER_LEAF >= point_masses)
+        .unwrap_or(MAX_DEPTH)
+}
+
+/// Every point mass of every plate in the leaves of a subdivided icosahedron, for fixed radius and nearest neighbour queries.
+/// Cells are keyed by icosahedron face and depth, the children of cell `i` one level down are `4 * i..4 * i + 4`.
+/// Queries descend from the 20 faces and skip every cell whose bounding cap is out of range,
+/// and the depth follows the point mass count so leaves stay small as the particle sphere grows.
+#[derive(Clone)]
+pub struct SphereTree {
+    /// Cells of every depth, from the 20 icosahedron faces down to the leaves
+    levels: Vec<Vec<Cell>>,
+    leaves: Vec<Vec<PointMassHandle>>,
+    /// Per plate and point mass
+    point_masses: Vec<Vec<BinnedPointMass>>,
+}
+
+impl SphereTree {
+    pub fn new(plates: &[Plate]) -> Self {
+        let mut tree = SphereTree {
+            levels: vec![icosahedron()],
+            leaves: Vec::new(),
+            point_masses: Vec::new(),
+        };
+        tree.rebuild(plates);
+        tree
+    }
+
+    fn rebuild(&mut self, plates: &[Plate]) {
+        let depth = depth_for(
+            plates
+                .iter()
+                .map(|plate| plate.shape.point_masses().len())
+                .sum(),
+        );
+        self.levels.truncate(depth + 1);
+        while self.levels.len() <= depth {
+            let children = self.levels[self.levels.len() - 1]
+                .iter()
+                .flat_map(Cell::children)
+                .collect();
+            self.levels.push(children);
+        }
+        self.leaves = vec![Vec::new(); self.levels[depth].len()];
+        self.point_masses = plates
+            .iter()
+            .enumerate()
+            .map(|(plate_index, plate)| {
+                plate
+                    .shape
+                    .point_masses()
+                    .iter()
+                    .enumerate()
+                    .map(|(point_mass_index, point_mass)| {
+                        let leaf = self.leaf(point_mass.position);
+                        self.leaves[leaf].push(PointMassHandle {
+                            plate: plate_index,
+                            point_mass: point_mass_index,
+                        });
+                        self.binned(leaf, point_mass.position)
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Leaf containing `normal`, rounding errors on cell edges fall back to the child with the closest center
+    fn leaf(&self, normal: Vec3) -> usize {
+        let mut cells = 0..self.levels[0].len();
+        let mut index = 0;
+        for level in &self.levels {
+            index = cells
+                .clone()
+                .find(|&cell| level[cell].contains(normal))
+                .unwrap_or_else(|| {
+                    cells
+                        .clone()
+                        .max_by(|&a, &b| {
+                            normal
+                                .dot(level[a].center)
+                                .total_cmp(&normal.dot(level[b].center))
+                        })
+                        .expect("Every cell has children")
+                });
+            cells = 4 * index..4 * index + 4;
+        }
+        index
+    }
+
+    fn binned(&self, leaf: usize, position: Vec3) -> BinnedPointMass {
+        BinnedPointMass {
+            leaf,
+            position,
+            binned_at: position,
+            slack_cos: self.levels[self.levels.len() - 1][leaf].slack_cos(position),
+        }
+    }
+
+    /// Whether plates or point masses were added or removed since the last rebuild
+    fn resized(&self, plates: &[Plate]) -> bool {
+        self.point_masses.len() != plates.len()
+            || self
+                .point_masses
+                .iter()
+                .zip(plates)
+                .any(|(point_masses, plate)| point_masses.len() != plate.shape.point_masses().len())
+    }
+
+    /// Moves `handle` to the leaf containing `position` if it left its old one
+    fn rebin(&mut self, handle: PointMassHandle, position: Vec3) {
+        let old_leaf = self.point_masses[handle.plate][handle.point_mass].leaf;
+        // Most point masses stay in their leaf, which is cheaper to check than descending again
+        let new_leaf = if self.levels[self.levels.len() - 1][old_leaf].contains(position) {
+            old_leaf
+        } else {
+            self.leaf(position)
+        };
+        self.point_masses[handle.plate][handle.point_mass] = self.binned(new_leaf, position);
+        if new_leaf != old_leaf {
+            let leaf = &mut self.leaves[old_leaf];
+            if let Some(index) = leaf.iter().position(|other| *other == handle) {
+                leaf.swap_remove(index);
+            }
+            self.leaves[new_leaf].push(handle);
+        }
+    }
+
+    /// Updates positions and checks the leaf of every point mass, moving those that left it.
+    /// Rebuilds everything when plates or point masses were added or removed, which invalidates earlier handles.
+    pub fn refresh(&mut self, plates: &[Plate]) {
+        if self.resized(plates) {
+            self.rebuild(plates);
+            return;
+        }
+        for (plate_index, plate) in plates.iter().enumerate() {
+            for (point_mass_index, point_mass) in plate.shape.point_masses().iter().enumerate() {
+                let handle = PointMassHandle {
+                    plate: plate_index,
+                    point_mass: point_mass_index,
+                };
+                self.rebin(handle, point_mass.position);
+            }
+        }
+    }
+
+    /// Like [SphereTree::refresh], but only checks the leaf of point masses that moved further than the distance to the closest edge of their leaf since it was last checked.
+    /// The rest only have their position updated, a single dot product each, so this is cheaper when most point masses move less than a leaf per step.
+    pub fn refresh_incremental(&mut self, plates: &[Plate]) {
+        if self.resized(plates) {
+            self.rebuild(plates);
+            return;
+        }
+        for (plate_index, plate) in plates.iter().enumerate() {
+            for (point_mass_index, point_mass) in plate.shape.point_masses().iter().enumerate() {
+                let binned = &mut self.point_masses[plate_index][point_mass_index];
+                if point_mass.position.dot(binned.binned_at) > binned.slack_cos {
+                    binned.position = point_mass.position;
+                    continue;
+                }
+                let handle = PointMassHandle {
+                    plate: plate_index,
+                    point_mass: point_mass_index,
+                };
+                self.rebin(handle, point_mass.position);
+            }
+        }
+    }
+
+    /// Adds a point mass appended to its plate, or the first point mass of a plate appended to the plates.
+    /// Keeps the tree in step with [soft_sphere::Shape::merge] and new plates without rebuilding it.
+    pub fn insert(&mut self, handle: PointMassHandle, position: Vec3) {
+        if handle.plate == self.point_masses.len() {
+            self.point_masses.push(Vec::new());
+        }
+        let point_masses = &self.point_masses[handle.plate];
+        assert_eq!(
+            handle.point_mass,
+            point_masses.len(),
+            "Point masses are inserted at the end of their plate"
+        );
+        let leaf = self.leaf(position);
+        self.leaves[leaf].push(handle);
+        let binned = self.binned(leaf, position);
+        self.point_masses[handle.plate].push(binned);
+    }
+
+    /// Removes a point mass, moving the last point mass of its plate into its place like [soft_sphere::Shape::remove_point_mass].
+    /// Returns the handle the moved point mass had, which now refers to `handle` instead, `None` if the removed point mass was last.
+    /// Every other handle stays valid, so the tree does not need to be rebuilt.
+    pub fn remove(&mut self, handle: PointMassHandle) -> Option<PointMassHandle> {
+        let point_masses = &mut self.point_masses[handle.plate];
+        let removed = point_masses.swap_remove(handle.point_mass);
+        let moved =
+            (handle.point_mass < point_masses.len()).then(|| point_masses[handle.point_mass].leaf);
+        let leaf = &mut self.leaves[removed.leaf];
+        if let Some(index) = leaf.iter().position(|other| *other == handle) {
+            leaf.swap_remove(index);
+        }
+        let moved_leaf = moved?;
+        let moved_from = PointMassHandle {
+            plate: handle.plate,
+            point_mass: self.point_masses[handle.plate].len(),
+        };
+        if let Some(other) = self.leaves[moved_leaf]
+            .iter_mut()
+            .find(|other| **other == moved_from)
+        {
+            *other = handle;
+        }
+        Some(moved_from)
+    }
+
+    pub fn position(&self, handle: PointMassHandle) -> Vec3 {
+        self.point_masses[handle.plate][handle.point_mass].position
+    }
+
+    /// Every point mass within geodesic distance `radius` of `normal`, with its distance.
+    /// The order only depends on the tree contents, so repeated runs sum results identically.
+    pub fn query_within(&self, normal: Vec3, radius: f32) -> Vec<(PointMassHandle, f32)> {
+        let mut found = Vec::new();
+        self.search(normal, radius, 0, 0..self.levels[0].len(), &mut found);
+        found
+    }
+
+    /// Like [SphereTree::query_within], also borrowing each point mass found from `plates` so forces can be applied to it directly.
+    /// `plates` must be what the tree was last refreshed with. Results come in handle order instead of tree order.
+    pub fn query_within_mut<'a>(
+        &self,
+        plates: &'a mut [Plate],
+        normal: Vec3,
+        radius: f32,
+    ) -> Vec<(PointMassHandle, f32, &'a mut PointMass)> {
+        let mut found = self.query_within(normal, radius);
+        found.sort_unstable_by_key(|(handle, _)| *handle);
+        let mut found = found.into_iter().peekable();
+        let mut borrowed = Vec::with_capacity(found.len());
+        for (plate_index, plate) in plates.iter_mut().enumerate() {
+            // Handles are sorted, so each point mass is split off the front of what is left of the plate
+            let mut rest = plate.shape.point_masses_mut();
+            let mut offset = 0;
+            while let Some((handle, distance)) =
+                found.next_if(|(handle, _)| handle.plate == plate_index)
+            {
+                let (_, tail) = std::mem::take(&mut rest).split_at_mut(handle.point_mass - offset);
+                let (point_mass, tail) = tail
+                    .split_first_mut()
+                    .expect("Handles of a refreshed tree are in bounds");
+                borrowed.push((handle, distance, point_mass));
+                rest = tail;
+                offset = handle.point_mass + 1;
+            }
+        }
+        borrowed
+    }
+
+    fn search(
+        &self,
+        normal: Vec3,
+        radius: f32,
+        depth: usize,
+        cells: std::ops::Range<usize>,
+        found: &mut Vec<(PointMassHandle, f32)>,
+    ) {
+        for index in cells {
+            let cell = &self.levels[depth][index];
+            if vec_utils::geodesic_distance(normal, cell.center) - cell.radius > radius {
+                continue;
+            }
+            if depth + 1 < self.levels.len() {
+                self.search(normal, radius, depth + 1, 4 * index..4 * index + 4, found);
+                continue;
+            }
+            for &handle in &self.leaves[index] {
+                let distance = vec_utils::geodesic_distance(normal, self.position(handle));
+                if distance <= radius {
+                    found.push((handle, distance));
+                }
+            }
+        }
+    }
+
+    /// The closest point mass to `normal`, `None` only when there are no point masses
+    pub fn nearest(&self, normal: Vec3) -> Option<PointMassHandle> {
+        let mut radius = self.levels[self.levels.len() - 1][0].radius;
+        loop {
+            let nearest = self
+                .query_within(normal, radius)
+                .into_iter()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+            if nearest.is_some() || radius >= PI {
+                return nearest.map(|(handle, _)| handle);
+            }
+            radius *= 2.;
+        }
+    }
+}
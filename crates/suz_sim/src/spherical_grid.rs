@@ -0,0 +1,165 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use glam::Vec3;
+
+use crate::{plate::Plate, sphere_tree::PointMassHandle, vec_utils};
+
+/// Latitude rows, with twice as many longitude columns
+pub const BIN_COUNT: usize = 60;
+
+const ROWS: usize = BIN_COUNT;
+const COLUMNS: usize = 2 * BIN_COUNT;
+const ROW_HEIGHT: f32 = PI / ROWS as f32;
+const COLUMN_WIDTH: f32 = TAU / COLUMNS as f32;
+
+/// Latitude and longitude bins of every point mass of every plate, for fixed radius and nearest neighbour queries.
+/// Superseded by [crate::sphere_tree::SphereTree], whose leaves stay small as the point mass count grows, and kept to benchmark against.
+#[derive(Clone)]
+pub struct SphericalGrid {
+    cells: Vec<Vec<PointMassHandle>>,
+    /// Cell and position of each point mass, per plate and point mass
+    point_masses: Vec<Vec<(usize, Vec3)>>,
+}
+
+fn row(latitude: f32) -> usize {
+    (((latitude + FRAC_PI_2) / ROW_HEIGHT) as usize).min(ROWS - 1)
+}
+
+fn column(longitude: f32) -> usize {
+    (((longitude + PI) / COLUMN_WIDTH) as usize).min(COLUMNS - 1)
+}
+
+fn cell(normal: Vec3) -> usize {
+    let (latitude, longitude) = vec_utils::vec3_to_lat_long(normal);
+    row(latitude) * COLUMNS + column(longitude)
+}
+
+impl SphericalGrid {
+    pub fn new(plates: &[Plate]) -> Self {
+        let mut grid = SphericalGrid {
+            cells: vec![Vec::new(); ROWS * COLUMNS],
+            point_masses: Vec::new(),
+        };
+        grid.rebuild(plates);
+        grid
+    }
+
+    fn rebuild(&mut self, plates: &[Plate]) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.point_masses = plates
+            .iter()
+            .enumerate()
+            .map(|(plate_index, plate)| {
+                plate
+                    .shape
+                    .point_masses()
+                    .iter()
+                    .enumerate()
+                    .map(|(point_mass_index, point_mass)| {
+                        let cell = cell(point_mass.position);
+                        self.cells[cell].push(PointMassHandle {
+                            plate: plate_index,
+                            point_mass: point_mass_index,
+                        });
+                        (cell, point_mass.position)
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Updates positions and moves point masses that left their cell.
+    /// Rebuilds everything when plates or point masses were added or removed, which invalidates earlier handles.
+    pub fn refresh(&mut self, plates: &[Plate]) {
+        if self.point_masses.len() != plates.len()
+            || self
+                .point_masses
+                .iter()
+                .zip(plates)
+                .any(|(point_masses, plate)| point_masses.len() != plate.shape.point_masses().len())
+        {
+            self.rebuild(plates);
+            return;
+        }
+        for (plate_index, plate) in plates.iter().enumerate() {
+            for (point_mass_index, point_mass) in plate.shape.point_masses().iter().enumerate() {
+                let (old_cell, position) = &mut self.point_masses[plate_index][point_mass_index];
+                *position = point_mass.position;
+                let new_cell = cell(point_mass.position);
+                if new_cell != *old_cell {
+                    let handle = PointMassHandle {
+                        plate: plate_index,
+                        point_mass: point_mass_index,
+                    };
+                    let cell = &mut self.cells[*old_cell];
+                    if let Some(index) = cell.iter().position(|other| *other == handle) {
+                        cell.swap_remove(index);
+                    }
+                    self.cells[new_cell].push(handle);
+                    *old_cell = new_cell;
+                }
+            }
+        }
+    }
+
+    pub fn position(&self, handle: PointMassHandle) -> Vec3 {
+        self.point_masses[handle.plate][handle.point_mass].1
+    }
+
+    /// Every point mass within geodesic distance `radius` of `normal`, with its distance.
+    /// The order only depends on the grid contents, so repeated runs sum results identically.
+    pub fn query_within(&self, normal: Vec3, radius: f32) -> Vec<(PointMassHandle, f32)> {
+        let (latitude, longitude) = vec_utils::vec3_to_lat_long(normal);
+        let rows = row(latitude - radius)..=row(latitude + radius);
+        // Widest longitude offset of the spherical cap, all longitudes when the cap contains a pole
+        let columns = if latitude.abs() + radius >= FRAC_PI_2 {
+            None
+        } else {
+            let longitude_radius = (radius.sin() / latitude.cos()).min(1.).asin();
+            let first = ((longitude - longitude_radius + PI) / COLUMN_WIDTH).floor() as i64;
+            let last = ((longitude + longitude_radius + PI) / COLUMN_WIDTH).floor() as i64;
+            (last - first + 1 < COLUMNS as i64).then_some((first, last))
+        };
+        let mut found = Vec::new();
+        let mut search_cell = |cell: usize| {
+            for &handle in &self.cells[cell] {
+                let distance = vec_utils::geodesic_distance(normal, self.position(handle));
+                if distance <= radius {
+                    found.push((handle, distance));
+                }
+            }
+        };
+        for row in rows {
+            match columns {
+                Some((first, last)) => {
+                    for column in first..=last {
+                        search_cell(row * COLUMNS + column.rem_euclid(COLUMNS as i64) as usize);
+                    }
+                }
+                None => {
+                    for column in 0..COLUMNS {
+                        search_cell(row * COLUMNS + column);
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// The closest point mass to `normal`, `None` only when there are no point masses
+    pub fn nearest(&self, normal: Vec3) -> Option<PointMassHandle> {
+        let mut radius = ROW_HEIGHT;
+        loop {
+            let nearest = self
+                .query_within(normal, radius)
+                .into_iter()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b));
+            if nearest.is_some() || radius >= PI {
+                return nearest.map(|(handle, _)| handle);
+            }
+            radius *= 2.;
+        }
+    }
+}
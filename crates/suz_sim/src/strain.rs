@@ -0,0 +1,100 @@
+use crate::tectonics::Tectonics;
+
+/// Tracks how quickly the strain of each spring changes between simulation iterations
+#[derive(Clone)]
+pub struct StrainTracker {
+    /// Spring strains from the previous update, per plate and spring
+    previous_strains: Vec<Vec<f32>>,
+    /// Average absolute strain rate of the springs anchored to each point mass, per plate and point mass
+    pub point_mass_rates: Vec<Vec<f32>>,
+    /// Average absolute strain of the springs anchored to each point mass, proportional to the spring stress
+    pub point_mass_strains: Vec<Vec<f32>>,
+    /// Average absolute strain rate over all springs
+    pub global_rate: f32,
+}
+
+impl StrainTracker {
+    pub fn new(tectonics: &Tectonics) -> Self {
+        StrainTracker {
+            previous_strains: Self::strains(tectonics),
+            point_mass_rates: tectonics
+                .plates
+                .iter()
+                .map(|plate| vec![0.; plate.shape.point_masses().len()])
+                .collect(),
+            point_mass_strains: tectonics
+                .plates
+                .iter()
+                .map(|plate| vec![0.; plate.shape.point_masses().len()])
+                .collect(),
+            global_rate: 0.,
+        }
+    }
+
+    fn strains(tectonics: &Tectonics) -> Vec<Vec<f32>> {
+        tectonics
+            .plates
+            .iter()
+            .map(|plate| {
+                plate
+                    .shape
+                    .iter_spring_strains()
+                    .map(|(_, strain)| strain)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Compares current spring strains to the ones from the previous update, should be called once per iteration.
+    /// When plates were merged since the previous update the springs no longer line up, and every rate is zero for one update.
+    pub fn update(&mut self, tectonics: &Tectonics) {
+        let strains = Self::strains(tectonics);
+        if strains.len() != self.previous_strains.len()
+            || strains
+                .iter()
+                .zip(&self.previous_strains)
+                .any(|(current, previous)| current.len() != previous.len())
+        {
+            self.previous_strains = strains.clone();
+        }
+        let mut total_rate = 0.;
+        let mut spring_count = 0;
+        self.point_mass_rates.clear();
+        self.point_mass_strains.clear();
+        for (plate_index, plate) in tectonics.plates.iter().enumerate() {
+            let point_mass_count = plate.shape.point_masses().len();
+            let mut rates = vec![0.; point_mass_count];
+            let mut point_mass_strains = vec![0.; point_mass_count];
+            let mut counts = vec![0usize; point_mass_count];
+            let previous = &self.previous_strains[plate_index];
+            for (spring_index, spring) in plate.shape.springs().iter().enumerate() {
+                let rate = (strains[plate_index][spring_index] - previous[spring_index]).abs()
+                    / tectonics.timestep;
+                let strain = strains[plate_index][spring_index].abs();
+                rates[spring.anchor_a] += rate;
+                point_mass_strains[spring.anchor_a] += strain;
+                counts[spring.anchor_a] += 1;
+                rates[spring.anchor_b] += rate;
+                point_mass_strains[spring.anchor_b] += strain;
+                counts[spring.anchor_b] += 1;
+                total_rate += rate;
+                spring_count += 1;
+            }
+            for ((rate, strain), count) in rates.iter_mut().zip(&mut point_mass_strains).zip(counts)
+            {
+                if count > 0 {
+                    *rate /= count as f32;
+                    *strain /= count as f32;
+                }
+            }
+            self.point_mass_rates.push(rates);
+            self.point_mass_strains.push(point_mass_strains);
+        }
+        self.global_rate = if spring_count > 0 {
+            total_rate / spring_count as f32
+        } else {
+            0.
+        };
+        self.previous_strains = strains;
+    }
+}
@@ -1,24 +1,46 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 
-use bevy::{
-    ecs::resource::Resource,
-    math::{EulerRot, Quat, Vec2, Vec3},
-};
+use glam::{EulerRot, Quat, Vec2, Vec3};
 use rand::Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use soft_sphere::Solver;
 
 use crate::{
+    config::ConfigError,
+    crust::{CrustNoise, CrustSampler},
+    isostasy::IsostasyConfig,
     particle_sphere::ParticleSphere,
     plate::{Plate, PlateType},
+    plate_motion::{
+        DrivingForceConfig, TorqueConfig, apply_torques, boundary_torques, driving_forces,
+    },
+    sphere_tree::SphereTree,
+    trails::{PlateTrails, TrailConfig},
+    units::{EARTH_RADIUS_KM, PhysicalUnits},
+    validation::{InvalidState, ValidationConfig, repair, validate},
 };
 
 pub const OCEANIC_PARTICLE_MASS: f32 = 1.;
 pub const OCEANIC_HEIGHT: f32 = 0.98;
 pub const CONTINENTAL_PARTICLE_MASS: f32 = 5.;
 pub const CONTINENTAL_HEIGHT: f32 = 1.02;
+/// Tile heights below this are considered ocean
+pub const SEA_LEVEL: f32 = 1.0;
+
+/// An adaptive timestep grows by at most this factor per step, shrinking is immediate
+const MAX_TIMESTEP_GROWTH: f32 = 1.1;
 
-pub const BIN_COUNT: usize = 60;
+/// Plate contacts are checked for suturing every this many iterations
+const MERGE_CHECK_INTERVAL: usize = 10;
+/// Point masses of different plates closer than this many [Tectonics::ideal_distance] are in contact
+const CONTACT_DISTANCE: f32 = 1.5;
+/// Boundary torques and driving forces are summed every this many iterations, finding the contacts costs as much as a merge check
+const BOUNDARY_FORCE_INTERVAL: usize = 10;
+/// Starting tiles drawn for a plate before giving up on [TectonicsConfiguration::min_seed_separation]
+const SEED_ATTEMPTS: usize = 30;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub struct TectonicsConfiguration {
     /// How many plates the simulation tries to create
     pub plate_goal: usize,
@@ -37,25 +59,107 @@ pub struct TectonicsConfiguration {
     // Dampener coefficient for the spring forces, used to dampen oscillations
     pub dampener_coefficient: f32,
     /// Modifier to the force applies by the plate rotational axis to plate particles.
+    /// Scaled by [DrivingForceConfig::rotation_forcing] when [TectonicsConfiguration::driving_forces] is set.
     pub plate_force_modifier: f32,
-    /// The rate at which the plate axis of rotation drifts in position
+    /// The rate at which the plate axis of rotation drifts in position, unused when [TectonicsConfiguration::torque] is set
     pub plate_rotation_drift_rate: f32,
     pub timestep: f32,
+    /// Integration steps per [TectonicsConfiguration::timestep] with [TectonicsConfiguration::solver], keeping stiff springs stable at long timesteps.
+    /// 0 takes the single velocity verlet step of [soft_sphere::Shape::update] instead.
+    #[serde(default)]
+    pub substeps: usize,
+    /// Integration scheme of the substeps, unused when [TectonicsConfiguration::substeps] is 0
+    #[serde(default)]
+    pub solver: Solver,
     pub iterations: usize,
     // Friction between plate particles and mantle
     pub friction_coefficient: f32,
+    /// Iterations two plates of the same type must stay locked in convergence before they suture into one, 0 disables merging
+    #[serde(default)]
+    pub merge_iterations: usize,
+    /// Average relative speed at a converging boundary below which the plates count as locked
+    #[serde(default)]
+    pub merge_speed: f32,
+    /// Scales the timestep to the fastest point mass when set, [TectonicsConfiguration::timestep] is then only the first step
+    #[serde(default)]
+    pub adaptive_timestep: Option<AdaptiveTimestep>,
+    /// Simulated time to run for instead of [TectonicsConfiguration::iterations] when positive, in the units of [TectonicsConfiguration::timestep].
+    /// Taking one unit as a mega-year makes this the simulated mega-years.
+    #[serde(default)]
+    pub duration: f32,
+    /// Splits and merges plates after generation until there are exactly [TectonicsConfiguration::plate_goal], even below [TectonicsConfiguration::min_plate_size]
+    #[serde(default)]
+    pub strict_plate_count: bool,
+    /// Smallest angle in radians between the starting tiles of plates, 0 starts them anywhere.
+    /// Spreading the starting tiles out keeps the continents from clustering in one hemisphere.
+    #[serde(default)]
+    pub min_seed_separation: f32,
+    /// Radius of the planet the unit sphere stands for, only used to report and export in physical units
+    #[serde(default = "default_planet_radius_km")]
+    pub planet_radius_km: f32,
+    /// Flat crust of a single height per plate type by default
+    #[serde(default)]
+    pub crust_noise: CrustNoise,
+    /// Heights from the isostatic balance of the crust when set, instead of [OCEANIC_HEIGHT] and [CONTINENTAL_HEIGHT] plus compression
+    #[serde(default)]
+    pub isostasy: Option<IsostasyConfig>,
+    /// Centroid paths kept in [Tectonics::trails]
+    #[serde(default)]
+    pub trails: TrailConfig,
+    /// Checks every point mass after each step when set, see [Tectonics::validate]
+    #[serde(default)]
+    pub validation: Option<ValidationConfig>,
+    /// Turns plate axes with the torque of their boundary forces when set, instead of drifting them at random
+    #[serde(default)]
+    pub torque: Option<TorqueConfig>,
+    /// Pulls point masses towards subduction zones and pushes them away from ridges when set, see [driving_forces]
+    #[serde(default)]
+    pub driving_forces: Option<DrivingForceConfig>,
+}
+
+fn default_planet_radius_km() -> f32 {
+    EARTH_RADIUS_KM
+}
+
+impl TectonicsConfiguration {
+    pub fn units(&self) -> PhysicalUnits {
+        PhysicalUnits::new(self.planet_radius_km)
+    }
+
+    /// Height of new, uncompressed crust of `plate_type`
+    pub fn plate_height(&self, plate_type: PlateType) -> f32 {
+        match (&self.isostasy, plate_type) {
+            (Some(isostasy), _) => {
+                isostasy.surface_height(plate_type, isostasy.thickness(plate_type))
+            }
+            (None, PlateType::Oceanic) => OCEANIC_HEIGHT,
+            (None, PlateType::Continental) => CONTINENTAL_HEIGHT,
+        }
+    }
+}
+
+/// Bounds for [TectonicsConfiguration::adaptive_timestep]
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct AdaptiveTimestep {
+    /// Distance the fastest point mass should move in a step, in [Tectonics::ideal_distance]s
+    pub target_displacement: f32,
+    pub min_timestep: f32,
+    pub max_timestep: f32,
 }
 
 struct PlateBuilder {
     plate: Plate,
-    tile_to_point_mass: HashMap<usize, usize>,
+    shape: soft_sphere::ShapeBuilder,
+    /// Ordered so merging small plates adds point masses in the same order on every run
+    tile_to_point_mass: BTreeMap<usize, usize>,
 }
 
 impl PlateBuilder {
     fn new(plate: Plate) -> Self {
         Self {
             plate,
-            tile_to_point_mass: HashMap::new(),
+            shape: soft_sphere::ShapeBuilder::new(),
+            tile_to_point_mass: BTreeMap::new(),
         }
     }
     fn add_point_mass(
@@ -64,61 +168,254 @@ impl PlateBuilder {
         point_mass: soft_sphere::PointMass,
         particle_sphere: &ParticleSphere,
         config: &TectonicsConfiguration,
+        crust: &CrustSampler,
     ) {
-        let point_mass_index = self.plate.shape.point_masses.len();
-        self.plate.shape.add_point_mass(point_mass);
+        self.plate
+            .crust_thickness
+            .push(crust.thickness(point_mass.position));
+        let point_mass_index = self.shape.point_mass(point_mass);
         self.tile_to_point_mass.insert(tile_index, point_mass_index);
         // Add springs to already-added adjacent tiles (if they are in this plate)
         for adj_tile in &particle_sphere.tiles[tile_index].adjacent {
             if let Some(&adj_index) = self.tile_to_point_mass.get(adj_tile) {
-                let rest_length = self.plate.shape.point_masses[point_mass_index]
-                    .geodesic_distance(&self.plate.shape.point_masses[adj_index]);
-                self.plate.shape.add_spring(soft_sphere::Spring {
-                    anchor_a: point_mass_index,
-                    anchor_b: adj_index,
-                    rest_length,
-                    spring_constant: config.spring_constant,
-                    damping_coefficient: config.dampener_coefficient,
-                });
+                let rest_length = self.shape.point_masses()[point_mass_index]
+                    .geodesic_distance(&self.shape.point_masses()[adj_index]);
+                self.shape
+                    .spring(soft_sphere::Spring {
+                        anchor_a: point_mass_index,
+                        anchor_b: adj_index,
+                        rest_length,
+                        spring_constant: config.spring_constant,
+                        damping_coefficient: config.dampener_coefficient,
+                    })
+                    .expect("Each tile pair gets a single spring");
+            }
+        }
+    }
+}
+
+/// Draws the starting tile of the next plate from `available`, at least [TectonicsConfiguration::min_seed_separation] from every tile in `seeds`.
+/// Like Poisson disc sampling by dart throwing, a draw too close is thrown again, up to [SEED_ATTEMPTS] times before the draw furthest from the earlier seeds is kept.
+fn pick_seed_tile(
+    available: &[usize],
+    seeds: &[usize],
+    particle_sphere: &ParticleSphere,
+    config: &TectonicsConfiguration,
+    rng: &mut rand::rngs::StdRng,
+) -> usize {
+    let draw = available[rng.random_range(0..available.len())];
+    // Without a separation this is a single uniform draw, so existing seeds generate the same planets
+    if config.min_seed_separation <= 0. || seeds.is_empty() {
+        return draw;
+    }
+    let separation = |tile: usize| {
+        let normal = particle_sphere.tiles[tile].normal;
+        seeds
+            .iter()
+            .map(|&seed| normal.angle_between(particle_sphere.tiles[seed].normal))
+            .fold(f32::INFINITY, f32::min)
+    };
+    let mut best = (draw, separation(draw));
+    for _ in 1..SEED_ATTEMPTS {
+        if best.1 >= config.min_seed_separation {
+            break;
+        }
+        let draw = available[rng.random_range(0..available.len())];
+        let draw_separation = separation(draw);
+        if draw_separation > best.1 {
+            best = (draw, draw_separation);
+        }
+    }
+    best.0
+}
+
+/// Merges the smallest plate into its smallest neighbour or splits the largest plate in two until there are [TectonicsConfiguration::plate_goal] plates.
+/// Changed plates are rebuilt from their tiles, so springs only ever join tiles of the same plate.
+fn balance_plate_count(
+    plate_builders: Vec<PlateBuilder>,
+    particle_sphere: &ParticleSphere,
+    config: &TectonicsConfiguration,
+    crust: &CrustSampler,
+    rng: &mut rand::rngs::StdRng,
+) -> Vec<PlateBuilder> {
+    let plate_goal = config.plate_goal.clamp(1, particle_sphere.tiles.len());
+    if plate_builders.len() == plate_goal {
+        return plate_builders;
+    }
+    let mut plates: Vec<(Plate, BTreeSet<usize>)> = plate_builders
+        .into_iter()
+        .map(|builder| {
+            (
+                builder.plate,
+                builder.tile_to_point_mass.into_keys().collect(),
+            )
+        })
+        .collect();
+
+    while plates.len() > plate_goal {
+        let mut tile_plates = vec![0; particle_sphere.tiles.len()];
+        for (plate_index, (_, tiles)) in plates.iter().enumerate() {
+            for &tile in tiles {
+                tile_plates[tile] = plate_index;
+            }
+        }
+        let (smallest, _) = plates
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (_, tiles))| tiles.len())
+            .expect("More plates than the goal, so there is at least one");
+        let neighbour = plates[smallest]
+            .1
+            .iter()
+            .flat_map(|&tile| &particle_sphere.tiles[tile].adjacent)
+            .map(|&adjacent| tile_plates[adjacent])
+            .filter(|&plate_index| plate_index != smallest)
+            .min_by_key(|&plate_index| (plates[plate_index].1.len(), plate_index))
+            .expect("Plates cover the sphere, so every plate borders another");
+        // The neighbour is at least as large, so it keeps its type and motion
+        let (_, tiles) = plates.remove(smallest);
+        let neighbour = if neighbour > smallest {
+            neighbour - 1
+        } else {
+            neighbour
+        };
+        plates[neighbour].1.extend(tiles);
+    }
+
+    while plates.len() < plate_goal {
+        let (largest, _) = plates
+            .iter()
+            .enumerate()
+            .max_by_key(|(plate_index, (_, tiles))| (tiles.len(), std::cmp::Reverse(*plate_index)))
+            .expect("The goal is at least one plate, so there is one to split");
+        let tiles = &plates[largest].1;
+        if tiles.len() < 2 {
+            break;
+        }
+        // Seed the halves at two tiles far apart: the furthest from any tile, then the furthest from that
+        let furthest_from = |from: usize| {
+            let normal = particle_sphere.tiles[from].normal;
+            *tiles
+                .iter()
+                .min_by(|&&a, &&b| {
+                    let a = particle_sphere.tiles[a].normal.dot(normal);
+                    let b = particle_sphere.tiles[b].normal.dot(normal);
+                    a.total_cmp(&b)
+                })
+                .expect("The plate has at least two tiles")
+        };
+        let seed_a = furthest_from(*tiles.first().expect("The plate has at least two tiles"));
+        let seed_b = furthest_from(seed_a);
+
+        // Grow both halves through the plate at the same pace, tiles not reached stay on the original plate
+        let mut split_off = BTreeSet::from([seed_b]);
+        let mut claimed = BTreeSet::from([seed_a, seed_b]);
+        let mut frontier = std::collections::VecDeque::from([(seed_a, false), (seed_b, true)]);
+        while let Some((tile, is_split_off)) = frontier.pop_front() {
+            for &adjacent in &particle_sphere.tiles[tile].adjacent {
+                if tiles.contains(&adjacent) && claimed.insert(adjacent) {
+                    if is_split_off {
+                        split_off.insert(adjacent);
+                    }
+                    frontier.push_back((adjacent, is_split_off));
+                }
             }
         }
+        let plate_type = plates[largest].0.plate_type;
+        plates[largest].1.retain(|tile| !split_off.contains(tile));
+        plates.push((Plate::random(plate_type, rng), split_off));
     }
+
+    plates
+        .into_iter()
+        .map(|(mut plate, tiles)| {
+            // Sampled again from the same noise as the tiles are added back
+            plate.crust_thickness.clear();
+            let mass = if plate.plate_type == PlateType::Continental {
+                CONTINENTAL_PARTICLE_MASS
+            } else {
+                OCEANIC_PARTICLE_MASS
+            };
+            let mut builder = PlateBuilder::new(plate);
+            for tile_index in tiles {
+                let point_mass =
+                    soft_sphere::PointMass::new(particle_sphere.tiles[tile_index].normal, mass);
+                builder.add_point_mass(tile_index, point_mass, particle_sphere, config, crust);
+            }
+            builder
+        })
+        .collect()
 }
 
-#[derive(Resource)]
+#[derive(Clone)]
 pub struct Tectonics {
     pub config: TectonicsConfiguration,
     /// Average distance if all particles were spaced out evenly
     pub ideal_distance: f32,
     pub plates: Vec<Plate>,
+    /// Number of [Tectonics::simulate] steps taken so far
+    pub iteration: usize,
+    /// Timestep of the latest step, differs from [TectonicsConfiguration::timestep] with an adaptive timestep
+    pub timestep: f32,
+    /// Sum of the timesteps of all steps taken so far
+    pub simulated_time: f32,
+    /// Every point mass of every plate, refreshed after each step
+    pub tree: SphereTree,
+    /// Recent centroids of every plate, sampled every [TrailConfig::interval] iterations
+    pub trails: PlateTrails,
+    /// The invalid state that stopped the simulation, with [ValidationConfig::halt]
+    pub halted: Option<InvalidState>,
+    /// Point masses clamped back into range so far, without [ValidationConfig::halt]
+    pub repaired_point_masses: usize,
+    /// How many iterations each pair of plates has been locked in convergence, lower plate index first
+    locked_iterations: BTreeMap<(usize, usize), usize>,
+    /// Slab pull and ridge push per unit mass on every point mass, from the latest [BOUNDARY_FORCE_INTERVAL]
+    driving_forces: Vec<Vec<Vec3>>,
+}
+
+/// Summary of a single simulation step, yielded by [Tectonics::iterations]
+#[derive(Clone, Copy, Debug)]
+pub struct StepReport {
+    /// Number of steps taken including this one
+    pub iteration: usize,
+    /// Total kinetic energy of all point masses after the step
+    pub kinetic_energy: f32,
+    /// [Tectonics::simulated_time] after the step
+    pub simulated_time: f32,
 }
 
 impl Tectonics {
+    /// Seeds the plates on `particle_sphere`.
+    /// Returns an error before anything is generated if [TectonicsConfiguration::validate] fails.
     pub fn from_config(
         config: TectonicsConfiguration,
         particle_sphere: &ParticleSphere,
         rng: &mut rand::rngs::StdRng,
-    ) -> Self {
-        assert!((0.0..=1.0).contains(&config.major_tile_fraction));
-        assert!((0.0..=1.0).contains(&config.major_plate_fraction));
-        assert!((0.0..=1.0).contains(&config.continental_rate));
+    ) -> Result<Self, ConfigError> {
+        config.validate()?;
 
+        let crust = CrustSampler::new(&config.crust_noise, rng);
         let mut plate_builders: Vec<PlateBuilder> = Vec::new();
-        let ideal_distance = f32::acos(1. - 2. / particle_sphere.tiles.len() as f32) * 2.;
 
         let mut generated_majors = 0;
         let mut generated_minors = 0;
 
         let tile_count = particle_sphere.tiles.len();
-        let major_tile_count: usize = (tile_count as f32 * config.major_tile_fraction
+        // A plate goal larger than the sphere would round plates down to no tiles, which never uses up the sphere
+        let major_tile_count: usize = ((tile_count as f32 * config.major_tile_fraction
             / (config.plate_goal as f32 / 2.)
-            / config.major_plate_fraction) as usize;
-        let minor_tile_count: usize = (tile_count as f32 * (1. - config.major_tile_fraction)
+            / config.major_plate_fraction) as usize)
+            .max(1);
+        let minor_tile_count: usize = ((tile_count as f32 * (1. - config.major_tile_fraction)
             / (config.plate_goal as f32 / 2.)
-            / (1. - config.major_plate_fraction)) as usize;
+            / (1. - config.major_plate_fraction)) as usize)
+            .max(1);
 
-        let starting_tile = rng.random_range(0..particle_sphere.tiles.len());
-        let mut available_tiles: HashSet<usize> = (0..particle_sphere.tiles.len()).collect();
+        let all_tiles: Vec<usize> = (0..particle_sphere.tiles.len()).collect();
+        let starting_tile = pick_seed_tile(&all_tiles, &[], particle_sphere, &config, rng);
+        let mut seed_tiles = vec![starting_tile];
+        // Ordered sets keep plate generation reproducible, hash set iteration order changes between runs
+        let mut available_tiles: BTreeSet<usize> = all_tiles.into_iter().collect();
         available_tiles.remove(&starting_tile);
         let mut adjacent_tiles = vec![starting_tile];
 
@@ -159,7 +456,13 @@ impl Tectonics {
                     particle_sphere.tiles[random_adjacent_tile].normal,
                     mass,
                 );
-                builder.add_point_mass(random_adjacent_tile, point_mass, particle_sphere, &config);
+                builder.add_point_mass(
+                    random_adjacent_tile,
+                    point_mass,
+                    particle_sphere,
+                    &config,
+                    &crust,
+                );
                 adjacent_tiles.extend(
                     particle_sphere.tiles[random_adjacent_tile]
                         .adjacent
@@ -167,30 +470,31 @@ impl Tectonics {
                         .filter(|index| available_tiles.remove(index)),
                 );
             }
-            if builder.plate.shape.point_masses.len() >= config.min_plate_size {
+            // The first plate has nothing to merge into, so it is kept however small
+            if builder.shape.point_masses().len() >= config.min_plate_size
+                || plate_builders.is_empty()
+            {
                 plate_builders.push(builder);
-            } else if !builder.plate.shape.point_masses.is_empty() {
+            } else if !builder.shape.point_masses().is_empty() {
                 // Plate is too small, merge into closest plate
                 let closest_plate_builder = plate_builders
                     .iter_mut()
                     .min_by(|pb_a, pb_b| {
                         let closest_point_mass_a = pb_a
-                            .plate
                             .shape
-                            .point_masses
+                            .point_masses()
                             .iter()
                             .map(|point_mass| {
-                                point_mass.geodesic_distance(&builder.plate.shape.point_masses[0])
+                                point_mass.geodesic_distance(&builder.shape.point_masses()[0])
                             })
                             .min_by(|a, b| a.partial_cmp(b).unwrap())
                             .unwrap();
                         let closest_point_mass_b = pb_b
-                            .plate
                             .shape
-                            .point_masses
+                            .point_masses()
                             .iter()
                             .map(|point_mass| {
-                                point_mass.geodesic_distance(&builder.plate.shape.point_masses[0])
+                                point_mass.geodesic_distance(&builder.shape.point_masses()[0])
                             })
                             .min_by(|a, b| a.partial_cmp(b).unwrap())
                             .unwrap();
@@ -201,46 +505,48 @@ impl Tectonics {
                     .expect("Failed to find closest plate when plate was too small");
                 // For each point mass in the too-small plate, add to closest plate and add springs
                 for (&tile_index, &pm_index) in builder.tile_to_point_mass.iter() {
-                    let point_mass = &builder.plate.shape.point_masses[pm_index];
-                    let new_index = closest_plate_builder.plate.shape.point_masses.len();
-                    closest_plate_builder
-                        .plate
-                        .shape
-                        .add_point_mass(soft_sphere::PointMass {
-                            position: point_mass.position,
-                            mass: if closest_plate_builder.plate.plate_type
-                                == PlateType::Continental
-                            {
-                                CONTINENTAL_PARTICLE_MASS
-                            } else {
-                                OCEANIC_PARTICLE_MASS
-                            },
-                            velocity: Vec3::ZERO,
-                            force: Vec3::ZERO,
-                            prev_force: Vec3::ZERO,
-                        });
+                    let point_mass = &builder.shape.point_masses()[pm_index];
+                    let new_index =
+                        closest_plate_builder
+                            .shape
+                            .point_mass(soft_sphere::PointMass {
+                                position: point_mass.position,
+                                mass: if closest_plate_builder.plate.plate_type
+                                    == PlateType::Continental
+                                {
+                                    CONTINENTAL_PARTICLE_MASS
+                                } else {
+                                    OCEANIC_PARTICLE_MASS
+                                },
+                                velocity: Vec3::ZERO,
+                                force: Vec3::ZERO,
+                                prev_force: Vec3::ZERO,
+                            });
                     closest_plate_builder
                         .tile_to_point_mass
                         .insert(tile_index, new_index);
+                    closest_plate_builder
+                        .plate
+                        .crust_thickness
+                        .push(builder.plate.crust_thickness[pm_index]);
                     for adj_tile in &particle_sphere.tiles[tile_index].adjacent {
                         if let Some(&adjacent_index) =
                             closest_plate_builder.tile_to_point_mass.get(adj_tile)
                         {
-                            let rest_length = closest_plate_builder.plate.shape.point_masses
-                                [new_index]
+                            let rest_length = closest_plate_builder.shape.point_masses()[new_index]
                                 .geodesic_distance(
-                                    &closest_plate_builder.plate.shape.point_masses[adjacent_index],
+                                    &closest_plate_builder.shape.point_masses()[adjacent_index],
                                 );
                             closest_plate_builder
-                                .plate
                                 .shape
-                                .add_spring(soft_sphere::Spring {
+                                .spring(soft_sphere::Spring {
                                     anchor_a: new_index,
                                     anchor_b: adjacent_index,
                                     rest_length,
                                     spring_constant: config.spring_constant,
                                     damping_coefficient: config.dampener_coefficient,
-                                });
+                                })
+                                .expect("Each tile pair gets a single spring");
                         }
                     }
                 }
@@ -250,16 +556,76 @@ impl Tectonics {
             available_tiles.extend(adjacent_tiles.drain(..));
             if available_tiles.len() > 0 {
                 let available_tiles_vec: Vec<usize> = available_tiles.iter().cloned().collect();
-                let starting_tile =
-                    available_tiles_vec[rng.random_range(0..available_tiles_vec.len())];
+                let starting_tile = pick_seed_tile(
+                    &available_tiles_vec,
+                    &seed_tiles,
+                    particle_sphere,
+                    &config,
+                    rng,
+                );
+                seed_tiles.push(starting_tile);
                 available_tiles.remove(&starting_tile);
                 adjacent_tiles.push(starting_tile);
             }
         }
 
+        if config.strict_plate_count {
+            plate_builders =
+                balance_plate_count(plate_builders, particle_sphere, &config, &crust, rng);
+        }
+        Ok(Self::from_plate_builders(
+            config,
+            particle_sphere,
+            plate_builders,
+        ))
+    }
+
+    /// Builds the plates from an existing tiling instead of seeding them at random, like [crate::plate_import::assign_tiles] reads from a plate dataset.
+    /// `tile_plates` holds an index into `plate_types` for every tile of `particle_sphere`, plate colors and axes are random.
+    /// [TectonicsConfiguration::min_plate_size] and [TectonicsConfiguration::strict_plate_count] are ignored, plates without tiles are dropped.
+    pub fn from_tile_plates(
+        config: TectonicsConfiguration,
+        particle_sphere: &ParticleSphere,
+        tile_plates: &[usize],
+        plate_types: &[PlateType],
+        rng: &mut rand::rngs::StdRng,
+    ) -> Result<Self, ConfigError> {
+        config.validate()?;
+
+        let crust = CrustSampler::new(&config.crust_noise, rng);
+        let mut plate_builders: Vec<PlateBuilder> = plate_types
+            .iter()
+            .map(|&plate_type| PlateBuilder::new(Plate::random(plate_type, rng)))
+            .collect();
+        for (tile_index, &plate_index) in tile_plates.iter().enumerate() {
+            let builder = &mut plate_builders[plate_index];
+            let mass = if builder.plate.plate_type == PlateType::Continental {
+                CONTINENTAL_PARTICLE_MASS
+            } else {
+                OCEANIC_PARTICLE_MASS
+            };
+            let point_mass =
+                soft_sphere::PointMass::new(particle_sphere.tiles[tile_index].normal, mass);
+            builder.add_point_mass(tile_index, point_mass, particle_sphere, &config, &crust);
+        }
+        plate_builders.retain(|builder| !builder.shape.point_masses().is_empty());
+        Ok(Self::from_plate_builders(
+            config,
+            particle_sphere,
+            plate_builders,
+        ))
+    }
+
+    /// Builds the seeded plates, every tile of `particle_sphere` must be on exactly one of them
+    fn from_plate_builders(
+        config: TectonicsConfiguration,
+        particle_sphere: &ParticleSphere,
+        mut plate_builders: Vec<PlateBuilder>,
+    ) -> Self {
+        let ideal_distance = f32::acos(1. - 2. / particle_sphere.tiles.len() as f32) * 2.;
         let point_mass_count = plate_builders
             .iter()
-            .map(|pb| pb.plate.shape.point_masses.len())
+            .map(|pb| pb.shape.point_masses().len())
             .sum::<usize>();
         assert!(
             point_mass_count == particle_sphere.tiles.len(),
@@ -268,23 +634,163 @@ impl Tectonics {
             particle_sphere.tiles.len()
         );
 
+        let plates: Vec<Plate> = plate_builders
+            .drain(..)
+            .map(|pb| Plate {
+                shape: pb.shape.build(),
+                ..pb.plate
+            })
+            .collect();
         Tectonics {
             config,
-            plates: plate_builders.drain(..).map(|pb| pb.plate).collect(),
+            tree: SphereTree::new(&plates),
+            plates,
             ideal_distance,
+            iteration: 0,
+            timestep: config.timestep,
+            simulated_time: 0.,
+            trails: PlateTrails::default(),
+            halted: None,
+            repaired_point_masses: 0,
+            locked_iterations: BTreeMap::new(),
+            driving_forces: Vec::new(),
+        }
+    }
+
+    /// Replaces the simulation parameters of a running simulation.
+    /// Spring parameters are copied into every existing spring, plate generation parameters have no effect after [Tectonics::from_config].
+    pub fn set_config(&mut self, config: TectonicsConfiguration) {
+        for plate in &mut self.plates {
+            plate
+                .shape
+                .set_spring_parameters(config.spring_constant, config.dampener_coefficient);
         }
+        self.timestep = match config.adaptive_timestep {
+            Some(adaptive) => self
+                .timestep
+                .clamp(adaptive.min_timestep, adaptive.max_timestep),
+            None => config.timestep,
+        };
+        self.config = config;
+    }
+
+    /// Total kinetic energy of all point masses
+    pub fn kinetic_energy(&self) -> f32 {
+        self.plates
+            .iter()
+            .map(|plate| plate.shape.kinetic_energy())
+            .sum()
+    }
+
+    /// Spring force evaluations skipped across all plates because their point masses coincided or were antipodal, see [soft_sphere::Shape::degenerate_springs]
+    pub fn degenerate_springs(&self) -> usize {
+        self.plates
+            .iter()
+            .map(|plate| plate.shape.degenerate_springs())
+            .sum()
+    }
+
+    /// Kinetic energy plus the energy stored in all springs.
+    /// The plate forces keep adding energy and friction removes it, so it should level off, steady growth means the springs are unstable
+    pub fn total_energy(&self) -> f32 {
+        self.kinetic_energy()
+            + self
+                .plates
+                .iter()
+                .map(|plate| plate.shape.spring_potential_energy())
+                .sum::<f32>()
+    }
+
+    /// Whether [TectonicsConfiguration::duration] of simulated time has passed, or [TectonicsConfiguration::iterations] steps without a duration.
+    /// A simulation [Tectonics::halted] on an invalid state is finished too.
+    pub fn finished(&self) -> bool {
+        if self.halted.is_some() {
+            true
+        } else if self.config.duration > 0. {
+            self.simulated_time >= self.config.duration
+        } else {
+            self.iteration >= self.config.iterations
+        }
+    }
+
+    /// Fraction of the simulation done, measured the same way as [Tectonics::finished]
+    pub fn progress(&self) -> f32 {
+        let progress = if self.config.duration > 0. {
+            self.simulated_time / self.config.duration
+        } else if self.config.iterations > 0 {
+            self.iteration as f32 / self.config.iterations as f32
+        } else {
+            1.
+        };
+        progress.clamp(0., 1.)
+    }
+
+    /// Steps the simulation lazily until it is [Tectonics::finished].
+    /// Dropping the iterator stops early, calling this again resumes from [Tectonics::iteration].
+    pub fn iterations<'a>(
+        &'a mut self,
+        rng: &'a mut rand::rngs::StdRng,
+    ) -> impl Iterator<Item = StepReport> + 'a {
+        std::iter::from_fn(move || {
+            if self.finished() {
+                return None;
+            }
+            self.simulate(rng);
+            Some(StepReport {
+                iteration: self.iteration,
+                kinetic_energy: self.kinetic_energy(),
+                simulated_time: self.simulated_time,
+            })
+        })
+    }
+
+    /// Picks the next timestep so the fastest point mass moves about [AdaptiveTimestep::target_displacement]
+    fn adapt_timestep(&mut self, adaptive: AdaptiveTimestep) {
+        let max_speed = self
+            .plates
+            .iter()
+            .flat_map(|plate| plate.shape.point_masses())
+            .map(|point_mass| point_mass.velocity.length())
+            .fold(0., f32::max);
+        let target = if max_speed > 0. {
+            adaptive.target_displacement * self.ideal_distance / max_speed
+        } else {
+            adaptive.max_timestep
+        };
+        self.timestep = target
+            .min(self.timestep * MAX_TIMESTEP_GROWTH)
+            .clamp(adaptive.min_timestep, adaptive.max_timestep);
     }
 
     // Each point mass will be forced to have the velocity matching rotation around the ownings plate axis of rotation
     // Then we adjust that velocity depending on other particles
     pub fn simulate(&mut self, rng: &mut rand::rngs::StdRng) {
-        // Apply forces and update velocity and position
-        for plate in &mut self.plates {
+        if self.halted.is_some() {
+            return;
+        }
+        self.iteration += 1;
+        if let Some(adaptive) = self.config.adaptive_timestep {
+            self.adapt_timestep(adaptive);
+        }
+        self.simulated_time += self.timestep;
+        let rotation_forcing = self
+            .config
+            .driving_forces
+            .map_or(1., |driving| driving.rotation_forcing);
+        // Slab pull and ridge push as last summed, a plate merged since has none until the next sum, see [Tectonics::merge_plates]
+        for (plate, forces) in self.plates.iter_mut().zip(&self.driving_forces) {
+            for (point_mass, force) in plate.shape.point_masses_mut().iter_mut().zip(forces) {
+                point_mass.force += *force * point_mass.mass;
+            }
+        }
+        // Apply forces and update velocity and position, plates do not interact so each is updated on its own thread
+        self.plates.par_iter_mut().for_each(|plate| {
             plate.shape.apply_external_force(|point_mass| {
                 let plate_force = plate
                     .axis_of_rotation
                     .cross(point_mass.position)
                     * self.config.plate_force_modifier
+                    * rotation_forcing
                     // We make this force mass independent so oceanic and continental plates move equally
                     * point_mass.mass;
                 let friction_force = if point_mass.velocity.length() > 0. {
@@ -294,18 +800,73 @@ impl Tectonics {
                 };
                 plate_force + friction_force
             });
-            plate.shape.apply_spring_forces();
             // TODO: Update and add frame forces to maintain shape
             // TODO: Simulate collisions
-            plate.shape.update(self.config.timestep);
+            if self.config.substeps == 0 {
+                plate.shape.apply_spring_forces();
+                plate.shape.update(self.timestep);
+            } else {
+                // Evaluates the spring forces itself at every substep
+                plate.shape.set_solver(self.config.solver);
+                plate
+                    .shape
+                    .update_substepped(self.timestep, self.config.substeps);
+            }
+        });
+        if let Some(validation) = self.config.validation {
+            self.check_state(&validation);
+        }
+        self.tree.refresh_incremental(&self.plates);
+        if self.config.merge_iterations > 0 && self.iteration % MERGE_CHECK_INTERVAL == 0 {
+            self.suture_plates();
+            self.tree.refresh_incremental(&self.plates);
+        }
+        self.record_trails();
+        let boundary_forces = self.config.torque.is_some() || self.config.driving_forces.is_some();
+        if boundary_forces && self.iteration % BOUNDARY_FORCE_INTERVAL == 0 {
+            let contacts = self.plate_contacts();
+            if let Some(torque) = self.config.torque {
+                self.torque_plates(&torque, &contacts);
+            }
+            if let Some(driving) = self.config.driving_forces {
+                self.driving_forces = driving_forces(&self.plates, &contacts, &self.tree, &driving);
+            }
+        }
+        if self.config.torque.is_none() {
+            self.drift_plates(rng);
         }
-        // Randomly modify each plates axis of rotation slightly
+    }
+
+    /// Finds the first point mass that is not finite, too fast or off the unit sphere.
+    /// Uses [TectonicsConfiguration::validation] if set and the default limits otherwise.
+    pub fn validate(&self) -> Result<(), InvalidState> {
+        validate(&self.plates, &self.config.validation.unwrap_or_default())
+    }
+
+    /// Halts on or repairs an invalid state, as [ValidationConfig::halt] says
+    pub(crate) fn check_state(&mut self, validation: &ValidationConfig) {
+        if validation.halt {
+            self.halted = validate(&self.plates, validation).err();
+        } else {
+            self.repaired_point_masses += repair(&mut self.plates, validation);
+        }
+    }
+
+    /// Samples [Tectonics::trails] on every [TrailConfig::interval]th iteration
+    pub(crate) fn record_trails(&mut self) {
+        if self.iteration % self.config.trails.interval.max(1) == 0 {
+            self.trails.record(&self.config.trails, &self.plates);
+        }
+    }
+
+    /// Randomly modify each plates axis of rotation slightly, the last part of every [Tectonics::simulate] step
+    pub fn drift_plates(&mut self, rng: &mut rand::rngs::StdRng) {
         for plate in self.plates.iter_mut() {
             plate.drift_direction = (plate.drift_direction
                 + Vec2::new(
                     rng.random_range(-1.0..1.0) * self.config.plate_rotation_drift_rate,
                     rng.random_range(-1.0..1.0) * self.config.plate_rotation_drift_rate,
-                ) * self.config.timestep)
+                ) * self.timestep)
                 .normalize();
             plate.axis_of_rotation = Quat::from_euler(
                 EulerRot::XYZ,
@@ -315,4 +876,122 @@ impl Tectonics {
             ) * plate.axis_of_rotation;
         }
     }
+
+    /// Turns each plate's axis with the torque of its boundary forces at `contacts`, over the time since the last [BOUNDARY_FORCE_INTERVAL]
+    fn torque_plates(&mut self, torque: &TorqueConfig, contacts: &[[(usize, usize); 2]]) {
+        let torques = boundary_torques(&self.plates, contacts, torque);
+        let elapsed = self.timestep * BOUNDARY_FORCE_INTERVAL as f32;
+        apply_torques(&mut self.plates, &torques, elapsed, torque);
+    }
+
+    /// Pairs of point masses on different plates that are in contact, as (plate, point mass) with the lower plate first
+    fn plate_contacts(&self) -> Vec<[(usize, usize); 2]> {
+        let mut contacts = Vec::new();
+        for (plate_index, plate) in self.plates.iter().enumerate() {
+            for (point_mass_index, point_mass) in plate.shape.point_masses().iter().enumerate() {
+                for (other, _) in self
+                    .tree
+                    .query_within(point_mass.position, CONTACT_DISTANCE * self.ideal_distance)
+                {
+                    if other.plate > plate_index {
+                        contacts.push([
+                            (plate_index, point_mass_index),
+                            (other.plate, other.point_mass),
+                        ]);
+                    }
+                }
+            }
+        }
+        contacts
+    }
+
+    /// Tracks how long touching plates have been locked in convergence,
+    /// and merges the pair locked the longest once it reaches [TectonicsConfiguration::merge_iterations]
+    fn suture_plates(&mut self) {
+        let contacts = self.plate_contacts();
+        // Summed closing speed, summed relative speed and contact count of every touching pair
+        let mut boundaries: BTreeMap<(usize, usize), (f32, f32, usize)> = BTreeMap::new();
+        for &[(plate_a, point_mass_a), (plate_b, point_mass_b)] in &contacts {
+            // Oceanic crust subducts instead of suturing, and a merged plate has a single crust type
+            if self.plates[plate_a].plate_type != self.plates[plate_b].plate_type {
+                continue;
+            }
+            let point_mass_a = &self.plates[plate_a].shape.point_masses()[point_mass_a];
+            let point_mass_b = &self.plates[plate_b].shape.point_masses()[point_mass_b];
+            let direction = (point_mass_b.position - point_mass_a.position).normalize_or_zero();
+            let relative_velocity = point_mass_b.velocity - point_mass_a.velocity;
+            let boundary = boundaries.entry((plate_a, plate_b)).or_default();
+            boundary.0 += relative_velocity.dot(direction);
+            boundary.1 += relative_velocity.length();
+            boundary.2 += 1;
+        }
+        let previous = std::mem::take(&mut self.locked_iterations);
+        for (&pair, &(closing_speed, speed, count)) in &boundaries {
+            if closing_speed <= 0. && speed / (count as f32) < self.config.merge_speed {
+                let locked = previous.get(&pair).unwrap_or(&0) + MERGE_CHECK_INTERVAL;
+                self.locked_iterations.insert(pair, locked);
+            }
+        }
+        let Some((plate_a, plate_b)) = self
+            .locked_iterations
+            .iter()
+            .filter(|(_, locked)| **locked >= self.config.merge_iterations)
+            .max_by_key(|(_, locked)| **locked)
+            .map(|(pair, _)| *pair)
+        else {
+            return;
+        };
+        self.merge_plates(plate_a, plate_b, &contacts);
+    }
+
+    /// Moves plate `plate_b` into `plate_a` and adds suture springs between their contacts.
+    /// The larger of the two plates keeps its motion and color, plate indices after `plate_b` shift down by one.
+    fn merge_plates(&mut self, plate_a: usize, plate_b: usize, contacts: &[[(usize, usize); 2]]) {
+        let mut absorbed = self.plates.remove(plate_b);
+        let plate = &mut self.plates[plate_a];
+        let swapped = absorbed.shape.point_masses().len() > plate.shape.point_masses().len();
+        if swapped {
+            std::mem::swap(plate, &mut absorbed);
+        }
+        self.trails.merge(plate_a, plate_b, swapped);
+        let offset = plate.shape.merge(absorbed.shape);
+        plate.crust_thickness.extend(absorbed.crust_thickness);
+        for &[(_, point_mass_a), (_, point_mass_b)] in contacts
+            .iter()
+            .filter(|[(a, _), (b, _)]| *a == plate_a && *b == plate_b)
+        {
+            let (anchor_a, anchor_b) = if swapped {
+                (point_mass_a + offset, point_mass_b)
+            } else {
+                (point_mass_a, point_mass_b + offset)
+            };
+            let rest_length = plate.shape.point_masses()[anchor_a]
+                .geodesic_distance(&plate.shape.point_masses()[anchor_b]);
+            plate
+                .shape
+                .add_spring(soft_sphere::Spring {
+                    anchor_a,
+                    anchor_b,
+                    rest_length,
+                    spring_constant: self.config.spring_constant,
+                    damping_coefficient: self.config.dampener_coefficient,
+                })
+                .expect("Contacts pair point masses of two different plates once");
+        }
+
+        // The merged plate gets no driving forces until they are next summed, its old forces cover only part of it
+        if plate_b < self.driving_forces.len() {
+            self.driving_forces.remove(plate_b);
+            self.driving_forces[plate_a].clear();
+        }
+
+        let shift = |plate: usize| if plate > plate_b { plate - 1 } else { plate };
+        self.locked_iterations = std::mem::take(&mut self.locked_iterations)
+            .into_iter()
+            .filter(|((a, b), _)| {
+                ![plate_a, plate_b].contains(a) && ![plate_a, plate_b].contains(b)
+            })
+            .map(|((a, b), locked)| ((shift(a), shift(b)), locked))
+            .collect();
+    }
 }
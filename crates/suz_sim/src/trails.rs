@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::plate::Plate;
+
+/// How much of each plate's path [PlateTrails] keeps
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct TrailConfig {
+    /// Samples kept per plate, the oldest is dropped once full. 0 disables trails
+    pub length: usize,
+    /// Iterations between samples
+    pub interval: usize,
+}
+
+impl Default for TrailConfig {
+    fn default() -> Self {
+        TrailConfig {
+            length: 64,
+            interval: 10,
+        }
+    }
+}
+
+/// Past centroids of every plate on the unit sphere, oldest first, in the same order as [crate::tectonics::Tectonics::plates]
+#[derive(Clone, Default)]
+pub struct PlateTrails {
+    trails: Vec<VecDeque<Vec3>>,
+}
+
+impl PlateTrails {
+    pub fn trails(&self) -> &[VecDeque<Vec3>] {
+        &self.trails
+    }
+
+    /// Appends the current centroid of every plate, dropping samples past [TrailConfig::length]
+    pub fn record(&mut self, config: &TrailConfig, plates: &[Plate]) {
+        if config.length == 0 {
+            self.trails.clear();
+            return;
+        }
+        self.trails.resize_with(plates.len(), VecDeque::new);
+        for (trail, plate) in self.trails.iter_mut().zip(plates) {
+            let centroid = plate.shape.centroid().normalize_or_zero();
+            if centroid == Vec3::ZERO {
+                continue;
+            }
+            trail.push_back(centroid);
+            while trail.len() > config.length {
+                trail.pop_front();
+            }
+        }
+    }
+
+    /// Follows [crate::tectonics::Tectonics] merging plate `plate_b` into `plate_a`.
+    /// The merged plate continues the trail of the plate that kept its motion, `swapped` when that was `plate_b`.
+    pub fn merge(&mut self, plate_a: usize, plate_b: usize, swapped: bool) {
+        if plate_b >= self.trails.len() || plate_a >= self.trails.len() {
+            return;
+        }
+        let absorbed = self.trails.remove(plate_b);
+        if swapped {
+            self.trails[plate_a] = absorbed;
+        }
+    }
+}
@@ -0,0 +1,50 @@
+/// Mean radius of the Earth, the default [crate::tectonics::TectonicsConfiguration::planet_radius_km]
+pub const EARTH_RADIUS_KM: f32 = 6371.;
+/// Kilometres of elevation per unit of tile height on an Earth sized planet.
+/// Relief is exaggerated so it shows on a unit sphere, [crate::climate::ClimateConfig::lapse_rate] assumes this scale.
+const EARTH_KM_PER_HEIGHT: f32 = 100.;
+
+/// Converts the unit sphere quantities of the simulation into physical units for a planet of `planet_radius_km`.
+/// A simulated time unit is taken as a mega-year, see [crate::tectonics::TectonicsConfiguration::duration].
+#[derive(Clone, Copy, Debug)]
+pub struct PhysicalUnits {
+    pub planet_radius_km: f32,
+}
+
+impl PhysicalUnits {
+    pub fn new(planet_radius_km: f32) -> Self {
+        PhysicalUnits { planet_radius_km }
+    }
+
+    /// Metres of elevation per unit of tile height, relief keeps the same exaggeration relative to the radius on every planet
+    pub fn meters_per_height(&self) -> f32 {
+        EARTH_KM_PER_HEIGHT * 1000. * self.planet_radius_km / EARTH_RADIUS_KM
+    }
+
+    /// Elevation in metres of a tile at `height`, negative below `sea_level`
+    pub fn elevation_m(&self, height: f32, sea_level: f32) -> f32 {
+        (height - sea_level) * self.meters_per_height()
+    }
+
+    /// Tile height at `elevation_m` metres above `sea_level`, the inverse of [PhysicalUnits::elevation_m]
+    pub fn height(&self, elevation_m: f32, sea_level: f32) -> f32 {
+        sea_level + elevation_m / self.meters_per_height()
+    }
+
+    /// Surface distance in kilometres spanned by `angle` radians
+    pub fn distance_km(&self, angle: f32) -> f32 {
+        angle * self.planet_radius_km
+    }
+
+    /// Surface speed in millimetres per year of a point mass moving `speed` unit sphere radians per time unit.
+    /// A kilometre per mega-year is a millimetre per year.
+    pub fn speed_mm_per_year(&self, speed: f32) -> f32 {
+        speed * self.planet_radius_km
+    }
+}
+
+impl Default for PhysicalUnits {
+    fn default() -> Self {
+        PhysicalUnits::new(EARTH_RADIUS_KM)
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+
+use crate::plate::Plate;
+
+/// Checks run on every point mass after each step when [crate::tectonics::TectonicsConfiguration::validation] is set
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ValidationConfig {
+    /// Point masses moving faster than this are invalid
+    pub max_speed: f32,
+    /// How far a point mass may drift off the unit sphere before it is invalid
+    pub max_radius_error: f32,
+    /// Stops the simulation at the first invalid point mass instead of repairing it
+    pub halt: bool,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        ValidationConfig {
+            max_speed: 10.,
+            max_radius_error: 1e-3,
+            halt: false,
+        }
+    }
+}
+
+/// What is wrong with an invalid point mass
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Invalid {
+    /// Position or velocity is NaN or infinite
+    NotFinite,
+    TooFast {
+        speed: f32,
+    },
+    OffSphere {
+        radius: f32,
+    },
+}
+
+/// The first invalid point mass found by [validate]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvalidState {
+    pub plate: usize,
+    pub point_mass: usize,
+    pub invalid: Invalid,
+}
+
+impl fmt::Display for InvalidState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (plate, point_mass) = (self.plate, self.point_mass);
+        match self.invalid {
+            Invalid::NotFinite => write!(
+                f,
+                "Point mass {point_mass} of plate {plate} has a non-finite position or velocity"
+            ),
+            Invalid::TooFast { speed } => write!(
+                f,
+                "Point mass {point_mass} of plate {plate} moves at {speed}, faster than the maximum speed"
+            ),
+            Invalid::OffSphere { radius } => write!(
+                f,
+                "Point mass {point_mass} of plate {plate} drifted off the unit sphere to radius {radius}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidState {}
+
+fn check(position: Vec3, velocity: Vec3, config: &ValidationConfig) -> Option<Invalid> {
+    if !position.is_finite() || !velocity.is_finite() {
+        return Some(Invalid::NotFinite);
+    }
+    let speed = velocity.length();
+    if speed > config.max_speed {
+        return Some(Invalid::TooFast { speed });
+    }
+    let radius = position.length();
+    if (radius - 1.).abs() > config.max_radius_error {
+        return Some(Invalid::OffSphere { radius });
+    }
+    None
+}
+
+/// Finds the first point mass of `plates` that is not finite, too fast or off the unit sphere
+pub fn validate(plates: &[Plate], config: &ValidationConfig) -> Result<(), InvalidState> {
+    for (plate_index, plate) in plates.iter().enumerate() {
+        for (point_mass_index, point_mass) in plate.shape.point_masses().iter().enumerate() {
+            if let Some(invalid) = check(point_mass.position, point_mass.velocity, config) {
+                return Err(InvalidState {
+                    plate: plate_index,
+                    point_mass: point_mass_index,
+                    invalid,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Clamps every invalid point mass back into range, returning how many were repaired.
+/// Non-finite point masses are moved to their plate's centroid and stopped, as their last valid position is lost.
+pub fn repair(plates: &mut [Plate], config: &ValidationConfig) -> usize {
+    let mut repaired = 0;
+    for plate in plates {
+        let centroid = plate.shape.centroid().normalize_or(Vec3::Y);
+        for point_mass in plate.shape.point_masses_mut() {
+            let Some(invalid) = check(point_mass.position, point_mass.velocity, config) else {
+                continue;
+            };
+            match invalid {
+                Invalid::NotFinite => {
+                    if !point_mass.position.is_finite() {
+                        point_mass.position = centroid;
+                    }
+                    point_mass.velocity = Vec3::ZERO;
+                    point_mass.prev_force = Vec3::ZERO;
+                }
+                Invalid::TooFast { .. } => {
+                    point_mass.velocity = point_mass.velocity.clamp_length_max(config.max_speed);
+                }
+                Invalid::OffSphere { .. } => {}
+            }
+            // Each repair can leave the point mass off the sphere, the tangent part of the velocity is what moves it
+            point_mass.position = point_mass.position.normalize_or(centroid);
+            point_mass.velocity -=
+                point_mass.velocity.dot(point_mass.position) * point_mass.position;
+            repaired += 1;
+        }
+    }
+    repaired
+}
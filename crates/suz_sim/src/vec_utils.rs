@@ -1,4 +1,4 @@
-use bevy::math::Vec3;
+use glam::{Quat, Vec3};
 
 #[inline]
 pub fn f64_3_to_f32_3(input: &[f64; 3]) -> [f32; 3] {
@@ -18,19 +18,85 @@ pub fn vec3_to_f64_3(input: Vec3) -> [f64; 3] {
     arr.map(|p| p as f64)
 }
 
+/// Unit sphere normal from latitude and longitude in radians, with the poles along the Y axis
+#[inline]
+pub fn lat_long_to_vec3(latitude: f32, longitude: f32) -> Vec3 {
+    Vec3::new(
+        latitude.cos() * longitude.cos(),
+        latitude.sin(),
+        latitude.cos() * longitude.sin(),
+    )
+}
+
+/// Latitude and longitude in radians of a unit sphere normal, the inverse of [lat_long_to_vec3]
+#[inline]
+pub fn vec3_to_lat_long(normal: Vec3) -> (f32, f32) {
+    (normal.y.clamp(-1., 1.).asin(), normal.z.atan2(normal.x))
+}
+
+/// East and north unit tangents at `normal`, both zero at the poles where they are undefined
+#[inline]
+pub fn tangent_frame(normal: Vec3) -> (Vec3, Vec3) {
+    let east = Vec3::Y.cross(normal).normalize_or_zero();
+    (east, normal.cross(east))
+}
+
+/// Initial direction of the great circle from `from` to `to`, in radians clockwise from north towards east of [tangent_frame]
+#[inline]
+pub fn bearing(from: Vec3, to: Vec3) -> f32 {
+    let (east, north) = tangent_frame(from);
+    to.dot(east).atan2(to.dot(north))
+}
+
+/// The point a fraction `t` of the way along the great circle arc from `a` to `b`.
+/// Falls back to a normalized straight line for nearly equal or opposite normals, where the arc is not unique.
+#[inline]
+pub fn slerp(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    let angle = geodesic_distance(a, b);
+    let sin_angle = angle.sin();
+    if sin_angle < 1e-6 {
+        return a.lerp(b, t).normalize_or(a);
+    }
+    (a * ((1. - t) * angle).sin() + b * (t * angle).sin()) / sin_angle
+}
+
+/// Solid angle of the spherical triangle between three unit normals in steradians, after Van Oosterom and Strackee (1983)
+#[inline]
+pub fn spherical_triangle_area(a: Vec3, b: Vec3, c: Vec3) -> f32 {
+    2. * a
+        .dot(b.cross(c))
+        .abs()
+        .atan2(1. + a.dot(b) + b.dot(c) + c.dot(a))
+}
+
+/// Rotates `point` about `axis` so it travels `arc_length` along its circle of rotation, points on the axis do not move
+#[inline]
+pub fn rotate_by_arc_length(point: Vec3, axis: Vec3, arc_length: f32) -> Vec3 {
+    let axis = axis.normalize();
+    let radius = axis.cross(point).length();
+    if radius <= f32::EPSILON {
+        return point;
+    }
+    Quat::from_axis_angle(axis, arc_length / radius) * point
+}
+
+/// See [soft_sphere::precision::angle_between] for the precision with and without the `f64` feature
 #[inline]
 pub fn geodesic_distance(a: Vec3, b: Vec3) -> f32 {
-    f32::acos(a.dot(b).clamp(-1., 1.))
+    soft_sphere::precision::angle_between(a, b)
 }
 
 #[inline]
 pub fn geodesic_distance_arr(a: &[f32], b: &[f32]) -> f32 {
     debug_assert_eq!(a.len(), b.len());
-    let dot = if a.len() == 3 {
-        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
-    } else {
-        // Forced by kdtree to have this be generic
-        a.iter().zip(b.iter()).map(|(a, b)| *a * *b).sum::<f32>()
-    };
-    dot.clamp(-1.0, 1.0).acos()
+    if a.len() == 3 {
+        return geodesic_distance(Vec3::from_slice(a), Vec3::from_slice(b));
+    }
+    // Forced by kdtree to have this be generic, the length of the cross product follows from Lagrange's identity
+    let dot = a.iter().zip(b.iter()).map(|(a, b)| *a * *b).sum::<f32>();
+    let length_squared = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>();
+    let cross = (length_squared(a) * length_squared(b) - dot * dot)
+        .max(0.)
+        .sqrt();
+    cross.atan2(dot)
 }
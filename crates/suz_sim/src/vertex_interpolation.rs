@@ -0,0 +1,104 @@
+use glam::Vec3;
+use hex_sphere::Tile;
+use rayon::prelude::*;
+
+use crate::interpolation::HeightUpdate;
+
+/// Smooth normals of a tile's own vertices, tiles do not share vertices so only the tile's own triangles are averaged
+pub fn tile_vertex_normals(
+    tiles: &[Tile],
+    vertices: &[[f32; 3]],
+    tile_index: usize,
+) -> Vec<(usize, Vec3)> {
+    let tile = &tiles[tile_index];
+    let position = |vertex_index: usize| Vec3::from(vertices[vertex_index]);
+    let center = position(tile.center);
+    let mut normals: Vec<(usize, Vec3)> = tile
+        .vertices
+        .iter()
+        .map(|&vertex_index| (vertex_index, Vec3::ZERO))
+        .collect();
+    let mut center_normal = Vec3::ZERO;
+    // Triangles fan out from the center in the same winding the mesh is built with
+    for corner in 0..normals.len() {
+        let previous = (corner + normals.len() - 1) % normals.len();
+        let a = position(normals[previous].0);
+        let b = position(normals[corner].0);
+        let face_normal = (b - a).cross(center - a).normalize_or_zero();
+        normals[previous].1 += face_normal;
+        normals[corner].1 += face_normal;
+        center_normal += face_normal;
+    }
+    normals.push((tile.center, center_normal));
+    for (_, normal) in &mut normals {
+        *normal = normal.normalize_or_zero();
+    }
+    normals
+}
+
+/// Corner vertices sit at the average of the tile centers around them
+pub fn corner_position(
+    tiles: &[Tile],
+    vertices_to_tiles: &[Vec<usize>],
+    vertex_index: usize,
+) -> [f32; 3] {
+    let mut sum = Vec3::ZERO;
+    for tile_index in &vertices_to_tiles[vertex_index] {
+        let tile = &tiles[*tile_index];
+        sum += tile.normal * tile.height;
+    }
+    (sum / 3.).into()
+}
+
+/// Moves the updated tiles to their new heights.
+/// Returns the tiles whose vertices have to be moved, corners are shared with the adjacent tiles so those are included.
+pub fn apply_height_updates(tiles: &mut [Tile], updates: &[HeightUpdate]) -> Vec<usize> {
+    let mut moved = vec![false; tiles.len()];
+    for update in updates {
+        let tile = &mut tiles[update.tile];
+        tile.height = update.height;
+        // The adjacent tiles include the tile itself
+        for &adjacent in &tile.adjacent {
+            moved[adjacent] = true;
+        }
+    }
+    (0..tiles.len())
+        .filter(|&tile_index| moved[tile_index])
+        .collect()
+}
+
+/// Moves the center and corner vertices of the `moved` tiles to their tile heights.
+/// Returns the new smooth normal of every vertex of those tiles.
+pub fn move_tile_vertices(
+    tiles: &[Tile],
+    vertices: &mut [[f32; 3]],
+    vertices_to_tiles: &[Vec<usize>],
+    moved: &[usize],
+) -> Vec<(usize, Vec3)> {
+    let new_positions: Vec<(usize, [f32; 3])> = moved
+        .par_iter()
+        .flat_map_iter(|&tile_index| {
+            let tile = &tiles[tile_index];
+            tile.vertices
+                .iter()
+                .map(|&vertex_index| {
+                    (
+                        vertex_index,
+                        corner_position(tiles, vertices_to_tiles, vertex_index),
+                    )
+                })
+                .chain(std::iter::once((
+                    tile.center,
+                    (tile.normal * tile.height).into(),
+                )))
+        })
+        .collect();
+    for (vertex_index, new_position) in new_positions {
+        vertices[vertex_index] = new_position;
+    }
+    let vertices = &*vertices;
+    moved
+        .par_iter()
+        .flat_map_iter(|&tile_index| tile_vertex_normals(tiles, vertices, tile_index))
+        .collect()
+}
@@ -0,0 +1,74 @@
+use subsphere::{Face, Sphere};
+use suz_sim::{
+    bake::{BakeConfig, bake_ambient_occlusion, bake_normal_map},
+    export::TileLayer,
+};
+
+#[test]
+fn flat_planet_bakes_to_straight_normals_and_no_occlusion() {
+    let subsphere = hex_sphere::subsphere(16);
+    let heights = vec![0.01; subsphere.num_faces()];
+    let layer = TileLayer {
+        subsphere: &subsphere,
+        values: &heights,
+    };
+    let config = BakeConfig::default();
+
+    let normals = bake_normal_map(&layer, 64, &config);
+    assert_eq!((normals.width, normals.height), (64, 32));
+    assert!(
+        normals
+            .pixels
+            .iter()
+            .all(|&pixel| pixel == Some([128, 128, 255]))
+    );
+
+    let occlusion = bake_ambient_occlusion(&layer, 64, &config);
+    assert_eq!((occlusion.width, occlusion.height), (64, 32));
+    assert!(occlusion.pixels.iter().all(|&pixel| pixel == Some(u8::MAX)));
+}
+
+#[test]
+fn raised_northern_hemisphere_tilts_normals_south_and_shades_below_the_step() {
+    let subsphere = hex_sphere::subsphere(16);
+    let heights: Vec<f32> = subsphere
+        .faces()
+        .map(|face| {
+            if face.center().pos()[1] > 0. {
+                0.01
+            } else {
+                0.
+            }
+        })
+        .collect();
+    let layer = TileLayer {
+        subsphere: &subsphere,
+        values: &heights,
+    };
+    let config = BakeConfig::default();
+
+    // Green is the northward component, the slope rises to the north so normals lean south
+    let normals = bake_normal_map(&layer, 64, &config);
+    let green: Vec<i32> = normals
+        .pixels
+        .iter()
+        .map(|pixel| pixel.unwrap()[1] as i32 - 128)
+        .collect();
+    assert!(green.iter().sum::<i32>() < 0);
+    assert!(green.iter().any(|&green| green < -8));
+    assert!(green[..64].iter().all(|&green| green == 0));
+
+    let occlusion = bake_ambient_occlusion(&layer, 64, &config);
+    assert!(occlusion.pixels.iter().any(|&pixel| pixel < Some(u8::MAX)));
+    // Nothing rises above the poles within the search radius
+    assert!(
+        occlusion.pixels[..64]
+            .iter()
+            .all(|&pixel| pixel == Some(u8::MAX))
+    );
+    assert!(
+        occlusion.pixels[31 * 64..]
+            .iter()
+            .all(|&pixel| pixel == Some(u8::MAX))
+    );
+}
@@ -0,0 +1,64 @@
+use rand::SeedableRng;
+use suz_sim::{
+    config::SimulationConfig,
+    flexure::apply_flexure,
+    interpolation::{containing_plates, interpolate_tile_heights},
+    particle_sphere::{ParticleSphere, ParticleSphereConfig},
+    tectonics::{Tectonics, TectonicsConfiguration},
+};
+
+const SEED: u64 = 4767;
+
+/// Runs the whole pipeline on a small sphere and returns the bit patterns of the final heights and the plate of every tile
+fn generate(threads: usize) -> (Vec<u32>, Vec<usize>) {
+    let default = SimulationConfig::default();
+    let config = SimulationConfig {
+        particle_sphere: ParticleSphereConfig::regular(16),
+        tectonics: TectonicsConfiguration {
+            plate_goal: 10,
+            iterations: 50,
+            ..default.tectonics
+        },
+        ..default
+    };
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("Failed to build thread pool");
+    pool.install(|| {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(SEED);
+        let particle_sphere = ParticleSphere::from_config(config.particle_sphere, &mut rng);
+        let mut tectonics = Tectonics::from_config(config.tectonics, &particle_sphere, &mut rng)
+            .expect("Invalid tectonics config");
+        tectonics.iterations(&mut rng).for_each(drop);
+        let normals: Vec<_> = particle_sphere
+            .tiles
+            .iter()
+            .map(|tile| tile.normal)
+            .collect();
+        let mut heights = interpolate_tile_heights(&tectonics, &normals);
+        apply_flexure(&config.flexure, &mut heights, &normals, |tile_index| {
+            particle_sphere.tiles[tile_index].adjacent.as_slice()
+        });
+        (
+            heights.into_iter().map(f32::to_bits).collect(),
+            containing_plates(&tectonics, &normals),
+        )
+    })
+}
+
+#[test]
+fn same_seed_is_bit_identical() {
+    assert!(generate(2) == generate(2), "Repeated runs differ");
+}
+
+#[test]
+fn thread_count_does_not_change_planet() {
+    let single = generate(1);
+    for threads in [2, 3, 8] {
+        assert!(
+            generate(threads) == single,
+            "Planet generated with {threads} threads differs from single threaded"
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use glam::Vec3;
+use subsphere::{Face, Sphere};
+use suz_sim::export::{Pole, Projection, TileLayer, project_layer};
+
+fn tile_at(subsphere: &subsphere::HexSphere<subsphere::proj::Fuller>, at: Vec3) -> usize {
+    let at: [f32; 3] = at.into();
+    subsphere.face_at(at.map(f64::from)).index()
+}
+
+#[test]
+fn projections_sample_the_tile_under_each_pixel() {
+    let subsphere = hex_sphere::subsphere(16);
+    let tiles: Vec<usize> = (0..subsphere.num_faces()).collect();
+    let layer = TileLayer {
+        subsphere: &subsphere,
+        values: &tiles,
+    };
+
+    let cube_map = project_layer(&layer, Projection::CubeMap, 9);
+    assert_eq!((cube_map.width, cube_map.height), (54, 9));
+    assert!(cube_map.pixels.iter().all(Option::is_some));
+    // Center pixels of the +X and -Z faces
+    assert_eq!(
+        cube_map.pixels[4 * 54 + 4],
+        Some(tile_at(&subsphere, Vec3::X))
+    );
+    assert_eq!(
+        cube_map.pixels[4 * 54 + 5 * 9 + 4],
+        Some(tile_at(&subsphere, Vec3::NEG_Z))
+    );
+
+    for (pole, normal) in [(Pole::North, Vec3::Y), (Pole::South, Vec3::NEG_Y)] {
+        let azimuthal = project_layer(&layer, Projection::Azimuthal(pole), 9);
+        assert_eq!(azimuthal.pixels.len(), 81);
+        assert_eq!(azimuthal.pixels[0], None);
+        assert_eq!(
+            azimuthal.pixels[4 * 9 + 4],
+            Some(tile_at(&subsphere, normal))
+        );
+    }
+
+    let equirectangular = project_layer(&layer, Projection::Equirectangular, 10);
+    assert_eq!((equirectangular.width, equirectangular.height), (10, 5));
+    assert!(equirectangular.pixels.iter().all(Option::is_some));
+}
@@ -0,0 +1,51 @@
+use suz_sim::{
+    PlanetGenerator,
+    config::{HexSphereConfig, SimulationConfig},
+    detail::DetailConfig,
+    generator::DEFAULT_OCEAN_FRACTION,
+    particle_sphere::ParticleSphereConfig,
+    tectonics::TectonicsConfiguration,
+};
+
+const SEED: u64 = 4809;
+
+/// A small planet with detail noise, which runs last and could otherwise move tiles across the sea level
+fn config() -> SimulationConfig {
+    let default = SimulationConfig::default();
+    SimulationConfig {
+        hex_sphere: HexSphereConfig { subdivisions: 16 },
+        particle_sphere: ParticleSphereConfig::regular(16),
+        tectonics: TectonicsConfiguration {
+            plate_goal: 10,
+            iterations: 50,
+            ..default.tectonics
+        },
+        detail: DetailConfig {
+            amplitude: 0.05,
+            ..DetailConfig::default()
+        },
+        ..default
+    }
+}
+
+#[test]
+fn planet_has_the_configured_ocean_fraction() {
+    for ocean_fraction in [None, Some(0.4)] {
+        let mut generator = PlanetGenerator::new(config(), SEED).expect("Valid config");
+        if let Some(ocean_fraction) = ocean_fraction {
+            generator = generator.ocean_fraction(ocean_fraction);
+        }
+        let planet = generator.generate().expect("Nothing to abort or fail");
+        let ocean_tiles = planet
+            .heights
+            .iter()
+            .filter(|&&height| height < planet.sea_level)
+            .count();
+        let actual = ocean_tiles as f32 / planet.heights.len() as f32;
+        let expected = ocean_fraction.unwrap_or(DEFAULT_OCEAN_FRACTION);
+        assert!(
+            (actual - expected).abs() < 0.02,
+            "{actual} of the tiles are ocean, expected {expected}"
+        );
+    }
+}
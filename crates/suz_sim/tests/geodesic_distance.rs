@@ -0,0 +1,105 @@
+use glam::{Quat, Vec3};
+use suz_sim::{PointMass, vec_utils::geodesic_distance};
+
+/// A unit vector and the unit vector `angle` radians away from it
+fn pair(angle: f32) -> (Vec3, Vec3) {
+    let a = Vec3::new(0.3, 0.5, 0.8).normalize();
+    let b = Quat::from_axis_angle(a.any_orthonormal_vector(), angle) * a;
+    (a, b)
+}
+
+/// Relative error allowed on `angle`, plus the rounding of building the pair in `f32` and of angles near pi
+fn tolerance(angle: f32) -> f32 {
+    angle * 1e-3 + 4e-7
+}
+
+fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+    assert!(
+        actual.is_finite() && (actual - expected).abs() <= tolerance,
+        "{actual} is not within {tolerance} of {expected}"
+    );
+}
+
+#[test]
+fn nearly_parallel_vectors_keep_their_angle() {
+    for angle in [1e-6, 1e-5, 1e-4, 1e-3] {
+        let (a, b) = pair(angle);
+        assert_close(geodesic_distance(a, b), angle, tolerance(angle));
+        assert_close(
+            PointMass::new(a, 1.).geodesic_distance(&PointMass::new(b, 1.)),
+            angle,
+            tolerance(angle),
+        );
+    }
+}
+
+#[test]
+fn identical_vectors_are_zero_apart() {
+    let (a, _) = pair(0.);
+    assert_eq!(geodesic_distance(a, a), 0.);
+}
+
+#[test]
+fn antipodal_vectors_are_pi_apart() {
+    let (a, _) = pair(0.);
+    assert_close(geodesic_distance(a, -a), std::f32::consts::PI, 1e-6);
+    for angle in [1e-5, 1e-3] {
+        let (a, b) = pair(angle);
+        assert_close(
+            geodesic_distance(a, -b),
+            std::f32::consts::PI - angle,
+            tolerance(angle),
+        );
+    }
+}
+
+#[test]
+fn unnormalized_vectors_give_no_nan() {
+    // Positions drift slightly off the unit sphere between normalizations, pushing the dot product past 1
+    let a = Vec3::new(0., 0., 1.0001);
+    assert_eq!(geodesic_distance(a, a), 0.);
+    assert_close(geodesic_distance(a, -a), std::f32::consts::PI, 1e-6);
+}
+
+/// A shape of two point masses joined by one spring
+fn spring_shape(a: Vec3, b: Vec3) -> soft_sphere::Shape {
+    let mut shape = soft_sphere::ShapeBuilder::new();
+    let anchor_a = shape.point_mass(PointMass::new(a, 1.));
+    let anchor_b = shape.point_mass(PointMass::new(b, 1.));
+    shape
+        .spring(soft_sphere::Spring {
+            anchor_a,
+            anchor_b,
+            rest_length: 0.01,
+            spring_constant: 1.,
+            damping_coefficient: 0.5,
+        })
+        .expect("Two distinct point masses");
+    shape.build()
+}
+
+#[test]
+fn coincident_and_antipodal_springs_are_skipped_and_counted() {
+    let (a, _) = pair(0.);
+    for b in [a, -a] {
+        let mut shape = spring_shape(a, b);
+        shape.apply_spring_forces();
+        shape.apply_spring_forces();
+        assert_eq!(shape.degenerate_springs(), 2);
+        for point_mass in shape.point_masses() {
+            assert_eq!(point_mass.force, Vec3::ZERO);
+        }
+    }
+}
+
+#[test]
+fn spring_forces_stay_finite_for_nearly_coincident_and_antipodal_point_masses() {
+    for (a, b) in [pair(1e-6), pair(std::f32::consts::PI - 1e-4)] {
+        let mut shape = spring_shape(a, b);
+        shape.apply_spring_forces();
+        assert_eq!(shape.degenerate_springs(), 0);
+        for point_mass in shape.point_masses() {
+            assert!(point_mass.force.is_finite(), "{:?}", point_mass.force);
+        }
+    }
+}
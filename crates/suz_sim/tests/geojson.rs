@@ -0,0 +1,62 @@
+use std::f32::consts::TAU;
+
+use glam::Vec3;
+use suz_sim::{
+    geojson::{region_polygons, ring_positions},
+    vec_utils::lat_long_to_vec3,
+};
+
+/// Loop around latitude and longitude zero with the enclosed square on its left seen from outside, `size` in radians
+fn square(size: f32) -> Vec<Vec3> {
+    // Longitude grows to the west, so going east first is going towards negative longitude
+    [(-1., -1.), (-1., 1.), (1., 1.), (1., -1.)]
+        .iter()
+        .map(|&(longitude, latitude)| lat_long_to_vec3(latitude * size, longitude * size))
+        .collect()
+}
+
+fn signed_area(ring: &[[f32; 2]]) -> f32 {
+    ring.windows(2)
+        .map(|pair| pair[0][0] * pair[1][1] - pair[1][0] * pair[0][1])
+        .sum::<f32>()
+        / 2.
+}
+
+#[test]
+fn rings_are_closed_and_counterclockwise() {
+    let ring = ring_positions(&square(0.1));
+    assert_eq!(ring.len(), 5);
+    assert_eq!(ring.first(), ring.last());
+    assert!(signed_area(&ring) > 0.);
+}
+
+#[test]
+fn rings_around_a_pole_are_closed_over_it() {
+    // Counterclockwise seen from above the north pole
+    let circle: Vec<Vec3> = (0..32)
+        .map(|i| lat_long_to_vec3(80f32.to_radians(), -(i as f32) / 32. * TAU))
+        .collect();
+    let ring = ring_positions(&circle);
+    assert_eq!(ring.first(), ring.last());
+    assert!(ring.iter().any(|&[_, latitude]| latitude == 90.));
+    assert!(signed_area(&ring) > 0.);
+}
+
+#[test]
+fn holes_join_the_polygon_around_them() {
+    let mut hole = square(0.05);
+    hole.reverse();
+    let polygons = region_polygons(&[square(0.2), hole]);
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(polygons[0].len(), 2);
+}
+
+#[test]
+fn holes_without_an_exterior_are_cut_from_the_whole_map() {
+    let mut hole = square(0.05);
+    hole.reverse();
+    let polygons = region_polygons(&[hole]);
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(polygons[0].len(), 2);
+    assert!(signed_area(&polygons[0][0]) > 0.);
+}
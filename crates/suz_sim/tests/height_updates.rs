@@ -0,0 +1,53 @@
+use suz_sim::{
+    interpolation::{HeightUpdate, height_updates},
+    vertex_interpolation::{apply_height_updates, move_tile_vertices},
+};
+
+const SUBDIVISIONS: u32 = 8;
+
+#[test]
+fn unchanged_heights_give_no_updates() {
+    let hex_sphere = hex_sphere::HexSphere::new(SUBDIVISIONS, None);
+    let heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+    assert!(height_updates(&hex_sphere.tiles, &heights, 0.).is_empty());
+}
+
+#[test]
+fn updates_skip_changes_within_epsilon() {
+    let hex_sphere = hex_sphere::HexSphere::new(SUBDIVISIONS, None);
+    let mut heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+    heights[3] += 0.5;
+    heights[7] += 1e-6;
+    assert_eq!(
+        height_updates(&hex_sphere.tiles, &heights, 1e-4),
+        vec![HeightUpdate {
+            tile: 3,
+            height: heights[3],
+        }]
+    );
+}
+
+#[test]
+fn applied_updates_match_a_mesh_built_at_those_heights() {
+    let mut hex_sphere = hex_sphere::HexSphere::new(SUBDIVISIONS, None);
+    let heights: Vec<f32> = hex_sphere
+        .tiles
+        .iter()
+        .map(|tile| 1. + 0.01 * (tile.index % 5) as f32)
+        .collect();
+    let updates = height_updates(&hex_sphere.tiles, &heights, 0.);
+    let moved = apply_height_updates(&mut hex_sphere.tiles, &updates);
+    move_tile_vertices(
+        &hex_sphere.tiles,
+        &mut hex_sphere.vertices,
+        &hex_sphere.vertices_to_tiles,
+        &moved,
+    );
+    assert!(height_updates(&hex_sphere.tiles, &heights, 0.).is_empty());
+    let expected = hex_sphere::HexSphere::new(SUBDIVISIONS, Some(&heights));
+    for (vertex, expected) in hex_sphere.vertices.iter().zip(&expected.vertices) {
+        for (a, b) in vertex.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-5, "{vertex:?} != {expected:?}");
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use glam::Vec3;
+use rand::SeedableRng;
+use suz_sim::{
+    config::SimulationConfig,
+    particle_sphere::{ParticleSphere, ParticleSphereConfig},
+    plate::PlateType,
+    plate_import::{assign_tiles, plates_from_geojson},
+    tectonics::Tectonics,
+};
+
+/// A continent around latitude and longitude zero, wound clockwise, in an ocean plate covering the map
+const PLATES: &str = r#"{
+    "type": "FeatureCollection",
+    "features": [
+        {
+            "type": "Feature",
+            "properties": { "PlateName": "Island" },
+            "geometry": {
+                "type": "Polygon",
+                "coordinates": [[[-10, -10], [-10, 10], [10, 10], [10, -10], [-10, -10]]]
+            }
+        },
+        {
+            "type": "Feature",
+            "properties": { "name": "Ocean", "plate_type": "Oceanic" },
+            "geometry": {
+                "type": "MultiPolygon",
+                "coordinates": [[[[-180, -90], [180, -90], [180, 90], [-180, 90], [-180, -90]]]]
+            }
+        },
+        { "type": "Feature", "properties": null, "geometry": { "type": "Point", "coordinates": [0, 0] } }
+    ]
+}"#;
+
+#[test]
+fn tiles_take_the_first_plate_around_them() {
+    let plates = plates_from_geojson(PLATES, &["Island".to_string()]).unwrap();
+    assert_eq!(plates.len(), 2);
+    assert_eq!(plates[0].name, "Island");
+    assert!(plates[0].plate_type == PlateType::Continental);
+    assert!(plates[1].plate_type == PlateType::Oceanic);
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let particle_sphere = ParticleSphere::from_config(ParticleSphereConfig::regular(8), &mut rng);
+    let normals: Vec<Vec3> = particle_sphere
+        .tiles
+        .iter()
+        .map(|tile| tile.normal)
+        .collect();
+    let tile_plates = assign_tiles(&plates, &normals, |tile_index| {
+        particle_sphere.tiles[tile_index].adjacent.as_slice()
+    })
+    .unwrap();
+    for (normal, &plate) in normals.iter().zip(&tile_plates) {
+        let angle = normal.angle_between(Vec3::X).to_degrees();
+        if angle < 5. {
+            assert_eq!(plate, 0);
+        } else if angle > 20. {
+            assert_eq!(plate, 1);
+        }
+    }
+
+    let plate_types: Vec<PlateType> = plates.iter().map(|plate| plate.plate_type).collect();
+    let tectonics = Tectonics::from_tile_plates(
+        SimulationConfig::default().tectonics,
+        &particle_sphere,
+        &tile_plates,
+        &plate_types,
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(tectonics.plates.len(), 2);
+    assert!(tectonics.plates[0].plate_type == PlateType::Continental);
+}
+
+#[test]
+fn files_without_polygons_are_rejected() {
+    let points = r#"{"type":"FeatureCollection","features":[]}"#;
+    assert!(plates_from_geojson(points, &[]).is_err());
+    assert!(plates_from_geojson("{\"type\":", &[]).is_err());
+}
@@ -0,0 +1,126 @@
+use glam::{Vec2, Vec3};
+use soft_sphere::{PointMass, ShapeBuilder};
+use suz_sim::{
+    plate::{Plate, PlateType},
+    plate_motion::{
+        DrivingForceConfig, TorqueConfig, apply_torques, boundary_torques, driving_forces,
+    },
+    sphere_tree::SphereTree,
+};
+
+fn plate(plate_type: PlateType, position: Vec3, velocity: Vec3) -> Plate {
+    let mut builder = ShapeBuilder::new();
+    builder.point_mass(PointMass {
+        velocity,
+        ..PointMass::new(position, 1.)
+    });
+    Plate {
+        plate_type,
+        color: [1.; 4],
+        axis_of_rotation: position.cross(velocity),
+        drift_direction: Vec2::X,
+        shape: builder.build(),
+        crust_thickness: vec![0.],
+    }
+}
+
+/// Two plates with a single point mass each, on either side of the equator at longitude 0 and moving towards each other
+fn converging(type_a: PlateType, type_b: PlateType) -> Vec<Plate> {
+    vec![
+        plate(type_a, Vec3::new(1., 0., -0.01).normalize(), Vec3::Z * 0.1),
+        plate(
+            type_b,
+            Vec3::new(1., 0., 0.01).normalize(),
+            Vec3::NEG_Z * 0.1,
+        ),
+    ]
+}
+
+const CONTACT: [[(usize, usize); 2]; 1] = [[(0, 0), (1, 0)]];
+
+#[test]
+fn collisions_slow_both_plates() {
+    let plates = converging(PlateType::Continental, PlateType::Continental);
+    let config = TorqueConfig {
+        slab_pull: 0.,
+        ..TorqueConfig::default()
+    };
+    let torques = boundary_torques(&plates, &CONTACT, &config);
+    for (plate, torque) in plates.iter().zip(torques) {
+        assert!(torque.dot(plate.axis_of_rotation) < 0.);
+    }
+}
+
+#[test]
+fn slab_pull_accelerates_only_the_subducting_plate() {
+    let plates = converging(PlateType::Oceanic, PlateType::Continental);
+    let config = TorqueConfig {
+        collision_resistance: 0.,
+        ..TorqueConfig::default()
+    };
+    let torques = boundary_torques(&plates, &CONTACT, &config);
+    assert!(torques[0].dot(plates[0].axis_of_rotation) > 0.);
+    assert_eq!(torques[1], Vec3::ZERO);
+}
+
+#[test]
+fn diverging_plates_feel_no_torque() {
+    let plates = vec![
+        plate(
+            PlateType::Oceanic,
+            Vec3::new(1., 0., -0.01).normalize(),
+            Vec3::NEG_Z * 0.1,
+        ),
+        plate(
+            PlateType::Continental,
+            Vec3::new(1., 0., 0.01).normalize(),
+            Vec3::Z * 0.1,
+        ),
+    ];
+    let torques = boundary_torques(&plates, &CONTACT, &TorqueConfig::default());
+    assert!(torques.iter().all(|torque| *torque == Vec3::ZERO));
+}
+
+#[test]
+fn spin_is_capped() {
+    let mut plates = converging(PlateType::Oceanic, PlateType::Oceanic);
+    let config = TorqueConfig::default();
+    apply_torques(&mut plates, &[Vec3::Y * 1e6; 2], 1., &config);
+    for plate in &plates {
+        assert!(plate.axis_of_rotation.length() <= config.max_spin * (1. + 1e-6));
+    }
+}
+
+#[test]
+fn slab_pull_drags_the_subducting_point_mass_towards_the_trench() {
+    let plates = converging(PlateType::Oceanic, PlateType::Continental);
+    let tree = SphereTree::new(&plates);
+    let forces = driving_forces(&plates, &CONTACT, &tree, &DrivingForceConfig::default());
+    // The subducting plate sits south of the continental one
+    assert!(forces[0][0].z > 0.);
+    assert_eq!(forces[1][0], Vec3::ZERO);
+}
+
+#[test]
+fn ridge_push_separates_diverging_point_masses() {
+    let plates = vec![
+        plate(
+            PlateType::Oceanic,
+            Vec3::new(1., 0., -0.01).normalize(),
+            Vec3::NEG_Z * 0.1,
+        ),
+        plate(
+            PlateType::Oceanic,
+            Vec3::new(1., 0., 0.01).normalize(),
+            Vec3::Z * 0.1,
+        ),
+    ];
+    let tree = SphereTree::new(&plates);
+    let config = DrivingForceConfig::default();
+    let forces = driving_forces(&plates, &CONTACT, &tree, &config);
+    assert!(forces[0][0].z < 0.);
+    assert!(forces[1][0].z > 0.);
+    for force in forces.iter().flatten() {
+        assert!(force.length() <= config.ridge_push * (1. + 1e-6));
+    }
+}
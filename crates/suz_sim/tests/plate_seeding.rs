@@ -0,0 +1,91 @@
+use glam::Vec3;
+use rand::SeedableRng;
+use suz_sim::{
+    config::{ConfigError, SimulationConfig},
+    particle_sphere::{ParticleSphere, ParticleSphereConfig},
+    tectonics::{Tectonics, TectonicsConfiguration},
+};
+
+fn seed_plates(config: TectonicsConfiguration) -> Result<Tectonics, ConfigError> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let particle_sphere = ParticleSphere::from_config(ParticleSphereConfig::regular(4), &mut rng);
+    Tectonics::from_config(config, &particle_sphere, &mut rng)
+}
+
+#[test]
+fn boundary_fractions_are_rejected() {
+    let default = SimulationConfig::default().tectonics;
+    for fraction in [0., 1.] {
+        for config in [
+            TectonicsConfiguration {
+                major_plate_fraction: fraction,
+                ..default
+            },
+            TectonicsConfiguration {
+                major_tile_fraction: fraction,
+                ..default
+            },
+        ] {
+            assert!(matches!(
+                seed_plates(config),
+                Err(ConfigError::Invalid { .. })
+            ));
+        }
+    }
+}
+
+#[test]
+fn boundary_continental_rates_seed_plates() {
+    let default = SimulationConfig::default().tectonics;
+    for continental_rate in [0., 1.] {
+        let tectonics = seed_plates(TectonicsConfiguration {
+            continental_rate,
+            ..default
+        })
+        .expect("Continental rates of 0 and 1 are valid");
+        assert!(!tectonics.plates.is_empty());
+    }
+}
+
+#[test]
+fn plate_goal_larger_than_sphere_seeds_plates() {
+    let tectonics = seed_plates(TectonicsConfiguration {
+        plate_goal: 100_000,
+        min_plate_size: 1,
+        ..SimulationConfig::default().tectonics
+    })
+    .expect("A large plate goal is valid");
+    assert!(!tectonics.plates.is_empty());
+}
+
+#[test]
+fn seed_separation_spreads_the_first_plates_apart() {
+    let min_seed_separation = 0.6;
+    let tectonics = seed_plates(TectonicsConfiguration {
+        min_plate_size: 1,
+        min_seed_separation,
+        ..SimulationConfig::default().tectonics
+    })
+    .expect("A seed separation is valid");
+    // Plates grow outwards from their starting tile, which stays their first point mass
+    let seeds: Vec<Vec3> = tectonics.plates[..4]
+        .iter()
+        .map(|plate| plate.shape.point_masses()[0].position)
+        .collect();
+    for (i, a) in seeds.iter().enumerate() {
+        for b in &seeds[i + 1..] {
+            assert!(a.angle_between(*b) >= min_seed_separation);
+        }
+    }
+}
+
+#[test]
+fn negative_seed_separation_is_rejected() {
+    assert!(matches!(
+        seed_plates(TectonicsConfiguration {
+            min_seed_separation: -1.,
+            ..SimulationConfig::default().tectonics
+        }),
+        Err(ConfigError::Invalid { .. })
+    ));
+}
@@ -0,0 +1,351 @@
+use std::{collections::BTreeSet, f32::consts::PI};
+
+use glam::{Vec2, Vec3};
+use proptest::prelude::*;
+use soft_sphere::{PointMass, ShapeBuilder};
+use suz_sim::{
+    plate::{Plate, PlateType},
+    sphere_tree::{PointMassHandle, SphereTree},
+    spherical_grid::SphericalGrid,
+    vec_utils,
+};
+
+/// The queries both point mass indices answer, so every property is checked for each
+trait PointMassIndex: Sized {
+    fn new(plates: &[Plate]) -> Self;
+    fn refresh(&mut self, plates: &[Plate]);
+    fn position(&self, handle: PointMassHandle) -> Vec3;
+    fn query_within(&self, normal: Vec3, radius: f32) -> Vec<(PointMassHandle, f32)>;
+}
+
+impl PointMassIndex for SphericalGrid {
+    fn new(plates: &[Plate]) -> Self {
+        SphericalGrid::new(plates)
+    }
+    fn refresh(&mut self, plates: &[Plate]) {
+        SphericalGrid::refresh(self, plates)
+    }
+    fn position(&self, handle: PointMassHandle) -> Vec3 {
+        SphericalGrid::position(self, handle)
+    }
+    fn query_within(&self, normal: Vec3, radius: f32) -> Vec<(PointMassHandle, f32)> {
+        SphericalGrid::query_within(self, normal, radius)
+    }
+}
+
+/// [SphereTree::refresh_incremental] in place of [SphereTree::refresh]
+struct IncrementalSphereTree(SphereTree);
+
+impl PointMassIndex for IncrementalSphereTree {
+    fn new(plates: &[Plate]) -> Self {
+        IncrementalSphereTree(SphereTree::new(plates))
+    }
+    fn refresh(&mut self, plates: &[Plate]) {
+        self.0.refresh_incremental(plates)
+    }
+    fn position(&self, handle: PointMassHandle) -> Vec3 {
+        self.0.position(handle)
+    }
+    fn query_within(&self, normal: Vec3, radius: f32) -> Vec<(PointMassHandle, f32)> {
+        self.0.query_within(normal, radius)
+    }
+}
+
+impl PointMassIndex for SphereTree {
+    fn new(plates: &[Plate]) -> Self {
+        SphereTree::new(plates)
+    }
+    fn refresh(&mut self, plates: &[Plate]) {
+        SphereTree::refresh(self, plates)
+    }
+    fn position(&self, handle: PointMassHandle) -> Vec3 {
+        SphereTree::position(self, handle)
+    }
+    fn query_within(&self, normal: Vec3, radius: f32) -> Vec<(PointMassHandle, f32)> {
+        SphereTree::query_within(self, normal, radius)
+    }
+}
+
+fn unit_vector() -> impl Strategy<Value = Vec3> {
+    (-1f32..1., -1f32..1., -1f32..1.)
+        .prop_map(|(x, y, z)| Vec3::new(x, y, z))
+        .prop_filter("Too short to normalize", |vector| vector.length() > 0.1)
+        .prop_map(Vec3::normalize)
+}
+
+/// Caps around the poles search every column, so they are picked more often than chance would
+fn query_normal() -> impl Strategy<Value = Vec3> {
+    prop_oneof![
+        8 => unit_vector(),
+        1 => Just(Vec3::Y),
+        1 => Just(Vec3::NEG_Y),
+    ]
+}
+
+fn queries() -> impl Strategy<Value = Vec<(Vec3, f32)>> {
+    prop::collection::vec((query_normal(), 0f32..PI), 1..8)
+}
+
+/// Positions of the point masses of up to four plates
+fn plate_positions() -> impl Strategy<Value = Vec<Vec<Vec3>>> {
+    prop::collection::vec(prop::collection::vec(unit_vector(), 0..64), 1..5)
+}
+
+fn plates(positions: &[Vec<Vec3>]) -> Vec<Plate> {
+    positions
+        .iter()
+        .map(|positions| {
+            let mut builder = ShapeBuilder::new();
+            for &position in positions {
+                builder.point_mass(PointMass::new(position, 1.));
+            }
+            Plate {
+                plate_type: PlateType::Oceanic,
+                color: [1.; 4],
+                axis_of_rotation: Vec3::Y,
+                drift_direction: Vec2::X,
+                shape: builder.build(),
+                crust_thickness: vec![0.; positions.len()],
+            }
+        })
+        .collect()
+}
+
+fn brute_force_within(plates: &[Plate], normal: Vec3, radius: f32) -> BTreeSet<PointMassHandle> {
+    let mut within = BTreeSet::new();
+    for (plate_index, plate) in plates.iter().enumerate() {
+        for (point_mass_index, point_mass) in plate.shape.point_masses().iter().enumerate() {
+            if vec_utils::geodesic_distance(normal, point_mass.position) <= radius {
+                within.insert(PointMassHandle {
+                    plate: plate_index,
+                    point_mass: point_mass_index,
+                });
+            }
+        }
+    }
+    within
+}
+
+/// Fails if a point mass is found twice, since a set would hide it
+fn index_within(
+    index: &impl PointMassIndex,
+    normal: Vec3,
+    radius: f32,
+) -> BTreeSet<PointMassHandle> {
+    let found = index.query_within(normal, radius);
+    let within: BTreeSet<PointMassHandle> = found.iter().map(|&(handle, _)| handle).collect();
+    assert_eq!(within.len(), found.len(), "A point mass was found twice");
+    within
+}
+
+fn check_query_within<I: PointMassIndex>(
+    positions: &[Vec<Vec3>],
+    queries: &[(Vec3, f32)],
+) -> Result<(), TestCaseError> {
+    let plates = plates(positions);
+    let index = I::new(&plates);
+    for &(normal, radius) in queries {
+        prop_assert_eq!(
+            index_within(&index, normal, radius),
+            brute_force_within(&plates, normal, radius),
+            "Query around {} with radius {}",
+            normal,
+            radius
+        );
+        for (handle, distance) in index.query_within(normal, radius) {
+            let position = plates[handle.plate].shape.point_masses()[handle.point_mass].position;
+            prop_assert_eq!(distance, vec_utils::geodesic_distance(normal, position));
+        }
+    }
+    Ok(())
+}
+
+/// Point masses are moved from the first to the second position of each pair
+fn check_refresh_after_moving<I: PointMassIndex>(
+    moves: &[Vec<(Vec3, Vec3)>],
+    queries: &[(Vec3, f32)],
+) -> Result<(), TestCaseError> {
+    let before: Vec<Vec<Vec3>> = moves
+        .iter()
+        .map(|plate| plate.iter().map(|&(before, _)| before).collect())
+        .collect();
+    let mut plates = plates(&before);
+    let mut index = I::new(&plates);
+    for (plate, moves) in plates.iter_mut().zip(moves) {
+        for (point_mass, &(_, after)) in plate.shape.point_masses_mut().iter_mut().zip(moves) {
+            point_mass.position = after;
+        }
+    }
+    index.refresh(&plates);
+    let rebuilt = I::new(&plates);
+    for (plate_index, plate) in plates.iter().enumerate() {
+        for point_mass_index in 0..plate.shape.point_masses().len() {
+            let handle = PointMassHandle {
+                plate: plate_index,
+                point_mass: point_mass_index,
+            };
+            prop_assert_eq!(index.position(handle), rebuilt.position(handle));
+        }
+    }
+    for &(normal, radius) in queries {
+        prop_assert_eq!(
+            index_within(&index, normal, radius),
+            index_within(&rebuilt, normal, radius)
+        );
+    }
+    Ok(())
+}
+
+fn check_refresh_after_resizing<I: PointMassIndex>(
+    before: &[Vec<Vec3>],
+    after: &[Vec<Vec3>],
+    queries: &[(Vec3, f32)],
+) -> Result<(), TestCaseError> {
+    let mut index = I::new(&plates(before));
+    let plates = plates(after);
+    index.refresh(&plates);
+    let rebuilt = I::new(&plates);
+    for &(normal, radius) in queries {
+        prop_assert_eq!(
+            index_within(&index, normal, radius),
+            index_within(&rebuilt, normal, radius)
+        );
+    }
+    Ok(())
+}
+
+fn point_mass_moves() -> impl Strategy<Value = Vec<Vec<(Vec3, Vec3)>>> {
+    prop::collection::vec(
+        prop::collection::vec((unit_vector(), unit_vector()), 0..64),
+        1..5,
+    )
+}
+
+proptest! {
+    #[test]
+    fn grid_query_within_matches_brute_force(positions in plate_positions(), queries in queries()) {
+        check_query_within::<SphericalGrid>(&positions, &queries)?;
+    }
+
+    #[test]
+    fn tree_query_within_matches_brute_force(positions in plate_positions(), queries in queries()) {
+        check_query_within::<SphereTree>(&positions, &queries)?;
+    }
+
+    #[test]
+    fn tree_query_within_mut_matches_query_within(positions in plate_positions(), queries in queries()) {
+        let mut plates = plates(&positions);
+        let tree = SphereTree::new(&plates);
+        for (normal, radius) in queries {
+            let mut expected = tree.query_within(normal, radius);
+            expected.sort_by_key(|(handle, _)| *handle);
+            let found: Vec<(PointMassHandle, f32)> = tree
+                .query_within_mut(&mut plates, normal, radius)
+                .into_iter()
+                .map(|(handle, distance, point_mass)| {
+                    point_mass.force += Vec3::X;
+                    (handle, distance)
+                })
+                .collect();
+            prop_assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn tree_insert_and_remove_match_new(
+        positions in plate_positions(),
+        edits in prop::collection::vec((any::<bool>(), any::<prop::sample::Index>(), any::<prop::sample::Index>(), unit_vector()), 0..32),
+        queries in queries(),
+    ) {
+        let mut plates = plates(&positions);
+        let mut tree = SphereTree::new(&plates);
+        for (insert, plate, point_mass, position) in edits {
+            let plate_index = plate.index(plates.len());
+            let shape = &mut plates[plate_index].shape;
+            if insert || shape.point_masses().is_empty() {
+                let mut builder = ShapeBuilder::new();
+                builder.point_mass(PointMass::new(position, 1.));
+                let point_mass = shape.merge(builder.build());
+                tree.insert(PointMassHandle { plate: plate_index, point_mass }, position);
+            } else {
+                let point_mass = point_mass.index(shape.point_masses().len());
+                let removed = shape.remove_point_mass(point_mass);
+                let moved_from = tree.remove(PointMassHandle { plate: plate_index, point_mass });
+                prop_assert_eq!(moved_from.map(|handle| handle.point_mass), removed.moved_from);
+            }
+        }
+        let rebuilt = SphereTree::new(&plates);
+        for (normal, radius) in queries {
+            prop_assert_eq!(index_within(&tree, normal, radius), index_within(&rebuilt, normal, radius));
+        }
+    }
+
+    #[test]
+    fn grid_refresh_after_moving_matches_new(moves in point_mass_moves(), queries in queries()) {
+        check_refresh_after_moving::<SphericalGrid>(&moves, &queries)?;
+    }
+
+    #[test]
+    fn tree_refresh_after_moving_matches_new(moves in point_mass_moves(), queries in queries()) {
+        check_refresh_after_moving::<SphereTree>(&moves, &queries)?;
+    }
+
+    #[test]
+    fn incremental_tree_refresh_after_moving_matches_new(moves in point_mass_moves(), queries in queries()) {
+        check_refresh_after_moving::<IncrementalSphereTree>(&moves, &queries)?;
+    }
+
+    #[test]
+    fn grid_refresh_after_resizing_matches_new(
+        before in plate_positions(),
+        after in plate_positions(),
+        queries in queries(),
+    ) {
+        check_refresh_after_resizing::<SphericalGrid>(&before, &after, &queries)?;
+    }
+
+    #[test]
+    fn tree_refresh_after_resizing_matches_new(
+        before in plate_positions(),
+        after in plate_positions(),
+        queries in queries(),
+    ) {
+        check_refresh_after_resizing::<SphereTree>(&before, &after, &queries)?;
+    }
+}
+
+#[test]
+fn tree_remove_reports_the_moved_handle() {
+    let positions = [Vec3::X, Vec3::Y, Vec3::Z, Vec3::NEG_X];
+    let mut plates = plates(&[positions.to_vec()]);
+    let mut tree = SphereTree::new(&plates);
+    let removed = PointMassHandle {
+        plate: 0,
+        point_mass: 1,
+    };
+    plates[0].shape.remove_point_mass(removed.point_mass);
+    let moved_from = tree.remove(removed);
+
+    // The last point mass took the place of the removed one, its old handle is out of bounds now
+    assert_eq!(
+        moved_from,
+        Some(PointMassHandle {
+            plate: 0,
+            point_mass: 3,
+        })
+    );
+    assert_eq!(tree.position(removed), Vec3::NEG_X);
+    assert_eq!(tree.nearest(Vec3::NEG_X), Some(removed));
+    assert_eq!(
+        plates[0].shape.point_masses()[removed.point_mass].position,
+        Vec3::NEG_X
+    );
+    assert!(tree.query_within(Vec3::Y, 0.1).is_empty());
+
+    let last = PointMassHandle {
+        plate: 0,
+        point_mass: 2,
+    };
+    assert_eq!(tree.remove(last), None);
+    assert!(tree.query_within(Vec3::Z, 0.1).is_empty());
+}
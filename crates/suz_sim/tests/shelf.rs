@@ -0,0 +1,48 @@
+use glam::Vec3;
+use suz_sim::{
+    shelf::{ShelfConfig, apply_shelf},
+    tectonics::{CONTINENTAL_HEIGHT, OCEANIC_HEIGHT, SEA_LEVEL},
+    vec_utils,
+};
+
+const ABYSSAL_HEIGHT: f32 = 0.95;
+
+#[test]
+fn shelf_raises_the_sea_floor_next_to_coasts() {
+    let hex_sphere = hex_sphere::HexSphere::new(64, None);
+    let normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+    // Land north of the equator, ocean floor at abyssal depth south of it
+    let mut heights: Vec<f32> = normals
+        .iter()
+        .map(|normal| {
+            if normal.y >= 0. {
+                CONTINENTAL_HEIGHT
+            } else {
+                ABYSSAL_HEIGHT
+            }
+        })
+        .collect();
+    let config = ShelfConfig::default();
+    apply_shelf(&config, &mut heights, &normals, |tile_index| {
+        hex_sphere.tiles[tile_index].adjacent.as_slice()
+    });
+
+    let mut ocean: Vec<(f32, f32)> = normals
+        .iter()
+        .zip(&heights)
+        .filter(|(normal, _)| normal.y < 0.)
+        .map(|(normal, &height)| {
+            let latitude = vec_utils::vec3_to_lat_long(*normal).0;
+            // Distance south of the coast
+            (-latitude, height)
+        })
+        .collect();
+    ocean.sort_by(|a, b| a.0.total_cmp(&b.0));
+    for &(_, height) in &ocean {
+        assert!(height < SEA_LEVEL);
+        assert!(height >= ABYSSAL_HEIGHT);
+    }
+    // The shelf is above the old oceanic height close to the coast and gone past the slope
+    assert!(ocean.first().unwrap().1 > OCEANIC_HEIGHT);
+    assert_eq!(ocean.last().unwrap().1, ABYSSAL_HEIGHT);
+}
@@ -0,0 +1,39 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+struct AtmosphereSettings {
+    color: vec4<f32>,
+    planet_radius: f32,
+    shell_radius: f32,
+    density: f32,
+    falloff: f32,
+}
+
+@group(2) @binding(0) var<uniform> atmosphere: AtmosphereSettings;
+
+// Glow from the length of the view ray inside the shell, the planet is centered on the origin
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let position = in.world_position.xyz;
+    var ray = normalize(position - view.world_position);
+    // Orthographic views look along their -Z axis from every pixel
+    if view.clip_from_view[3].w == 1.0 {
+        ray = -normalize(view.world_from_view[2].xyz);
+    }
+    let closest = cross(position, ray);
+    let closest_squared = dot(closest, closest);
+    let shell_squared = atmosphere.shell_radius * atmosphere.shell_radius;
+    let planet_squared = atmosphere.planet_radius * atmosphere.planet_radius;
+    let outer = sqrt(max(shell_squared - closest_squared, 0.0));
+    // Rays over the planet stop at its surface, rays past its limb cross the whole shell
+    var path = 2.0 * outer;
+    if closest_squared < planet_squared {
+        path = outer - sqrt(planet_squared - closest_squared);
+    }
+    // Relative to the path from the surface to the shell along a ray grazing the surface
+    let depth = path / sqrt(max(shell_squared - planet_squared, 1e-6));
+    let glow = 1.0 - exp(-atmosphere.density * pow(depth, atmosphere.falloff));
+    return vec4<f32>(atmosphere.color.rgb * glow * atmosphere.color.a, glow);
+}
@@ -0,0 +1,144 @@
+use bevy::{
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef, ShaderType},
+};
+
+use crate::{coastlines::Coastlines, textured::equirectangular_sphere};
+
+/// Segments of the atmosphere shell around the equator
+const SHELL_SEGMENTS: u32 = 128;
+
+/// Draws a glowing shell around the planet approximating atmospheric scattering, so screenshots look like a planet seen from space.
+/// U toggles it, it starts hidden.
+pub struct AtmospherePlugin {
+    pub config: AtmosphereConfig,
+}
+impl Plugin for AtmospherePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MaterialPlugin::<AtmosphereMaterial>::default())
+            .insert_resource(self.config)
+            .add_systems(Startup, spawn_atmosphere)
+            .add_systems(
+                Update,
+                (
+                    toggle_atmosphere,
+                    sync_atmosphere.run_if(
+                        resource_changed::<AtmosphereConfig>.or(resource_changed::<Coastlines>),
+                    ),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct AtmosphereConfig {
+    pub visible: bool,
+    /// Color of the scattered light, its alpha scales the glow
+    pub color: LinearRgba,
+    /// Height of the shell as a fraction of the planet radius
+    pub thickness: f32,
+    /// How quickly the glow saturates with the distance the view travels through the shell
+    pub density: f32,
+    /// Exponent on that distance, higher keeps the glow to the limb
+    pub falloff: f32,
+}
+
+#[derive(ShaderType, Clone, Copy)]
+pub struct AtmosphereSettings {
+    pub color: LinearRgba,
+    pub planet_radius: f32,
+    pub shell_radius: f32,
+    pub density: f32,
+    pub falloff: f32,
+}
+
+/// Additive glow from the length of the view ray inside the shell, see `assets/shaders/atmosphere.wgsl`
+#[derive(Asset, TypePath, AsBindGroup, Clone)]
+pub struct AtmosphereMaterial {
+    #[uniform(0)]
+    pub settings: AtmosphereSettings,
+}
+
+impl Material for AtmosphereMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/atmosphere.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Add
+    }
+}
+
+#[derive(Component)]
+struct Atmosphere;
+
+fn settings(config: &AtmosphereConfig, coastlines: &Coastlines) -> AtmosphereSettings {
+    // The sea level is only picked once tectonics is done, tile heights start around 1
+    let planet_radius = if coastlines.sea_level > 0. {
+        coastlines.sea_level
+    } else {
+        1.
+    };
+    AtmosphereSettings {
+        color: config.color,
+        planet_radius,
+        shell_radius: planet_radius * (1. + config.thickness.max(f32::EPSILON)),
+        density: config.density,
+        falloff: config.falloff,
+    }
+}
+
+fn spawn_atmosphere(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<AtmosphereMaterial>>,
+    config: Res<AtmosphereConfig>,
+    coastlines: Res<Coastlines>,
+) {
+    let settings = settings(&config, &coastlines);
+    commands.spawn((
+        Mesh3d(meshes.add(equirectangular_sphere(SHELL_SEGMENTS, 1.))),
+        MeshMaterial3d(materials.add(AtmosphereMaterial { settings })),
+        Transform::from_scale(Vec3::splat(settings.shell_radius)),
+        if config.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        },
+        Atmosphere,
+    ));
+}
+
+fn toggle_atmosphere(keys: Res<ButtonInput<KeyCode>>, mut config: ResMut<AtmosphereConfig>) {
+    if keys.just_pressed(KeyCode::KeyU) {
+        config.visible = !config.visible;
+    }
+}
+
+fn sync_atmosphere(
+    config: Res<AtmosphereConfig>,
+    coastlines: Res<Coastlines>,
+    mut materials: ResMut<Assets<AtmosphereMaterial>>,
+    mut atmosphere: Query<
+        (
+            &MeshMaterial3d<AtmosphereMaterial>,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<Atmosphere>,
+    >,
+) {
+    let settings = settings(&config, &coastlines);
+    for (material, mut transform, mut visibility) in &mut atmosphere {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.settings = settings;
+        }
+        transform.scale = Vec3::splat(settings.shell_radius);
+        *visibility = if config.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
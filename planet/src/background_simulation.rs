@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, block_on, futures_lite::future},
+};
+use rand::rngs::StdRng;
+use suz_sim::{
+    observer::AbortHandle,
+    strain::StrainTracker,
+    tectonics::{Tectonics, TectonicsConfiguration},
+};
+use web_time::Instant;
+
+/// Everything a batch of simulation steps mutates, moved into the background task while it runs
+pub struct SimulationBatch {
+    pub tectonics: Tectonics,
+    pub strain: StrainTracker,
+    pub rng: StdRng,
+    /// Global strain rate of every step taken in the last batch
+    pub global_rates: Vec<f32>,
+    /// Total energy after every step taken in the last batch
+    pub energies: Vec<f32>,
+    /// Shared with [BackgroundSimulation] so a running batch can be cancelled
+    abort: AbortHandle,
+}
+
+impl SimulationBatch {
+    /// Takes at most `max_steps` steps, stopping early once `budget` is used up or the simulation is done
+    fn run(&mut self, max_steps: usize, budget: Duration) {
+        let start = Instant::now();
+        self.global_rates.clear();
+        self.energies.clear();
+        for _ in 0..max_steps {
+            if self.tectonics.finished() || self.abort.is_aborted() {
+                break;
+            }
+            self.tectonics.simulate(&mut self.rng);
+            self.strain.update(&self.tectonics);
+            self.global_rates.push(self.strain.global_rate);
+            self.energies.push(self.tectonics.total_energy());
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+    }
+}
+
+/// Working copy of the tectonic simulation, stepped on the [AsyncComputeTaskPool].
+/// The [Tectonics] resource is a snapshot of the last finished batch, so render systems never wait on a step.
+#[derive(Resource)]
+pub struct BackgroundSimulation {
+    /// `None` while a batch is running
+    idle: Option<SimulationBatch>,
+    task: Option<Task<SimulationBatch>>,
+    /// Config changed while a batch was running, applied once it finishes
+    pending_config: Option<TectonicsConfiguration>,
+    abort: AbortHandle,
+}
+
+impl BackgroundSimulation {
+    pub fn new(tectonics: Tectonics, strain: StrainTracker, rng: StdRng) -> Self {
+        let abort = AbortHandle::new();
+        BackgroundSimulation {
+            idle: Some(SimulationBatch {
+                tectonics,
+                strain,
+                rng,
+                global_rates: Vec::new(),
+                energies: Vec::new(),
+                abort: abort.clone(),
+            }),
+            task: None,
+            pending_config: None,
+            abort,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Stops the running batch after its current step and drops it, nothing can be started afterwards
+    pub fn cancel(&mut self) {
+        self.abort.abort();
+        self.task = None;
+        self.idle = None;
+    }
+
+    /// Starts a batch on the task pool, does nothing if one is already running
+    pub fn start(&mut self, max_steps: usize, budget: Duration) {
+        if let Some(mut batch) = self.idle.take() {
+            self.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+                batch.run(max_steps, budget);
+                batch
+            }));
+        }
+    }
+
+    /// The finished batch if the running one is done. It stays owned by [BackgroundSimulation], copy what the snapshot needs
+    pub fn poll(&mut self) -> Option<&SimulationBatch> {
+        let batch = block_on(future::poll_once(self.task.as_mut()?))?;
+        self.task = None;
+        let batch = self.idle.insert(batch);
+        if let Some(config) = self.pending_config.take() {
+            batch.tectonics.set_config(config);
+        }
+        Some(batch)
+    }
+
+    /// Applies `config` to the working copy, waiting for the running batch to finish if needed
+    pub fn set_config(&mut self, config: TectonicsConfiguration) {
+        match &mut self.idle {
+            Some(batch) => batch.tectonics.set_config(config),
+            None => self.pending_config = Some(config),
+        }
+    }
+}
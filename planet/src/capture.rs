@@ -0,0 +1,123 @@
+use std::{f32::consts::TAU, path::Path};
+
+use bevy::{
+    prelude::*,
+    render::view::screenshot::{Screenshot, save_to_disk},
+};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{
+    MainCamera,
+    debug_ui::DebugDiagnostics,
+    fly_camera::{CameraMode, orbiting},
+};
+
+/// Turntable frames are written here as `frame_00000.png`, `frame_00001.png`, ...
+pub const TURNTABLE_DIRECTORY: &str = "turntable";
+
+pub struct CapturePlugin {
+    /// Frames a [Turntable] started with F11 takes for a full turn
+    pub turntable_frames: usize,
+}
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(TurntableFrames(self.turntable_frames))
+            .add_systems(
+                Update,
+                (
+                    capture_input,
+                    turntable
+                        .run_if(resource_exists::<Turntable>)
+                        .run_if(orbiting),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct TurntableFrames(usize);
+
+/// Saves the next rendered frame of the primary window to `path` as a PNG
+pub fn capture_screenshot(commands: &mut Commands, path: impl AsRef<Path>) {
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path.as_ref().to_path_buf()));
+}
+
+/// Turns the orbit camera a full turn around the planet, capturing a screenshot of every frame into [TURNTABLE_DIRECTORY].
+/// Insert it as a resource to start, it removes itself once the turn is done.
+#[derive(Resource)]
+pub struct Turntable {
+    /// Next frame to capture
+    frame: usize,
+    frames: usize,
+    /// Yaw of the orbit camera when the turntable started
+    start_yaw: f32,
+}
+
+impl Turntable {
+    pub fn new(frames: usize, start_yaw: f32) -> Self {
+        Turntable {
+            frame: 0,
+            frames: frames.max(1),
+            start_yaw,
+        }
+    }
+}
+
+/// F12 captures a screenshot named after the seed, F11 starts or stops a [Turntable]
+fn capture_input(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    diagnostics: Res<DebugDiagnostics>,
+    turntable_frames: Res<TurntableFrames>,
+    camera_mode: Res<CameraMode>,
+    turntable: Option<Res<Turntable>>,
+    camera_query: Query<&PanOrbitCamera, With<MainCamera>>,
+) {
+    if keys.just_pressed(KeyCode::F12) {
+        let path = format!("screenshot_{}.png", diagnostics.seed);
+        capture_screenshot(&mut commands, &path);
+        info!("Saving screenshot to {path}");
+    }
+    if keys.just_pressed(KeyCode::F11) {
+        if turntable.is_some() {
+            commands.remove_resource::<Turntable>();
+            info!("Turntable stopped");
+        } else if *camera_mode != CameraMode::Orbit {
+            warn!("The turntable only turns the orbit camera, leave the fly camera first");
+        } else if let Err(e) = std::fs::create_dir_all(TURNTABLE_DIRECTORY) {
+            error!("Failed to create {TURNTABLE_DIRECTORY} for the turntable: {e}");
+        } else {
+            let start_yaw = camera_query.single().unwrap().target_yaw;
+            commands.insert_resource(Turntable::new(turntable_frames.0, start_yaw));
+            info!(
+                "Capturing {} turntable frames to {TURNTABLE_DIRECTORY}",
+                turntable_frames.0
+            );
+        }
+    }
+}
+
+/// The yaw is set without smoothing, so every frame is captured exactly at its step of the turn
+fn turntable(
+    mut commands: Commands,
+    mut turntable: ResMut<Turntable>,
+    mut camera_query: Query<&mut PanOrbitCamera, With<MainCamera>>,
+) {
+    let mut pan_orbit = camera_query.single_mut().unwrap();
+    let yaw = turntable.start_yaw + TAU * turntable.frame as f32 / turntable.frames as f32;
+    pan_orbit.target_yaw = yaw;
+    pan_orbit.yaw = Some(yaw);
+    pan_orbit.force_update = true;
+    capture_screenshot(
+        &mut commands,
+        Path::new(TURNTABLE_DIRECTORY).join(format!("frame_{:05}.png", turntable.frame)),
+    );
+    turntable.frame += 1;
+    if turntable.frame == turntable.frames {
+        commands.remove_resource::<Turntable>();
+        info!("Captured {} turntable frames", turntable.frames);
+    }
+}
@@ -0,0 +1,242 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        primitives::Aabb,
+    },
+};
+
+use crate::{MainCamera, hex_sphere::HexSphere, textured::TexturedPlanet};
+
+/// Extra angle past the horizon before a chunk is hidden, so mountains poking over the horizon are still drawn
+const CULL_MARGIN: f32 = 0.2;
+
+/// The tiles of one base icosahedron face, drawn as their own mesh
+pub struct HexSphereChunk {
+    /// Unit direction to the center of the chunk's tiles
+    pub center: Vec3,
+    /// Largest angle between [HexSphereChunk::center] and a vertex of the chunk
+    pub angular_radius: f32,
+    /// Indices to the tiles in the chunk
+    pub tiles: Vec<usize>,
+    /// For each vertex of the chunk mesh, the index of the full mesh vertex it copies
+    pub vertices: Vec<usize>,
+    pub mesh: Handle<Mesh>,
+}
+
+/// The planet split into one mesh per base icosahedron face, so chunks on the far side can be culled.
+/// The full mesh in [crate::hex_sphere::HexSphereMeshHandle] stays the one systems edit and export, chunks copy the tiles that changed from it.
+#[derive(Resource)]
+pub struct HexSphereChunks {
+    /// Chunks in base face order
+    pub chunks: Vec<HexSphereChunk>,
+    /// For each tile, the index of its chunk
+    pub tile_chunks: Vec<usize>,
+}
+
+/// Index into [HexSphereChunks::chunks] of the chunk an entity draws
+#[derive(Component)]
+pub struct ChunkIndex(pub usize);
+
+fn full_mesh_attributes(full_mesh: &Mesh) -> Option<(&[[f32; 3]], &[[f32; 3]], &[[f32; 4]])> {
+    match (
+        full_mesh.attribute(Mesh::ATTRIBUTE_POSITION),
+        full_mesh.attribute(Mesh::ATTRIBUTE_NORMAL),
+        full_mesh.attribute(Mesh::ATTRIBUTE_COLOR),
+    ) {
+        (
+            Some(VertexAttributeValues::Float32x3(positions)),
+            Some(VertexAttributeValues::Float32x3(normals)),
+            Some(VertexAttributeValues::Float32x4(colors)),
+        ) => Some((positions, normals, colors)),
+        _ => None,
+    }
+}
+
+/// Copies positions, normals and colors of `vertices` from the full mesh
+fn gather_attributes(
+    full_mesh: &Mesh,
+    vertices: &[usize],
+) -> Option<(Vec<[f32; 3]>, Vec<[f32; 3]>, Vec<[f32; 4]>)> {
+    let (positions, normals, colors) = full_mesh_attributes(full_mesh)?;
+    Some((
+        vertices.iter().map(|&vertex| positions[vertex]).collect(),
+        vertices.iter().map(|&vertex| normals[vertex]).collect(),
+        vertices.iter().map(|&vertex| colors[vertex]).collect(),
+    ))
+}
+
+impl HexSphereChunks {
+    /// Splits `full_mesh` of `hex_sphere` into chunk meshes, the full mesh needs positions, normals and colors
+    pub fn new(hex_sphere: &HexSphere, full_mesh: &Mesh, meshes: &mut Assets<Mesh>) -> Self {
+        let tile_chunks: Vec<usize> = (0..hex_sphere.tiles.len())
+            .map(|tile_index| hex_sphere.coords.from_index(tile_index).face as usize)
+            .collect();
+        let chunk_count = tile_chunks.iter().max().map_or(0, |face| face + 1);
+        let mut chunk_tiles = vec![Vec::new(); chunk_count];
+        for (tile_index, &chunk) in tile_chunks.iter().enumerate() {
+            chunk_tiles[chunk].push(tile_index);
+        }
+        let chunks = chunk_tiles
+            .into_iter()
+            .map(|tiles| {
+                // Tiles do not share vertices, so each tile fans out from its own center like in the full mesh
+                let mut vertices = Vec::new();
+                let mut indices: Vec<u32> = Vec::new();
+                for &tile_index in &tiles {
+                    let tile = &hex_sphere.tiles[tile_index];
+                    let first = vertices.len() as u32;
+                    let corners = tile.vertices.len() as u32;
+                    vertices.extend(tile.vertices.iter().chain(std::iter::once(&tile.center)));
+                    for corner in 0..corners {
+                        let previous = (corner + corners - 1) % corners;
+                        indices.extend([first + previous, first + corner, first + corners]);
+                    }
+                }
+                let center = tiles
+                    .iter()
+                    .map(|&tile_index| hex_sphere.tiles[tile_index].normal)
+                    .sum::<Vec3>()
+                    .normalize_or_zero();
+                let angular_radius = vertices
+                    .iter()
+                    .map(|&vertex| Vec3::from(hex_sphere.vertices[vertex]).angle_between(center))
+                    .fold(0., f32::max);
+                let mut mesh = Mesh::new(
+                    PrimitiveTopology::TriangleList,
+                    RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+                )
+                .with_inserted_indices(Indices::U32(indices));
+                if let Some((positions, normals, colors)) = gather_attributes(full_mesh, &vertices)
+                {
+                    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+                    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+                    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+                }
+                HexSphereChunk {
+                    center,
+                    angular_radius,
+                    tiles,
+                    vertices,
+                    mesh: meshes.add(mesh),
+                }
+            })
+            .collect();
+        HexSphereChunks {
+            chunks,
+            tile_chunks,
+        }
+    }
+
+    /// Copies the full mesh into the chunks holding any of `tiles`, other chunks are left alone
+    pub fn patch(
+        &self,
+        meshes: &mut Assets<Mesh>,
+        full_mesh: &Handle<Mesh>,
+        tiles: impl IntoIterator<Item = usize>,
+    ) {
+        let mut affected = vec![false; self.chunks.len()];
+        for tile_index in tiles {
+            affected[self.tile_chunks[tile_index]] = true;
+        }
+        let Some(full) = meshes.get(full_mesh) else {
+            return;
+        };
+        let patched: Vec<_> = self
+            .chunks
+            .iter()
+            .zip(affected)
+            .filter(|(_, affected)| *affected)
+            .filter_map(|(chunk, _)| {
+                gather_attributes(full, &chunk.vertices)
+                    .map(|attributes| (chunk.mesh.clone(), attributes))
+            })
+            .collect();
+        for (handle, (positions, normals, colors)) in patched {
+            if let Some(mesh) = meshes.get_mut(&handle) {
+                mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+            }
+        }
+    }
+
+    /// Copies the whole full mesh into every chunk
+    pub fn patch_all(&self, meshes: &mut Assets<Mesh>, full_mesh: &Handle<Mesh>) {
+        self.patch(meshes, full_mesh, 0..self.tile_chunks.len());
+    }
+}
+
+pub struct ChunksPlugin;
+impl Plugin for ChunksPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                cull_chunks
+                    .run_if(resource_exists::<HexSphereChunks>)
+                    .run_if(not(any_with_component::<TexturedPlanet>)),
+                refresh_chunk_bounds,
+            ),
+        );
+    }
+}
+
+/// Hides chunks entirely past the horizon seen from the camera, which frustum culling keeps since they are inside the view
+fn cull_chunks(
+    hex_sphere_chunks: Res<HexSphereChunks>,
+    camera_query: Query<(&GlobalTransform, &Projection), With<MainCamera>>,
+    mut chunks: Query<(&ChunkIndex, &mut Visibility)>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.single() else {
+        return;
+    };
+    let camera_position = camera_transform.translation();
+    // An orthographic camera looks along parallel rays, so its horizon is the great circle facing it
+    let horizon = match projection {
+        Projection::Perspective(_) if camera_position.length() > 1. => {
+            (1. / camera_position.length()).acos()
+        }
+        Projection::Perspective(_) => std::f32::consts::PI,
+        _ => std::f32::consts::FRAC_PI_2,
+    };
+    let towards_camera = match projection {
+        Projection::Perspective(_) => camera_position.normalize_or_zero(),
+        _ => camera_transform.back().into(),
+    };
+    for (chunk_index, mut visibility) in &mut chunks {
+        let Some(chunk) = hex_sphere_chunks.chunks.get(chunk_index.0) else {
+            continue;
+        };
+        let nearest_angle = chunk.center.angle_between(towards_camera) - chunk.angular_radius;
+        visibility.set_if_neq(if nearest_angle > horizon + CULL_MARGIN {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+    }
+}
+
+/// Bevy only computes bounding boxes once, so chunks whose mesh changed get theirs recomputed for frustum culling
+fn refresh_chunk_bounds(
+    mut commands: Commands,
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    chunks: Query<(Entity, &Mesh3d), (With<ChunkIndex>, With<Aabb>)>,
+) {
+    let modified: Vec<AssetId<Mesh>> = mesh_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if modified.is_empty() {
+        return;
+    }
+    for (entity, mesh_3d) in &chunks {
+        if modified.contains(&mesh_3d.id()) {
+            commands.entity(entity).remove::<Aabb>();
+        }
+    }
+}
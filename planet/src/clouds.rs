@@ -0,0 +1,364 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    color::palettes,
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use rand::Rng;
+use rayon::prelude::*;
+use suz_sim::vec_utils;
+
+use crate::{
+    GlobalRng,
+    coastlines::Coastlines,
+    hex_sphere::HexSphere,
+    states::SimulationState,
+    textured::equirectangular_sphere,
+    tile_data::{TileData, update_climate},
+};
+
+/// Segments of the cloud shell around the equator
+const CLOUD_SEGMENTS: u32 = 128;
+/// Octaves of noise summed into the cloud cover
+const CLOUD_OCTAVES: usize = 5;
+/// Noise frequency on the unit sphere, higher makes smaller cloud systems
+const CLOUD_FREQUENCY: f32 = 3.;
+/// Width of the fade from clear sky to full cover, in noise units
+const CLOUD_SOFTNESS: f32 = 0.15;
+/// Seconds between rebuilds of the cloud texture as the noise evolves
+const CLOUD_UPDATE_INTERVAL: f32 = 0.25;
+
+/// Draws a seeded, slowly evolving noise cloud layer on a shell above the finished planet, thicker over rainy tiles.
+/// A panel in the top left corner toggles the clouds and sets their opacity.
+pub struct CloudsPlugin {
+    pub config: CloudConfig,
+}
+impl Plugin for CloudsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config)
+            .add_systems(
+                OnEnter(SimulationState::Erosion),
+                (spawn_clouds.after(update_climate), setup_panel),
+            )
+            .add_systems(OnExit(SimulationState::Erosion), teardown)
+            .add_systems(
+                Update,
+                (
+                    cloud_buttons,
+                    // The panel and shell are rebuilt with every planet
+                    (update_cloud_texts, sync_clouds)
+                        .run_if(resource_changed::<CloudConfig>.or(resource_added::<CloudLayer>)),
+                    evolve_clouds,
+                )
+                    .chain()
+                    .run_if(resource_exists::<CloudLayer>),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct CloudConfig {
+    pub visible: bool,
+    /// Alpha of fully clouded sky
+    pub opacity: f32,
+    /// Height of the cloud shell above sea level as a fraction of the planet radius
+    pub altitude: f32,
+    /// Fraction of the sky clouded before precipitation is taken into account, 0.5 clouds about half of it
+    pub coverage: f32,
+    /// Extra cover over the rainiest tiles, and as much less over the driest
+    pub precipitation_correlation: f32,
+    /// Width of the cloud texture, the height is half of this
+    pub resolution: u32,
+    /// Noise units the clouds evolve by per second, 0 keeps them still
+    pub speed: f32,
+}
+
+/// The cloud texture of the current planet and what it is rebuilt from
+#[derive(Resource)]
+struct CloudLayer {
+    seed: u32,
+    /// Unit sphere direction under every texture pixel, row by row from the north pole
+    directions: Vec<Vec3>,
+    /// Precipitation of the tile under every texture pixel, relative to the rainiest tile
+    wetness: Vec<f32>,
+    image: Handle<Image>,
+    /// App time in seconds when the texture was last rebuilt
+    updated: f32,
+}
+
+#[derive(Component)]
+struct Clouds;
+
+#[derive(Component)]
+struct CloudPanelRoot;
+
+#[derive(Component, Clone, Copy)]
+enum CloudButton {
+    Toggle,
+    /// Adds this to the opacity when pressed
+    Opacity(f32),
+}
+
+#[derive(Component)]
+struct CloudVisibleText;
+
+#[derive(Component)]
+struct CloudOpacityText;
+
+/// White RGBA pixels with the cloud cover at `time` as alpha
+fn cloud_pixels(layer: &CloudLayer, config: &CloudConfig, time: f32) -> Vec<u8> {
+    let noise = Fbm::<Perlin>::new(layer.seed).set_octaves(CLOUD_OCTAVES);
+    layer
+        .directions
+        .par_iter()
+        .zip(layer.wetness.par_iter())
+        .flat_map_iter(|(direction, wetness)| {
+            let [x, y, z] = (*direction * CLOUD_FREQUENCY).to_array().map(f64::from);
+            // Noise mapped to [0, 1], shifted so `coverage` of the sky lies above the 0.5 threshold
+            let cloudiness = noise.get([x, y, z, time as f64]) as f32 * 0.5 + 0.5;
+            let cover = cloudiness + config.coverage - 0.5
+                + config.precipitation_correlation * (wetness - 0.5);
+            let alpha = ((cover - 0.5) / CLOUD_SOFTNESS + 0.5).clamp(0., 1.);
+            [u8::MAX, u8::MAX, u8::MAX, (alpha * 255.).round() as u8]
+        })
+        .collect()
+}
+
+fn spawn_clouds(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    mut rng: ResMut<GlobalRng>,
+    hex_sphere: Res<HexSphere>,
+    tile_data: Res<TileData>,
+    coastlines: Res<Coastlines>,
+    config: Res<CloudConfig>,
+) {
+    let width = config.resolution.max(2);
+    let height = width / 2;
+    let directions: Vec<Vec3> = (0..height)
+        .flat_map(|y| {
+            let latitude = PI / 2. - (y as f32 + 0.5) / height as f32 * PI;
+            (0..width).map(move |x| {
+                let longitude = (x as f32 + 0.5) / width as f32 * 2. * PI - PI;
+                vec_utils::lat_long_to_vec3(latitude, longitude)
+            })
+        })
+        .collect();
+    let rainiest = tile_data.precipitation.iter().cloned().fold(0., f32::max);
+    let wetness = directions
+        .par_iter()
+        .map(|&direction| {
+            let tile = hex_sphere.tile_at(direction).index;
+            match tile_data.precipitation.get(tile) {
+                Some(precipitation) if rainiest > 0. => precipitation / rainiest,
+                _ => 0.5,
+            }
+        })
+        .collect();
+    let mut layer = CloudLayer {
+        seed: rng.0.random(),
+        directions,
+        wetness,
+        image: Handle::default(),
+        updated: 0.,
+    };
+    let mut image = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        cloud_pixels(&layer, &config, 0.),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    // Nearest sampling would show the cloud edges as pixel steps
+    image.sampler = ImageSampler::linear();
+    layer.image = images.add(image);
+
+    let radius = coastlines.sea_level * (1. + config.altitude);
+    commands.spawn((
+        Mesh3d(meshes.add(equirectangular_sphere(CLOUD_SEGMENTS, radius))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::WHITE.with_alpha(config.opacity),
+            base_color_texture: Some(layer.image.clone()),
+            alpha_mode: AlphaMode::Blend,
+            perceptual_roughness: 1.,
+            reflectance: 0.,
+            ..Default::default()
+        })),
+        if config.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        },
+        Clouds,
+    ));
+    commands.insert_resource(layer);
+}
+
+/// Rebuilds the cloud texture every [CLOUD_UPDATE_INTERVAL] while the clouds are shown
+fn evolve_clouds(
+    time: Res<Time>,
+    config: Res<CloudConfig>,
+    mut layer: ResMut<CloudLayer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let now = time.elapsed_secs();
+    if !config.visible || config.speed == 0. || now - layer.updated < CLOUD_UPDATE_INTERVAL {
+        return;
+    }
+    layer.updated = now;
+    let pixels = cloud_pixels(&layer, &config, now * config.speed);
+    if let Some(image) = images.get_mut(&layer.image) {
+        image.data = Some(pixels);
+    }
+}
+
+fn sync_clouds(
+    config: Res<CloudConfig>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut clouds: Query<(&MeshMaterial3d<StandardMaterial>, &mut Visibility), With<Clouds>>,
+) {
+    for (material, mut visibility) in &mut clouds {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color = Color::WHITE.with_alpha(config.opacity);
+        }
+        *visibility = if config.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn setup_panel(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let label_font = TextFont {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 12.0,
+        ..default()
+    };
+    let value_font = TextFont {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 12.0,
+        ..default()
+    };
+    let button = |label: &str, cloud_button: CloudButton| {
+        (
+            Button,
+            cloud_button,
+            Node {
+                min_width: Val::Px(18.),
+                margin: UiRect::left(Val::Px(5.)),
+                padding: UiRect::axes(Val::Px(4.), Val::Px(0.)),
+                border: UiRect::all(Val::Px(1.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BorderColor(LinearRgba::new(0.4, 0.4, 0.4, 1.).into()),
+            BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 1.).into()),
+            children![(Text::new(label), label_font.clone())],
+        )
+    };
+    let row = Node {
+        width: Val::Percent(100.),
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let value = Node {
+        margin: UiRect::left(Val::Auto),
+        ..default()
+    };
+
+    commands.spawn((
+        CloudPanelRoot,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.),
+            top: Val::Px(10.),
+            width: Val::Px(180.),
+            padding: UiRect::all(Val::Px(10.)),
+            row_gap: Val::Px(5.),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        BackgroundColor(LinearRgba::new(0.01, 0.01, 0.01, 0.8).into()),
+        children![
+            (Text::new("Clouds"), label_font.clone()),
+            (
+                row.clone(),
+                children![
+                    (Text::new("Visible: "), label_font.clone()),
+                    (
+                        value.clone(),
+                        Text::default(),
+                        value_font.clone(),
+                        TextColor(palettes::css::GOLD.into()),
+                        CloudVisibleText,
+                    ),
+                    button("Toggle", CloudButton::Toggle),
+                ]
+            ),
+            (
+                row,
+                children![
+                    (Text::new("Opacity: "), label_font.clone()),
+                    (
+                        value,
+                        Text::default(),
+                        value_font,
+                        TextColor(palettes::css::GOLD.into()),
+                        CloudOpacityText,
+                    ),
+                    button("-", CloudButton::Opacity(-0.1)),
+                    button("+", CloudButton::Opacity(0.1)),
+                ]
+            ),
+        ],
+    ));
+}
+
+fn teardown(
+    mut commands: Commands,
+    entities: Query<Entity, Or<(With<Clouds>, With<CloudPanelRoot>)>>,
+) {
+    for entity in &entities {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<CloudLayer>();
+}
+
+fn cloud_buttons(
+    interactions: Query<(&Interaction, &CloudButton), Changed<Interaction>>,
+    mut config: ResMut<CloudConfig>,
+) {
+    for (interaction, button) in &interactions {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            CloudButton::Toggle => config.visible = !config.visible,
+            CloudButton::Opacity(step) => config.opacity = (config.opacity + step).clamp(0., 1.),
+        }
+    }
+}
+
+fn update_cloud_texts(
+    config: Res<CloudConfig>,
+    mut visible_texts: Query<&mut Text, (With<CloudVisibleText>, Without<CloudOpacityText>)>,
+    mut opacity_texts: Query<&mut Text, With<CloudOpacityText>>,
+) {
+    for mut text in &mut visible_texts {
+        **text = if config.visible { "On" } else { "Off" }.to_string();
+    }
+    for mut text in &mut opacity_texts {
+        **text = format!("{:.1}", config.opacity);
+    }
+}
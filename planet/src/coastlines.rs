@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use bevy::{color::palettes, prelude::*};
+use suz_sim::generator;
+
+use crate::{hex_sphere::HexSphere, states::SimulationState};
+
+/// Sea level, land mask and coastlines of the finished planet
+#[derive(Resource, Default)]
+pub struct Coastlines {
+    /// Tiles below this height are ocean
+    pub sea_level: f32,
+    /// For each tile, whether it is above [Coastlines::sea_level]
+    pub land: Vec<bool>,
+    /// Closed loops of mesh corner positions along the edges between land and ocean tiles, in the winding order of the land tiles
+    pub lines: Vec<Vec<Vec3>>,
+}
+
+impl Coastlines {
+    /// Picks the height below which `ocean_fraction` of the tiles lie, see [generator::pick_sea_level]
+    pub fn pick_sea_level(hex_sphere: &HexSphere, ocean_fraction: f32) -> f32 {
+        let heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+        generator::pick_sea_level(&heights, ocean_fraction)
+    }
+
+    pub fn from_hex_sphere(hex_sphere: &HexSphere, ocean_fraction: f32) -> Self {
+        let sea_level = Self::pick_sea_level(hex_sphere, ocean_fraction);
+        let land: Vec<bool> = hex_sphere
+            .tiles
+            .iter()
+            .map(|tile| tile.height >= sea_level)
+            .collect();
+        let lines = region_outlines(hex_sphere, &land);
+        Coastlines {
+            sea_level,
+            land,
+            lines,
+        }
+    }
+}
+
+/// Closed loops of mesh corner positions along the edges between tiles `inside` a region and tiles outside it,
+/// in the winding order of the inside tiles so the region is on the left of every loop seen from outside the sphere
+pub fn region_outlines(hex_sphere: &HexSphere, inside: &[bool]) -> Vec<Vec<Vec3>> {
+    // Tiles do not share mesh vertices, so corners are identified by the tiles meeting at them
+    let corner = |vertex_index: usize| {
+        let mut tiles = hex_sphere.vertices_to_tiles[vertex_index].clone();
+        tiles.sort_unstable();
+        tiles
+    };
+    // Outline edges of every inside tile in winding order, keyed by their start corner
+    let mut edges: HashMap<Vec<usize>, (Vec<usize>, Vec3)> = HashMap::new();
+    for tile in hex_sphere.tiles.iter().filter(|tile| inside[tile.index]) {
+        for k in 0..tile.vertices.len() {
+            let start = tile.vertices[(k + tile.vertices.len() - 1) % tile.vertices.len()];
+            let end = tile.vertices[k];
+            let across = hex_sphere.vertices_to_tiles[start].iter().find(|&&other| {
+                other != tile.index && hex_sphere.vertices_to_tiles[end].contains(&other)
+            });
+            if across.is_some_and(|&other| !inside[other]) {
+                edges.insert(
+                    corner(start),
+                    (corner(end), Vec3::from(hex_sphere.vertices[start])),
+                );
+            }
+        }
+    }
+
+    // Every outline corner has exactly one edge leaving it, so following them traces closed loops
+    let mut lines = Vec::new();
+    while let Some(first) = edges.keys().next().cloned() {
+        let mut line = Vec::new();
+        let mut current = first;
+        while let Some((next, position)) = edges.remove(&current) {
+            line.push(position);
+            current = next;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+pub struct CoastlinesPlugin {
+    /// Fraction of tiles placed below sea level
+    pub ocean_fraction: f32,
+}
+impl Plugin for CoastlinesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(OceanFraction(self.ocean_fraction))
+            .init_resource::<Coastlines>()
+            .add_systems(OnEnter(SimulationState::Erosion), extract_coastlines)
+            .add_systems(
+                Update,
+                draw_coastlines.run_if(in_state(SimulationState::Erosion)),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct OceanFraction(f32);
+
+pub fn extract_coastlines(
+    mut coastlines: ResMut<Coastlines>,
+    hex_sphere: Res<HexSphere>,
+    ocean_fraction: Res<OceanFraction>,
+) {
+    *coastlines = Coastlines::from_hex_sphere(&hex_sphere, ocean_fraction.0);
+}
+
+fn draw_coastlines(mut gizmos: Gizmos, coastlines: Res<Coastlines>) {
+    for line in &coastlines.lines {
+        // Lifted slightly so the line is not hidden by the tiles it runs along
+        let mut points: Vec<Vec3> = line.iter().map(|position| *position * 1.002).collect();
+        points.extend(points.first().copied());
+        gizmos.linestrip(points, palettes::css::WHITE);
+    }
+}
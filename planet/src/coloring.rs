@@ -0,0 +1,333 @@
+use bevy::prelude::*;
+use suz_sim::{
+    boundaries::{BoundaryType, PlateBoundaries},
+    tectonics::{CONTINENTAL_HEIGHT, OCEANIC_HEIGHT, SEA_LEVEL},
+};
+
+use crate::{
+    chunks::HexSphereChunks,
+    hex_sphere::{HexSphere, HexSphereMeshHandle},
+    lakes::Lakes,
+    sim_resources::SimPlateBoundaries,
+    strain_rate::StrainRate,
+    tile_data::TileData,
+};
+
+/// Which tile layer is used to color the planet mesh
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapMode {
+    #[default]
+    Elevation,
+    Plates,
+    PlateBoundaries,
+    SpringStress,
+    StrainRate,
+    CrustAge,
+    Trenches,
+    Temperature,
+    Precipitation,
+}
+
+impl MapMode {
+    /// Every mode in cycling order, the nth one is selected with the nth function key
+    const ALL: [MapMode; 9] = [
+        MapMode::Elevation,
+        MapMode::Plates,
+        MapMode::PlateBoundaries,
+        MapMode::SpringStress,
+        MapMode::StrainRate,
+        MapMode::CrustAge,
+        MapMode::Trenches,
+        MapMode::Temperature,
+        MapMode::Precipitation,
+    ];
+    const KEYS: [KeyCode; 9] = [
+        KeyCode::F1,
+        KeyCode::F2,
+        KeyCode::F3,
+        KeyCode::F4,
+        KeyCode::F5,
+        KeyCode::F6,
+        KeyCode::F7,
+        KeyCode::F8,
+        KeyCode::F9,
+    ];
+
+    fn next(self) -> Self {
+        let index = MapMode::ALL
+            .iter()
+            .position(|mode| *mode == self)
+            .unwrap_or(0);
+        MapMode::ALL[(index + 1) % MapMode::ALL.len()]
+    }
+}
+
+impl std::fmt::Display for MapMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MapMode::Elevation => write!(f, "Elevation"),
+            MapMode::Plates => write!(f, "Plates"),
+            MapMode::PlateBoundaries => write!(f, "Plate boundaries"),
+            MapMode::SpringStress => write!(f, "Spring stress"),
+            MapMode::StrainRate => write!(f, "Strain rate"),
+            MapMode::CrustAge => write!(f, "Crust age"),
+            MapMode::Trenches => write!(f, "Trenches"),
+            MapMode::Temperature => write!(f, "Temperature"),
+            MapMode::Precipitation => write!(f, "Precipitation"),
+        }
+    }
+}
+
+/// Inland water drawn over [MapMode::Elevation]
+const LAKE_COLOR: [f32; 4] = [0.15, 0.4, 0.7, 1.0];
+/// Glaciers drawn over [MapMode::Elevation], slightly blue to stand apart from snow capped peaks
+const ICE_COLOR: [f32; 4] = [0.8, 0.9, 1.0, 1.0];
+
+/// A color at a given value of the sampled layer, such as tile height
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorStop {
+    pub value: f32,
+    pub color: [f32; 4],
+}
+
+/// Hypsometric tint used by [MapMode::Elevation], colors are linearly blended between the stops.
+/// Stops must be sorted by value, values outside the stops take the color of the closest one.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    pub stops: Vec<ColorStop>,
+}
+
+impl Default for ColorRamp {
+    fn default() -> Self {
+        let stop = |value, color| ColorStop { value, color };
+        ColorRamp {
+            stops: vec![
+                // Deep ocean
+                stop(OCEANIC_HEIGHT - 0.02, [0.02, 0.05, 0.25, 1.0]),
+                stop(OCEANIC_HEIGHT, [0.05, 0.15, 0.5, 1.0]),
+                // Shelf
+                stop(SEA_LEVEL - 0.001, [0.2, 0.45, 0.75, 1.0]),
+                // Lowland
+                stop(SEA_LEVEL, [0.2, 0.55, 0.2, 1.0]),
+                stop(CONTINENTAL_HEIGHT, [0.45, 0.6, 0.25, 1.0]),
+                // Highland
+                stop(CONTINENTAL_HEIGHT + 0.03, [0.5, 0.4, 0.25, 1.0]),
+                // Snow
+                stop(CONTINENTAL_HEIGHT + 0.06, [0.95, 0.95, 0.95, 1.0]),
+            ],
+        }
+    }
+}
+
+impl ColorRamp {
+    /// Blue through white at freezing to red, in °C
+    pub fn temperature() -> Self {
+        let stop = |value, color| ColorStop { value, color };
+        ColorRamp {
+            stops: vec![
+                stop(-30., [0.1, 0.2, 0.8, 1.0]),
+                stop(0., [0.95, 0.95, 0.95, 1.0]),
+                stop(30., [0.8, 0.1, 0.1, 1.0]),
+            ],
+        }
+    }
+
+    pub fn sample(&self, value: f32) -> [f32; 4] {
+        let Some(first) = self.stops.first() else {
+            return [0.0, 0.0, 0.0, 1.0];
+        };
+        if value <= first.value {
+            return first.color;
+        }
+        for pair in self.stops.windows(2) {
+            let (low, high) = (pair[0], pair[1]);
+            if value <= high.value {
+                let t = (value - low.value) / (high.value - low.value).max(f32::EPSILON);
+                return std::array::from_fn(|channel| {
+                    low.color[channel] + (high.color[channel] - low.color[channel]) * t
+                });
+            }
+        }
+        self.stops[self.stops.len() - 1].color
+    }
+}
+
+pub struct ColoringPlugin;
+impl Plugin for ColoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapMode>()
+            .init_resource::<ColorRamp>()
+            .add_systems(
+                Update,
+                (
+                    cycle_map_mode,
+                    recolor
+                        .run_if(resource_changed::<MapMode>.or(resource_changed::<ColorRamp>))
+                        .run_if(resource_exists::<HexSphere>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Updates [HexSphere::colors] from the layer selected by `map_mode`
+pub fn color_tiles(
+    hex_sphere: &mut HexSphere,
+    map_mode: MapMode,
+    color_ramp: &ColorRamp,
+    strain_rate: Option<&StrainRate>,
+    plate_boundaries: Option<&PlateBoundaries>,
+    tile_data: Option<&TileData>,
+    lakes: Option<&Lakes>,
+) {
+    let max_strain_rate = strain_rate
+        .map(|strain_rate| strain_rate.tiles.iter().cloned().fold(0., f32::max))
+        .unwrap_or(0.);
+    let max_spring_stress = tile_data
+        .map(|tile_data| tile_data.spring_stress.iter().cloned().fold(0., f32::max))
+        .unwrap_or(0.);
+    let max_crust_age = tile_data
+        .map(|tile_data| tile_data.crust_age.ages.iter().cloned().fold(0., f32::max))
+        .unwrap_or(0.);
+    let max_trench_depth = tile_data
+        .map(|tile_data| tile_data.trench_depths.iter().cloned().fold(0., f32::max))
+        .unwrap_or(0.);
+    let temperature_ramp = ColorRamp::temperature();
+    let max_precipitation = tile_data
+        .map(|tile_data| tile_data.precipitation.iter().cloned().fold(0., f32::max))
+        .unwrap_or(0.);
+    let normalized = |value: f32, max: f32| if max > 0. { value / max } else { 0. };
+    for tile_index in 0..hex_sphere.tiles.len() {
+        let tile = &hex_sphere.tiles[tile_index];
+        let color = match map_mode {
+            MapMode::Elevation => {
+                let in_lake = lakes
+                    .and_then(|lakes| lakes.tile_to_lake.get(tile_index))
+                    .is_some_and(Option::is_some);
+                let glaciated = tile_data
+                    .and_then(|tile_data| tile_data.ice_thickness.get(tile_index))
+                    .is_some_and(|thickness| *thickness > 0.);
+                if glaciated {
+                    ICE_COLOR
+                } else if in_lake {
+                    LAKE_COLOR
+                } else {
+                    color_ramp.sample(tile.height)
+                }
+            }
+            MapMode::StrainRate => {
+                let rate = strain_rate
+                    .and_then(|strain_rate| strain_rate.tiles.get(tile_index))
+                    .cloned()
+                    .unwrap_or(0.);
+                let t = normalized(rate, max_strain_rate);
+                // Dark blue for resting crust, through red to yellow for the fastest deformation
+                [t, t * t, 0.2 * (1. - t), 1.0]
+            }
+            MapMode::PlateBoundaries => {
+                match plate_boundaries
+                    .and_then(|plate_boundaries| plate_boundaries.tiles.get(tile_index))
+                {
+                    Some(Some(BoundaryType::Convergent)) => [1.0, 0.1, 0.1, 1.0],
+                    Some(Some(BoundaryType::Divergent)) => [0.1, 0.6, 1.0, 1.0],
+                    Some(Some(BoundaryType::Transform)) => [1.0, 0.9, 0.2, 1.0],
+                    // Plate interiors stay dark so the boundaries stand out
+                    _ => [0.1, 0.1, 0.1, 1.0],
+                }
+            }
+            MapMode::Plates => tile_data
+                .and_then(|tile_data| {
+                    let plate = tile_data.plates.get(tile_index)?;
+                    tile_data.plate_colors.get(*plate).copied()
+                })
+                .unwrap_or([0.1, 0.1, 0.1, 1.0]),
+            MapMode::SpringStress => {
+                let stress = tile_data
+                    .and_then(|tile_data| tile_data.spring_stress.get(tile_index))
+                    .cloned()
+                    .unwrap_or(0.);
+                let t = normalized(stress, max_spring_stress);
+                // Black for relaxed springs, through purple to white for the most stretched or compressed
+                [t, t * t, 0.1 + 0.9 * t.sqrt(), 1.0]
+            }
+            MapMode::CrustAge => {
+                let age = tile_data
+                    .and_then(|tile_data| tile_data.crust_age.ages.get(tile_index))
+                    .cloned()
+                    .unwrap_or(0.);
+                let t = normalized(age, max_crust_age);
+                // Red for crust fresh from a rift, blue for the oldest
+                [1. - t, 0.2, t, 1.0]
+            }
+            MapMode::Trenches => {
+                let depth = tile_data
+                    .and_then(|tile_data| tile_data.trench_depths.get(tile_index))
+                    .cloned()
+                    .unwrap_or(0.);
+                let t = normalized(depth, max_trench_depth);
+                // Dark grey away from trenches, through purple to bright magenta for the deepest
+                [0.1 + 0.8 * t, 0.1, 0.1 + 0.6 * t, 1.0]
+            }
+            MapMode::Temperature => tile_data
+                .and_then(|tile_data| tile_data.temperature.get(tile_index))
+                .map_or([0.1, 0.1, 0.1, 1.0], |temperature| {
+                    temperature_ramp.sample(*temperature)
+                }),
+            MapMode::Precipitation => {
+                let precipitation = tile_data
+                    .and_then(|tile_data| tile_data.precipitation.get(tile_index))
+                    .cloned()
+                    .unwrap_or(0.);
+                let t = normalized(precipitation, max_precipitation).sqrt();
+                // Sandy for deserts, through green to deep blue for the wettest tiles
+                [0.8 * (1. - t), 0.7 - 0.3 * t, 0.3 + 0.6 * t, 1.0]
+            }
+        };
+        let center = tile.center;
+        for vertex_index in hex_sphere.tiles[tile_index].vertices.clone() {
+            hex_sphere.colors[vertex_index] = color;
+        }
+        hex_sphere.colors[center] = color;
+    }
+}
+
+/// C cycles through the modes, F1 to F9 select one directly
+fn cycle_map_mode(keys: Res<ButtonInput<KeyCode>>, mut map_mode: ResMut<MapMode>) {
+    if keys.just_pressed(KeyCode::KeyC) {
+        *map_mode = map_mode.next();
+    }
+    for (mode, key) in MapMode::ALL.into_iter().zip(MapMode::KEYS) {
+        if keys.just_pressed(key) && *map_mode != mode {
+            *map_mode = mode;
+        }
+    }
+}
+
+pub fn recolor(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut hex_sphere: ResMut<HexSphere>,
+    map_mode: Res<MapMode>,
+    color_ramp: Res<ColorRamp>,
+    strain_rate: Option<Res<StrainRate>>,
+    plate_boundaries: Option<Res<SimPlateBoundaries>>,
+    tile_data: Option<Res<TileData>>,
+    lakes: Option<Res<Lakes>>,
+    mesh_handle: Res<HexSphereMeshHandle>,
+    chunks: Res<HexSphereChunks>,
+) {
+    color_tiles(
+        &mut hex_sphere,
+        *map_mode,
+        &color_ramp,
+        strain_rate.as_deref(),
+        plate_boundaries
+            .as_deref()
+            .map(|plate_boundaries| &plate_boundaries.0),
+        tile_data.as_deref(),
+        lakes.as_deref(),
+    );
+    if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, hex_sphere.colors.clone());
+    }
+    chunks.patch_all(&mut meshes, &mesh_handle.0);
+}
@@ -0,0 +1,96 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use suz_sim::vec_utils;
+
+use crate::{
+    coastlines::{Coastlines, extract_coastlines},
+    hex_sphere::HexSphere,
+    states::SimulationState,
+};
+
+/// A connected landmass above sea level
+pub struct Continent {
+    /// Indices to the tiles making up the continent
+    pub tiles: Vec<usize>,
+    /// Surface area in steradians
+    pub area: f32,
+    /// Average tile normal, projected onto the unit sphere
+    pub centroid: Vec3,
+    /// Largest geodesic distance from the centroid to any tile of the continent
+    pub extent: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct Continents {
+    /// Continents sorted by descending area
+    pub continents: Vec<Continent>,
+    /// For each tile, the index of the continent it belongs to, [None] for ocean tiles
+    pub tile_to_continent: Vec<Option<usize>>,
+}
+
+impl Continents {
+    /// Labels every connected group of tiles at or above `sea_level` as a continent
+    pub fn from_hex_sphere(hex_sphere: &HexSphere, sea_level: f32) -> Self {
+        let tile_count = hex_sphere.tiles.len();
+        let tile_area = 4. * PI / tile_count as f32;
+        let mut visited = vec![false; tile_count];
+        let mut continents: Vec<Continent> = Vec::new();
+        for start in 0..tile_count {
+            let tiles = hex_sphere.flood_fill(start, &mut visited, |tile| tile.height >= sea_level);
+            if tiles.is_empty() {
+                continue;
+            }
+            let centroid = tiles
+                .iter()
+                .map(|&tile_index| hex_sphere.tiles[tile_index].normal)
+                .sum::<Vec3>()
+                .normalize_or(hex_sphere.tiles[tiles[0]].normal);
+            let extent = tiles
+                .iter()
+                .map(|&tile_index| {
+                    vec_utils::geodesic_distance(centroid, hex_sphere.tiles[tile_index].normal)
+                })
+                .fold(0., f32::max);
+            continents.push(Continent {
+                area: tiles.len() as f32 * tile_area,
+                tiles,
+                centroid,
+                extent,
+            });
+        }
+        continents.sort_by(|a, b| b.tiles.len().cmp(&a.tiles.len()));
+
+        let mut tile_to_continent = vec![None; tile_count];
+        for (continent_index, continent) in continents.iter().enumerate() {
+            for &tile_index in &continent.tiles {
+                tile_to_continent[tile_index] = Some(continent_index);
+            }
+        }
+        Continents {
+            continents,
+            tile_to_continent,
+        }
+    }
+}
+
+pub struct ContinentsPlugin;
+impl Plugin for ContinentsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Continents>().add_systems(
+            OnEnter(SimulationState::Erosion),
+            label_continents.after(extract_coastlines),
+        );
+    }
+}
+
+fn label_continents(
+    mut commands: Commands,
+    hex_sphere: Res<HexSphere>,
+    coastlines: Res<Coastlines>,
+) {
+    commands.insert_resource(Continents::from_hex_sphere(
+        &hex_sphere,
+        coastlines.sea_level,
+    ));
+}
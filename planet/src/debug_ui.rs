@@ -2,11 +2,25 @@ use std::time::Duration;
 
 use bevy::color::palettes;
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::spawn::SpawnIter;
 use bevy::prelude::*;
-use suz_sim::tectonics::Tectonics;
 
+use crate::coloring::MapMode;
+use crate::continents::Continents;
+use crate::energy::{ENERGY_HISTORY_LENGTH, EnergyHistory};
+use crate::hex_sphere::{CurrentMousePick, HexSphere};
+use crate::progress::StageProgress;
+use crate::regenerate::{RegenerateButton, RegenerateSeedInput};
+use crate::seed_input::SeedInput;
+use crate::sim_diagnostics::SimulationDiagnosticsPlugin;
+use crate::sparkline::{
+    SparklineValue, TimeSeries, TimeSeriesResource, sparkline, update_sparkline,
+};
 use crate::states::SimulationState;
-use crate::tectonics::TectonicsIteration;
+use crate::strain_rate::{STRAIN_HISTORY_LENGTH, StrainRate};
+use crate::tectonics::{
+    MAX_DRAWN_STRAIN, SimulationControl, TectonicsIteration, VelocityOverlay, strain_color,
+};
 
 #[derive(Copy, Clone)]
 pub struct DebugUIPlugin {
@@ -14,9 +28,19 @@ pub struct DebugUIPlugin {
 }
 impl Plugin for DebugUIPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(self.diagnostics);
+        app.insert_resource(self.diagnostics)
+            .init_resource::<FpsHistory>()
+            .init_resource::<IterationTimeHistory>()
+            .init_resource::<SpringStrainHistory>();
         app.add_systems(PreStartup, setup)
-            .add_systems(Update, update_fps)
+            .add_systems(
+                Update,
+                (update_fps, update_stage_progress, update_mesh_patch_time),
+            )
+            .add_systems(
+                Update,
+                update_seed.run_if(resource_changed::<DebugDiagnostics>),
+            )
             .add_systems(OnExit(SimulationState::MeshGen), add_mesh_gen_stats)
             .add_systems(OnExit(SimulationState::Tectonics), tectonics_add_time)
             .add_systems(
@@ -26,10 +50,104 @@ impl Plugin for DebugUIPlugin {
             .add_systems(
                 Update,
                 update_tectonics.run_if(in_state(SimulationState::Tectonics)),
+            )
+            .add_systems(
+                Update,
+                update_simulation_control.run_if(resource_changed::<SimulationControl>),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_velocity_overlay,
+                    update_velocity_overlay.run_if(resource_changed::<VelocityOverlay>),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                update_continents.run_if(resource_changed::<Continents>),
+            )
+            .add_systems(Update, update_map_mode.run_if(resource_changed::<MapMode>))
+            .add_systems(
+                Update,
+                update_picked_tile
+                    .run_if(resource_exists::<HexSphere>.and(resource_changed::<CurrentMousePick>)),
+            )
+            .add_systems(
+                Update,
+                update_strain_timeline.run_if(resource_exists_and_changed::<StrainRate>),
+            )
+            .add_systems(
+                Update,
+                update_energy_timeline.run_if(resource_exists_and_changed::<EnergyHistory>),
+            )
+            .add_systems(
+                Update,
+                (
+                    record_fps,
+                    record_simulation_performance.run_if(in_state(SimulationState::Tectonics)),
+                    update_sparkline::<FpsHistory>.run_if(resource_changed::<FpsHistory>),
+                    update_sparkline::<IterationTimeHistory>
+                        .run_if(resource_changed::<IterationTimeHistory>),
+                    update_sparkline::<SpringStrainHistory>
+                        .run_if(resource_changed::<SpringStrainHistory>),
+                )
+                    .chain(),
             );
     }
 }
 
+/// How many frames the performance sparklines cover
+const SPARKLINE_LENGTH: usize = 200;
+
+/// Smoothed frames per second of the latest frames
+#[derive(Resource)]
+struct FpsHistory(TimeSeries);
+
+impl Default for FpsHistory {
+    fn default() -> Self {
+        FpsHistory(TimeSeries::new(SPARKLINE_LENGTH))
+    }
+}
+
+impl TimeSeriesResource for FpsHistory {
+    fn time_series(&self) -> &TimeSeries {
+        &self.0
+    }
+}
+
+/// Milliseconds per tectonic iteration during the latest frames of the simulation
+#[derive(Resource)]
+struct IterationTimeHistory(TimeSeries);
+
+impl Default for IterationTimeHistory {
+    fn default() -> Self {
+        IterationTimeHistory(TimeSeries::new(SPARKLINE_LENGTH))
+    }
+}
+
+impl TimeSeriesResource for IterationTimeHistory {
+    fn time_series(&self) -> &TimeSeries {
+        &self.0
+    }
+}
+
+/// Summed absolute strain of every spring during the latest frames of the simulation
+#[derive(Resource)]
+struct SpringStrainHistory(TimeSeries);
+
+impl Default for SpringStrainHistory {
+    fn default() -> Self {
+        SpringStrainHistory(TimeSeries::new(SPARKLINE_LENGTH))
+    }
+}
+
+impl TimeSeriesResource for SpringStrainHistory {
+    fn time_series(&self) -> &TimeSeries {
+        &self.0
+    }
+}
+
 #[derive(Resource, Copy, Clone)]
 pub struct DebugDiagnostics {
     pub seed: u64,
@@ -58,7 +176,11 @@ struct StateText;
 struct FpsText;
 
 #[derive(Component)]
-struct SeedText;
+struct StageProgressText;
+
+/// Filled up to [StageProgress::fraction]
+#[derive(Component)]
+struct StageProgressBar;
 
 #[derive(Component)]
 struct SubdivisionsText;
@@ -69,15 +191,58 @@ struct TileAmountText;
 #[derive(Component)]
 struct MeshGenerationTimeText;
 
+#[derive(Component)]
+struct MeshPatchTimeText;
+
 #[derive(Component)]
 struct TectonicsPointMassText;
 
 #[derive(Component)]
 struct TectonicsIterationText;
 
+#[derive(Component)]
+struct SimulationControlText;
+
 #[derive(Component)]
 struct TectonicsTimeText;
 
+/// Average spring strain next to the range of the strain legend
+#[derive(Component)]
+struct AverageStrainText;
+
+#[derive(Component)]
+struct ContinentCountText;
+
+#[derive(Component)]
+struct MapModeText;
+
+#[derive(Component)]
+struct PickedTileText;
+
+#[derive(Component)]
+struct StrainRateText;
+
+/// Color steps in the spring strain legend
+const STRAIN_LEGEND_STEPS: usize = 20;
+
+/// Toggles [VelocityOverlay]
+#[derive(Component)]
+struct VelocityOverlayButton;
+
+#[derive(Component)]
+struct VelocityOverlayText;
+
+/// Bar in the strain rate timeline, holds its index into [StrainRate::history]
+#[derive(Component)]
+struct StrainTimelineBar(usize);
+
+#[derive(Component)]
+struct EnergyText;
+
+/// Bar in the energy timeline, holds its index into [EnergyHistory::history]
+#[derive(Component)]
+struct EnergyTimelineBar(usize);
+
 fn add_thousands_seperator(input: String) -> String {
     input
         .as_bytes()
@@ -101,6 +266,57 @@ fn update_fps(
     }
 }
 
+fn record_fps(bevy_diagnostics: Res<DiagnosticsStore>, mut fps_history: ResMut<FpsHistory>) {
+    if let Some(fps) = bevy_diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+    {
+        fps_history.0.push(fps as f32);
+    }
+}
+
+/// Read from the [SimulationDiagnosticsPlugin] diagnostics, frames without finished iterations are skipped
+fn record_simulation_performance(
+    bevy_diagnostics: Res<DiagnosticsStore>,
+    mut iteration_time_history: ResMut<IterationTimeHistory>,
+    mut spring_strain_history: ResMut<SpringStrainHistory>,
+) {
+    if let Some(iterations_per_second) = bevy_diagnostics
+        .get(&SimulationDiagnosticsPlugin::ITERATIONS_PER_SECOND)
+        .and_then(|diagnostic| diagnostic.smoothed())
+    {
+        if iterations_per_second > 0. {
+            iteration_time_history
+                .0
+                .push((1000. / iterations_per_second) as f32);
+        }
+    }
+    let value = |path| {
+        bevy_diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.value())
+    };
+    if let (Some(average_strain), Some(springs)) = (
+        value(&SimulationDiagnosticsPlugin::AVERAGE_STRAIN),
+        value(&SimulationDiagnosticsPlugin::SPRINGS),
+    ) {
+        spring_strain_history
+            .0
+            .push((average_strain * springs) as f32);
+    }
+}
+
+/// Shows the seed of the current planet, unless the user is typing a new one
+fn update_seed(
+    diagnostics: Res<DebugDiagnostics>,
+    mut seed_input_query: Query<&mut SeedInput, With<RegenerateSeedInput>>,
+) {
+    let mut seed_input = seed_input_query.single_mut().unwrap();
+    if !seed_input.editing() {
+        seed_input.seed = diagnostics.seed;
+    }
+}
+
 fn update_state_text(
     mut state_text_query: Query<&mut Text, With<StateText>>,
     current_state: Res<State<SimulationState>>,
@@ -108,6 +324,26 @@ fn update_state_text(
     **state_text_query.single_mut().unwrap() = current_state.to_string();
 }
 
+fn update_stage_progress(
+    stage_progress: Res<StageProgress>,
+    mut stage_progress_query: Query<&mut Text, With<StageProgressText>>,
+    mut bars: Query<&mut Node, With<StageProgressBar>>,
+) {
+    let fraction = stage_progress.fraction();
+    **stage_progress_query.single_mut().unwrap() = match stage_progress.remaining() {
+        _ if fraction >= 1. => "100%".to_string(),
+        Some(remaining) => format!(
+            "{:.0}% ({:.1}s left)",
+            fraction * 100.,
+            remaining.as_secs_f32()
+        ),
+        None => "-".to_string(),
+    };
+    for mut node in &mut bars {
+        node.width = Val::Percent(fraction * 100.);
+    }
+}
+
 fn tectonics_add_time(
     diagnostics: Res<DebugDiagnostics>,
     mut tectonics_time_query: Query<&mut Text, With<TectonicsTimeText>>,
@@ -150,23 +386,152 @@ fn add_mesh_gen_stats(
         .to_string();
 }
 
+/// Fed from the [SimulationDiagnosticsPlugin] diagnostics, rows stay unchanged until they have a measurement
 fn update_tectonics(
-    tectonics: Res<Tectonics>,
+    bevy_diagnostics: Res<DiagnosticsStore>,
     tectonics_iteration: Res<TectonicsIteration>,
     mut texts: ParamSet<(
         Query<&mut Text, With<TectonicsPointMassText>>,
         Query<&mut Text, With<TectonicsIterationText>>,
+        Query<&mut Text, With<AverageStrainText>>,
     )>,
 ) {
-    **texts.p0().single_mut().unwrap() = add_thousands_seperator(
-        tectonics
-            .plates
-            .iter()
-            .map(|plate| plate.shape.point_masses.len())
-            .sum::<usize>()
-            .to_string(),
-    );
-    **texts.p1().single_mut().unwrap() = add_thousands_seperator(tectonics_iteration.0.to_string());
+    let value = |path| {
+        bevy_diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.value())
+    };
+    if let (Some(point_masses), Some(springs)) = (
+        value(&SimulationDiagnosticsPlugin::POINT_MASSES),
+        value(&SimulationDiagnosticsPlugin::SPRINGS),
+    ) {
+        **texts.p0().single_mut().unwrap() = format!(
+            "{} ({} springs)",
+            add_thousands_seperator((point_masses as usize).to_string()),
+            add_thousands_seperator((springs as usize).to_string())
+        );
+    }
+    let iteration = add_thousands_seperator(tectonics_iteration.0.to_string());
+    **texts.p1().single_mut().unwrap() = match bevy_diagnostics
+        .get(&SimulationDiagnosticsPlugin::ITERATIONS_PER_SECOND)
+        .and_then(|diagnostic| diagnostic.smoothed())
+    {
+        Some(iterations_per_second) => format!("{iteration} ({iterations_per_second:.0}/s)"),
+        None => iteration,
+    };
+    if let Some(average_strain) = value(&SimulationDiagnosticsPlugin::AVERAGE_STRAIN) {
+        **texts.p2().single_mut().unwrap() = format!(
+            "{:.1}% avg, 0-{:.0}%",
+            average_strain * 100.,
+            MAX_DRAWN_STRAIN * 100.
+        );
+    }
+}
+
+fn update_mesh_patch_time(
+    bevy_diagnostics: Res<DiagnosticsStore>,
+    mut mesh_patch_time_query: Query<&mut Text, With<MeshPatchTimeText>>,
+) {
+    if let Some(milliseconds) = bevy_diagnostics
+        .get(&SimulationDiagnosticsPlugin::MESH_PATCH_TIME)
+        .and_then(|diagnostic| diagnostic.smoothed())
+    {
+        **mesh_patch_time_query.single_mut().unwrap() = format!("{milliseconds:.1}ms");
+    }
+}
+
+fn update_simulation_control(
+    simulation_control: Res<SimulationControl>,
+    mut simulation_control_query: Query<&mut Text, With<SimulationControlText>>,
+) {
+    **simulation_control_query.single_mut().unwrap() = simulation_control.to_string();
+}
+
+fn toggle_velocity_overlay(
+    interactions: Query<&Interaction, (With<VelocityOverlayButton>, Changed<Interaction>)>,
+    mut velocity_overlay: ResMut<VelocityOverlay>,
+) {
+    if interactions
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        velocity_overlay.0 = !velocity_overlay.0;
+    }
+}
+
+fn update_velocity_overlay(
+    velocity_overlay: Res<VelocityOverlay>,
+    mut velocity_overlay_query: Query<&mut Text, With<VelocityOverlayText>>,
+) {
+    **velocity_overlay_query.single_mut().unwrap() =
+        if velocity_overlay.0 { "On" } else { "Off" }.to_string();
+}
+
+fn update_continents(
+    continents: Res<Continents>,
+    mut continent_count_query: Query<&mut Text, With<ContinentCountText>>,
+) {
+    **continent_count_query.single_mut().unwrap() =
+        add_thousands_seperator(continents.continents.len().to_string());
+}
+
+fn update_map_mode(
+    map_mode: Res<MapMode>,
+    mut map_mode_query: Query<&mut Text, With<MapModeText>>,
+) {
+    **map_mode_query.single_mut().unwrap() = map_mode.to_string();
+}
+
+fn update_picked_tile(
+    hex_sphere: Res<HexSphere>,
+    current_mouse_pick: Res<CurrentMousePick>,
+    mut picked_tile_query: Query<&mut Text, With<PickedTileText>>,
+) {
+    **picked_tile_query.single_mut().unwrap() = match &current_mouse_pick.pick {
+        Some(mouse_pick) => {
+            let coord = hex_sphere.coords.from_index(mouse_pick.tile.index);
+            format!("{} ({}, {})", coord.face, coord.axial.x, coord.axial.y)
+        }
+        None => "-".to_string(),
+    };
+}
+
+fn update_strain_timeline(
+    strain_rate: Res<StrainRate>,
+    mut strain_rate_query: Query<&mut Text, With<StrainRateText>>,
+    mut bars: Query<(&StrainTimelineBar, &mut Node)>,
+) {
+    **strain_rate_query.single_mut().unwrap() = format!("{:.5}", strain_rate.tracker.global_rate);
+    let max_rate = strain_rate.history.iter().cloned().fold(0., f32::max);
+    for (bar, mut node) in &mut bars {
+        let rate = strain_rate.history.get(bar.0).cloned().unwrap_or(0.);
+        node.height = Val::Percent(if max_rate > 0. {
+            rate / max_rate * 100.
+        } else {
+            0.
+        });
+    }
+}
+
+/// Bars are scaled to the highest energy in the history, a blow-up flattens everything before it
+fn update_energy_timeline(
+    energy_history: Res<EnergyHistory>,
+    mut energy_query: Query<&mut Text, With<EnergyText>>,
+    mut bars: Query<(&EnergyTimelineBar, &mut Node)>,
+) {
+    **energy_query.single_mut().unwrap() = match energy_history.history.back() {
+        Some(energy) => format!("{energy:.5}"),
+        None => "-".to_string(),
+    };
+    let max_energy = energy_history.history.iter().cloned().fold(0., f32::max);
+    for (bar, mut node) in &mut bars {
+        let energy = energy_history.history.get(bar.0).cloned().unwrap_or(0.);
+        node.height = Val::Percent(if max_energy > 0. {
+            energy / max_energy * 100.
+        } else {
+            0.
+        });
+    }
 }
 
 fn setup(
@@ -243,17 +608,37 @@ fn setup(
                                     margin: UiRect::left(Val::Auto),
                                     ..Default::default()
                                 },
-                                Text::new(diagnostics.seed.to_string()),
+                                SeedInput::new(diagnostics.seed),
                                 TextFont {
                                     font: asset_server.load("fonts/FiraMono-Medium.ttf"),
                                     font_size: 12.0,
                                     ..Default::default()
                                 },
                                 TextColor(palettes::css::GOLD.into()),
-                                SeedText
+                                RegenerateSeedInput
                             )
                         ]
                     ),
+                    (
+                        Button,
+                        RegenerateButton,
+                        Node {
+                            margin: UiRect::left(Val::Auto),
+                            padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                            border: UiRect::all(Val::Px(1.)),
+                            ..Default::default()
+                        },
+                        BorderColor(LinearRgba::new(0.4, 0.4, 0.4, 1.).into()),
+                        BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 1.).into()),
+                        children![(
+                            Text::new("Regenerate"),
+                            TextFont {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 12.0,
+                                ..default()
+                            }
+                        )]
+                    ),
                     (
                         Node {
                             width: Val::Percent(100.),
@@ -284,34 +669,6 @@ fn setup(
                             )
                         ]
                     ),
-                ]
-            ),
-            (
-                Node {
-                    padding: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(5.), Val::Px(5.)),
-                    border: UiRect::bottom(Val::Px(1.)),
-                    flex_direction: FlexDirection::Column,
-                    ..Default::default()
-                },
-                BorderColor(LinearRgba::new(0.2, 0.2, 0.2, 0.8).into()),
-                children![
-                    (
-                        Node {
-                            width: Val::Percent(100.),
-                            display: Display::Flex,
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                            ..Default::default()
-                        },
-                        children![(
-                            Text::new("Mesh generation"),
-                            TextFont {
-                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                                font_size: 14.0,
-                                ..default()
-                            }
-                        ),]
-                    ),
                     (
                         Node {
                             width: Val::Percent(100.),
@@ -319,7 +676,7 @@ fn setup(
                         },
                         children![
                             (
-                                Text::new("Subdivisions: "),
+                                Text::new("Progress: "),
                                 TextFont {
                                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                     font_size: 12.0,
@@ -338,10 +695,28 @@ fn setup(
                                     ..Default::default()
                                 },
                                 TextColor(palettes::css::GOLD.into()),
-                                SubdivisionsText,
+                                StageProgressText
                             )
                         ]
                     ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            height: Val::Px(4.),
+                            margin: UiRect::vertical(Val::Px(2.)),
+                            ..Default::default()
+                        },
+                        BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 0.8).into()),
+                        children![(
+                            Node {
+                                width: Val::Percent(0.),
+                                height: Val::Percent(100.),
+                                ..Default::default()
+                            },
+                            BackgroundColor(palettes::css::GOLD.into()),
+                            StageProgressBar
+                        )]
+                    ),
                     (
                         Node {
                             width: Val::Percent(100.),
@@ -349,7 +724,7 @@ fn setup(
                         },
                         children![
                             (
-                                Text::new("Tiles: "),
+                                Text::new("Map mode: "),
                                 TextFont {
                                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                     font_size: 12.0,
@@ -368,7 +743,7 @@ fn setup(
                                     ..Default::default()
                                 },
                                 TextColor(palettes::css::GOLD.into()),
-                                TileAmountText
+                                MapModeText
                             )
                         ]
                     ),
@@ -379,7 +754,7 @@ fn setup(
                         },
                         children![
                             (
-                                Text::new("Time: "),
+                                Text::new("Tile: "),
                                 TextFont {
                                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                     font_size: 12.0,
@@ -391,14 +766,14 @@ fn setup(
                                     margin: UiRect::left(Val::Auto),
                                     ..Default::default()
                                 },
-                                Text::default(),
+                                Text::new("-"),
                                 TextFont {
                                     font: asset_server.load("fonts/FiraMono-Medium.ttf"),
                                     font_size: 12.0,
                                     ..Default::default()
                                 },
                                 TextColor(palettes::css::GOLD.into()),
-                                MeshGenerationTimeText
+                                PickedTileText
                             )
                         ]
                     ),
@@ -422,7 +797,7 @@ fn setup(
                             ..Default::default()
                         },
                         children![(
-                            Text::new("Tectonic simulation"),
+                            Text::new("Mesh generation"),
                             TextFont {
                                 font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                 font_size: 14.0,
@@ -437,7 +812,7 @@ fn setup(
                         },
                         children![
                             (
-                                Text::new("Point masses: "),
+                                Text::new("Subdivisions: "),
                                 TextFont {
                                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                     font_size: 12.0,
@@ -456,7 +831,7 @@ fn setup(
                                     ..Default::default()
                                 },
                                 TextColor(palettes::css::GOLD.into()),
-                                TectonicsPointMassText
+                                SubdivisionsText,
                             )
                         ]
                     ),
@@ -467,7 +842,7 @@ fn setup(
                         },
                         children![
                             (
-                                Text::new("Iteration: "),
+                                Text::new("Tiles: "),
                                 TextFont {
                                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                     font_size: 12.0,
@@ -486,7 +861,7 @@ fn setup(
                                     ..Default::default()
                                 },
                                 TextColor(palettes::css::GOLD.into()),
-                                TectonicsIterationText
+                                TileAmountText
                             )
                         ]
                     ),
@@ -516,10 +891,40 @@ fn setup(
                                     ..Default::default()
                                 },
                                 TextColor(palettes::css::GOLD.into()),
-                                TectonicsTimeText
+                                MeshGenerationTimeText
                             )
                         ]
-                    )
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Patch time: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::new("-"),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                MeshPatchTimeText
+                            )
+                        ]
+                    ),
                 ]
             ),
             (
@@ -530,23 +935,521 @@ fn setup(
                     ..Default::default()
                 },
                 BorderColor(LinearRgba::new(0.2, 0.2, 0.2, 0.8).into()),
-                children![(
-                    Node {
-                        width: Val::Percent(100.),
-                        display: Display::Flex,
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        ..Default::default()
-                    },
-                    children![(
-                        Text::new("Erosion simulation"),
-                        TextFont {
-                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                            font_size: 14.0,
-                            ..default()
-                        }
-                    ),]
-                ),]
+                children![
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            display: Display::Flex,
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
+                        },
+                        children![(
+                            Text::new("Tectonic simulation"),
+                            TextFont {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 14.0,
+                                ..default()
+                            }
+                        ),]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Point masses: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::default(),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                TectonicsPointMassText
+                            )
+                        ]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Iteration: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::default(),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                TectonicsIterationText
+                            )
+                        ]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Simulation: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::default(),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                SimulationControlText
+                            )
+                        ]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Time: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::default(),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                TectonicsTimeText
+                            )
+                        ]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Strain rate: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::default(),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                StrainRateText
+                            )
+                        ]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Velocities: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Button,
+                                VelocityOverlayButton,
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    padding: UiRect::axes(Val::Px(6.), Val::Px(2.)),
+                                    border: UiRect::all(Val::Px(1.)),
+                                    ..Default::default()
+                                },
+                                BorderColor(LinearRgba::new(0.4, 0.4, 0.4, 1.).into()),
+                                BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 1.).into()),
+                                children![(
+                                    Text::default(),
+                                    TextFont {
+                                        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                        font_size: 12.0,
+                                        ..Default::default()
+                                    },
+                                    TextColor(palettes::css::GOLD.into()),
+                                    VelocityOverlayText
+                                )]
+                            )
+                        ]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Spring strain: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::new(format!("0-{:.0}%", MAX_DRAWN_STRAIN * 100.)),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                AverageStrainText
+                            )
+                        ]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            height: Val::Px(8.),
+                            ..Default::default()
+                        },
+                        Children::spawn(SpawnIter((0..STRAIN_LEGEND_STEPS).map(|i| {
+                            let strain =
+                                (i as f32 + 0.5) / STRAIN_LEGEND_STEPS as f32 * MAX_DRAWN_STRAIN;
+                            (
+                                Node {
+                                    width: Val::Percent(100. / STRAIN_LEGEND_STEPS as f32),
+                                    height: Val::Percent(100.),
+                                    ..Default::default()
+                                },
+                                BackgroundColor(strain_color(strain).with_alpha(1.)),
+                            )
+                        })))
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            height: Val::Px(40.),
+                            margin: UiRect::top(Val::Px(5.)),
+                            align_items: AlignItems::End,
+                            ..Default::default()
+                        },
+                        BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 0.8).into()),
+                        Children::spawn(SpawnIter((0..STRAIN_HISTORY_LENGTH).map(|i| {
+                            (
+                                Node {
+                                    width: Val::Percent(100. / STRAIN_HISTORY_LENGTH as f32),
+                                    height: Val::Percent(0.),
+                                    ..Default::default()
+                                },
+                                BackgroundColor(palettes::css::GOLD.into()),
+                                StrainTimelineBar(i),
+                            )
+                        })))
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            margin: UiRect::top(Val::Px(5.)),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Total energy: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::default(),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                EnergyText
+                            )
+                        ]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            height: Val::Px(40.),
+                            margin: UiRect::top(Val::Px(5.)),
+                            align_items: AlignItems::End,
+                            ..Default::default()
+                        },
+                        BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 0.8).into()),
+                        Children::spawn(SpawnIter((0..ENERGY_HISTORY_LENGTH).map(|i| {
+                            (
+                                Node {
+                                    width: Val::Percent(100. / ENERGY_HISTORY_LENGTH as f32),
+                                    height: Val::Percent(0.),
+                                    ..Default::default()
+                                },
+                                BackgroundColor(palettes::css::AQUA.into()),
+                                EnergyTimelineBar(i),
+                            )
+                        })))
+                    )
+                ]
+            ),
+            (
+                Node {
+                    padding: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(5.), Val::Px(5.)),
+                    border: UiRect::bottom(Val::Px(1.)),
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                BorderColor(LinearRgba::new(0.2, 0.2, 0.2, 0.8).into()),
+                children![
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            display: Display::Flex,
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
+                        },
+                        children![(
+                            Text::new("Erosion simulation"),
+                            TextFont {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 14.0,
+                                ..default()
+                            }
+                        ),]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Continents: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::default(),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                ContinentCountText
+                            )
+                        ]
+                    )
+                ]
+            ),
+            (
+                Node {
+                    padding: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(5.), Val::Px(5.)),
+                    border: UiRect::bottom(Val::Px(1.)),
+                    flex_direction: FlexDirection::Column,
+                    ..Default::default()
+                },
+                BorderColor(LinearRgba::new(0.2, 0.2, 0.2, 0.8).into()),
+                children![
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            display: Display::Flex,
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::Center,
+                            ..Default::default()
+                        },
+                        children![(
+                            Text::new("Performance"),
+                            TextFont {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 14.0,
+                                ..default()
+                            }
+                        ),]
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            margin: UiRect::top(Val::Px(5.)),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Frame rate: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::new("-"),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                SparklineValue::<FpsHistory>::new(0, " fps")
+                            )
+                        ]
+                    ),
+                    sparkline::<FpsHistory>(SPARKLINE_LENGTH, palettes::css::LIME.into()),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            margin: UiRect::top(Val::Px(5.)),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Iteration time: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::new("-"),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                SparklineValue::<IterationTimeHistory>::new(2, "ms")
+                            )
+                        ]
+                    ),
+                    sparkline::<IterationTimeHistory>(
+                        SPARKLINE_LENGTH,
+                        palettes::css::ORANGE.into()
+                    ),
+                    (
+                        Node {
+                            width: Val::Percent(100.),
+                            margin: UiRect::top(Val::Px(5.)),
+                            ..Default::default()
+                        },
+                        children![
+                            (
+                                Text::new("Total strain: "),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                    font_size: 12.0,
+                                    ..default()
+                                }
+                            ),
+                            (
+                                Node {
+                                    margin: UiRect::left(Val::Auto),
+                                    ..Default::default()
+                                },
+                                Text::new("-"),
+                                TextFont {
+                                    font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                                    font_size: 12.0,
+                                    ..Default::default()
+                                },
+                                TextColor(palettes::css::GOLD.into()),
+                                SparklineValue::<SpringStrainHistory>::new(2, "")
+                            )
+                        ]
+                    ),
+                    sparkline::<SpringStrainHistory>(SPARKLINE_LENGTH, palettes::css::GOLD.into()),
+                ]
             )
         ],
     ));
@@ -0,0 +1,65 @@
+use bevy::prelude::*;
+use rand::Rng;
+use suz_sim::{detail::apply_detail, interpolation::interpolate_tile_uplift};
+
+use crate::{
+    GlobalRng,
+    chunks::HexSphereChunks,
+    hex_sphere::{HexSphere, HexSphereMeshHandle},
+    ice::apply_glaciation,
+    persistence::LoadedPlanet,
+    sim_resources::{SimEpochSchedule, SimTectonics},
+    states::SimulationState,
+    tectonics::TectonicsPluginConfig,
+    vertex_interpolation::apply_tile_heights,
+};
+
+/// Layers ridged noise onto the finished terrain, see [TectonicsPluginConfig::detail_config]
+pub struct DetailPlugin;
+impl Plugin for DetailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(SimulationState::Erosion),
+            apply_detail_pass.after(apply_glaciation),
+        );
+    }
+}
+
+/// Runs once after the erosion of the last epoch, earlier epochs would erode the detail away.
+/// Loaded planets were saved with their detail, and have no tectonics to take the uplift from.
+fn apply_detail_pass(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut hex_sphere: ResMut<HexSphere>,
+    mut rng: ResMut<GlobalRng>,
+    config: Res<TectonicsPluginConfig>,
+    tectonics: Option<Res<SimTectonics>>,
+    loaded_planet: Option<Res<LoadedPlanet>>,
+    epoch_schedule: Option<Res<SimEpochSchedule>>,
+    mesh_handle: Res<HexSphereMeshHandle>,
+    chunks: Res<HexSphereChunks>,
+) {
+    let detail_config = config.detail_config;
+    if !detail_config.enabled()
+        || loaded_planet.is_some()
+        || epoch_schedule.is_some_and(|epoch_schedule| !epoch_schedule.is_last())
+    {
+        return;
+    }
+    let Some(tectonics) = tectonics else {
+        return;
+    };
+    let normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+    let uplift = interpolate_tile_uplift(&tectonics, &normals);
+    let mut heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+    apply_detail(
+        &detail_config,
+        &mut heights,
+        &normals,
+        &uplift,
+        rng.0.random(),
+    );
+    if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+        apply_tile_heights(&mut hex_sphere, mesh, &heights);
+    }
+    chunks.patch_all(&mut meshes, &mesh_handle.0);
+}
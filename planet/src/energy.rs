@@ -0,0 +1,33 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+/// How many iterations of total energy are kept for the timeline
+pub const ENERGY_HISTORY_LENGTH: usize = 100;
+
+/// Total energy of the tectonic simulation over the latest iterations, see [suz_sim::tectonics::Tectonics::total_energy]
+#[derive(Resource)]
+pub struct EnergyHistory {
+    /// Oldest first
+    pub history: VecDeque<f32>,
+}
+
+impl Default for EnergyHistory {
+    fn default() -> Self {
+        EnergyHistory {
+            history: VecDeque::with_capacity(ENERGY_HISTORY_LENGTH),
+        }
+    }
+}
+
+impl EnergyHistory {
+    /// `energies` holds the total energy of each step since the last record
+    pub fn record(&mut self, energies: &[f32]) {
+        for &energy in energies {
+            if self.history.len() == ENERGY_HISTORY_LENGTH {
+                self.history.pop_front();
+            }
+            self.history.push_back(energy);
+        }
+    }
+}
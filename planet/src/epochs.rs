@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use suz_sim::{
+    epochs::EpochSchedule,
+    observer::{Control, Stage},
+};
+
+use crate::{
+    persistence::LoadedPlanet,
+    progress::StageProgress,
+    sim_resources::{SimEpochSchedule, SimObservers},
+    states::SimulationState,
+    tectonics::TectonicsPluginConfig,
+};
+
+/// Loops the pipeline back from [SimulationState::Erosion] to [SimulationState::Tectonics] until every epoch of [TectonicsPluginConfig::epoch_config] ran
+pub struct EpochsPlugin;
+impl Plugin for EpochsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(SimulationState::MeshGen), start_epochs)
+            .add_systems(
+                Update,
+                finish_erosion.run_if(in_state(SimulationState::Erosion)),
+            );
+    }
+}
+
+/// Run condition for setup that only happens when the tectonic simulation first starts, not when a later epoch resumes it
+pub fn first_epoch(epoch_schedule: Option<Res<SimEpochSchedule>>) -> bool {
+    epoch_schedule.is_none_or(|epoch_schedule| epoch_schedule.epoch == 0)
+}
+
+/// Loaded planets skip the tectonic simulation, so they have no epochs to run
+fn start_epochs(
+    mut commands: Commands,
+    config: Res<TectonicsPluginConfig>,
+    loaded_planet: Option<Res<LoadedPlanet>>,
+) {
+    if loaded_planet.is_some() {
+        commands.remove_resource::<SimEpochSchedule>();
+    } else {
+        commands.insert_resource(SimEpochSchedule(EpochSchedule::new(config.epoch_config)));
+    }
+}
+
+/// Runs the frame after the erosion of an epoch, once every [OnEnter] erosion system is done.
+/// Finished erosion is marked by a full [StageProgress], so it is only reported once.
+fn finish_erosion(
+    mut stage_progress: ResMut<StageProgress>,
+    mut observers: ResMut<SimObservers>,
+    epoch_schedule: Option<ResMut<SimEpochSchedule>>,
+    mut next_state: ResMut<NextState<SimulationState>>,
+) {
+    if stage_progress.fraction() >= 1. {
+        return;
+    }
+    stage_progress.set(1.);
+    if observers.on_stage_complete(Stage::Erosion, stage_progress.elapsed()) == Control::Abort {
+        warn!("Generation aborted by an observer after erosion");
+        next_state.set(SimulationState::Menu);
+        return;
+    }
+    let Some(mut epoch_schedule) = epoch_schedule else {
+        return;
+    };
+    if epoch_schedule.is_last() {
+        return;
+    }
+    epoch_schedule.advance();
+    info!(
+        "Starting epoch {} of {}",
+        epoch_schedule.epoch + 1,
+        epoch_schedule.config.epochs
+    );
+    next_state.set(SimulationState::Tectonics);
+}
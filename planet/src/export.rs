@@ -0,0 +1,658 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use bevy::{
+    prelude::*,
+    render::mesh::{MeshVertexAttribute, VertexAttributeValues},
+};
+use rayon::prelude::*;
+use suz_sim::{
+    bake::{BakeConfig, bake_ambient_occlusion, bake_normal_map},
+    boundaries::{BoundaryType, PlateBoundaries},
+    export::{ProjectedLayer, Projection, TileLayer, project_layer},
+    geojson::{self, Feature, Geometry},
+    history::{HistoryFrame, TectonicsHistory},
+    interpolation::containing_plates,
+    plate::PlateType,
+    tectonics::Tectonics,
+    units::PhysicalUnits,
+};
+
+use crate::{
+    coastlines::{Coastlines, region_outlines},
+    hex_sphere::{HexSphere, HexSphereMeshHandle},
+    persistence::LoadedPlanet,
+    sim_resources::{SimPlateBoundaries, SimTectonics, SimTectonicsHistory, plate_color},
+    states::SimulationState,
+    tectonics::TectonicsPluginConfig,
+};
+
+pub const HEIGHTMAP_PATH: &str = "heightmap.png";
+pub const PLATE_MAP_PATH: &str = "plates.png";
+pub const GLB_PATH: &str = "planet.glb";
+pub const GEOJSON_PATH: &str = "planet.geojson";
+pub const NORMAL_MAP_PATH: &str = "normal_map.png";
+pub const AMBIENT_OCCLUSION_PATH: &str = "ambient_occlusion.png";
+/// Time-lapse frames are written here as `frame_00000.png`, `frame_00001.png`, ...
+pub const TIMELAPSE_DIRECTORY: &str = "timelapse";
+
+#[derive(Resource, Clone, Copy)]
+pub struct ExportConfig {
+    /// Resolution of the exported heightmap and plate map, see [project_layer]
+    pub heightmap_width: u32,
+    /// Projection of the exported heightmap and plate map
+    pub map_projection: Projection,
+    /// Baking of the normal and ambient occlusion maps, exported equirectangular at [ExportConfig::heightmap_width]
+    pub bake: BakeConfig,
+    /// Include a `_PLATE_ID` vertex attribute in the exported GLB
+    pub glb_plate_ids: bool,
+    /// Width in pixels of the exported time-lapse frames, the height is half of this
+    pub timelapse_width: u32,
+    /// Iterations between exported time-lapse frames, rounded up to the recorded history interval
+    pub timelapse_every: usize,
+}
+
+pub struct ExportPlugin {
+    pub config: ExportConfig,
+}
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config).add_systems(
+            Update,
+            export_system.run_if(in_state(SimulationState::Erosion)),
+        );
+    }
+}
+
+/// Index of the tile under every pixel of `projection` at `resolution`
+fn projected_tiles(
+    hex_sphere: &HexSphere,
+    projection: Projection,
+    resolution: u32,
+) -> ProjectedLayer<usize> {
+    let tiles: Vec<usize> = (0..hex_sphere.tiles.len()).collect();
+    let layer = TileLayer {
+        subsphere: &hex_sphere.subsphere,
+        values: &tiles,
+    };
+    project_layer(&layer, projection, resolution)
+}
+
+fn write_gray16(
+    image: &ProjectedLayer<u16>,
+    path: impl AsRef<Path>,
+) -> Result<(), png::EncodingError> {
+    // PNG stores 16-bit samples big-endian, pixels outside the projection are black
+    let data: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.unwrap_or(0).to_be_bytes())
+        .collect();
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        image.width,
+        image.height,
+    );
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    writer.finish()
+}
+
+/// Samples tile heights in `projection` and writes them as a 16-bit grayscale PNG.
+/// Heights are normalized so the lowest tile is black and the highest is white.
+/// Returns the elevations in metres of black and white, to map the image back to physical units.
+pub fn export_heightmap(
+    hex_sphere: &HexSphere,
+    projection: Projection,
+    resolution: u32,
+    units: PhysicalUnits,
+    sea_level: f32,
+    path: impl AsRef<Path>,
+) -> Result<(f32, f32), png::EncodingError> {
+    let heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+    let layer = TileLayer {
+        subsphere: &hex_sphere.subsphere,
+        values: &heights,
+    };
+    let samples = project_layer(&layer, projection, resolution);
+    let (min, max) = samples
+        .pixels
+        .iter()
+        .flatten()
+        .fold((f32::MAX, f32::MIN), |(min, max), &h| {
+            (min.min(h), max.max(h))
+        });
+    let range = (max - min).max(f32::EPSILON);
+    let image = ProjectedLayer {
+        width: samples.width,
+        height: samples.height,
+        pixels: samples
+            .pixels
+            .iter()
+            .map(|h| h.map(|h| ((h - min) / range * u16::MAX as f32) as u16))
+            .collect(),
+    };
+    write_gray16(&image, path)?;
+    Ok((
+        units.elevation_m(min, sea_level),
+        units.elevation_m(max, sea_level),
+    ))
+}
+
+/// Writes the plate ID of every tile in `projection` as a 16-bit grayscale PNG, plate `n` is stored as `n + 1` so black stays off the map
+pub fn export_plate_map(
+    hex_sphere: &HexSphere,
+    tile_plates: &[u32],
+    projection: Projection,
+    resolution: u32,
+    path: impl AsRef<Path>,
+) -> Result<(), png::EncodingError> {
+    let ids: Vec<u16> = tile_plates
+        .iter()
+        .map(|&plate| (plate + 1).min(u16::MAX as u32) as u16)
+        .collect();
+    let layer = TileLayer {
+        subsphere: &hex_sphere.subsphere,
+        values: &ids,
+    };
+    write_gray16(&project_layer(&layer, projection, resolution), path)
+}
+
+fn write_png8(
+    width: u32,
+    height: u32,
+    color: png::ColorType,
+    data: &[u8],
+    path: impl AsRef<Path>,
+) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(color);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(data)?;
+    writer.finish()
+}
+
+/// Bakes the tile heights into an equirectangular tangent-space normal map and ambient occlusion map and writes them as 8-bit PNGs.
+/// Both wrap onto the UVs of an equirectangular sphere, for external renderers to show relief on a low-poly planet.
+pub fn export_baked_maps(
+    hex_sphere: &HexSphere,
+    resolution: u32,
+    config: &BakeConfig,
+    normal_map_path: impl AsRef<Path>,
+    ambient_occlusion_path: impl AsRef<Path>,
+) -> Result<(), png::EncodingError> {
+    let heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+    let layer = TileLayer {
+        subsphere: &hex_sphere.subsphere,
+        values: &heights,
+    };
+    let normals = bake_normal_map(&layer, resolution, config);
+    let data: Vec<u8> = normals
+        .pixels
+        .iter()
+        .flat_map(|pixel| pixel.unwrap_or([128, 128, 255]))
+        .collect();
+    write_png8(
+        normals.width,
+        normals.height,
+        png::ColorType::Rgb,
+        &data,
+        normal_map_path,
+    )?;
+    let occlusion = bake_ambient_occlusion(&layer, resolution, config);
+    let data: Vec<u8> = occlusion
+        .pixels
+        .iter()
+        .map(|pixel| pixel.unwrap_or(u8::MAX))
+        .collect();
+    write_png8(
+        occlusion.width,
+        occlusion.height,
+        png::ColorType::Grayscale,
+        &data,
+        ambient_occlusion_path,
+    )
+}
+
+/// Plate of every tile in `frame`, each tile takes the plate of the point mass fewest tiles away.
+/// Tiles stay `None` only if the frame has no point masses.
+fn frame_tile_plates(hex_sphere: &HexSphere, frame: &HistoryFrame) -> Vec<Option<usize>> {
+    let mut tile_plates = vec![None; hex_sphere.tiles.len()];
+    let mut queue = VecDeque::new();
+    for (plate_index, plate) in frame.plates.iter().enumerate() {
+        for position in plate.positions() {
+            let tile = hex_sphere.tile_at(position).index;
+            if tile_plates[tile].is_none() {
+                tile_plates[tile] = Some(plate_index);
+                queue.push_back(tile);
+            }
+        }
+    }
+    // Breadth first from every point mass at once, so plates grow evenly into the gaps between them
+    while let Some(tile) = queue.pop_front() {
+        for &adjacent in &hex_sphere.tiles[tile].adjacent {
+            if tile_plates[adjacent].is_none() {
+                tile_plates[adjacent] = tile_plates[tile];
+                queue.push_back(adjacent);
+            }
+        }
+    }
+    tile_plates
+}
+
+/// Renders the recorded plates as plate-colored equirectangular RGB PNGs, numbered in order in `directory`.
+/// A frame is exported at most every `every` iterations, returns how many frames were written.
+pub fn export_timelapse(
+    hex_sphere: &HexSphere,
+    history: &TectonicsHistory,
+    width: u32,
+    every: usize,
+    directory: impl AsRef<Path>,
+) -> Result<usize, png::EncodingError> {
+    let directory = directory.as_ref();
+    std::fs::create_dir_all(directory)?;
+    let pixel_tiles = projected_tiles(hex_sphere, Projection::Equirectangular, width);
+    let (width, height) = (pixel_tiles.width, pixel_tiles.height);
+
+    let mut next_iteration = 0;
+    let frames: Vec<&HistoryFrame> = history
+        .frames
+        .iter()
+        .filter(|frame| {
+            let due = frame.iteration >= next_iteration;
+            if due {
+                next_iteration = frame.iteration + every.max(1);
+            }
+            due
+        })
+        .collect();
+    frames
+        .par_iter()
+        .enumerate()
+        .try_for_each(|(number, frame)| {
+            let colors: Vec<[u8; 3]> = frame
+                .plates
+                .iter()
+                .map(|plate| {
+                    let [r, g, b, _] = plate_color(plate.color).to_srgba().to_u8_array();
+                    [r, g, b]
+                })
+                .collect();
+            let tile_plates = frame_tile_plates(hex_sphere, frame);
+            let data: Vec<u8> = pixel_tiles
+                .pixels
+                .iter()
+                .flat_map(|&tile| {
+                    tile.and_then(|tile| tile_plates[tile])
+                        .map_or([0; 3], |plate| colors[plate])
+                })
+                .collect();
+
+            let path = directory.join(format!("frame_{number:05}.png"));
+            write_png8(width, height, png::ColorType::Rgb, &data, path)
+        })?;
+    Ok(frames.len())
+}
+
+fn float3_attribute(mesh: &Mesh, attribute: MeshVertexAttribute) -> io::Result<&[[f32; 3]]> {
+    mesh.attribute(attribute)
+        .and_then(VertexAttributeValues::as_float3)
+        .ok_or_else(|| io::Error::other(format!("Mesh has no {} attribute", attribute.name)))
+}
+
+/// Appends `values` to the binary buffer and returns the (offset, length) of the written bytes
+fn push_f32s(buffer: &mut Vec<u8>, values: impl IntoIterator<Item = f32>) -> (usize, usize) {
+    let offset = buffer.len();
+    buffer.extend(values.into_iter().flat_map(f32::to_le_bytes));
+    (offset, buffer.len() - offset)
+}
+
+/// Writes the mesh as a binary glTF with positions, normals, vertex colors and triangle indices.
+/// `vertex_plates` adds a custom `_PLATE_ID` float attribute with one plate index per vertex.
+pub fn export_glb(
+    mesh: &Mesh,
+    vertex_plates: Option<&[u32]>,
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let positions = float3_attribute(mesh, Mesh::ATTRIBUTE_POSITION)?;
+    let normals = float3_attribute(mesh, Mesh::ATTRIBUTE_NORMAL)?;
+    let Some(VertexAttributeValues::Float32x4(colors)) = mesh.attribute(Mesh::ATTRIBUTE_COLOR)
+    else {
+        return Err(io::Error::other("Mesh has no Vertex_Color attribute"));
+    };
+    let indices: Vec<u32> = mesh
+        .indices()
+        .ok_or_else(|| io::Error::other("Mesh has no indices"))?
+        .iter()
+        .map(|index| index as u32)
+        .collect();
+    let vertex_count = positions.len();
+    if let Some(vertex_plates) = vertex_plates {
+        if vertex_plates.len() != vertex_count {
+            return Err(io::Error::other(format!(
+                "{} vertex plates but the mesh has {vertex_count} vertices",
+                vertex_plates.len()
+            )));
+        }
+    }
+
+    let (min, max) = positions.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), position| {
+            (
+                min.min(Vec3::from(*position)),
+                max.max(Vec3::from(*position)),
+            )
+        },
+    );
+
+    // Every element is 4 bytes, so all buffer views stay aligned without padding
+    let mut buffer = Vec::new();
+    let mut views = vec![
+        push_f32s(&mut buffer, positions.iter().flatten().copied()),
+        push_f32s(&mut buffer, normals.iter().flatten().copied()),
+        push_f32s(&mut buffer, colors.iter().flatten().copied()),
+    ];
+    let indices_offset = buffer.len();
+    buffer.extend(indices.iter().flat_map(|index| index.to_le_bytes()));
+    views.push((indices_offset, buffer.len() - indices_offset));
+    if let Some(vertex_plates) = vertex_plates {
+        views.push(push_f32s(
+            &mut buffer,
+            vertex_plates.iter().map(|&plate| plate as f32),
+        ));
+    }
+
+    // Component types: 5126 = FLOAT, 5125 = UNSIGNED_INT. Targets: 34962 = ARRAY_BUFFER, 34963 = ELEMENT_ARRAY_BUFFER
+    let buffer_views = views
+        .iter()
+        .enumerate()
+        .map(|(index, (offset, length))| {
+            let target = if index == 3 { 34963 } else { 34962 };
+            format!(
+                r#"{{"buffer":0,"byteOffset":{offset},"byteLength":{length},"target":{target}}}"#
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut accessors = vec![
+        format!(
+            r#"{{"bufferView":0,"componentType":5126,"count":{vertex_count},"type":"VEC3","min":[{},{},{}],"max":[{},{},{}]}}"#,
+            min.x, min.y, min.z, max.x, max.y, max.z
+        ),
+        format!(r#"{{"bufferView":1,"componentType":5126,"count":{vertex_count},"type":"VEC3"}}"#),
+        format!(r#"{{"bufferView":2,"componentType":5126,"count":{vertex_count},"type":"VEC4"}}"#),
+        format!(
+            r#"{{"bufferView":3,"componentType":5125,"count":{},"type":"SCALAR"}}"#,
+            indices.len()
+        ),
+    ];
+    let mut attributes = r#""POSITION":0,"NORMAL":1,"COLOR_0":2"#.to_string();
+    if vertex_plates.is_some() {
+        accessors.push(format!(
+            r#"{{"bufferView":4,"componentType":5126,"count":{vertex_count},"type":"SCALAR"}}"#
+        ));
+        attributes.push_str(r#","_PLATE_ID":4"#);
+    }
+    let mut json = format!(
+        r#"{{"asset":{{"version":"2.0","generator":"Suzerainty"}},"scene":0,"scenes":[{{"nodes":[0]}}],"nodes":[{{"mesh":0,"name":"Planet"}}],"meshes":[{{"primitives":[{{"attributes":{{{attributes}}},"indices":3,"mode":4}}]}}],"buffers":[{{"byteLength":{}}}],"bufferViews":[{buffer_views}],"accessors":[{}]}}"#,
+        buffer.len(),
+        accessors.join(",")
+    )
+    .into_bytes();
+    // Chunks must be 4 byte aligned, JSON is padded with spaces
+    json.resize(json.len().next_multiple_of(4), b' ');
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(b"glTF")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&((12 + 8 + json.len() + 8 + buffer.len()) as u32).to_le_bytes())?;
+    writer.write_all(&(json.len() as u32).to_le_bytes())?;
+    writer.write_all(b"JSON")?;
+    writer.write_all(&json)?;
+    writer.write_all(&(buffer.len() as u32).to_le_bytes())?;
+    writer.write_all(b"BIN\0")?;
+    writer.write_all(&buffer)?;
+    writer.flush()
+}
+
+/// Writes the plates as polygons, the plate boundaries by type and the coastlines as a GeoJSON FeatureCollection in degrees.
+/// `plates` holds the type and linear RGBA color of every plate `tile_plates` indexes into.
+pub fn export_geojson(
+    hex_sphere: &HexSphere,
+    tile_plates: &[u32],
+    plates: &[(PlateType, [f32; 4])],
+    boundaries: &PlateBoundaries,
+    coastlines: &Coastlines,
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    let mut features: Vec<Feature> = plates
+        .par_iter()
+        .enumerate()
+        .map(|(plate, (plate_type, color))| {
+            let inside: Vec<bool> = tile_plates
+                .iter()
+                .map(|&tile_plate| tile_plate as usize == plate)
+                .collect();
+            let [r, g, b, _] = plate_color(*color).to_srgba().to_u8_array();
+            Feature {
+                geometry: Geometry::MultiPolygon(geojson::region_polygons(&region_outlines(
+                    hex_sphere, &inside,
+                ))),
+                properties: vec![
+                    ("kind", r#""plate""#.to_string()),
+                    ("plate", plate.to_string()),
+                    ("plate_type", format!(r#""{plate_type}""#)),
+                    ("color", format!(r##""#{r:02x}{g:02x}{b:02x}""##)),
+                ],
+            }
+        })
+        .collect();
+    for boundary_type in [
+        BoundaryType::Convergent,
+        BoundaryType::Divergent,
+        BoundaryType::Transform,
+    ] {
+        // Each segment is the mesh edge shared by its two tiles
+        let lines = boundaries
+            .segments
+            .iter()
+            .filter(|segment| segment.boundary_type == boundary_type)
+            .map(|segment| {
+                let [tile, other] = segment.tiles;
+                geojson::line_positions(
+                    hex_sphere.tiles[tile]
+                        .vertices
+                        .iter()
+                        .filter(|&&vertex| hex_sphere.vertices_to_tiles[vertex].contains(&other))
+                        .map(|&vertex| Vec3::from(hex_sphere.vertices[vertex])),
+                )
+            })
+            .collect();
+        features.push(Feature {
+            geometry: Geometry::MultiLineString(lines),
+            properties: vec![
+                ("kind", r#""boundary""#.to_string()),
+                ("boundary_type", format!(r#""{boundary_type}""#)),
+            ],
+        });
+    }
+    features.push(Feature {
+        geometry: Geometry::MultiLineString(
+            coastlines
+                .lines
+                .iter()
+                .map(|line| geojson::line_positions(line.iter().chain(line.first()).copied()))
+                .collect(),
+        ),
+        properties: vec![("kind", r#""coastline""#.to_string())],
+    });
+    std::fs::write(path, geojson::feature_collection(&features))
+}
+
+/// Plate of every tile, from the nearest point mass
+fn tectonics_tile_plates(hex_sphere: &HexSphere, tectonics: &Tectonics) -> Vec<u32> {
+    let normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+    containing_plates(tectonics, &normals)
+        .into_iter()
+        .map(|plate| plate as u32)
+        .collect()
+}
+
+/// Plate index for every mesh vertex, every vertex belongs to exactly one tile
+fn vertex_plates(hex_sphere: &HexSphere, tile_plates: &[u32]) -> Vec<u32> {
+    let mut plates = vec![0; hex_sphere.vertices.len()];
+    for tile in &hex_sphere.tiles {
+        for &vertex in tile.vertices.iter().chain(std::iter::once(&tile.center)) {
+            plates[vertex] = tile_plates[tile.index];
+        }
+    }
+    plates
+}
+
+fn export_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    hex_sphere: Res<HexSphere>,
+    config: Res<ExportConfig>,
+    meshes: Res<Assets<Mesh>>,
+    mesh_handle: Res<HexSphereMeshHandle>,
+    tectonics: Option<Res<SimTectonics>>,
+    loaded_planet: Option<Res<LoadedPlanet>>,
+    tectonics_history: Option<Res<SimTectonicsHistory>>,
+    coastlines: Res<Coastlines>,
+    tectonics_plugin_config: Res<TectonicsPluginConfig>,
+    plate_boundaries: Option<Res<SimPlateBoundaries>>,
+) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        match tectonics_history {
+            Some(tectonics_history) => match export_timelapse(
+                &hex_sphere,
+                &tectonics_history,
+                config.timelapse_width,
+                config.timelapse_every,
+                TIMELAPSE_DIRECTORY,
+            ) {
+                Ok(frames) => info!("Exported {frames} time-lapse frames to {TIMELAPSE_DIRECTORY}"),
+                Err(e) => error!("Failed to export time-lapse to {TIMELAPSE_DIRECTORY}: {e}"),
+            },
+            None => warn!("No tectonics history recorded, nothing to export as a time-lapse"),
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyH) {
+        match export_heightmap(
+            &hex_sphere,
+            config.map_projection,
+            config.heightmap_width,
+            tectonics_plugin_config.tectonics_config.units(),
+            coastlines.sea_level,
+            HEIGHTMAP_PATH,
+        ) {
+            Ok((black, white)) => info!(
+                "Exported heightmap to {HEIGHTMAP_PATH}, black is {black:.0} m and white is {white:.0} m"
+            ),
+            Err(e) => error!("Failed to export heightmap to {HEIGHTMAP_PATH}: {e}"),
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyN) {
+        match export_baked_maps(
+            &hex_sphere,
+            config.heightmap_width,
+            &config.bake,
+            NORMAL_MAP_PATH,
+            AMBIENT_OCCLUSION_PATH,
+        ) {
+            Ok(()) => info!("Exported {NORMAL_MAP_PATH} and {AMBIENT_OCCLUSION_PATH}"),
+            Err(e) => error!("Failed to export baked maps: {e}"),
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyI) {
+        let tile_plates = match (&tectonics, &loaded_planet) {
+            (Some(tectonics), _) => Some(tectonics_tile_plates(&hex_sphere, tectonics)),
+            (None, Some(loaded_planet)) => Some(loaded_planet.0.tile_plates.clone()),
+            (None, None) => None,
+        };
+        match tile_plates {
+            Some(tile_plates) => match export_plate_map(
+                &hex_sphere,
+                &tile_plates,
+                config.map_projection,
+                config.heightmap_width,
+                PLATE_MAP_PATH,
+            ) {
+                Ok(()) => info!("Exported plate map to {PLATE_MAP_PATH}"),
+                Err(e) => error!("Failed to export plate map to {PLATE_MAP_PATH}: {e}"),
+            },
+            None => warn!("No plates to export as a plate map"),
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyJ) {
+        let no_boundaries = PlateBoundaries::default();
+        // Boundaries are only classified while tectonics runs, loaded planets export without them
+        let plates: Option<(Vec<u32>, Vec<(PlateType, [f32; 4])>, &PlateBoundaries)> =
+            match (&tectonics, &loaded_planet) {
+                (Some(tectonics), _) => Some((
+                    tectonics_tile_plates(&hex_sphere, tectonics),
+                    tectonics
+                        .plates
+                        .iter()
+                        .map(|plate| (plate.plate_type, plate.color))
+                        .collect(),
+                    plate_boundaries
+                        .as_deref()
+                        .map_or(&no_boundaries, |boundaries| &boundaries.0),
+                )),
+                (None, Some(loaded_planet)) => Some((
+                    loaded_planet.0.tile_plates.clone(),
+                    loaded_planet
+                        .0
+                        .plates
+                        .iter()
+                        .map(|plate| (plate.plate_type, plate.color))
+                        .collect(),
+                    &no_boundaries,
+                )),
+                (None, None) => None,
+            };
+        match plates {
+            Some((tile_plates, plates, boundaries)) => match export_geojson(
+                &hex_sphere,
+                &tile_plates,
+                &plates,
+                boundaries,
+                &coastlines,
+                GEOJSON_PATH,
+            ) {
+                Ok(()) => info!("Exported plates and coastlines to {GEOJSON_PATH}"),
+                Err(e) => error!("Failed to export {GEOJSON_PATH}: {e}"),
+            },
+            None => warn!("No plates to export as GeoJSON"),
+        }
+    }
+    if keys.just_pressed(KeyCode::KeyG) {
+        let Some(mesh) = meshes.get(&mesh_handle.0) else {
+            error!("Failed to export {GLB_PATH}, hex sphere mesh is not loaded");
+            return;
+        };
+        let tile_plates = match (config.glb_plate_ids, tectonics, loaded_planet) {
+            (false, _, _) => None,
+            (true, Some(tectonics), _) => Some(tectonics_tile_plates(&hex_sphere, &tectonics)),
+            (true, None, Some(loaded_planet)) => Some(loaded_planet.0.tile_plates.clone()),
+            (true, None, None) => {
+                warn!("No plates to export, writing {GLB_PATH} without plate IDs");
+                None
+            }
+        };
+        let vertex_plates = tile_plates.map(|tile_plates| vertex_plates(&hex_sphere, &tile_plates));
+        match export_glb(mesh, vertex_plates.as_deref(), GLB_PATH) {
+            Ok(()) => info!("Exported mesh to {GLB_PATH}"),
+            Err(e) => error!("Failed to export mesh to {GLB_PATH}: {e}"),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use bevy::{input::mouse::AccumulatedMouseMotion, prelude::*, window::PrimaryWindow};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::{
+    MainCamera,
+    hex_sphere::{HexSphere, ray_pick},
+};
+
+/// How the main camera is controlled
+#[derive(Resource, Default, PartialEq, Eq, Clone, Copy)]
+pub enum CameraMode {
+    /// Orthographic view orbiting the whole planet
+    #[default]
+    Orbit,
+    /// Perspective view flying close over the surface
+    Fly,
+}
+
+/// Run condition for systems whose keys are taken over by the fly camera
+pub fn orbiting(camera_mode: Res<CameraMode>) -> bool {
+    *camera_mode == CameraMode::Orbit
+}
+
+pub struct FlyCameraPlugin {
+    /// Flying speed in altitudes per second, so the ground seems to pass at the same pace at any altitude
+    pub speed: f32,
+    /// Radians turned per pixel of mouse movement
+    pub sensitivity: f32,
+    /// Lowest height above the tile under the camera
+    pub min_altitude: f32,
+    /// Height above the tile in the middle of the screen the fly camera starts at
+    pub start_altitude: f32,
+}
+impl Plugin for FlyCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraMode>()
+            .insert_resource(FlyCameraSettings {
+                speed: self.speed,
+                sensitivity: self.sensitivity,
+                min_altitude: self.min_altitude,
+                start_altitude: self.start_altitude,
+            })
+            .add_systems(
+                Update,
+                (
+                    toggle_camera_mode,
+                    fly.run_if(resource_equals(CameraMode::Fly)),
+                )
+                    .chain()
+                    .run_if(resource_exists::<HexSphere>),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct FlyCameraSettings {
+    speed: f32,
+    sensitivity: f32,
+    min_altitude: f32,
+    start_altitude: f32,
+}
+
+/// Orientation of the fly camera relative to the surface below it
+#[derive(Component)]
+struct FlyCamera {
+    /// Unit tangent to the sphere under the camera that it faces
+    heading: Vec3,
+    /// Radians above the horizon the camera looks at
+    pitch: f32,
+    /// Projection of the orbit camera, restored when leaving the fly camera
+    orbit_projection: Projection,
+}
+
+/// V switches between the orbit and fly cameras.
+/// The fly camera starts above the tile in the middle of the screen, facing the top of the screen.
+fn toggle_camera_mode(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    hex_sphere: Res<HexSphere>,
+    settings: Res<FlyCameraSettings>,
+    mut camera_mode: ResMut<CameraMode>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut camera_query: Query<
+        (
+            Entity,
+            &Camera,
+            &GlobalTransform,
+            &mut Transform,
+            &mut Projection,
+            &mut PanOrbitCamera,
+            Option<&FlyCamera>,
+        ),
+        With<MainCamera>,
+    >,
+) {
+    if !keys.just_pressed(KeyCode::KeyV) {
+        return;
+    }
+    let (
+        entity,
+        camera,
+        global_transform,
+        mut transform,
+        mut projection,
+        mut pan_orbit,
+        fly_camera,
+    ) = camera_query.single_mut().unwrap();
+    match *camera_mode {
+        CameraMode::Orbit => {
+            let window = window_query.single().unwrap();
+            let below = camera
+                .viewport_to_world(global_transform, window.size() / 2.)
+                .ok()
+                .and_then(|ray| ray_pick(&hex_sphere, ray))
+                .unwrap_or(transform.translation)
+                .normalize();
+            let ground = hex_sphere.tile_at(below).height;
+            let up_tangent = transform.up() - below * transform.up().dot(below);
+            let heading = up_tangent
+                .try_normalize()
+                .unwrap_or_else(|| below.any_orthonormal_vector());
+            transform.translation = below * (ground + settings.start_altitude);
+            transform.look_to(heading, below);
+            commands.entity(entity).insert(FlyCamera {
+                heading,
+                pitch: 0.,
+                orbit_projection: projection.clone(),
+            });
+            *projection = Projection::Perspective(PerspectiveProjection {
+                near: settings.min_altitude / 10.,
+                ..default()
+            });
+            pan_orbit.enabled = false;
+            *camera_mode = CameraMode::Fly;
+        }
+        CameraMode::Fly => {
+            if let Some(fly_camera) = fly_camera {
+                *projection = fly_camera.orbit_projection.clone();
+            }
+            commands.entity(entity).remove::<FlyCamera>();
+            pan_orbit.enabled = true;
+            // Puts the camera back where it orbited before flying
+            pan_orbit.force_update = true;
+            *camera_mode = CameraMode::Orbit;
+        }
+    }
+}
+
+/// WASD moves along the surface, E and Q climb and descend, dragging with the left mouse button looks around.
+/// The camera is kept at least [FlyCameraPlugin::min_altitude] above the tile under it.
+fn fly(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    hex_sphere: Res<HexSphere>,
+    settings: Res<FlyCameraSettings>,
+    mut camera_query: Query<(&mut Transform, &mut FlyCamera), With<MainCamera>>,
+) {
+    let Ok((mut transform, mut fly_camera)) = camera_query.single_mut() else {
+        return;
+    };
+    let mut up = transform.translation.normalize();
+    if mouse_buttons.pressed(MouseButton::Left) {
+        let delta = mouse_motion.delta * settings.sensitivity;
+        fly_camera.heading = Quat::from_axis_angle(up, -delta.x) * fly_camera.heading;
+        fly_camera.pitch = (fly_camera.pitch - delta.y).clamp(-1.5, 1.5);
+    }
+
+    let right = fly_camera.heading.cross(up);
+    let mut direction = Vec3::ZERO;
+    for (key, towards) in [
+        (KeyCode::KeyW, fly_camera.heading),
+        (KeyCode::KeyS, -fly_camera.heading),
+        (KeyCode::KeyD, right),
+        (KeyCode::KeyA, -right),
+        (KeyCode::KeyE, up),
+        (KeyCode::KeyQ, -up),
+    ] {
+        if keys.pressed(key) {
+            direction += towards;
+        }
+    }
+    let ground = hex_sphere.tile_at(up).height;
+    let altitude = (transform.translation.length() - ground).max(settings.min_altitude);
+    let moved = transform.translation
+        + direction.normalize_or_zero() * settings.speed * altitude * time.delta_secs();
+
+    up = moved.normalize();
+    let ground = hex_sphere.tile_at(up).height;
+    transform.translation = up * moved.length().max(ground + settings.min_altitude);
+    // Moving over the curved surface tilts the tangent plane, so the heading is projected back onto it
+    fly_camera.heading = (fly_camera.heading - up * fly_camera.heading.dot(up))
+        .try_normalize()
+        .unwrap_or_else(|| up.any_orthonormal_vector());
+    let forward = fly_camera.heading * fly_camera.pitch.cos() + up * fly_camera.pitch.sin();
+    transform.look_to(forward, up);
+}
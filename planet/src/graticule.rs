@@ -0,0 +1,104 @@
+use bevy::{color::palettes, prelude::*};
+use suz_sim::vec_utils::lat_long_to_vec3;
+
+use crate::tectonics::TectonicsPluginConfig;
+
+/// Lines are drawn slightly above the surface so the tiles do not hide them
+const GRATICULE_RADIUS: f32 = 1.02;
+/// Number of segments of each parallel and meridian
+const GRATICULE_RESOLUTION: usize = 128;
+/// How far the rotation axis sticks out of each pole
+const AXIS_LENGTH: f32 = 1.3;
+
+/// Draws the equator, tropics, polar circles and meridians, plus the rotation axis.
+/// O toggles the overlay.
+pub struct GraticulePlugin {
+    /// Degrees of longitude between meridians
+    pub meridian_spacing: f32,
+}
+impl Plugin for GraticulePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GraticuleConfig {
+            visible: false,
+            meridian_spacing: self.meridian_spacing,
+        })
+        .init_resource::<AxialTilt>()
+        .add_systems(
+            Update,
+            (
+                toggle_graticule,
+                sync_axial_tilt.run_if(resource_changed::<TectonicsPluginConfig>),
+                draw_graticule.run_if(|config: Res<GraticuleConfig>| config.visible),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct GraticuleConfig {
+    pub visible: bool,
+    /// Degrees of longitude between meridians
+    pub meridian_spacing: f32,
+}
+
+/// Angle in degrees between the rotation axis and the orbital plane normal, which places the tropics and polar circles.
+/// Follows [suz_sim::climate::ClimateConfig::axial_tilt] so the overlay matches the climate it is read against.
+#[derive(Resource)]
+pub struct AxialTilt(pub f32);
+
+impl Default for AxialTilt {
+    fn default() -> Self {
+        AxialTilt(suz_sim::climate::ClimateConfig::default().axial_tilt)
+    }
+}
+
+fn toggle_graticule(keys: Res<ButtonInput<KeyCode>>, mut config: ResMut<GraticuleConfig>) {
+    if keys.just_pressed(KeyCode::KeyO) {
+        config.visible = !config.visible;
+    }
+}
+
+fn sync_axial_tilt(config: Res<TectonicsPluginConfig>, mut axial_tilt: ResMut<AxialTilt>) {
+    axial_tilt.0 = config.climate_config.axial_tilt;
+}
+
+/// Points along the circle of constant `latitude`, closed so it can be drawn as one linestrip
+fn parallel(latitude: f32) -> impl Iterator<Item = Vec3> {
+    (0..=GRATICULE_RESOLUTION).map(move |k| {
+        let longitude = k as f32 / GRATICULE_RESOLUTION as f32 * std::f32::consts::TAU;
+        lat_long_to_vec3(latitude, longitude) * GRATICULE_RADIUS
+    })
+}
+
+/// Points along the half circle of constant `longitude` from the south pole to the north pole
+fn meridian(longitude: f32) -> impl Iterator<Item = Vec3> {
+    (0..=GRATICULE_RESOLUTION / 2).map(move |k| {
+        let latitude = (k as f32 / (GRATICULE_RESOLUTION / 2) as f32 - 0.5) * std::f32::consts::PI;
+        lat_long_to_vec3(latitude, longitude) * GRATICULE_RADIUS
+    })
+}
+
+fn draw_graticule(mut gizmos: Gizmos, config: Res<GraticuleConfig>, axial_tilt: Res<AxialTilt>) {
+    let tilt = axial_tilt.0.clamp(0., 90.).to_radians();
+    gizmos.linestrip(parallel(0.), palettes::css::YELLOW);
+    for sign in [-1., 1.] {
+        gizmos.linestrip(parallel(sign * tilt), palettes::css::ORANGE);
+        gizmos.linestrip(
+            parallel(sign * (std::f32::consts::FRAC_PI_2 - tilt)),
+            palettes::css::LIGHT_BLUE,
+        );
+    }
+    if config.meridian_spacing > 0. {
+        let meridians = (360. / config.meridian_spacing).round() as usize;
+        for k in 0..meridians {
+            let longitude = (k as f32 * config.meridian_spacing).to_radians();
+            gizmos.linestrip(meridian(longitude), palettes::css::GRAY);
+        }
+    }
+    gizmos.line(
+        Vec3::NEG_Y * AXIS_LENGTH,
+        Vec3::Y * AXIS_LENGTH,
+        palettes::css::RED,
+    );
+}
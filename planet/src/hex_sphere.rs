@@ -1,4 +1,10 @@
 use crate::MainCamera;
+use crate::chunks::{ChunkIndex, HexSphereChunks};
+use crate::coloring::{ColorRamp, MapMode, color_tiles};
+use crate::persistence::LoadedPlanet;
+use crate::progress::StageProgress;
+use crate::sim_resources::{SimHexSphereConfig, SimObservers, SimTectonics};
+use crate::tile_coords::{AXIAL_DIRECTIONS, TileCoords};
 use crate::{debug_ui::DebugDiagnostics, states::SimulationState};
 use bevy::prelude::*;
 use bevy::{
@@ -7,39 +13,23 @@ use bevy::{
     window::PrimaryWindow,
 };
 use bevy::{color::Color, gizmos::gizmos::Gizmos, math::Vec3};
-use std::{num::NonZero, time::Instant};
-use subsphere::Vertex;
-use subsphere::{Face, Sphere, proj::Fuller};
-use suz_sim::tectonics::Tectonics;
-use suz_sim::vec_utils::{self};
-
-/// A helper for the modified faces with a central vertex
-#[derive(Clone)]
-pub struct Tile {
-    /// Index to [subsphere::hex::Face<Fuller>] (same index in wrapper and subsphere)
-    pub index: usize,
-    /// Index to the central vertex in HexSphere.vertices
-    pub center: usize,
-    /// Indices to corner vertices in HexSphere.vertices
-    pub vertices: Vec<usize>,
-    /// Height of the tile center
-    pub height: f32,
-    /// Indices to adjacent tiles
-    pub adjacent: Vec<usize>,
-    /// Tile face normal
-    pub normal: Vec3,
-}
-
-impl Tile {
-    pub fn draw_border(&self, vertices: &Vec<[f32; 3]>, color: Color, gizmos: &mut Gizmos) {
-        gizmos.linestrip(
-            self.vertices
-                .iter()
-                .chain(std::iter::once(&self.vertices[0]))
-                .map(|vertex_index| vertices[*vertex_index].map(|val| val * 1.01).into()),
-            color,
-        );
-    }
+use subsphere::{Sphere, proj::Fuller};
+use suz_sim::config::HexSphereConfig;
+use suz_sim::observer::{Control, Stage};
+// std::time::Instant panics on wasm32
+use web_time::Instant;
+
+pub use ::hex_sphere::{Tile, TileGraph};
+
+/// Draws the outline of `tile` slightly above the surface
+pub fn draw_tile_border(tile: &Tile, vertices: &[[f32; 3]], color: Color, gizmos: &mut Gizmos) {
+    gizmos.linestrip(
+        tile.vertices
+            .iter()
+            .chain(std::iter::once(&tile.vertices[0]))
+            .map(|vertex_index| vertices[*vertex_index].map(|val| val * 1.01).into()),
+        color,
+    );
 }
 
 #[derive(Resource)]
@@ -54,31 +44,45 @@ pub struct HexSphere {
     pub tiles: Vec<Tile>,
     /// For each vertex, the indices of the tiles it is adjacent to
     pub vertices_to_tiles: Vec<Vec<usize>>,
+    /// Stable per-tile coordinates for gameplay
+    pub coords: TileCoords,
+    /// Tile neighbours and centers for neighbourhood and path queries
+    pub graph: TileGraph,
 }
 
 impl HexSphere {
     /// Returns [Tile] from unit sphere normal
     pub fn tile_at(&self, at: Vec3) -> &Tile {
-        &self.tiles[self.subsphere.face_at(vec_utils::vec3_to_f64_3(at)).index()]
+        ::hex_sphere::tile_at(&self.subsphere, &self.tiles, at)
+    }
+
+    /// See [::hex_sphere::flood_fill]
+    pub fn flood_fill<P>(&self, start: usize, visited: &mut [bool], predicate: P) -> Vec<usize>
+    where
+        P: Fn(&Tile) -> bool,
+    {
+        ::hex_sphere::flood_fill(&self.tiles, start, visited, predicate)
     }
 }
 
 #[derive(Component)]
 struct SphereMeshMarker;
 
-#[derive(Resource, Clone, Copy)]
-pub struct HexSphereConfig {
-    pub subdivisions: u32,
-}
 pub struct HexSpherePlugin {
     pub config: HexSphereConfig,
 }
 impl Plugin for HexSpherePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(self.config)
+        app.insert_resource(SimHexSphereConfig(self.config))
             .insert_resource(CurrentMousePick::default())
             .add_systems(OnEnter(SimulationState::MeshGen), setup)
-            .add_systems(Update, (mouse_pick, draw_selected));
+            .add_systems(
+                Update,
+                (
+                    mouse_pick.run_if(resource_exists::<HexSphere>),
+                    draw_selected.run_if(resource_exists::<SimTectonics>),
+                ),
+            );
     }
 }
 
@@ -90,207 +94,235 @@ fn setup(
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut diagnostics: ResMut<DebugDiagnostics>,
-    config: Res<HexSphereConfig>,
+    mut stage_progress: ResMut<StageProgress>,
+    mut observers: ResMut<SimObservers>,
+    config: Res<SimHexSphereConfig>,
+    existing_meshes: Query<Entity, With<SphereMeshMarker>>,
+    loaded_planet: Option<Res<LoadedPlanet>>,
+    map_mode: Res<MapMode>,
+    color_ramp: Res<ColorRamp>,
     mut next_state: ResMut<NextState<SimulationState>>,
 ) {
-    let start = Instant::now();
-    // Create and save a handle to the mesh.
-    // 548 is the smallest number above a million tiles.
-    let c = config.subdivisions % 3;
-    let hex_sphere = subsphere::HexSphere::from_kis(subsphere::TriSphere::new(
-        subsphere::BaseTriSphere::Icosa,
-        subsphere::proj::Fuller,
-        NonZero::new(config.subdivisions).unwrap(),
-        c,
-    ))
-    .unwrap();
-
-    let num_pentagons = 12;
-    let num_hexagons = hex_sphere.num_faces() - num_pentagons;
-    let num_vertices = num_pentagons * 6 + num_hexagons * 7;
-    let num_faces = hex_sphere.num_faces();
-
-    let mut vertices: Vec<[f32; 3]> = Vec::with_capacity(num_vertices);
-    let mut vertices_to_tiles: Vec<Vec<usize>> = vec![Vec::new(); num_vertices];
-    let mut tiles: Vec<Tile> = Vec::with_capacity(num_faces);
-    let mut triangles: Vec<u32> = Vec::with_capacity(num_hexagons * 6 + num_pentagons + 5);
-    let mut colors: Vec<[f32; 4]> = vec![[0.; 4]; num_vertices];
-    // let mut normals: Vec<[f32; 3]> = vec![[0.; 3]; num_vertices];
-
-    let mut tile_heights: Vec<f32> = Vec::with_capacity(hex_sphere.num_faces());
-    for face in hex_sphere.faces() {
-        let vec: Vec3 = face.center().pos().map(|f| f as f32).into();
-        tile_heights.push(vec.length());
+    // Regenerating replaces the previous planet
+    for entity in &existing_meshes {
+        commands.entity(entity).despawn();
     }
-
-    // Create tiles and mesh
-    for (i, face) in hex_sphere.faces().enumerate() {
-        // Build triangles, we want each face to be triangular slices around the center point
-        let height_color = 1.0;
-        let face_color = [height_color, height_color, height_color, 1.0];
-        let face_normal = vec_utils::f64_3_to_f32_3(&face.center().pos());
-        let face_center = face_normal.map(|f| f * tile_heights[i]);
-        let face_vertex_count = if face.is_hex() { 7 } else { 6 };
-
-        // For each face vertex excluding the center, interpolate between adjacent tile centers
-        vertices.extend(face.vertices().map(|v| {
-            let interpolated_pos: [f32; 3] = v
-                .faces()
-                .map(|face| {
-                    face.center()
-                        .pos()
-                        .map(|val| val as f32 * tile_heights[face.index()] / 3.)
-                })
-                .reduce(|acc, e| [acc[0] + e[0], acc[1] + e[1], acc[2] + e[2]])
-                .unwrap();
-            interpolated_pos
-        }));
-        vertices.push(face_center);
-        let face_center_index: usize = vertices.len() - 1;
-
-        let face_vertex_indices: Vec<usize> =
-            (face_center_index + 1 - face_vertex_count..=face_center_index).collect();
-
-        let mut face_triangles: Vec<u32> = face_vertex_indices[..face_vertex_indices.len() - 1]
-            .iter()
-            .flat_map(move |i| vec![*i as u32, face_center_index as u32, *i as u32])
-            .collect();
-        face_triangles.rotate_right(1);
-        triangles.extend(face_triangles);
-
-        for index in &face_vertex_indices {
-            colors[*index] = face_color;
-        }
-
-        let mut adjacent = face
-            .vertices()
-            // Need explicit collect or we run into a infinite type recursion for some reason
-            .flat_map(|v| v.faces().map(|f| f.index()).collect::<Vec<usize>>())
-            .collect::<Vec<usize>>();
-        adjacent.sort_unstable();
-        adjacent.dedup();
-
-        vertices_to_tiles[face_center_index] = vec![];
-        for (i, vertex) in face.vertices().enumerate() {
-            vertices_to_tiles[face_vertex_indices[i]] =
-                vertex.faces().map(|f| f.index()).collect::<Vec<usize>>();
+    let start = Instant::now();
+    // A planet loaded from disk already has its final heights
+    let num_faces = ::hex_sphere::subsphere(config.subdivisions).num_faces();
+    let loaded_heights = loaded_planet.as_ref().and_then(|loaded_planet| {
+        if loaded_planet.0.tile_heights.len() == num_faces {
+            Some(loaded_planet.0.tile_heights.as_slice())
+        } else {
+            warn!(
+                "Loaded planet has {} tiles but the mesh has {}, ignoring loaded heights",
+                loaded_planet.0.tile_heights.len(),
+                num_faces
+            );
+            None
         }
+    });
 
-        tiles.push(Tile {
-            index: i,
-            center: face_center_index,
-            vertices: face_vertex_indices[..face_vertex_indices.len() - 1].into(),
-            height: tile_heights[i],
-            adjacent,
-            normal: face_normal.into(),
-        });
-    }
-
-    commands.insert_resource(HexSphere {
-        subsphere: hex_sphere,
+    // 548 is the smallest number of subdivisions above a million tiles.
+    let ::hex_sphere::HexSphere {
+        subsphere,
+        vertices,
+        triangles,
         tiles,
-        vertices: vertices.clone(),
-        colors: colors.clone(),
         vertices_to_tiles,
-    });
+        graph,
+    } = ::hex_sphere::HexSphere::new(config.subdivisions, loaded_heights);
+    let loaded_heights = loaded_heights.is_some();
+
+    let mut hex_sphere = HexSphere {
+        coords: TileCoords::new(&subsphere),
+        subsphere,
+        colors: vec![[1.; 4]; vertices.len()],
+        tiles,
+        vertices,
+        vertices_to_tiles,
+        graph,
+    };
+    if loaded_heights {
+        color_tiles(
+            &mut hex_sphere,
+            *map_mode,
+            &color_ramp,
+            None,
+            None,
+            None,
+            None,
+        );
+    }
 
+    // The full mesh is only edited and exported, the chunks copied from it are what gets rendered
     let mut mesh = Mesh::new(
         PrimitiveTopology::TriangleList,
-        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        RenderAssetUsages::MAIN_WORLD,
     )
-    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, hex_sphere.vertices.clone())
     .with_inserted_indices(Indices::U32(triangles))
-    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, hex_sphere.colors.clone());
     mesh.compute_normals();
+    let chunks = HexSphereChunks::new(&hex_sphere, &mesh, &mut meshes);
+    commands.insert_resource(hex_sphere);
     let mesh_handle = meshes.add(mesh);
     commands.insert_resource(HexSphereMeshHandle(mesh_handle.clone()));
 
-    // Render the mesh with the custom texture, and add the marker.
-    commands.spawn((
-        Mesh3d(mesh_handle),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            perceptual_roughness: 0.9,
-            reflectance: 0.18,
-            ..Default::default()
-        })),
-        SphereMeshMarker,
-    ));
+    // Render the chunks with the custom texture, and add the marker.
+    let material = materials.add(StandardMaterial {
+        perceptual_roughness: 0.9,
+        reflectance: 0.18,
+        ..Default::default()
+    });
+    for (chunk_index, chunk) in chunks.chunks.iter().enumerate() {
+        commands.spawn((
+            Mesh3d(chunk.mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            SphereMeshMarker,
+            ChunkIndex(chunk_index),
+        ));
+    }
+    commands.insert_resource(chunks);
 
     diagnostics.tiles = Some(num_faces);
     diagnostics.subdivisions = Some(config.subdivisions);
     diagnostics.mesh_gen_time = Some(start.elapsed());
-    next_state.set(SimulationState::Tectonics)
+    // Mesh generation runs within a single frame, there is nothing to report until it is done
+    stage_progress.set(1.);
+    if observers.on_stage_complete(Stage::HexSphere, start.elapsed()) == Control::Abort {
+        warn!("Generation aborted by an observer after mesh generation");
+        next_state.set(SimulationState::Menu);
+        return;
+    }
+    next_state.set(if loaded_heights {
+        SimulationState::Erosion
+    } else {
+        SimulationState::Tectonics
+    })
 }
 
 #[derive(Resource, Default)]
-pub struct CurrentMousePick(pub Option<MousePickInfo>);
+pub struct CurrentMousePick {
+    pub pick: Option<MousePickInfo>,
+    /// Right clicking pins the picked tile, the pick then stays on it until the next right click
+    pub pinned: bool,
+}
 
 pub struct MousePickInfo {
     pub normal: Vec3,
     pub tile: Tile,
 }
 
-/// Picks the tile under the cursor
-/// This depends on the fact that the camera is orthographic and always pointing at a unit sphere in origin.
+/// Oversampling of the ray march in [ray_pick], in steps per tile width
+const PICK_STEPS_PER_TILE: f32 = 4.;
+/// Bisection iterations refining the hit point once the ray march has entered the surface
+const PICK_REFINEMENTS: usize = 16;
+
+/// Returns the first point where `ray` enters the sphere displaced by the tile heights, if it hits it at all.
+/// The ray is marched through the sphere bounding the highest tile in steps a fraction of a tile wide, so it can not skip past a tile.
+pub fn ray_pick(hex_sphere: &HexSphere, ray: Ray3d) -> Option<Vec3> {
+    let max_height = hex_sphere
+        .tiles
+        .iter()
+        .map(|tile| tile.height)
+        .fold(f32::MIN, f32::max);
+    let direction = *ray.direction;
+    // Ray and bounding sphere intersection, the direction is unit length
+    let b = ray.origin.dot(direction);
+    let c = ray.origin.length_squared() - max_height * max_height;
+    let discriminant = b * b - c;
+    if discriminant < 0. {
+        return None;
+    }
+    let enter = (-b - discriminant.sqrt()).max(0.);
+    let exit = -b + discriminant.sqrt();
+    if exit < 0. {
+        return None;
+    }
+    let inside = |distance: f32| {
+        let point = ray.get_point(distance);
+        point.length() <= hex_sphere.tile_at(point.normalize()).height
+    };
+    // Angular width of a tile on the unit sphere
+    let tile_width = (4. * std::f32::consts::PI / hex_sphere.tiles.len() as f32).sqrt();
+    let step = tile_width / PICK_STEPS_PER_TILE;
+    let mut outside_distance = enter;
+    let mut distance = enter;
+    while distance <= exit {
+        if inside(distance) {
+            let mut inside_distance = distance;
+            for _ in 0..PICK_REFINEMENTS {
+                let middle = (outside_distance + inside_distance) / 2.;
+                if inside(middle) {
+                    inside_distance = middle;
+                } else {
+                    outside_distance = middle;
+                }
+            }
+            return Some(ray.get_point(inside_distance));
+        }
+        outside_distance = distance;
+        distance += step;
+    }
+    None
+}
+
+/// Picks the tile under the cursor by casting a ray from the camera through it, for any projection and camera placement
 fn mouse_pick(
     window_query: Query<&Window, With<PrimaryWindow>>,
-    camera_query: Query<(&Projection, &Transform), With<MainCamera>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mouse_buttons: Res<ButtonInput<MouseButton>>,
     hex_sphere: Res<HexSphere>,
     mut current_mouse_pick: ResMut<CurrentMousePick>,
 ) {
+    if mouse_buttons.just_pressed(MouseButton::Right) {
+        current_mouse_pick.pinned = !current_mouse_pick.pinned && current_mouse_pick.pick.is_some();
+    }
+    if current_mouse_pick.pinned {
+        return;
+    }
     let window = window_query.single().unwrap();
-    let aspect_ratio = window.size().x / window.size().y;
-    let (camera_projection, camera_translation) = camera_query.single().unwrap();
-    if let Some(cursor_pos) = window.cursor_position() {
-        if let Projection::Orthographic(orthographic_projection) = camera_projection {
-            // [-1, 1] in x and y relative to screen
-            let ndc = cursor_pos / window.size() * 2.0 - Vec2::ONE;
-
-            // Adjust for scale and aspect ratio, so that [-1, 1] is the position on the 2d unit circle
-            let mouse_pos_circle =
-                ndc * orthographic_projection.scale * vec2(aspect_ratio, 1.) / 2.;
-
-            // If inside the circle
-            if mouse_pos_circle.length_squared() <= 1.0 {
-                // Reconstruct Z from the unit sphere constraint: x² + y² + z² = 1
-                let point_camera = Vec3::new(
-                    mouse_pos_circle.x,
-                    -mouse_pos_circle.y,
-                    (1.0 - mouse_pos_circle.x * mouse_pos_circle.x
-                        - mouse_pos_circle.y * mouse_pos_circle.y)
-                        .sqrt(),
-                );
-
-                // Adjust for camera rotation
-                let rotation = -camera_translation.rotation;
-                let mut point_transform = Transform::from_translation(point_camera);
-                point_transform.rotate_around(Vec3::ZERO, rotation);
-                let point_world = point_transform.translation;
-
-                let tile = &hex_sphere.tiles[hex_sphere
-                    .subsphere
-                    .face_at(vec_utils::f32_3_to_f64_3(&point_world.into()))
-                    .index()];
-
-                current_mouse_pick.0 = Some(MousePickInfo {
-                    normal: point_world,
-                    tile: tile.clone(),
-                });
-            } else {
-                current_mouse_pick.0 = None;
-            }
+    let (camera, camera_transform) = camera_query.single().unwrap();
+    let Some(cursor_pos) = window.cursor_position() else {
+        return;
+    };
+    let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_pos) else {
+        return;
+    };
+    current_mouse_pick.pick = ray_pick(&hex_sphere, ray).map(|point_world| {
+        let normal = point_world.normalize();
+        MousePickInfo {
+            normal,
+            tile: hex_sphere.tile_at(normal).clone(),
         }
-    }
+    });
 }
 
 fn draw_selected(
     mut gizmos: Gizmos,
     hex_sphere: Res<HexSphere>,
-    tectonics: Res<Tectonics>,
+    tectonics: Res<SimTectonics>,
     current_mouse_pick: Res<CurrentMousePick>,
 ) {
-    if let Some(MousePickInfo { tile, normal }) = &current_mouse_pick.0 {
-        tile.draw_border(&hex_sphere.vertices, LinearRgba::WHITE.into(), &mut gizmos);
+    if let Some(MousePickInfo { tile, normal }) = &current_mouse_pick.pick {
+        draw_tile_border(
+            tile,
+            &hex_sphere.vertices,
+            LinearRgba::WHITE.into(),
+            &mut gizmos,
+        );
+        for direction in 0..AXIAL_DIRECTIONS.len() {
+            let neighbor = hex_sphere
+                .coords
+                .neighbor(&hex_sphere.tiles, tile.index, direction);
+            draw_tile_border(
+                &hex_sphere.tiles[neighbor],
+                &hex_sphere.vertices,
+                LinearRgba::new(0.4, 0.4, 0.4, 1.).into(),
+                &mut gizmos,
+            );
+        }
         gizmos.circle(
             Isometry3d {
                 rotation: Quat::from_rotation_arc(Vec3::Z, *normal),
@@ -0,0 +1,241 @@
+use std::f32::consts::PI;
+
+use bevy::{color::palettes, prelude::*, ui::RelativeCursorPosition};
+use suz_sim::history::TectonicsHistory;
+
+use crate::{
+    epochs::first_epoch,
+    fly_camera::orbiting,
+    sim_resources::{SimTectonicsHistory, plate_color},
+    states::SimulationState,
+};
+
+/// Where the history of a saved planet is kept, next to [crate::persistence::SAVE_PATH]
+pub const HISTORY_PATH: &str = "planet.suzh";
+
+/// Records the tectonic simulation while it runs, R replays it on the finished planet.
+/// Dragging along the timeline or the arrow keys move through the recorded frames.
+pub struct HistoryPlugin {
+    /// Iterations between recorded frames
+    pub interval: usize,
+}
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HistorySettings {
+            interval: self.interval,
+        })
+        .add_systems(
+            OnEnter(SimulationState::Tectonics),
+            start_recording.run_if(first_epoch),
+        )
+        .add_systems(OnExit(SimulationState::Erosion), stop_playback)
+        .add_systems(
+            Update,
+            (
+                toggle_playback.run_if(orbiting),
+                (
+                    scrub_timeline,
+                    step_playback,
+                    update_timeline.run_if(resource_changed::<Playback>),
+                    draw_playback,
+                )
+                    .chain()
+                    .run_if(resource_exists::<Playback>),
+            )
+                .chain()
+                .run_if(
+                    in_state(SimulationState::Erosion).and(resource_exists::<SimTectonicsHistory>),
+                ),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct HistorySettings {
+    interval: usize,
+}
+
+/// Index into [TectonicsHistory::frames] being replayed, only present during playback
+#[derive(Resource)]
+struct Playback {
+    frame: usize,
+}
+
+#[derive(Component)]
+struct PlaybackTimeline;
+
+/// The clickable bar of the timeline
+#[derive(Component)]
+struct PlaybackTrack;
+
+/// Part of the track up to the replayed frame
+#[derive(Component)]
+struct PlaybackFill;
+
+#[derive(Component)]
+struct PlaybackText;
+
+fn start_recording(mut commands: Commands, settings: Res<HistorySettings>) {
+    commands.insert_resource(SimTectonicsHistory(TectonicsHistory::new(
+        settings.interval,
+    )));
+}
+
+fn toggle_playback(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    history: Res<SimTectonicsHistory>,
+    playback: Option<Res<Playback>>,
+    timelines: Query<Entity, With<PlaybackTimeline>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyR) {
+        return;
+    }
+    if playback.is_some() {
+        commands.remove_resource::<Playback>();
+        for entity in &timelines {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    let Some(last) = history.frames.len().checked_sub(1) else {
+        warn!("No tectonics history recorded to replay");
+        return;
+    };
+    commands.insert_resource(Playback { frame: last });
+    commands.spawn((
+        PlaybackTimeline,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(10.),
+            bottom: Val::Px(20.),
+            width: Val::Percent(80.),
+            flex_direction: FlexDirection::Column,
+            ..default()
+        },
+        children![
+            (
+                Text::default(),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(palettes::css::GOLD.into()),
+                PlaybackText,
+            ),
+            (
+                PlaybackTrack,
+                Interaction::default(),
+                RelativeCursorPosition::default(),
+                Node {
+                    width: Val::Percent(100.),
+                    height: Val::Px(12.),
+                    margin: UiRect::top(Val::Px(4.)),
+                    ..default()
+                },
+                BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 0.8).into()),
+                children![(
+                    PlaybackFill,
+                    Node {
+                        width: Val::Percent(100.),
+                        height: Val::Percent(100.),
+                        ..default()
+                    },
+                    BackgroundColor(palettes::css::GOLD.into()),
+                )]
+            )
+        ],
+    ));
+}
+
+fn stop_playback(mut commands: Commands, timelines: Query<Entity, With<PlaybackTimeline>>) {
+    commands.remove_resource::<Playback>();
+    for entity in &timelines {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Holding the mouse on the track jumps to the frame under the cursor
+fn scrub_timeline(
+    history: Res<SimTectonicsHistory>,
+    mut playback: ResMut<Playback>,
+    tracks: Query<(&Interaction, &RelativeCursorPosition), With<PlaybackTrack>>,
+) {
+    let last = history.frames.len().saturating_sub(1);
+    for (interaction, cursor) in &tracks {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        // Relative cursor positions are centered on the node
+        if let Some(normalized) = cursor.normalized {
+            let frame = ((normalized.x + 0.5).clamp(0., 1.) * last as f32).round() as usize;
+            if playback.frame != frame {
+                playback.frame = frame;
+            }
+        }
+    }
+}
+
+fn step_playback(
+    keys: Res<ButtonInput<KeyCode>>,
+    history: Res<SimTectonicsHistory>,
+    mut playback: ResMut<Playback>,
+) {
+    let last = history.frames.len().saturating_sub(1);
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        playback.frame = playback.frame.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        playback.frame = (playback.frame + 1).min(last);
+    }
+}
+
+fn update_timeline(
+    history: Res<SimTectonicsHistory>,
+    playback: Res<Playback>,
+    mut fills: Query<&mut Node, With<PlaybackFill>>,
+    mut texts: Query<&mut Text, With<PlaybackText>>,
+) {
+    let Some(frame) = history.frames.get(playback.frame) else {
+        return;
+    };
+    let last = history.frames.len().saturating_sub(1).max(1);
+    for mut node in &mut fills {
+        node.width = Val::Percent(playback.frame as f32 / last as f32 * 100.);
+    }
+    for mut text in &mut texts {
+        **text = format!(
+            "Iteration {} ({:.2} simulated time), frame {}/{}",
+            frame.iteration,
+            frame.simulated_time,
+            playback.frame + 1,
+            history.frames.len()
+        );
+    }
+}
+
+/// Point masses of the replayed frame in their plate colors, above the finished planet
+fn draw_playback(mut gizmos: Gizmos, history: Res<SimTectonicsHistory>, playback: Res<Playback>) {
+    let Some(frame) = history.frames.get(playback.frame) else {
+        return;
+    };
+    let point_mass_count: usize = frame
+        .plates
+        .iter()
+        .map(|plate| plate.positions().len())
+        .sum();
+    let size = 16. * PI / point_mass_count.max(1) as f32;
+    for plate in &frame.plates {
+        let color = plate_color(plate.color);
+        for position in plate.positions() {
+            gizmos.cross(
+                Isometry3d {
+                    translation: (position * 1.03).into(),
+                    rotation: Quat::from_rotation_arc(Vec3::Z, position),
+                },
+                size,
+                color,
+            );
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+use suz_sim::ice::glaciate;
+
+use crate::{
+    chunks::HexSphereChunks,
+    coastlines::Coastlines,
+    hex_sphere::{HexSphere, HexSphereMeshHandle},
+    persistence::LoadedPlanet,
+    sim_resources::SimEpochSchedule,
+    states::SimulationState,
+    tectonics::TectonicsPluginConfig,
+    tile_data::{TileData, update_climate},
+    vertex_interpolation::apply_tile_heights,
+};
+
+pub struct IcePlugin;
+impl Plugin for IcePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(SimulationState::Erosion),
+            apply_glaciation.after(update_climate),
+        );
+    }
+}
+
+/// Grows glaciers from the finished climate and carves their valleys into the mesh.
+/// Loaded planets were saved with their valleys already carved, so only their ice is rebuilt.
+pub fn apply_glaciation(
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut hex_sphere: ResMut<HexSphere>,
+    mut tile_data: ResMut<TileData>,
+    coastlines: Res<Coastlines>,
+    config: Res<TectonicsPluginConfig>,
+    loaded_planet: Option<Res<LoadedPlanet>>,
+    mesh_handle: Res<HexSphereMeshHandle>,
+    chunks: Res<HexSphereChunks>,
+    epoch_schedule: Option<ResMut<SimEpochSchedule>>,
+) {
+    let before: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+    let mut heights = before.clone();
+    // Every epoch erodes the planet again, a loaded planet only needs its ice back
+    let passes = match (&epoch_schedule, &loaded_planet) {
+        (Some(epoch_schedule), None) => epoch_schedule.config.erosion_passes.max(1),
+        _ => 1,
+    };
+    for _ in 0..passes {
+        tile_data.ice_thickness = glaciate(
+            &config.ice_config,
+            &mut heights,
+            &tile_data.temperature,
+            &tile_data.precipitation,
+            coastlines.sea_level,
+            |tile_index| hex_sphere.tiles[tile_index].adjacent.as_slice(),
+        );
+    }
+    if loaded_planet.is_some() {
+        return;
+    }
+    let deepest_cut = before
+        .iter()
+        .zip(&heights)
+        .map(|(before, after)| before - after)
+        .fold(0., f32::max);
+    let units = config.tectonics_config.units();
+    info!(
+        "Glaciers carved valleys up to {:.0} m deep",
+        deepest_cut * units.meters_per_height()
+    );
+    if let Some(mut epoch_schedule) = epoch_schedule {
+        epoch_schedule.record_erosion(&before, &heights);
+    }
+    if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
+        apply_tile_heights(&mut hex_sphere, mesh, &heights);
+    }
+    chunks.patch_all(&mut meshes, &mesh_handle.0);
+}
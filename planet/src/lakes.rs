@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use suz_sim::hydrology::{Lake, fill_depressions, find_lakes};
+
+use crate::{
+    coastlines::Coastlines, hex_sphere::HexSphere, ice::apply_glaciation, states::SimulationState,
+};
+
+/// Inland water of the finished planet
+#[derive(Resource, Default)]
+pub struct Lakes {
+    pub lakes: Vec<Lake>,
+    /// For each tile, the index of the lake covering it, [None] for dry land and ocean
+    pub tile_to_lake: Vec<Option<usize>>,
+    /// Tile heights with every depression raised to its spill level, water flows downhill on it from any tile to the ocean
+    pub filled_heights: Vec<f32>,
+}
+
+impl Lakes {
+    pub fn from_hex_sphere(hex_sphere: &HexSphere, sea_level: f32) -> Self {
+        let heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+        let adjacent = |tile_index: usize| hex_sphere.tiles[tile_index].adjacent.as_slice();
+        let filled_heights = fill_depressions(&heights, sea_level, adjacent);
+        let lakes = find_lakes(&heights, &filled_heights, sea_level, adjacent);
+        let mut tile_to_lake = vec![None; heights.len()];
+        for (lake_index, lake) in lakes.iter().enumerate() {
+            for &tile_index in &lake.tiles {
+                tile_to_lake[tile_index] = Some(lake_index);
+            }
+        }
+        Lakes {
+            lakes,
+            tile_to_lake,
+            filled_heights,
+        }
+    }
+}
+
+pub struct LakesPlugin;
+impl Plugin for LakesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lakes>()
+            .add_systems(
+                OnEnter(SimulationState::Erosion),
+                // Glaciers carve new basins
+                detect_lakes.after(apply_glaciation),
+            )
+            // Regenerating starts a new planet, so the old lakes should not be drawn on it
+            .add_systems(OnExit(SimulationState::Erosion), clear_lakes);
+    }
+}
+
+pub fn detect_lakes(
+    mut lakes: ResMut<Lakes>,
+    hex_sphere: Res<HexSphere>,
+    coastlines: Res<Coastlines>,
+) {
+    *lakes = Lakes::from_hex_sphere(&hex_sphere, coastlines.sea_level);
+}
+
+fn clear_lakes(mut lakes: ResMut<Lakes>) {
+    *lakes = Lakes::default();
+}
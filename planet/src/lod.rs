@@ -0,0 +1,275 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+use subsphere::{Face, Sphere, Vertex};
+use suz_sim::vec_utils;
+
+use crate::{
+    MainCamera,
+    chunks::{ChunkIndex, HexSphereChunks},
+    hex_sphere::{HexSphere, HexSphereMeshHandle},
+    sim_resources::SimHexSphereConfig,
+};
+
+/// Chunks whose center faces the camera less than this are drawn at the first reduced level
+const LIMB_FACING: f32 = 0.3;
+/// Chunks whose center faces the camera less than this are entirely on the far side and drawn at the coarsest level
+const BACK_FACING: f32 = -0.65;
+/// Skirts hang this fraction of the surface radius below the edges of reduced chunks, covering cracks to their neighbours
+const SKIRT_DEPTH: f32 = 0.03;
+
+/// One vertex of a reduced chunk mesh, placed along a direction at the radius of a full mesh vertex
+#[derive(Clone, Copy)]
+struct ChunkVertex {
+    /// Index into [HexSphere::vertices] the radius and color are taken from
+    source: usize,
+    direction: Vec3,
+    /// Factor on the radius, below one for skirts
+    scale: f32,
+}
+
+struct ChunkLevel {
+    vertices: Vec<ChunkVertex>,
+    indices: Vec<u32>,
+    mesh: Handle<Mesh>,
+    /// The full mesh changed since this level was last built
+    stale: bool,
+}
+
+/// Reduced levels of a [HexSphereChunks] chunk, only added to planets with enough subdivisions
+#[derive(Component)]
+struct LodChunk {
+    /// Reduced levels from finest to coarsest
+    levels: Vec<ChunkLevel>,
+    /// The level being drawn, 0 is the full detail chunk mesh and `i` is [LodChunk::levels]`[i - 1]`
+    level: usize,
+}
+
+/// Draws chunks far from or facing away from the camera at reduced detail on large planets.
+/// The full detail chunk meshes stay patched by the systems editing the planet, reduced levels are rebuilt from the full mesh when drawn after it changed.
+pub struct LodPlugin {
+    /// Planets with fewer subdivisions are always drawn at full detail
+    pub min_subdivisions: u32,
+    /// Triangles along each base face edge for the reduced levels, from finest to coarsest
+    pub reduced_resolutions: Vec<u32>,
+    /// Chunks further than this from a perspective camera are drawn at the first reduced level
+    pub far_distance: f32,
+}
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LodSettings {
+            min_subdivisions: self.min_subdivisions,
+            reduced_resolutions: self.reduced_resolutions.clone(),
+            far_distance: self.far_distance,
+        })
+        .add_systems(
+            Update,
+            (
+                add_reduced_levels.run_if(resource_exists_and_changed::<HexSphereChunks>),
+                update_levels.run_if(resource_exists::<HexSphereChunks>),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct LodSettings {
+    min_subdivisions: u32,
+    reduced_resolutions: Vec<u32>,
+    far_distance: f32,
+}
+
+/// A triangular grid over the base face with `resolution` triangles along each edge, every vertex takes the height and color of the tile under it.
+/// Points on a shared edge only depend on the edge's end points, so neighbouring chunks at the same resolution meet exactly.
+fn reduced_level(
+    hex_sphere: &HexSphere,
+    corners: [Vec3; 3],
+    resolution: u32,
+) -> (Vec<ChunkVertex>, Vec<u32>) {
+    let [a, b, c] = corners;
+    let n = resolution.max(1);
+    let index = |i: u32, j: u32| -> u32 {
+        // Rows of decreasing length, row i holds n + 1 - i points
+        i * (n + 1) - i * (i.saturating_sub(1)) / 2 + j
+    };
+    let mut vertices = Vec::new();
+    for i in 0..=n {
+        for j in 0..=(n - i) {
+            let weights = [(n - i - j) as f32, i as f32, j as f32];
+            let direction = (a * weights[0] + b * weights[1] + c * weights[2]).normalize();
+            vertices.push(ChunkVertex {
+                source: hex_sphere.tile_at(direction).center,
+                direction,
+                scale: 1.,
+            });
+        }
+    }
+    let mut indices = Vec::new();
+    for i in 0..n {
+        for j in 0..(n - i) {
+            indices.extend([index(i, j), index(i + 1, j), index(i, j + 1)]);
+            if i + j + 1 < n {
+                indices.extend([index(i + 1, j), index(i + 1, j + 1), index(i, j + 1)]);
+            }
+        }
+    }
+
+    // Skirts around the border, drawn from both sides since they face neither in nor out
+    let border: Vec<u32> = (0..n)
+        .map(|i| index(i, 0))
+        .chain((0..n).map(|j| index(n - j, j)))
+        .chain((0..n).map(|k| index(0, n - k)))
+        .collect();
+    let skirt_start = vertices.len() as u32;
+    for &top in &border {
+        vertices.push(ChunkVertex {
+            scale: 1. - SKIRT_DEPTH,
+            ..vertices[top as usize]
+        });
+    }
+    for k in 0..border.len() {
+        let next = (k + 1) % border.len();
+        let (top, top_next) = (border[k], border[next]);
+        let (bottom, bottom_next) = (skirt_start + k as u32, skirt_start + next as u32);
+        indices.extend([top, bottom, top_next, top_next, bottom, bottom_next]);
+        indices.extend([top, top_next, bottom, top_next, bottom_next, bottom]);
+    }
+    (vertices, indices)
+}
+
+/// Gathers the reduced level's vertices from the full mesh
+fn level_mesh(level: &ChunkLevel, hex_sphere: &HexSphere) -> Mesh {
+    let positions: Vec<[f32; 3]> = level
+        .vertices
+        .iter()
+        .map(|vertex| {
+            let radius = Vec3::from(hex_sphere.vertices[vertex.source]).length();
+            (vertex.direction * radius * vertex.scale).into()
+        })
+        .collect();
+    let colors: Vec<[f32; 4]> = level
+        .vertices
+        .iter()
+        .map(|vertex| hex_sphere.colors[vertex.source])
+        .collect();
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(level.indices.clone()))
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.compute_normals();
+    mesh
+}
+
+/// Gives every chunk of a newly generated planet its reduced levels
+fn add_reduced_levels(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    settings: Res<LodSettings>,
+    hex_sphere_config: Res<SimHexSphereConfig>,
+    hex_sphere: Res<HexSphere>,
+    chunks: Query<(Entity, &ChunkIndex)>,
+) {
+    if hex_sphere_config.subdivisions < settings.min_subdivisions {
+        return;
+    }
+    for (entity, chunk_index) in &chunks {
+        let base_face = subsphere::BaseTriSphere::Icosa.face(chunk_index.0);
+        let mut corners: Vec<Vec3> = base_face
+            .vertices()
+            .map(|vertex| Vec3::from(vec_utils::f64_3_to_f32_3(&vertex.pos())))
+            .collect();
+        // Reduced levels are wound counter-clockwise seen from outside, like the full mesh
+        if (corners[1] - corners[0])
+            .cross(corners[2] - corners[0])
+            .dot(corners[0])
+            < 0.
+        {
+            corners.swap(1, 2);
+        }
+        let corners = [corners[0], corners[1], corners[2]];
+        let levels: Vec<ChunkLevel> = settings
+            .reduced_resolutions
+            .iter()
+            .map(|&resolution| {
+                let (vertices, indices) = reduced_level(&hex_sphere, corners, resolution);
+                let mut level = ChunkLevel {
+                    vertices,
+                    indices,
+                    mesh: Handle::default(),
+                    stale: false,
+                };
+                level.mesh = meshes.add(level_mesh(&level, &hex_sphere));
+                level
+            })
+            .collect();
+        commands
+            .entity(entity)
+            .insert(LodChunk { levels, level: 0 });
+    }
+}
+
+/// Picks the level of every chunk from the camera and rebuilds the drawn reduced level when the full mesh changed.
+/// Levels that are not drawn are only marked stale and rebuilt once they are swapped in.
+fn update_levels(
+    mut mesh_events: EventReader<AssetEvent<Mesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    settings: Res<LodSettings>,
+    hex_sphere: Res<HexSphere>,
+    hex_sphere_chunks: Res<HexSphereChunks>,
+    mesh_handle: Res<HexSphereMeshHandle>,
+    camera_query: Query<(&GlobalTransform, &Projection), With<MainCamera>>,
+    mut chunks: Query<(&ChunkIndex, &mut LodChunk, &mut Mesh3d)>,
+) {
+    let full_changed = mesh_events
+        .read()
+        .any(|event| matches!(event, AssetEvent::Modified { id } if *id == mesh_handle.0.id()));
+    let Ok((camera_transform, projection)) = camera_query.single() else {
+        return;
+    };
+    // Distance does not change how large things look through an orthographic camera
+    let perspective = matches!(projection, Projection::Perspective(_));
+    let camera_position = camera_transform.translation();
+    let coarsest = settings.reduced_resolutions.len();
+    for (chunk_index, mut lod_chunk, mut mesh_3d) in &mut chunks {
+        if full_changed {
+            for level in &mut lod_chunk.levels {
+                level.stale = true;
+            }
+        }
+        let chunk = &hex_sphere_chunks.chunks[chunk_index.0];
+        let to_camera = camera_position - chunk.center;
+        let facing = chunk.center.dot(to_camera.normalize_or_zero());
+        let level = if facing < BACK_FACING {
+            coarsest
+        } else if facing < LIMB_FACING
+            || (perspective && to_camera.length() > settings.far_distance)
+        {
+            coarsest.min(1)
+        } else {
+            0
+        };
+        let lod_chunk = &mut *lod_chunk;
+        if level > 0 {
+            let drawn = &mut lod_chunk.levels[level - 1];
+            if drawn.stale {
+                if let Some(mesh) = meshes.get_mut(&drawn.mesh) {
+                    *mesh = level_mesh(drawn, &hex_sphere);
+                }
+                drawn.stale = false;
+            }
+        }
+        if lod_chunk.level != level {
+            lod_chunk.level = level;
+            mesh_3d.0 = match level {
+                0 => chunk.mesh.clone(),
+                _ => lod_chunk.levels[level - 1].mesh.clone(),
+            };
+        }
+    }
+}
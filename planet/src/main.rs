@@ -1,72 +1,289 @@
-#![feature(slice_as_array)]
-
 use crate::{
+    atmosphere::{AtmosphereConfig, AtmospherePlugin},
+    capture::CapturePlugin,
+    chunks::ChunksPlugin,
+    clouds::{CloudConfig, CloudsPlugin},
+    coastlines::CoastlinesPlugin,
+    coloring::ColoringPlugin,
+    continents::ContinentsPlugin,
     debug_ui::{DebugDiagnostics, DebugUIPlugin},
-    hex_sphere::{HexSphereConfig, HexSpherePlugin},
+    detail::DetailPlugin,
+    epochs::EpochsPlugin,
+    export::{ExportConfig, ExportPlugin},
+    fly_camera::FlyCameraPlugin,
+    graticule::GraticulePlugin,
+    hex_sphere::HexSpherePlugin,
+    history::HistoryPlugin,
+    ice::IcePlugin,
+    lakes::LakesPlugin,
+    lod::LodPlugin,
+    menu::MenuPlugin,
+    parameter_panel::ParameterPanelPlugin,
+    persistence::PersistencePlugin,
+    progress::ProgressPlugin,
+    regenerate::RegeneratePlugin,
+    seed_input::SeedInputPlugin,
+    selection::SelectionPlugin,
+    sim_diagnostics::SimulationDiagnosticsPlugin,
+    starfield::{StarfieldConfig, StarfieldPlugin},
     states::SimulationState,
-    tectonics::{TectonicsPlugin, TectonicsPluginConfig},
+    sun::{SunConfig, SunPlugin},
+    tectonics::{FrameBudget, TectonicsPlugin, TectonicsPluginConfig},
+    textured::TexturedPlugin,
+    tile_data::TileDataPlugin,
+    tile_inspector::TileInspectorPlugin,
+    water::WaterPlugin,
+};
+use bevy::{
+    diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
+    prelude::*,
+    render::camera::ScalingMode,
 };
-use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*, render::camera::ScalingMode};
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 use rand::SeedableRng;
-use suz_sim::{particle_sphere::ParticleSphereConfig, tectonics::TectonicsConfiguration};
+use std::time::Duration;
+use suz_sim::{
+    bake::BakeConfig,
+    config::{Preset, SimulationConfig},
+    export::Projection as MapProjection,
+    generator::DEFAULT_OCEAN_FRACTION,
+};
 
+mod atmosphere;
+mod background_simulation;
+mod capture;
+mod chunks;
+mod clouds;
+mod coastlines;
+mod coloring;
+mod continents;
 mod debug_ui;
+mod detail;
+mod energy;
+mod epochs;
+mod export;
+mod fly_camera;
+mod graticule;
 mod hex_sphere;
+mod history;
+mod ice;
+mod lakes;
+mod lod;
+mod menu;
+mod parameter_panel;
+mod persistence;
+mod plate_boundaries;
+mod progress;
+mod regenerate;
+mod seed_input;
+mod selection;
+mod sim_diagnostics;
+mod sim_resources;
+mod sparkline;
+mod starfield;
 mod states;
+mod strain_rate;
+mod sun;
 mod tectonics;
+mod textured;
+mod tile_coords;
+mod tile_data;
+mod tile_inspector;
 mod vertex_interpolation;
+mod water;
 
 fn main() {
-    let seed = rand::random::<u64>();
-    App::new()
-        .add_plugins((
-            DefaultPlugins
-                .set(ImagePlugin::default_nearest())
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "Suzerainty".to_string(),
-                        ..Default::default()
-                    }),
+    // An optional path to a config file can be passed as an argument, it is listed as the first preset.
+    // `--seed <u64>` picks the starting seed instead of a random one.
+    // `--frame-budget <ms>` sets how long the tectonic simulation may run each frame
+    // `--log-diagnostics` logs frame time and simulation diagnostics every second
+    // `--no-starfield` leaves the background black, for benchmarking
+    let mut config_path = None;
+    let mut seed = None;
+    let mut frame_budget = FrameBudget::default();
+    let mut log_diagnostics = false;
+    let mut starfield = true;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            let value = args.next().unwrap_or_default();
+            seed = Some(value.parse::<u64>().unwrap_or_else(|e| {
+                eprintln!("Invalid seed {value}: {e}");
+                std::process::exit(1);
+            }));
+        } else if arg == "--frame-budget" {
+            let value = args.next().unwrap_or_default();
+            let milliseconds = value.parse::<u64>().unwrap_or_else(|e| {
+                eprintln!("Invalid frame budget {value}: {e}");
+                std::process::exit(1);
+            });
+            frame_budget = FrameBudget(Duration::from_millis(milliseconds));
+        } else if arg == "--log-diagnostics" {
+            log_diagnostics = true;
+        } else if arg == "--no-starfield" {
+            starfield = false;
+        } else {
+            config_path = Some(arg);
+        }
+    }
+    let mut presets = Preset::all();
+    if let Some(path) = config_path {
+        let config = SimulationConfig::load(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to load config {path}: {e}");
+            std::process::exit(1);
+        });
+        presets.insert(
+            0,
+            Preset {
+                name: "Config file".to_string(),
+                description: path,
+                config,
+            },
+        );
+    }
+    let config = presets[0].config;
+    let seed = seed.unwrap_or_else(rand::random::<u64>);
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(ImagePlugin::default_nearest())
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Suzerainty".to_string(),
                     ..Default::default()
                 }),
-            PanOrbitCameraPlugin,
-            FrameTimeDiagnosticsPlugin {
-                max_history_length: 60,
-                smoothing_factor: 0.1,
+                ..Default::default()
+            }),
+        PanOrbitCameraPlugin,
+        FlyCameraPlugin {
+            speed: 1.,
+            sensitivity: 0.003,
+            min_altitude: 0.002,
+            start_altitude: 0.05,
+        },
+        ColoringPlugin,
+        // Bevy only accepts plugin tuples of up to 15 elements
+        (
+            CoastlinesPlugin {
+                ocean_fraction: DEFAULT_OCEAN_FRACTION,
+            },
+            TileDataPlugin,
+            IcePlugin,
+            LakesPlugin,
+            TileInspectorPlugin,
+            SelectionPlugin { brush_radius: 0.1 },
+            ChunksPlugin,
+            LodPlugin {
+                min_subdivisions: 64,
+                reduced_resolutions: vec![32, 8],
+                far_distance: 2.,
             },
-            DebugUIPlugin {
-                diagnostics: DebugDiagnostics::seed(seed),
+            ContinentsPlugin,
+            HistoryPlugin { interval: 10 },
+            ProgressPlugin,
+            EpochsPlugin,
+            DetailPlugin,
+            SimulationDiagnosticsPlugin,
+            CapturePlugin {
+                turntable_frames: 120,
             },
-            HexSpherePlugin {
-                config: HexSphereConfig { subdivisions: 128 },
+        ),
+        FrameTimeDiagnosticsPlugin {
+            max_history_length: 60,
+            smoothing_factor: 0.1,
+        },
+        DebugUIPlugin {
+            diagnostics: DebugDiagnostics::seed(seed),
+        },
+        MenuPlugin { presets },
+        ParameterPanelPlugin,
+        PersistencePlugin,
+        RegeneratePlugin,
+        SeedInputPlugin,
+        ExportPlugin {
+            config: ExportConfig {
+                heightmap_width: 2048,
+                map_projection: MapProjection::Equirectangular,
+                bake: BakeConfig::default(),
+                glb_plate_ids: true,
+                timelapse_width: 1024,
+                timelapse_every: 20,
             },
-            TectonicsPlugin {
-                config: TectonicsPluginConfig {
-                    tectonics_config: TectonicsConfiguration {
-                        major_plate_fraction: 0.3,
-                        major_tile_fraction: 0.4,
-                        plate_goal: 30,
-                        continental_rate: 0.4,
-                        min_plate_size: 15,
-                        vertex_interpolation_radius: 0.10,
-                        spring_constant: 2.0,
-                        dampener_coefficient: 0.5,
-                        plate_force_modifier: 0.04,
-                        plate_rotation_drift_rate: 0.001,
-                        timestep: 0.10,
-                        iterations: 200,
-                        friction_coefficient: 0.6,
-                    },
-                    particle_config: ParticleSphereConfig { subdivisions: 64 },
-                },
+        },
+        HexSpherePlugin {
+            config: config.hex_sphere,
+        },
+        TectonicsPlugin {
+            config: TectonicsPluginConfig {
+                tectonics_config: config.tectonics,
+                particle_config: config.particle_sphere,
+                flexure_config: config.flexure,
+                climate_config: config.climate,
+                ice_config: config.ice,
+                epoch_config: config.epochs,
+                bathymetry_config: config.bathymetry,
+                shelf_config: config.shelf,
+                island_arc_config: config.island_arcs,
+                detail_config: config.detail,
             },
-        ))
-        .add_systems(Startup, setup)
-        .insert_resource(ClearColor(LinearRgba::BLACK.into()))
-        .insert_resource(GlobalRng(rand::rngs::StdRng::seed_from_u64(seed)))
-        .init_state::<SimulationState>()
-        .run();
+        },
+    ))
+    .add_plugins((
+        GraticulePlugin {
+            meridian_spacing: 30.,
+        },
+        TexturedPlugin,
+        WaterPlugin {
+            color: Color::srgba(0.1, 0.3, 0.6, 0.6),
+            wave_speed: Some(0.05),
+        },
+        CloudsPlugin {
+            config: CloudConfig {
+                visible: true,
+                opacity: 0.8,
+                altitude: 0.02,
+                coverage: 0.45,
+                precipitation_correlation: 0.3,
+                resolution: 512,
+                speed: 0.02,
+            },
+        },
+        SunPlugin {
+            config: SunConfig {
+                illuminance: 2000.,
+                night_illuminance: 100.,
+                day_length: 120.,
+                days_per_year: 20.,
+            },
+        },
+        AtmospherePlugin {
+            config: AtmosphereConfig {
+                visible: false,
+                color: LinearRgba::new(0.3, 0.55, 1.0, 1.0),
+                thickness: 0.05,
+                density: 1.5,
+                falloff: 2.,
+            },
+        },
+    ))
+    .add_systems(Startup, setup)
+    .insert_resource(ClearColor(LinearRgba::BLACK.into()))
+    .insert_resource(GlobalRng(rand::rngs::StdRng::seed_from_u64(seed)))
+    .insert_resource(frame_budget)
+    .init_state::<SimulationState>();
+    if log_diagnostics {
+        app.add_plugins(LogDiagnosticsPlugin::default());
+    }
+    if starfield {
+        app.add_plugins(StarfieldPlugin {
+            config: StarfieldConfig {
+                seed,
+                stars: 4000,
+                brightness: 1.,
+            },
+        });
+    }
+    app.run();
 }
 
 #[derive(Resource)]
@@ -76,13 +293,6 @@ pub struct GlobalRng(pub rand::rngs::StdRng);
 pub struct MainCamera;
 
 fn setup(mut commands: Commands) {
-    commands.spawn((
-        PointLight {
-            shadows_enabled: true,
-            ..default()
-        },
-        Transform::from_xyz(4.0, 8.0, 4.0),
-    ));
     // camera
     commands.spawn((
         MainCamera,
@@ -0,0 +1,408 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    color::palettes,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use rand::SeedableRng;
+use suz_sim::{
+    config::{ConfigError, HexSphereConfig, Preset},
+    interpolation::interpolate_tile_heights,
+    particle_sphere::{ParticleSphere, ParticleSphereConfig},
+    tectonics::Tectonics,
+    vec_utils,
+};
+
+use crate::{
+    GlobalRng, coloring::ColorRamp, debug_ui::DebugDiagnostics, persistence::SAVE_PATH,
+    seed_input::SeedInput, sim_resources::SimHexSphereConfig, states::SimulationState,
+    tectonics::TectonicsPluginConfig,
+};
+
+const THUMBNAIL_WIDTH: u32 = 96;
+const THUMBNAIL_HEIGHT: u32 = 48;
+/// Thumbnails are generated on a tiny particle sphere so the menu opens instantly
+const THUMBNAIL_SUBDIVISIONS: u32 = 8;
+const THUMBNAIL_ITERATIONS: usize = 50;
+/// Hex sphere subdivisions the user can cycle between
+const RESOLUTIONS: [u32; 4] = [32, 64, 128, 256];
+
+#[derive(Resource)]
+pub struct Presets(pub Vec<Preset>);
+
+#[derive(Resource)]
+struct MenuSelection {
+    preset: usize,
+    seed: u64,
+    resolution: u32,
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component, Clone, Copy)]
+enum MenuButton {
+    Preset(usize),
+    RerollSeed,
+    Resolution,
+    Generate,
+    Load,
+}
+
+#[derive(Component)]
+struct PresetThumbnail(usize);
+
+#[derive(Component)]
+struct MenuSeedText;
+
+#[derive(Component)]
+struct MenuResolutionText;
+
+#[derive(Component)]
+struct MenuDescriptionText;
+
+pub struct MenuPlugin {
+    pub presets: Vec<Preset>,
+}
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Presets(self.presets.clone()))
+            .add_systems(OnEnter(SimulationState::Menu), setup)
+            .add_systems(OnExit(SimulationState::Menu), teardown)
+            .add_systems(
+                Update,
+                (
+                    menu_buttons.run_if(resource_exists::<MenuSelection>),
+                    sync_seed.run_if(resource_exists::<MenuSelection>),
+                    update_thumbnails.run_if(resource_exists_and_changed::<MenuSelection>),
+                    update_menu_texts.run_if(resource_exists_and_changed::<MenuSelection>),
+                )
+                    .chain()
+                    .run_if(in_state(SimulationState::Menu)),
+            );
+    }
+}
+
+/// Runs a short tectonic simulation at tiny resolution and renders the result as an equirectangular image
+fn render_thumbnail(
+    preset: &Preset,
+    seed: u64,
+    color_ramp: &ColorRamp,
+) -> Result<Image, ConfigError> {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    let particle_sphere = ParticleSphere::from_config(
+        ParticleSphereConfig {
+            subdivisions: THUMBNAIL_SUBDIVISIONS,
+            ..preset.config.particle_sphere
+        },
+        &mut rng,
+    );
+    let mut config = preset.config.tectonics;
+    // Particles are spread far apart at this resolution, so widen the interpolation and allow tiny plates
+    config.vertex_interpolation_radius = config
+        .vertex_interpolation_radius
+        .max(4. / THUMBNAIL_SUBDIVISIONS as f32);
+    config.min_plate_size = 1;
+    let mut tectonics = Tectonics::from_config(config, &particle_sphere, &mut rng)?;
+    for _ in 0..THUMBNAIL_ITERATIONS {
+        tectonics.simulate(&mut rng);
+    }
+
+    let normals: Vec<Vec3> = (0..THUMBNAIL_HEIGHT)
+        .flat_map(|y| {
+            let latitude = PI / 2. - (y as f32 + 0.5) / THUMBNAIL_HEIGHT as f32 * PI;
+            (0..THUMBNAIL_WIDTH).map(move |x| {
+                let longitude = (x as f32 + 0.5) / THUMBNAIL_WIDTH as f32 * 2. * PI - PI;
+                vec_utils::lat_long_to_vec3(latitude, longitude)
+            })
+        })
+        .collect();
+    let data = interpolate_tile_heights(&tectonics, &normals)
+        .into_iter()
+        .flat_map(|height| {
+            color_ramp
+                .sample(height)
+                .map(|channel| (channel.clamp(0., 1.) * 255.) as u8)
+        })
+        .collect();
+    Ok(Image::new(
+        Extent3d {
+            width: THUMBNAIL_WIDTH,
+            height: THUMBNAIL_HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    ))
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    presets: Res<Presets>,
+    hex_sphere_config: Res<SimHexSphereConfig>,
+    diagnostics: Res<DebugDiagnostics>,
+) {
+    commands.insert_resource(MenuSelection {
+        preset: 0,
+        seed: diagnostics.seed,
+        resolution: hex_sphere_config.subdivisions,
+    });
+
+    let label_font = TextFont {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 14.0,
+        ..default()
+    };
+    let value_font = TextFont {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 14.0,
+        ..default()
+    };
+    let button = |label: &str, menu_button: MenuButton| {
+        (
+            Button,
+            menu_button,
+            Node {
+                padding: UiRect::axes(Val::Px(10.), Val::Px(4.)),
+                margin: UiRect::left(Val::Px(10.)),
+                border: UiRect::all(Val::Px(1.)),
+                ..default()
+            },
+            BorderColor(LinearRgba::new(0.4, 0.4, 0.4, 1.).into()),
+            BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 1.).into()),
+            children![(Text::new(label), label_font.clone())],
+        )
+    };
+
+    commands
+        .spawn((
+            MenuRoot,
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(15.),
+                ..default()
+            },
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new("Suzerainty"),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 32.0,
+                    ..default()
+                },
+            ));
+            root.spawn(Node {
+                column_gap: Val::Px(10.),
+                ..default()
+            })
+            .with_children(|row| {
+                for (index, preset) in presets.0.iter().enumerate() {
+                    row.spawn((
+                        Button,
+                        MenuButton::Preset(index),
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            padding: UiRect::all(Val::Px(5.)),
+                            border: UiRect::all(Val::Px(2.)),
+                            ..default()
+                        },
+                        BorderColor(LinearRgba::new(0.2, 0.2, 0.2, 1.).into()),
+                        BackgroundColor(LinearRgba::new(0.01, 0.01, 0.01, 0.8).into()),
+                    ))
+                    .with_children(|card| {
+                        card.spawn((
+                            PresetThumbnail(index),
+                            ImageNode::default(),
+                            Node {
+                                width: Val::Px(THUMBNAIL_WIDTH as f32 * 2.),
+                                height: Val::Px(THUMBNAIL_HEIGHT as f32 * 2.),
+                                ..default()
+                            },
+                        ));
+                        card.spawn((Text::new(preset.name.clone()), label_font.clone()));
+                    });
+                }
+            });
+            root.spawn((
+                Text::default(),
+                value_font.clone(),
+                TextColor(palettes::css::GOLD.into()),
+                MenuDescriptionText,
+            ));
+            root.spawn(Node {
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn((Text::new("Seed: "), label_font.clone()));
+                row.spawn((
+                    SeedInput::new(diagnostics.seed),
+                    value_font.clone(),
+                    TextColor(palettes::css::GOLD.into()),
+                    MenuSeedText,
+                ));
+                row.spawn(button("Reroll", MenuButton::RerollSeed));
+            });
+            root.spawn(Node {
+                align_items: AlignItems::Center,
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn((Text::new("Subdivisions: "), label_font.clone()));
+                row.spawn((
+                    Text::default(),
+                    value_font.clone(),
+                    TextColor(palettes::css::GOLD.into()),
+                    MenuResolutionText,
+                ));
+                row.spawn(button("Change", MenuButton::Resolution));
+            });
+            root.spawn(Node::default()).with_children(|row| {
+                row.spawn(button("Generate", MenuButton::Generate));
+                row.spawn(button(&format!("Load {SAVE_PATH}"), MenuButton::Load));
+            });
+        });
+}
+
+fn teardown(mut commands: Commands, menu_root: Query<Entity, With<MenuRoot>>) {
+    for entity in &menu_root {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<MenuSelection>();
+}
+
+fn menu_buttons(
+    interactions: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    presets: Res<Presets>,
+    mut selection: ResMut<MenuSelection>,
+    mut seed_input: Query<&mut SeedInput, With<MenuSeedText>>,
+    mut hex_sphere_config: ResMut<SimHexSphereConfig>,
+    mut tectonics_plugin_config: ResMut<TectonicsPluginConfig>,
+    mut diagnostics: ResMut<DebugDiagnostics>,
+    mut rng: ResMut<GlobalRng>,
+    mut next_state: ResMut<NextState<SimulationState>>,
+) {
+    for (interaction, menu_button) in &interactions {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match menu_button {
+            MenuButton::Preset(index) => selection.preset = *index,
+            MenuButton::RerollSeed => {
+                if let Ok(mut seed_input) = seed_input.single_mut() {
+                    seed_input.seed = rand::random::<u64>();
+                }
+            }
+            MenuButton::Resolution => {
+                let current = RESOLUTIONS
+                    .iter()
+                    .position(|&resolution| resolution == selection.resolution)
+                    .unwrap_or(0);
+                selection.resolution = RESOLUTIONS[(current + 1) % RESOLUTIONS.len()];
+            }
+            MenuButton::Generate => {
+                let config = presets.0[selection.preset].config;
+                if let Err(e) = config.validate() {
+                    warn!(
+                        "Cannot generate preset {}: {e}",
+                        presets.0[selection.preset].name
+                    );
+                    continue;
+                }
+                hex_sphere_config.0 = HexSphereConfig {
+                    subdivisions: selection.resolution,
+                };
+                *tectonics_plugin_config = TectonicsPluginConfig {
+                    tectonics_config: config.tectonics,
+                    particle_config: config.particle_sphere,
+                    flexure_config: config.flexure,
+                    climate_config: config.climate,
+                    ice_config: config.ice,
+                    epoch_config: config.epochs,
+                    bathymetry_config: config.bathymetry,
+                    shelf_config: config.shelf,
+                    island_arc_config: config.island_arcs,
+                    detail_config: config.detail,
+                };
+                // The seed may still be mid-edit and not yet synced to the selection
+                let seed = seed_input
+                    .single()
+                    .map_or(selection.seed, |seed_input| seed_input.seed);
+                diagnostics.seed = seed;
+                rng.0 = rand::rngs::StdRng::seed_from_u64(seed);
+                next_state.set(SimulationState::MeshGen);
+            }
+            MenuButton::Load => next_state.set(SimulationState::LoadFromDisk),
+        }
+    }
+}
+
+/// Thumbnails are only regenerated once the user is done typing a seed
+fn sync_seed(
+    seed_input: Query<&SeedInput, (With<MenuSeedText>, Changed<SeedInput>)>,
+    mut selection: ResMut<MenuSelection>,
+) {
+    if let Ok(seed_input) = seed_input.single() {
+        if !seed_input.editing() && seed_input.seed != selection.seed {
+            selection.seed = seed_input.seed;
+        }
+    }
+}
+
+fn update_thumbnails(
+    mut images: ResMut<Assets<Image>>,
+    presets: Res<Presets>,
+    selection: Res<MenuSelection>,
+    color_ramp: Res<ColorRamp>,
+    mut thumbnails: Query<(&PresetThumbnail, &mut ImageNode)>,
+    mut cards: Query<(&MenuButton, &mut BorderColor)>,
+    mut generated_for_seed: Local<Option<u64>>,
+) {
+    if *generated_for_seed != Some(selection.seed) || color_ramp.is_changed() {
+        for (thumbnail, mut image_node) in &mut thumbnails {
+            // Invalid presets keep an empty thumbnail, the description shows why
+            if let Ok(image) =
+                render_thumbnail(&presets.0[thumbnail.0], selection.seed, &color_ramp)
+            {
+                image_node.image = images.add(image);
+            }
+        }
+        *generated_for_seed = Some(selection.seed);
+    }
+    for (menu_button, mut border_color) in &mut cards {
+        if let MenuButton::Preset(index) = menu_button {
+            *border_color = if *index == selection.preset {
+                BorderColor(palettes::css::GOLD.into())
+            } else {
+                BorderColor(LinearRgba::new(0.2, 0.2, 0.2, 1.).into())
+            };
+        }
+    }
+}
+
+fn update_menu_texts(
+    presets: Res<Presets>,
+    selection: Res<MenuSelection>,
+    mut texts: ParamSet<(
+        Query<&mut Text, With<MenuResolutionText>>,
+        Query<&mut Text, With<MenuDescriptionText>>,
+    )>,
+) {
+    **texts.p0().single_mut().unwrap() = selection.resolution.to_string();
+    let preset = &presets.0[selection.preset];
+    **texts.p1().single_mut().unwrap() = match preset.config.validate() {
+        Ok(()) => preset.description.clone(),
+        Err(e) => format!("{}\n{e}", preset.description),
+    };
+}
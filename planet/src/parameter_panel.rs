@@ -0,0 +1,199 @@
+use bevy::{color::palettes, prelude::*};
+use suz_sim::tectonics::TectonicsConfiguration;
+
+use crate::{
+    background_simulation::BackgroundSimulation, sim_resources::SimTectonics,
+    states::SimulationState, tectonics::TectonicsPluginConfig,
+};
+
+/// [TectonicsConfiguration] fields that can be changed while the simulation runs
+#[derive(Clone, Copy)]
+enum TectonicsParameter {
+    SpringConstant,
+    FrictionCoefficient,
+    PlateForceModifier,
+    PlateRotationDriftRate,
+}
+
+impl TectonicsParameter {
+    const ALL: [TectonicsParameter; 4] = [
+        TectonicsParameter::SpringConstant,
+        TectonicsParameter::FrictionCoefficient,
+        TectonicsParameter::PlateForceModifier,
+        TectonicsParameter::PlateRotationDriftRate,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            TectonicsParameter::SpringConstant => "Spring constant: ",
+            TectonicsParameter::FrictionCoefficient => "Friction: ",
+            TectonicsParameter::PlateForceModifier => "Plate force: ",
+            TectonicsParameter::PlateRotationDriftRate => "Drift rate: ",
+        }
+    }
+
+    /// How much a single button press changes the value
+    fn step(self) -> f32 {
+        match self {
+            TectonicsParameter::SpringConstant => 0.1,
+            TectonicsParameter::FrictionCoefficient => 0.05,
+            TectonicsParameter::PlateForceModifier => 0.005,
+            TectonicsParameter::PlateRotationDriftRate => 0.0005,
+        }
+    }
+
+    fn value(self, config: &TectonicsConfiguration) -> f32 {
+        let mut config = *config;
+        *self.value_mut(&mut config)
+    }
+
+    fn value_mut(self, config: &mut TectonicsConfiguration) -> &mut f32 {
+        match self {
+            TectonicsParameter::SpringConstant => &mut config.spring_constant,
+            TectonicsParameter::FrictionCoefficient => &mut config.friction_coefficient,
+            TectonicsParameter::PlateForceModifier => &mut config.plate_force_modifier,
+            TectonicsParameter::PlateRotationDriftRate => &mut config.plate_rotation_drift_rate,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ParameterPanelRoot;
+
+/// Adds `steps` times the parameter step when pressed
+#[derive(Component)]
+struct ParameterButton {
+    parameter: TectonicsParameter,
+    steps: f32,
+}
+
+#[derive(Component)]
+struct ParameterValueText(TectonicsParameter);
+
+pub struct ParameterPanelPlugin;
+impl Plugin for ParameterPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(SimulationState::Tectonics), setup)
+            .add_systems(OnExit(SimulationState::Tectonics), teardown)
+            .add_systems(
+                Update,
+                (
+                    parameter_buttons,
+                    update_parameter_texts.run_if(resource_changed::<TectonicsPluginConfig>),
+                )
+                    .chain()
+                    .run_if(in_state(SimulationState::Tectonics)),
+            );
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let label_font = TextFont {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 12.0,
+        ..default()
+    };
+    let value_font = TextFont {
+        font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+        font_size: 12.0,
+        ..default()
+    };
+    let button = |label: &str, parameter: TectonicsParameter, steps: f32| {
+        (
+            Button,
+            ParameterButton { parameter, steps },
+            Node {
+                width: Val::Px(18.),
+                margin: UiRect::left(Val::Px(5.)),
+                border: UiRect::all(Val::Px(1.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BorderColor(LinearRgba::new(0.4, 0.4, 0.4, 1.).into()),
+            BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 1.).into()),
+            children![(Text::new(label), label_font.clone())],
+        )
+    };
+
+    commands
+        .spawn((
+            ParameterPanelRoot,
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.),
+                top: Val::Px(10.),
+                width: Val::Px(240.),
+                padding: UiRect::all(Val::Px(10.)),
+                row_gap: Val::Px(5.),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            BackgroundColor(LinearRgba::new(0.01, 0.01, 0.01, 0.8).into()),
+        ))
+        .with_children(|panel| {
+            panel.spawn((Text::new("Tectonic parameters"), label_font.clone()));
+            for parameter in TectonicsParameter::ALL {
+                panel
+                    .spawn(Node {
+                        width: Val::Percent(100.),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((Text::new(parameter.label()), label_font.clone()));
+                        row.spawn((
+                            Node {
+                                margin: UiRect::left(Val::Auto),
+                                ..default()
+                            },
+                            Text::default(),
+                            value_font.clone(),
+                            TextColor(palettes::css::GOLD.into()),
+                            ParameterValueText(parameter),
+                        ));
+                        row.spawn(button("-", parameter, -1.));
+                        row.spawn(button("+", parameter, 1.));
+                    });
+            }
+        });
+}
+
+fn teardown(mut commands: Commands, panel_root: Query<Entity, With<ParameterPanelRoot>>) {
+    for entity in &panel_root {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Applies button presses to the shown snapshot, the background simulation and the plugin config, so saved planets record the tuned values
+fn parameter_buttons(
+    interactions: Query<(&Interaction, &ParameterButton), Changed<Interaction>>,
+    mut tectonics: ResMut<SimTectonics>,
+    mut background_simulation: ResMut<BackgroundSimulation>,
+    mut tectonics_plugin_config: ResMut<TectonicsPluginConfig>,
+) {
+    for (interaction, button) in &interactions {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let mut config = tectonics.config;
+        let value = button.parameter.value_mut(&mut config);
+        *value = (*value + button.steps * button.parameter.step()).max(0.);
+        tectonics.set_config(config);
+        background_simulation.set_config(config);
+        tectonics_plugin_config.tectonics_config = config;
+    }
+}
+
+fn update_parameter_texts(
+    tectonics_plugin_config: Res<TectonicsPluginConfig>,
+    mut texts: Query<(&ParameterValueText, &mut Text)>,
+) {
+    for (value_text, mut text) in &mut texts {
+        **text = format!(
+            "{:.4}",
+            value_text
+                .0
+                .value(&tectonics_plugin_config.tectonics_config)
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use suz_sim::{
+    config::SimulationConfig,
+    history::TectonicsHistory,
+    interpolation::containing_plates,
+    serialize::{PlanetSnapshot, PlateSnapshot},
+};
+
+use crate::{
+    debug_ui::DebugDiagnostics,
+    fly_camera::orbiting,
+    hex_sphere::HexSphere,
+    history::HISTORY_PATH,
+    sim_resources::{SimHexSphereConfig, SimTectonics, SimTectonicsHistory},
+    states::SimulationState,
+    tectonics::TectonicsPluginConfig,
+};
+
+/// Where completed planets are saved to and loaded from
+pub const SAVE_PATH: &str = "planet.suz";
+
+/// A snapshot read from disk, consumed by the hex sphere setup instead of running tectonics
+#[derive(Resource)]
+pub struct LoadedPlanet(pub PlanetSnapshot);
+
+pub struct PersistencePlugin;
+impl Plugin for PersistencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(SimulationState::LoadFromDisk), load_planet)
+            .add_systems(
+                Update,
+                // S also flies backwards
+                save_planet.run_if(in_state(SimulationState::Erosion).and(orbiting)),
+            );
+    }
+}
+
+fn save_planet(
+    keys: Res<ButtonInput<KeyCode>>,
+    hex_sphere: Res<HexSphere>,
+    tectonics: Option<Res<SimTectonics>>,
+    loaded_planet: Option<Res<LoadedPlanet>>,
+    tectonics_history: Option<Res<SimTectonicsHistory>>,
+    hex_sphere_config: Res<SimHexSphereConfig>,
+    tectonics_plugin_config: Res<TectonicsPluginConfig>,
+    diagnostics: Res<DebugDiagnostics>,
+) {
+    if !keys.just_pressed(KeyCode::KeyS) {
+        return;
+    }
+    // A reloaded planet has no tectonic simulation, so plates are taken from the snapshot it came from
+    let (tile_plates, plates) = match (tectonics, loaded_planet) {
+        (Some(tectonics), _) => {
+            let normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+            (
+                containing_plates(&tectonics, &normals)
+                    .into_iter()
+                    .map(|plate| plate as u32)
+                    .collect(),
+                PlateSnapshot::from_tectonics(&tectonics),
+            )
+        }
+        (None, Some(loaded_planet)) => (
+            loaded_planet.0.tile_plates.clone(),
+            loaded_planet.0.plates.clone(),
+        ),
+        (None, None) => {
+            error!("Nothing to save, no tectonic simulation or loaded planet");
+            return;
+        }
+    };
+    let snapshot = PlanetSnapshot {
+        seed: diagnostics.seed,
+        config: SimulationConfig {
+            hex_sphere: hex_sphere_config.0,
+            particle_sphere: tectonics_plugin_config.particle_config,
+            tectonics: tectonics_plugin_config.tectonics_config,
+            flexure: tectonics_plugin_config.flexure_config,
+            climate: tectonics_plugin_config.climate_config,
+            ice: tectonics_plugin_config.ice_config,
+            epochs: tectonics_plugin_config.epoch_config,
+            bathymetry: tectonics_plugin_config.bathymetry_config,
+            shelf: tectonics_plugin_config.shelf_config,
+            island_arcs: tectonics_plugin_config.island_arc_config,
+            detail: tectonics_plugin_config.detail_config,
+        },
+        tile_heights: hex_sphere.tiles.iter().map(|tile| tile.height).collect(),
+        tile_plates,
+        plates,
+    };
+    match snapshot.save(SAVE_PATH) {
+        Ok(()) => info!("Saved planet to {SAVE_PATH}"),
+        Err(e) => error!("Failed to save planet to {SAVE_PATH}: {e}"),
+    }
+    if let Some(tectonics_history) = tectonics_history {
+        match tectonics_history.save(HISTORY_PATH) {
+            Ok(()) => info!("Saved tectonics history to {HISTORY_PATH}"),
+            Err(e) => error!("Failed to save tectonics history to {HISTORY_PATH}: {e}"),
+        }
+    }
+}
+
+fn load_planet(
+    mut commands: Commands,
+    mut hex_sphere_config: ResMut<SimHexSphereConfig>,
+    mut tectonics_plugin_config: ResMut<TectonicsPluginConfig>,
+    mut diagnostics: ResMut<DebugDiagnostics>,
+    mut next_state: ResMut<NextState<SimulationState>>,
+) {
+    match PlanetSnapshot::load(SAVE_PATH) {
+        Ok(snapshot) => {
+            hex_sphere_config.0 = snapshot.config.hex_sphere;
+            *tectonics_plugin_config = TectonicsPluginConfig {
+                tectonics_config: snapshot.config.tectonics,
+                particle_config: snapshot.config.particle_sphere,
+                flexure_config: snapshot.config.flexure,
+                climate_config: snapshot.config.climate,
+                ice_config: snapshot.config.ice,
+                epoch_config: snapshot.config.epochs,
+                bathymetry_config: snapshot.config.bathymetry,
+                shelf_config: snapshot.config.shelf,
+                island_arc_config: snapshot.config.island_arcs,
+                detail_config: snapshot.config.detail,
+            };
+            diagnostics.seed = snapshot.seed;
+            commands.insert_resource(LoadedPlanet(snapshot));
+            // The history is optional, planets saved without one just cannot be replayed
+            commands.remove_resource::<SimTectonicsHistory>();
+            if std::path::Path::new(HISTORY_PATH).exists() {
+                match TectonicsHistory::load(HISTORY_PATH) {
+                    Ok(history) => commands.insert_resource(SimTectonicsHistory(history)),
+                    Err(e) => warn!("Failed to load tectonics history from {HISTORY_PATH}: {e}"),
+                }
+            }
+            next_state.set(SimulationState::MeshGen);
+        }
+        Err(e) => {
+            error!("Failed to load planet from {SAVE_PATH}: {e}");
+            next_state.set(SimulationState::Menu);
+        }
+    }
+}
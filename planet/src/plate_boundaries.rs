@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+use suz_sim::boundaries::PlateBoundaries;
+
+use crate::{
+    hex_sphere::HexSphere,
+    sim_resources::{SimPlateBoundaries, SimTectonics},
+    tectonics::TectonicsIteration,
+};
+
+/// Boundaries are reclassified every this many tectonic iterations
+pub const BOUNDARY_UPDATE_INTERVAL: usize = 10;
+
+pub fn update_plate_boundaries(
+    mut plate_boundaries: ResMut<SimPlateBoundaries>,
+    hex_sphere: Res<HexSphere>,
+    tectonics: Res<SimTectonics>,
+    tectonics_iteration: Res<TectonicsIteration>,
+) {
+    if tectonics_iteration.0 % BOUNDARY_UPDATE_INTERVAL != 0 {
+        return;
+    }
+    let tile_normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+    plate_boundaries.0 = PlateBoundaries::classify(&tectonics, &tile_normals, |tile_index| {
+        hex_sphere.tiles[tile_index].adjacent.as_slice()
+    });
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use web_time::Instant;
+
+use crate::states::SimulationState;
+
+/// Restarts [StageProgress] whenever the [SimulationState] changes
+pub struct ProgressPlugin;
+impl Plugin for ProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(StageProgress::new(SimulationState::default()));
+        // Exit schedules run before the next state's setup, which may already report progress
+        for state in [
+            SimulationState::Menu,
+            SimulationState::LoadFromDisk,
+            SimulationState::MeshGen,
+            SimulationState::Tectonics,
+            SimulationState::Erosion,
+        ] {
+            app.add_systems(OnExit(state), restart_stage_progress);
+        }
+    }
+}
+
+/// How far the current [SimulationState] has come, for the progress bar in the debug UI.
+/// Stages that take more than a frame update it as they go, the rest just mark it finished.
+#[derive(Resource, Clone, Copy)]
+pub struct StageProgress {
+    pub state: SimulationState,
+    started: Instant,
+    /// 0 when the stage starts, 1 when it is done
+    fraction: f32,
+}
+
+impl StageProgress {
+    pub fn new(state: SimulationState) -> Self {
+        StageProgress {
+            state,
+            started: Instant::now(),
+            fraction: 0.,
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.fraction
+    }
+
+    pub fn set(&mut self, fraction: f32) {
+        self.fraction = fraction.clamp(0., 1.);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Extrapolated from the time taken so far, `None` until some progress has been made
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.fraction <= 0. {
+            return None;
+        }
+        Some(self.elapsed().mul_f32((1. - self.fraction) / self.fraction))
+    }
+}
+
+/// [State] already holds the state being entered when exit schedules run
+fn restart_stage_progress(
+    state: Res<State<SimulationState>>,
+    mut stage_progress: ResMut<StageProgress>,
+) {
+    *stage_progress = StageProgress::new(*state.get());
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+use rand::SeedableRng;
+
+use crate::{
+    GlobalRng, continents::Continents, debug_ui::DebugDiagnostics, persistence::LoadedPlanet,
+    seed_input::SeedInput, states::SimulationState, tectonics::SimulationControl,
+};
+
+/// Restarts generation from [SimulationState::MeshGen] with the seed in the [RegenerateSeedInput]
+#[derive(Component)]
+pub struct RegenerateButton;
+
+/// The seed input used by [RegenerateButton]
+#[derive(Component)]
+pub struct RegenerateSeedInput;
+
+pub struct RegeneratePlugin;
+impl Plugin for RegeneratePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            regenerate.run_if(
+                in_state(SimulationState::Tectonics).or(in_state(SimulationState::Erosion)),
+            ),
+        );
+    }
+}
+
+/// The hex sphere and tectonics resources are replaced by their setup systems, so only state that would leak into the new planet is reset here
+fn regenerate(
+    mut commands: Commands,
+    interactions: Query<&Interaction, (With<RegenerateButton>, Changed<Interaction>)>,
+    seed_input: Query<&SeedInput, With<RegenerateSeedInput>>,
+    mut diagnostics: ResMut<DebugDiagnostics>,
+    mut rng: ResMut<GlobalRng>,
+    mut simulation_control: ResMut<SimulationControl>,
+    mut next_state: ResMut<NextState<SimulationState>>,
+) {
+    if !interactions
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        return;
+    }
+    let seed = seed_input
+        .single()
+        .map_or(diagnostics.seed, |seed_input| seed_input.seed);
+    *diagnostics = DebugDiagnostics::seed(seed);
+    rng.0 = rand::rngs::StdRng::seed_from_u64(seed);
+    *simulation_control = SimulationControl::default();
+    commands.remove_resource::<LoadedPlanet>();
+    commands.insert_resource(Continents::default());
+    next_state.set(SimulationState::MeshGen);
+}
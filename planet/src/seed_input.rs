@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+
+/// A clickable text field holding a seed, digits typed while it is focused are appended
+#[derive(Component)]
+#[require(Button, Text)]
+pub struct SeedInput {
+    pub seed: u64,
+    editing: bool,
+}
+
+impl SeedInput {
+    pub fn new(seed: u64) -> Self {
+        SeedInput {
+            seed,
+            editing: false,
+        }
+    }
+
+    pub fn editing(&self) -> bool {
+        self.editing
+    }
+}
+
+pub struct SeedInputPlugin;
+impl Plugin for SeedInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (focus_seed_inputs, type_seed_inputs, update_seed_input_texts).chain(),
+        );
+    }
+}
+
+fn digit(key: KeyCode) -> Option<u64> {
+    match key {
+        KeyCode::Digit0 | KeyCode::Numpad0 => Some(0),
+        KeyCode::Digit1 | KeyCode::Numpad1 => Some(1),
+        KeyCode::Digit2 | KeyCode::Numpad2 => Some(2),
+        KeyCode::Digit3 | KeyCode::Numpad3 => Some(3),
+        KeyCode::Digit4 | KeyCode::Numpad4 => Some(4),
+        KeyCode::Digit5 | KeyCode::Numpad5 => Some(5),
+        KeyCode::Digit6 | KeyCode::Numpad6 => Some(6),
+        KeyCode::Digit7 | KeyCode::Numpad7 => Some(7),
+        KeyCode::Digit8 | KeyCode::Numpad8 => Some(8),
+        KeyCode::Digit9 | KeyCode::Numpad9 => Some(9),
+        _ => None,
+    }
+}
+
+/// Clicking a seed input focuses it and unfocuses every other one
+fn focus_seed_inputs(mut seed_inputs: Query<(Entity, &Interaction, &mut SeedInput)>) {
+    let Some(focused) = seed_inputs
+        .iter()
+        .find(|(_, interaction, _)| **interaction == Interaction::Pressed)
+        .map(|(entity, _, _)| entity)
+    else {
+        return;
+    };
+    for (entity, _, mut seed_input) in &mut seed_inputs {
+        let editing = entity == focused;
+        if seed_input.editing != editing {
+            seed_input.editing = editing;
+        }
+    }
+}
+
+/// Digits are appended as long as the seed fits in a u64, backspace removes the last digit, enter or escape unfocuses
+fn type_seed_inputs(keys: Res<ButtonInput<KeyCode>>, mut seed_inputs: Query<&mut SeedInput>) {
+    for mut seed_input in &mut seed_inputs {
+        if !seed_input.editing {
+            continue;
+        }
+        for key in keys.get_just_pressed() {
+            if let Some(digit) = digit(*key) {
+                if let Some(seed) = seed_input
+                    .seed
+                    .checked_mul(10)
+                    .and_then(|seed| seed.checked_add(digit))
+                {
+                    seed_input.seed = seed;
+                }
+            } else if *key == KeyCode::Backspace {
+                seed_input.seed /= 10;
+            } else if matches!(key, KeyCode::Enter | KeyCode::NumpadEnter | KeyCode::Escape) {
+                seed_input.editing = false;
+            }
+        }
+    }
+}
+
+fn update_seed_input_texts(mut seed_inputs: Query<(&SeedInput, &mut Text), Changed<SeedInput>>) {
+    for (seed_input, mut text) in &mut seed_inputs {
+        **text = if seed_input.editing {
+            format!("{}_", seed_input.seed)
+        } else {
+            seed_input.seed.to_string()
+        };
+    }
+}
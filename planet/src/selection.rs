@@ -0,0 +1,149 @@
+use bevy::{color::palettes, prelude::*};
+
+use crate::{
+    hex_sphere::{CurrentMousePick, HexSphere},
+    states::SimulationState,
+    tile_data::TileData,
+};
+
+/// Tiles picked out for other tools to act on, such as brushes, inspectors and exporters.
+/// Tile indices refer to the current [HexSphere], the selection is cleared whenever a new mesh is generated.
+#[derive(Resource, Default)]
+pub struct Selection {
+    /// Selected tile indices, in the order they were selected
+    tiles: Vec<usize>,
+    /// For each tile, whether it is in [Selection::tiles]
+    selected: Vec<bool>,
+}
+
+impl Selection {
+    pub fn tiles(&self) -> &[usize] {
+        &self.tiles
+    }
+
+    pub fn contains(&self, tile_index: usize) -> bool {
+        self.selected.get(tile_index).copied().unwrap_or(false)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.selected.clear();
+    }
+
+    /// Adds `tile_index` to the selection
+    pub fn select_tile(&mut self, hex_sphere: &HexSphere, tile_index: usize) {
+        self.selected.resize(hex_sphere.tiles.len(), false);
+        if !self.selected[tile_index] {
+            self.selected[tile_index] = true;
+            self.tiles.push(tile_index);
+        }
+    }
+
+    /// Adds every tile within `radius` radians of `normal`, see [crate::hex_sphere::TileGraph::tiles_within_geodesic]
+    pub fn select_radius(&mut self, hex_sphere: &HexSphere, normal: Vec3, radius: f32) {
+        for tile_index in hex_sphere.graph.tiles_within_geodesic(normal, radius) {
+            self.select_tile(hex_sphere, tile_index);
+        }
+    }
+
+    /// Adds every tile of the plate at `plate_index` in [TileData::plates], nothing is added before plates are known
+    pub fn select_plate(
+        &mut self,
+        hex_sphere: &HexSphere,
+        tile_data: &TileData,
+        plate_index: usize,
+    ) {
+        for (tile_index, &plate) in tile_data.plates.iter().enumerate() {
+            if plate == plate_index {
+                self.select_tile(hex_sphere, tile_index);
+            }
+        }
+    }
+}
+
+pub struct SelectionPlugin {
+    /// Radius in radians of the region selected around the cursor
+    pub brush_radius: f32,
+}
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .insert_resource(BrushRadius(self.brush_radius))
+            // Tile indices do not carry over to a new mesh
+            .add_systems(OnEnter(SimulationState::MeshGen), clear_selection)
+            .add_systems(
+                Update,
+                (select_from_keys, draw_selection).run_if(resource_exists::<HexSphere>),
+            );
+    }
+}
+
+#[derive(Resource)]
+struct BrushRadius(f32);
+
+fn clear_selection(mut selection: ResMut<Selection>) {
+    selection.clear();
+}
+
+/// T selects the picked tile, B the tiles around the cursor and P the picked tile's plate.
+/// Holding shift adds to the selection instead of replacing it, escape clears it.
+fn select_from_keys(
+    keys: Res<ButtonInput<KeyCode>>,
+    hex_sphere: Res<HexSphere>,
+    current_mouse_pick: Res<CurrentMousePick>,
+    tile_data: Res<TileData>,
+    brush_radius: Res<BrushRadius>,
+    mut selection: ResMut<Selection>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        selection.clear();
+        return;
+    }
+    let Some(pick) = &current_mouse_pick.pick else {
+        return;
+    };
+    let pressed = [KeyCode::KeyT, KeyCode::KeyB, KeyCode::KeyP]
+        .into_iter()
+        .find(|key| keys.just_pressed(*key));
+    let Some(pressed) = pressed else {
+        return;
+    };
+    if !keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        selection.clear();
+    }
+    match pressed {
+        KeyCode::KeyT => selection.select_tile(&hex_sphere, pick.tile.index),
+        KeyCode::KeyB => selection.select_radius(&hex_sphere, pick.normal, brush_radius.0),
+        _ => {
+            if let Some(&plate_index) = tile_data.plates.get(pick.tile.index) {
+                selection.select_plate(&hex_sphere, &tile_data, plate_index);
+            }
+        }
+    }
+}
+
+/// Outlines the selected regions, edges between two selected tiles are left out
+fn draw_selection(mut gizmos: Gizmos, hex_sphere: Res<HexSphere>, selection: Res<Selection>) {
+    for &tile_index in selection.tiles() {
+        let tile = &hex_sphere.tiles[tile_index];
+        for k in 0..tile.vertices.len() {
+            let start = tile.vertices[(k + tile.vertices.len() - 1) % tile.vertices.len()];
+            let end = tile.vertices[k];
+            let across = hex_sphere.vertices_to_tiles[start].iter().find(|&&other| {
+                other != tile_index && hex_sphere.vertices_to_tiles[end].contains(&other)
+            });
+            if across.is_some_and(|&other| selection.contains(other)) {
+                continue;
+            }
+            gizmos.line(
+                Vec3::from(hex_sphere.vertices[start]) * 1.01,
+                Vec3::from(hex_sphere.vertices[end]) * 1.01,
+                palettes::css::AQUA,
+            );
+        }
+    }
+}
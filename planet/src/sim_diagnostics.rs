@@ -0,0 +1,86 @@
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+};
+
+use crate::{sim_resources::SimTectonics, states::SimulationState, tectonics::TectonicsIteration};
+
+/// Registers the simulation metrics as Bevy [Diagnostic]s, so they are logged by [bevy::diagnostic::LogDiagnosticsPlugin] and can be read from the [bevy::diagnostic::DiagnosticsStore]
+pub struct SimulationDiagnosticsPlugin;
+
+impl SimulationDiagnosticsPlugin {
+    pub const ITERATIONS_PER_SECOND: DiagnosticPath =
+        DiagnosticPath::const_new("simulation/iterations_per_second");
+    pub const POINT_MASSES: DiagnosticPath = DiagnosticPath::const_new("simulation/point_masses");
+    pub const SPRINGS: DiagnosticPath = DiagnosticPath::const_new("simulation/springs");
+    /// Mean absolute strain of every spring
+    pub const AVERAGE_STRAIN: DiagnosticPath =
+        DiagnosticPath::const_new("simulation/average_strain");
+    /// Spring force evaluations skipped as degenerate since the simulation started, see [suz_sim::tectonics::Tectonics::degenerate_springs]
+    pub const DEGENERATE_SPRINGS: DiagnosticPath =
+        DiagnosticPath::const_new("simulation/degenerate_springs");
+    /// Milliseconds [crate::vertex_interpolation::interpolate_vertices] took to update the mesh
+    pub const MESH_PATCH_TIME: DiagnosticPath =
+        DiagnosticPath::const_new("simulation/mesh_patch_time");
+}
+
+impl Plugin for SimulationDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(
+            Diagnostic::new(Self::ITERATIONS_PER_SECOND).with_suffix(" iterations/s"),
+        )
+        .register_diagnostic(Diagnostic::new(Self::POINT_MASSES).with_smoothing_factor(0.))
+        .register_diagnostic(Diagnostic::new(Self::SPRINGS).with_smoothing_factor(0.))
+        .register_diagnostic(Diagnostic::new(Self::AVERAGE_STRAIN))
+        .register_diagnostic(Diagnostic::new(Self::DEGENERATE_SPRINGS).with_smoothing_factor(0.))
+        .register_diagnostic(Diagnostic::new(Self::MESH_PATCH_TIME).with_suffix("ms"))
+        .add_systems(
+            Update,
+            measure_simulation
+                .run_if(in_state(SimulationState::Tectonics).and(resource_exists::<SimTectonics>)),
+        );
+    }
+}
+
+/// Iterations per second are measured from the iterations finished since the last frame
+fn measure_simulation(
+    mut diagnostics: Diagnostics,
+    time: Res<Time>,
+    tectonics: Res<SimTectonics>,
+    tectonics_iteration: Res<TectonicsIteration>,
+    mut last_iteration: Local<Option<usize>>,
+) {
+    if let Some(last_iteration) = *last_iteration {
+        let delta = time.delta_secs_f64();
+        if delta > 0. {
+            // The iteration restarts from zero on a new planet
+            let iterations = tectonics_iteration.0.saturating_sub(last_iteration);
+            diagnostics
+                .add_measurement(&SimulationDiagnosticsPlugin::ITERATIONS_PER_SECOND, || {
+                    iterations as f64 / delta
+                });
+        }
+    }
+    *last_iteration = Some(tectonics_iteration.0);
+
+    let shapes = || tectonics.plates.iter().map(|plate| &plate.shape);
+    diagnostics.add_measurement(&SimulationDiagnosticsPlugin::POINT_MASSES, || {
+        shapes()
+            .map(|shape| shape.point_masses().len())
+            .sum::<usize>() as f64
+    });
+    diagnostics.add_measurement(&SimulationDiagnosticsPlugin::DEGENERATE_SPRINGS, || {
+        tectonics.degenerate_springs() as f64
+    });
+    let springs: usize = shapes().map(|shape| shape.springs().len()).sum();
+    diagnostics.add_measurement(&SimulationDiagnosticsPlugin::SPRINGS, || springs as f64);
+    if springs > 0 {
+        diagnostics.add_measurement(&SimulationDiagnosticsPlugin::AVERAGE_STRAIN, || {
+            shapes()
+                .flat_map(|shape| shape.iter_spring_strains())
+                .map(|(_, strain)| strain.abs() as f64)
+                .sum::<f64>()
+                / springs as f64
+        });
+    }
+}
@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use suz_sim::{
+    boundaries::PlateBoundaries,
+    compression::CompressionField,
+    config::HexSphereConfig,
+    epochs::EpochSchedule,
+    history::TectonicsHistory,
+    observer::{Control, SimulationObserver, Stage},
+    particle_sphere::ParticleSphere,
+    tectonics::Tectonics,
+};
+
+/// [Tectonics] as a resource, [suz_sim] does not depend on Bevy so its types are wrapped on this side
+#[derive(Resource, Deref, DerefMut, Clone)]
+pub struct SimTectonics(pub Tectonics);
+
+/// Spring compressions of [SimTectonics], updated before each height interpolation instead of summed from scratch
+#[derive(Resource, Deref, DerefMut)]
+pub struct SimCompressionField(pub CompressionField);
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct SimParticleSphere(pub ParticleSphere);
+
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct SimPlateBoundaries(pub PlateBoundaries);
+
+#[derive(Resource, Deref, DerefMut, Clone, Copy)]
+pub struct SimHexSphereConfig(pub HexSphereConfig);
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct SimTectonicsHistory(pub TectonicsHistory);
+
+#[derive(Resource, Deref, DerefMut)]
+pub struct SimEpochSchedule(pub EpochSchedule);
+
+/// Everything observing the generation the viewer runs, an abort from any of them returns to the menu
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct SimObservers(pub Vec<Box<dyn SimulationObserver + Send + Sync>>);
+
+impl SimObservers {
+    /// Reports to every observer, even after one aborts
+    pub fn on_iteration(&mut self, iteration: usize, tectonics: &Tectonics) -> Control {
+        self.iter_mut()
+            .map(|observer| observer.on_iteration(iteration, tectonics))
+            .fold(Control::Continue, Control::max)
+    }
+
+    /// Reports to every observer, even after one aborts
+    pub fn on_stage_complete(&mut self, stage: Stage, duration: Duration) -> Control {
+        self.iter_mut()
+            .map(|observer| observer.on_stage_complete(stage, duration))
+            .fold(Control::Continue, Control::max)
+    }
+}
+
+/// Plate colors are stored as linear RGBA in [suz_sim]
+pub fn plate_color(color: [f32; 4]) -> Color {
+    LinearRgba::from_f32_array(color).into()
+}
@@ -0,0 +1,130 @@
+use std::{collections::VecDeque, marker::PhantomData};
+
+use bevy::{ecs::spawn::SpawnIter, prelude::*};
+
+/// Latest values of a measurement, dropping the oldest once `capacity` is reached
+pub struct TimeSeries {
+    /// Oldest first
+    values: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl TimeSeries {
+    pub fn new(capacity: usize) -> Self {
+        TimeSeries {
+            values: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, value: f32) {
+        if self.values.len() == self.capacity {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    /// Oldest first
+    pub fn values(&self) -> &VecDeque<f32> {
+        &self.values
+    }
+
+    pub fn latest(&self) -> Option<f32> {
+        self.values.back().copied()
+    }
+}
+
+/// A resource [sparkline] can plot, register [update_sparkline] for it to keep its sparklines up to date
+pub trait TimeSeriesResource: Resource {
+    fn time_series(&self) -> &TimeSeries;
+}
+
+/// One value of the [TimeSeriesResource] `R`, the oldest is leftmost
+#[derive(Component)]
+pub struct SparklinePoint<R: TimeSeriesResource> {
+    index: usize,
+    series: PhantomData<fn() -> R>,
+}
+
+/// Text showing the latest value of the [TimeSeriesResource] `R`
+#[derive(Component)]
+pub struct SparklineValue<R: TimeSeriesResource> {
+    decimals: usize,
+    suffix: &'static str,
+    series: PhantomData<fn() -> R>,
+}
+
+impl<R: TimeSeriesResource> SparklineValue<R> {
+    pub fn new(decimals: usize, suffix: &'static str) -> Self {
+        SparklineValue {
+            decimals,
+            suffix,
+            series: PhantomData,
+        }
+    }
+}
+
+/// Line graph of the [TimeSeriesResource] `R` with room for `samples` values, as wide as its parent
+pub fn sparkline<R: TimeSeriesResource>(samples: usize, color: Color) -> impl Bundle {
+    (
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Px(30.),
+            margin: UiRect::top(Val::Px(5.)),
+            ..Default::default()
+        },
+        BackgroundColor(LinearRgba::new(0.05, 0.05, 0.05, 0.8).into()),
+        Children::spawn(SpawnIter((0..samples).map(move |index| {
+            (
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(index as f32 / samples as f32 * 100.),
+                    width: Val::Percent(100. / samples as f32),
+                    height: Val::Px(2.),
+                    ..Default::default()
+                },
+                BackgroundColor(color),
+                Visibility::Hidden,
+                SparklinePoint::<R> {
+                    index,
+                    series: PhantomData,
+                },
+            )
+        }))),
+    )
+}
+
+/// Points are scaled between the lowest and highest value of the series, a flat series is drawn through the middle
+pub fn update_sparkline<R: TimeSeriesResource>(
+    series: Res<R>,
+    mut points: Query<(&SparklinePoint<R>, &mut Node, &mut Visibility)>,
+    mut value_texts: Query<(&SparklineValue<R>, &mut Text)>,
+) {
+    let series = series.time_series();
+    let (min, max) = series
+        .values()
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), &value| {
+            (min.min(value), max.max(value))
+        });
+    for (point, mut node, mut visibility) in &mut points {
+        match series.values().get(point.index) {
+            Some(&value) => {
+                // Leave room for the point itself at the top
+                node.bottom = Val::Percent(if max > min {
+                    (value - min) / (max - min) * 90.
+                } else {
+                    45.
+                });
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    for (value, mut text) in &mut value_texts {
+        **text = match series.latest() {
+            Some(latest) => format!("{latest:.*}{}", value.decimals, value.suffix),
+            None => "-".to_string(),
+        };
+    }
+}
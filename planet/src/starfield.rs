@@ -0,0 +1,92 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    pbr::NotShadowCaster,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+use rand::{Rng, SeedableRng};
+
+/// Radius of the sphere the stars are scattered on, well outside the orbit camera
+const STARFIELD_RADIUS: f32 = 50.;
+/// Angular size of the brightest stars, in radians
+const STAR_SIZE: f32 = 0.004;
+
+/// Scatters seeded stars on a large sphere around the planet instead of a flat black background.
+/// The stars are a mesh rather than a skybox, so they also show through the orthographic orbit camera.
+pub struct StarfieldPlugin {
+    pub config: StarfieldConfig,
+}
+impl Plugin for StarfieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config)
+            .add_systems(Startup, spawn_starfield);
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct StarfieldConfig {
+    pub seed: u64,
+    /// Number of stars over the whole sky
+    pub stars: usize,
+    /// Color multiplier of the brightest stars, dimmer stars are scaled down from it
+    pub brightness: f32,
+}
+
+/// One small triangle facing the center per star, with the star color as vertex color
+fn starfield_mesh(config: &StarfieldConfig) -> Mesh {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(config.seed);
+    let mut positions = Vec::with_capacity(config.stars * 3);
+    let mut colors = Vec::with_capacity(config.stars * 3);
+    for _ in 0..config.stars {
+        // Uniform on the sphere
+        let y: f32 = rng.random_range(-1.0..1.0);
+        let angle: f32 = rng.random_range(0.0..TAU);
+        let ring = (1. - y * y).sqrt();
+        let direction = Vec3::new(ring * angle.cos(), y, ring * angle.sin());
+        // Most stars are faint, a few are bright
+        let magnitude = rng.random::<f32>().powi(4);
+        // From orange through white to blue
+        let temperature: f32 = rng.random();
+        let tint = Vec3::new(1., 0.85 + 0.15 * temperature, 0.7 + 0.3 * temperature);
+        let color = (tint * config.brightness * (0.2 + 0.8 * magnitude)).extend(1.);
+
+        let size = STARFIELD_RADIUS * STAR_SIZE * (0.4 + 0.6 * magnitude);
+        let u = direction.any_orthonormal_vector();
+        let v = direction.cross(u);
+        let center = direction * STARFIELD_RADIUS;
+        for corner in 0..3 {
+            let angle = corner as f32 / 3. * TAU;
+            positions.push((center + (u * angle.cos() + v * angle.sin()) * size).to_array());
+            colors.push(color.to_array());
+        }
+    }
+    let indices = (0..positions.len() as u32).collect();
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn spawn_starfield(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<StarfieldConfig>,
+) {
+    commands.spawn((
+        Mesh3d(meshes.add(starfield_mesh(&config))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            unlit: true,
+            // Stars are seen from inside the sphere
+            cull_mode: None,
+            ..Default::default()
+        })),
+        // The sun shines through the sphere of stars onto the planet
+        NotShadowCaster,
+    ));
+}
@@ -3,6 +3,8 @@ use bevy::prelude::*;
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
 pub enum SimulationState {
     #[default]
+    Menu,
+    LoadFromDisk,
     MeshGen,
     Tectonics,
     Erosion,
@@ -11,6 +13,8 @@ pub enum SimulationState {
 impl std::fmt::Display for SimulationState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            SimulationState::Menu => write!(f, "Menu"),
+            SimulationState::LoadFromDisk => write!(f, "LoadFromDisk"),
             SimulationState::MeshGen => write!(f, "MeshGen"),
             SimulationState::Tectonics => write!(f, "Tectonics"),
             SimulationState::Erosion => write!(f, "Erosion"),
@@ -0,0 +1,37 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use suz_sim::{strain::StrainTracker, tectonics::Tectonics};
+
+/// How many iterations of global strain rate are kept for the timeline
+pub const STRAIN_HISTORY_LENGTH: usize = 100;
+
+#[derive(Resource)]
+pub struct StrainRate {
+    pub tracker: StrainTracker,
+    /// Strain rate interpolated onto each [crate::hex_sphere::HexSphere] tile
+    pub tiles: Vec<f32>,
+    /// Global strain rate of the latest iterations, oldest first
+    pub history: VecDeque<f32>,
+}
+
+impl StrainRate {
+    pub fn new(tectonics: &Tectonics) -> Self {
+        StrainRate {
+            tracker: StrainTracker::new(tectonics),
+            tiles: Vec::new(),
+            history: VecDeque::with_capacity(STRAIN_HISTORY_LENGTH),
+        }
+    }
+
+    /// Replaces the tracker with one updated elsewhere, `global_rates` holds the global rate of each step since the last record
+    pub fn record(&mut self, tracker: StrainTracker, global_rates: &[f32]) {
+        self.tracker = tracker;
+        for &global_rate in global_rates {
+            if self.history.len() == STRAIN_HISTORY_LENGTH {
+                self.history.pop_front();
+            }
+            self.history.push_back(global_rate);
+        }
+    }
+}
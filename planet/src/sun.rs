@@ -0,0 +1,113 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use suz_sim::vec_utils::lat_long_to_vec3;
+
+use crate::graticule::AxialTilt;
+
+/// Lights the planet with a directional sun circling the rotation axis once per day, at the declination of the season.
+/// [ and ] halve and double the speed of the day.
+pub struct SunPlugin {
+    pub config: SunConfig,
+}
+impl Plugin for SunPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.config)
+            .init_resource::<SunClock>()
+            .add_systems(Startup, spawn_sun)
+            .add_systems(Update, (sun_speed_input, advance_sun).chain());
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+pub struct SunConfig {
+    /// Illuminance of the sun in lux
+    pub illuminance: f32,
+    /// Illuminance of a dim light from the opposite side, so relief still shows on the night side, 0 leaves it black
+    pub night_illuminance: f32,
+    /// Seconds for a full day, 0 keeps the sun still
+    pub day_length: f32,
+    /// Days in a year, over which the sun moves between the tropics
+    pub days_per_year: f32,
+}
+
+/// Time of day and of year, as angles
+#[derive(Resource, Default)]
+pub struct SunClock {
+    /// Longitude the sun is overhead
+    pub hour: f32,
+    /// Angle along the orbit from the spring equinox
+    pub season: f32,
+}
+
+impl SunClock {
+    /// Unit vector towards the sun, with the rotation axis along Y as in the rest of the viewer
+    pub fn direction(&self, axial_tilt: f32) -> Vec3 {
+        let tilt = axial_tilt.clamp(0., 90.).to_radians();
+        let declination = (tilt.sin() * self.season.sin()).asin();
+        lat_long_to_vec3(declination, self.hour)
+    }
+}
+
+#[derive(Component)]
+struct Sun;
+
+/// Lights the night side, always opposite the [Sun]
+#[derive(Component)]
+struct NightLight;
+
+fn spawn_sun(mut commands: Commands, config: Res<SunConfig>) {
+    commands.spawn((
+        DirectionalLight {
+            illuminance: config.illuminance,
+            shadows_enabled: true,
+            ..default()
+        },
+        Sun,
+    ));
+    if config.night_illuminance > 0. {
+        commands.spawn((
+            DirectionalLight {
+                illuminance: config.night_illuminance,
+                color: Color::srgb(0.6, 0.7, 1.0),
+                ..default()
+            },
+            NightLight,
+        ));
+    }
+}
+
+fn sun_speed_input(keys: Res<ButtonInput<KeyCode>>, mut config: ResMut<SunConfig>) {
+    // A shorter day is a faster sun
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        config.day_length *= 2.;
+    }
+    if keys.just_pressed(KeyCode::BracketRight) {
+        config.day_length /= 2.;
+    }
+}
+
+fn advance_sun(
+    time: Res<Time>,
+    config: Res<SunConfig>,
+    axial_tilt: Res<AxialTilt>,
+    mut clock: ResMut<SunClock>,
+    mut sun: Query<&mut Transform, (With<Sun>, Without<NightLight>)>,
+    mut night_light: Query<&mut Transform, With<NightLight>>,
+) {
+    if config.day_length > 0. {
+        let days = time.delta_secs() / config.day_length;
+        clock.hour = (clock.hour + days * TAU).rem_euclid(TAU);
+        if config.days_per_year > 0. {
+            clock.season = (clock.season + days / config.days_per_year * TAU).rem_euclid(TAU);
+        }
+    }
+    let direction = clock.direction(axial_tilt.0);
+    // Directional lights shine along their forward axis
+    for mut transform in &mut sun {
+        transform.rotation = Quat::from_rotation_arc(Vec3::NEG_Z, -direction);
+    }
+    for mut transform in &mut night_light {
+        transform.rotation = Quat::from_rotation_arc(Vec3::NEG_Z, direction);
+    }
+}
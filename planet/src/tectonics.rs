@@ -1,23 +1,89 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, time::Duration};
 use suz_sim::{
+    bathymetry::BathymetryConfig,
+    climate::ClimateConfig,
+    compression::CompressionField,
+    detail::DetailConfig,
+    epochs::EpochConfig,
+    flexure::FlexureConfig,
+    ice::IceConfig,
+    island_arcs::IslandArcConfig,
+    observer::{Control, Stage},
     particle_sphere::{ParticleSphere, ParticleSphereConfig},
+    shelf::ShelfConfig,
+    strain::StrainTracker,
     tectonics::{Tectonics, TectonicsConfiguration},
 };
 
 use bevy::prelude::*;
 
 use crate::{
-    GlobalRng, debug_ui::DebugDiagnostics, states::SimulationState,
+    GlobalRng,
+    background_simulation::BackgroundSimulation,
+    debug_ui::DebugDiagnostics,
+    energy::EnergyHistory,
+    epochs::first_epoch,
+    plate_boundaries::{BOUNDARY_UPDATE_INTERVAL, update_plate_boundaries},
+    progress::StageProgress,
+    sim_resources::{
+        SimCompressionField, SimEpochSchedule, SimObservers, SimParticleSphere, SimPlateBoundaries,
+        SimTectonics, SimTectonicsHistory, plate_color,
+    },
+    states::SimulationState,
+    strain_rate::StrainRate,
+    tile_data::{TileData, update_tile_data},
     vertex_interpolation::interpolate_vertices,
 };
 
 #[derive(Resource)]
 pub struct TectonicsIteration(pub usize);
 
+/// Whether [simulate_system] steps the tectonic simulation every frame
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationControl {
+    #[default]
+    Running,
+    Paused,
+    /// Take a single step, then pause
+    StepOnce,
+}
+
+impl std::fmt::Display for SimulationControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimulationControl::Running => write!(f, "Running"),
+            SimulationControl::Paused | SimulationControl::StepOnce => write!(f, "Paused"),
+        }
+    }
+}
+
+/// Whether [draw_velocities] draws an arrow for the velocity of every point mass
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VelocityOverlay(pub bool);
+
+/// How long a [BackgroundSimulation] batch may keep stepping before its result is shown.
+/// A step that takes longer than the budget still runs, so slow planets show every step.
+#[derive(Resource, Clone, Copy)]
+pub struct FrameBudget(pub Duration);
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        FrameBudget(Duration::from_millis(8))
+    }
+}
+
 #[derive(Resource, Clone, Copy)]
 pub struct TectonicsPluginConfig {
     pub tectonics_config: TectonicsConfiguration,
     pub particle_config: ParticleSphereConfig,
+    pub flexure_config: FlexureConfig,
+    pub climate_config: ClimateConfig,
+    pub ice_config: IceConfig,
+    pub epoch_config: EpochConfig,
+    pub bathymetry_config: Option<BathymetryConfig>,
+    pub shelf_config: Option<ShelfConfig>,
+    pub island_arc_config: Option<IslandArcConfig>,
+    pub detail_config: DetailConfig,
 }
 
 pub struct TectonicsPlugin {
@@ -27,78 +93,286 @@ impl Plugin for TectonicsPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(self.config)
             .insert_resource(TectonicsIteration(0))
-            .add_systems(OnEnter(SimulationState::Tectonics), setup)
-            .add_systems(OnExit(SimulationState::Tectonics), interpolate_vertices)
+            .init_resource::<SimulationControl>()
+            .init_resource::<FrameBudget>()
+            .init_resource::<VelocityOverlay>()
+            .init_resource::<SimObservers>()
+            // Later epochs carry on with the simulation of the previous one
+            .add_systems(
+                OnEnter(SimulationState::Tectonics),
+                setup.run_if(first_epoch),
+            )
+            .add_systems(
+                OnExit(SimulationState::Tectonics),
+                (cancel_background_simulation, interpolate_vertices),
+            )
             .add_systems(
                 Update,
                 (
-                    draw_point_masses,
-                    interpolate_vertices.run_if(in_state(SimulationState::Tectonics)),
-                    simulate_system.run_if(in_state(SimulationState::Tectonics)),
+                    draw_point_masses.run_if(resource_exists::<SimTectonics>),
+                    draw_trails.run_if(resource_exists::<SimTectonics>),
+                    draw_velocities.run_if(
+                        resource_exists::<SimTectonics>
+                            .and(|overlay: Res<VelocityOverlay>| overlay.0),
+                    ),
+                    (
+                        simulation_control_input,
+                        simulate_system,
+                        (
+                            update_plate_boundaries,
+                            update_tile_data,
+                            interpolate_vertices,
+                        )
+                            .chain()
+                            .run_if(resource_changed::<TectonicsIteration>),
+                    )
+                        .chain()
+                        .run_if(in_state(SimulationState::Tectonics)),
                 ),
             );
     }
 }
 
 #[derive(Resource)]
-struct TectonicsStartTime(std::time::Instant);
-
-fn setup(config: Res<TectonicsPluginConfig>, mut commands: Commands, mut rng: ResMut<GlobalRng>) {
-    let particle_sphere = ParticleSphere::from_config(config.particle_config);
-    let tectonics = Tectonics::from_config(config.tectonics_config, &particle_sphere, &mut rng.0);
-    commands.insert_resource(TectonicsStartTime(std::time::Instant::now()));
-    commands.insert_resource(tectonics);
-    commands.insert_resource(particle_sphere);
+struct TectonicsStartTime(web_time::Instant);
+
+fn setup(
+    config: Res<TectonicsPluginConfig>,
+    mut commands: Commands,
+    mut rng: ResMut<GlobalRng>,
+    mut observers: ResMut<SimObservers>,
+    mut next_state: ResMut<NextState<SimulationState>>,
+) {
+    let start = web_time::Instant::now();
+    let particle_sphere = ParticleSphere::from_config(config.particle_config, &mut rng.0);
+    if observers.on_stage_complete(Stage::ParticleSphere, start.elapsed()) == Control::Abort {
+        warn!("Generation aborted by an observer after creating the particle sphere");
+        next_state.set(SimulationState::Menu);
+        return;
+    }
+    let tectonics =
+        match Tectonics::from_config(config.tectonics_config, &particle_sphere, &mut rng.0) {
+            Ok(tectonics) => tectonics,
+            Err(e) => {
+                error!("Failed to seed plates: {e}");
+                next_state.set(SimulationState::Menu);
+                return;
+            }
+        };
+    commands.insert_resource(TectonicsStartTime(web_time::Instant::now()));
+    commands.insert_resource(TectonicsIteration(0));
+    commands.insert_resource(StrainRate::new(&tectonics));
+    commands.insert_resource(EnergyHistory::default());
+    commands.insert_resource(BackgroundSimulation::new(
+        tectonics.clone(),
+        StrainTracker::new(&tectonics),
+        rng.0.clone(),
+    ));
+    commands.insert_resource(SimPlateBoundaries::default());
+    commands.insert_resource(SimCompressionField(CompressionField::new(
+        &tectonics.plates,
+    )));
+    commands.insert_resource(TileData::default());
+    commands.insert_resource(SimTectonics(tectonics));
+    commands.insert_resource(SimParticleSphere(particle_sphere));
+}
+
+/// Leaving mid-batch means generation was cancelled, by regenerating or an observer.
+/// The batch is dropped instead of waited on, and the next run starts from a fresh [BackgroundSimulation].
+fn cancel_background_simulation(
+    mut commands: Commands,
+    background_simulation: Option<ResMut<BackgroundSimulation>>,
+) {
+    if let Some(mut background_simulation) = background_simulation {
+        if background_simulation.is_running() {
+            background_simulation.cancel();
+            commands.remove_resource::<BackgroundSimulation>();
+        }
+    }
+}
+
+/// Arrows point along each point mass velocity, the fastest point mass gets the longest and reddest arrow
+fn draw_velocities(
+    mut gizmos: Gizmos,
+    tectonics: Res<SimTectonics>,
+    particle_sphere: Res<SimParticleSphere>,
+) {
+    let max_speed = tectonics
+        .plates
+        .iter()
+        .flat_map(|plate| plate.shape.point_masses())
+        .map(|point_mass| point_mass.velocity.length())
+        .fold(0., f32::max);
+    if max_speed <= 0. {
+        return;
+    }
+    // About three particle spacings for the fastest point mass
+    let max_length = 48. * PI / particle_sphere.tiles.len() as f32;
+    for plate in &tectonics.plates {
+        for point_mass in plate.shape.point_masses() {
+            let speed = point_mass.velocity.length() / max_speed;
+            let start = point_mass.position * 1.02;
+            gizmos.arrow(
+                start,
+                start + point_mass.velocity / max_speed * max_length,
+                Color::hsl(240. * (1. - speed), 1., 0.5),
+            );
+        }
+    }
+}
+
+/// Springs strained this much or more are drawn in the hottest [strain_color]
+pub const MAX_DRAWN_STRAIN: f32 = 0.2;
+
+/// Heatmap color of an absolute spring strain, from blue for springs at rest to red at [MAX_DRAWN_STRAIN]
+pub fn strain_color(strain: f32) -> Color {
+    let t = (strain / MAX_DRAWN_STRAIN).clamp(0., 1.);
+    Color::hsla(240. * (1. - t), 1., 0.5, 0.5 + 0.5 * t)
 }
 
 fn draw_point_masses(
     mut gizmos: Gizmos,
-    tectonics: Res<Tectonics>,
-    particle_sphere: Res<ParticleSphere>,
+    tectonics: Res<SimTectonics>,
+    particle_sphere: Res<SimParticleSphere>,
 ) {
     for plate in &tectonics.plates {
-        gizmos.arrow(
-            plate.axis_of_rotation,
-            plate.axis_of_rotation * 1.1,
-            plate.color,
-        );
+        // Axes turned by torque also grow and shrink with the plate's spin, only the direction is drawn
+        let axis = plate.axis_of_rotation.normalize_or_zero();
+        gizmos.arrow(axis, axis * 1.1, plate_color(plate.color));
     }
     for plate in &tectonics.plates {
-        for point_mass in &plate.shape.point_masses {
+        for point_mass in plate.shape.point_masses() {
             gizmos.cross(
                 Isometry3d {
                     translation: (point_mass.position * 1.02).into(),
                     rotation: Quat::from_rotation_arc(Vec3::Z, point_mass.position),
                 },
                 16. * PI / particle_sphere.tiles.len() as f32,
-                plate.color,
+                plate_color(plate.color),
             );
         }
-        for spring in &plate.shape.springs {
-            let point_mass_a = &plate.shape.point_masses[spring.anchor_a];
-            let point_mass_b = &plate.shape.point_masses[spring.anchor_b];
+        for (spring, strain) in plate.shape.iter_spring_strains() {
+            let point_mass_a = &plate.shape.point_masses()[spring.anchor_a];
+            let point_mass_b = &plate.shape.point_masses()[spring.anchor_b];
             gizmos.line(
                 point_mass_a.position * 1.02,
                 point_mass_b.position * 1.02,
-                plate.color.with_alpha(0.5),
+                strain_color(strain.abs()),
             );
         }
     }
 }
 
+/// Fades each plate's [suz_sim::tectonics::Tectonics::trails] in from the oldest sample to its current centroid
+fn draw_trails(mut gizmos: Gizmos, tectonics: Res<SimTectonics>) {
+    for (plate, trail) in tectonics.plates.iter().zip(tectonics.trails.trails()) {
+        let color = plate_color(plate.color);
+        let samples = trail.len().max(2) - 1;
+        gizmos.linestrip_gradient(
+            trail.iter().enumerate().map(|(k, &centroid)| {
+                (centroid * 1.03, color.with_alpha(k as f32 / samples as f32))
+            }),
+        );
+    }
+}
+
+/// Space toggles pausing, period takes a single step while paused
+fn simulation_control_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut simulation_control: ResMut<SimulationControl>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        *simulation_control = match *simulation_control {
+            SimulationControl::Running => SimulationControl::Paused,
+            SimulationControl::Paused | SimulationControl::StepOnce => SimulationControl::Running,
+        };
+    }
+    if keys.just_pressed(KeyCode::Period) {
+        *simulation_control = SimulationControl::StepOnce;
+    }
+}
+
+/// Copies finished batches of the [BackgroundSimulation] into the [Tectonics] snapshot and starts the next batch.
+/// Batches end on every [BOUNDARY_UPDATE_INTERVAL]th iteration so boundary and mesh updates that run on those iterations are not skipped.
 fn simulate_system(
     tectonics_start_time: Res<TectonicsStartTime>,
-    mut tectonics: ResMut<Tectonics>,
+    frame_budget: Res<FrameBudget>,
+    mut simulation_control: ResMut<SimulationControl>,
+    mut background_simulation: ResMut<BackgroundSimulation>,
+    mut tectonics: ResMut<SimTectonics>,
+    mut strain_rate: ResMut<StrainRate>,
+    mut energy_history: ResMut<EnergyHistory>,
+    mut stage_progress: ResMut<StageProgress>,
+    tectonics_history: Option<ResMut<SimTectonicsHistory>>,
+    epoch_schedule: Option<Res<SimEpochSchedule>>,
+    mut observers: ResMut<SimObservers>,
     mut rng: ResMut<GlobalRng>,
     mut tectonics_iteration: ResMut<TectonicsIteration>,
     mut debug_diagnostics: ResMut<DebugDiagnostics>,
     mut next_state: ResMut<NextState<SimulationState>>,
 ) {
-    if tectonics_iteration.0 < tectonics.config.iterations {
-        tectonics.simulate(&mut rng.0);
-        tectonics_iteration.0 += 1;
-    } else {
+    if let Some(batch) = background_simulation.poll() {
+        let repaired = batch
+            .tectonics
+            .repaired_point_masses
+            .saturating_sub(tectonics.repaired_point_masses);
+        if repaired > 0 {
+            warn!("Repaired {repaired} invalid point masses");
+        }
+        if let (None, Some(invalid_state)) = (&tectonics.halted, &batch.tectonics.halted) {
+            error!(
+                "Tectonic simulation halted at iteration {}: {invalid_state}",
+                batch.tectonics.iteration
+            );
+        }
+        tectonics.0 = batch.tectonics.clone();
+        strain_rate.record(batch.strain.clone(), &batch.global_rates);
+        energy_history.record(&batch.energies);
+        if let Some(mut tectonics_history) = tectonics_history {
+            tectonics_history.record(&batch.tectonics);
+        }
+        rng.0 = batch.rng.clone();
+        tectonics_iteration.0 = batch.tectonics.iteration;
+        stage_progress.set(tectonics.progress());
+        if observers.on_iteration(batch.tectonics.iteration, &batch.tectonics) == Control::Abort {
+            warn!(
+                "Generation aborted by an observer at iteration {}",
+                batch.tectonics.iteration
+            );
+            next_state.set(SimulationState::Menu);
+            return;
+        }
+    }
+    if background_simulation.is_running() {
+        return;
+    }
+    let finished = match &epoch_schedule {
+        Some(epoch_schedule) => epoch_schedule.tectonics_finished(&tectonics),
+        None => tectonics.finished(),
+    };
+    if finished {
         debug_diagnostics.tectonics_time = Some(tectonics_start_time.0.elapsed());
-        next_state.set(SimulationState::Erosion);
+        // Each epoch reports its own tectonics, progress restarts whenever the state changes
+        next_state.set(
+            match observers.on_stage_complete(Stage::Tectonics, stage_progress.elapsed()) {
+                Control::Continue => SimulationState::Erosion,
+                Control::Abort => {
+                    warn!("Generation aborted by an observer after the tectonic simulation");
+                    SimulationState::Menu
+                }
+            },
+        );
+        return;
     }
+    let max_steps = match *simulation_control {
+        SimulationControl::Paused => return,
+        SimulationControl::StepOnce => {
+            *simulation_control = SimulationControl::Paused;
+            1
+        }
+        SimulationControl::Running => {
+            BOUNDARY_UPDATE_INTERVAL - tectonics_iteration.0 % BOUNDARY_UPDATE_INTERVAL
+        }
+    };
+    background_simulation.start(max_steps, frame_budget.0);
 }
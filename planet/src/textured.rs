@@ -0,0 +1,254 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use suz_sim::{
+    bake::{BakeConfig, bake_ambient_occlusion, bake_normal_map},
+    export::{ProjectedLayer, Projection, TileLayer, project_layer},
+    vec_utils,
+};
+
+use crate::{
+    chunks::ChunkIndex,
+    coastlines::Coastlines,
+    coloring::{MapMode, recolor},
+    export::ExportConfig,
+    hex_sphere::HexSphere,
+    states::SimulationState,
+};
+
+/// Segments of the textured sphere around the equator, it has half as many from pole to pole
+const SPHERE_SEGMENTS: u32 = 256;
+
+/// Previews the planet the way exports look in external renderers, M swaps the hex mesh for a sphere textured with the equirectangular albedo, normal and ambient occlusion maps.
+/// The textures are baked with the [ExportConfig] resolution and bake settings, the albedo follows the current [MapMode].
+pub struct TexturedPlugin;
+impl Plugin for TexturedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle_textured.run_if(in_state(SimulationState::Erosion)),
+                refresh_textures
+                    .after(recolor)
+                    .run_if(resource_changed::<MapMode>)
+                    .run_if(any_with_component::<TexturedPlanet>),
+            ),
+        )
+        .add_systems(OnExit(SimulationState::Erosion), remove_textured);
+    }
+}
+
+/// The textured sphere drawn instead of the hex sphere chunks
+#[derive(Component)]
+pub struct TexturedPlanet;
+
+/// Sphere of `radius` with UVs following the latitudes and longitudes of [Projection::Equirectangular], so the baked maps line up with the tiles
+pub fn equirectangular_sphere(segments: u32, radius: f32) -> Mesh {
+    let stacks = (segments / 2).max(1);
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for i in 0..=stacks {
+        let v = i as f32 / stacks as f32;
+        let latitude = std::f32::consts::FRAC_PI_2 - v * std::f32::consts::PI;
+        for j in 0..=segments {
+            let u = j as f32 / segments as f32;
+            let longitude = u * std::f32::consts::TAU - std::f32::consts::PI;
+            let normal = vec_utils::lat_long_to_vec3(latitude, longitude);
+            positions.push((normal * radius).to_array());
+            normals.push(normal.to_array());
+            uvs.push([u, v]);
+        }
+    }
+    // Longitude grows to the left seen from outside, so this winding is counterclockwise
+    let row = segments + 1;
+    let indices = (0..stacks)
+        .flat_map(|i| (0..segments).map(move |j| i * row + j))
+        .flat_map(|a| [a, a + 1, a + row, a + 1, a + row + 1, a + row])
+        .collect();
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn texture(layer: ProjectedLayer<[u8; 4]>, format: TextureFormat) -> Image {
+    let data = layer
+        .pixels
+        .into_iter()
+        .flat_map(|pixel| pixel.unwrap_or_default())
+        .collect();
+    let mut image = Image::new(
+        Extent3d {
+            width: layer.width,
+            height: layer.height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        format,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    // Images are sampled nearest by default, which would show the pixels of the baked maps up close
+    image.sampler = ImageSampler::linear();
+    image
+}
+
+/// Material with the tile colors as albedo and the normal and ambient occlusion maps baked from the tile heights
+fn planet_material(
+    hex_sphere: &HexSphere,
+    resolution: u32,
+    bake: &BakeConfig,
+    images: &mut Assets<Image>,
+) -> StandardMaterial {
+    let colors: Vec<[u8; 4]> = hex_sphere
+        .tiles
+        .iter()
+        .map(|tile| {
+            // Vertex colors are linear, textures are stored in sRGB
+            Srgba::from(LinearRgba::from_f32_array(hex_sphere.colors[tile.center])).to_u8_array()
+        })
+        .collect();
+    let albedo = project_layer(
+        &TileLayer {
+            subsphere: &hex_sphere.subsphere,
+            values: &colors,
+        },
+        Projection::Equirectangular,
+        resolution,
+    );
+    let heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+    let heights = TileLayer {
+        subsphere: &hex_sphere.subsphere,
+        values: &heights,
+    };
+    let normals = bake_normal_map(&heights, resolution, bake);
+    let occlusion = bake_ambient_occlusion(&heights, resolution, bake);
+    StandardMaterial {
+        base_color_texture: Some(images.add(texture(albedo, TextureFormat::Rgba8UnormSrgb))),
+        normal_map_texture: Some(
+            images.add(texture(
+                ProjectedLayer {
+                    width: normals.width,
+                    height: normals.height,
+                    pixels: normals
+                        .pixels
+                        .into_iter()
+                        .map(|pixel| pixel.map(|[r, g, b]| [r, g, b, u8::MAX]))
+                        .collect(),
+                },
+                TextureFormat::Rgba8Unorm,
+            )),
+        ),
+        // Bevy only applies occlusion to ambient light
+        occlusion_texture: Some(
+            images.add(texture(
+                ProjectedLayer {
+                    width: occlusion.width,
+                    height: occlusion.height,
+                    pixels: occlusion
+                        .pixels
+                        .into_iter()
+                        .map(|pixel| pixel.map(|ao| [ao, ao, ao, u8::MAX]))
+                        .collect(),
+                },
+                TextureFormat::Rgba8Unorm,
+            )),
+        ),
+        perceptual_roughness: 0.9,
+        reflectance: 0.18,
+        ..Default::default()
+    }
+}
+
+fn toggle_textured(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    hex_sphere: Res<HexSphere>,
+    config: Res<ExportConfig>,
+    coastlines: Res<Coastlines>,
+    textured: Query<Entity, With<TexturedPlanet>>,
+    mut chunks: Query<&mut Visibility, With<ChunkIndex>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyM) {
+        return;
+    }
+    if !textured.is_empty() {
+        for entity in &textured {
+            commands.entity(entity).despawn();
+        }
+        // Chunk culling takes over again from here
+        for mut visibility in &mut chunks {
+            *visibility = Visibility::Inherited;
+        }
+        return;
+    }
+    let mut mesh = equirectangular_sphere(SPHERE_SEGMENTS, coastlines.sea_level);
+    if let Err(e) = mesh.generate_tangents() {
+        warn!(
+            "Failed to generate tangents for the textured planet, the normal map will not show: {e}"
+        );
+    }
+    let material = planet_material(
+        &hex_sphere,
+        config.heightmap_width,
+        &config.bake,
+        &mut images,
+    );
+    commands.spawn((
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(materials.add(material)),
+        TexturedPlanet,
+    ));
+    for mut visibility in &mut chunks {
+        *visibility = Visibility::Hidden;
+    }
+    info!("Showing the planet with baked textures, press M to go back to the hex mesh");
+}
+
+/// Rebakes the textures so the albedo follows the new [MapMode]
+fn refresh_textures(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    hex_sphere: Res<HexSphere>,
+    config: Res<ExportConfig>,
+    textured: Query<&MeshMaterial3d<StandardMaterial>, With<TexturedPlanet>>,
+) {
+    for material in &textured {
+        if let Some(material) = materials.get_mut(&material.0) {
+            *material = planet_material(
+                &hex_sphere,
+                config.heightmap_width,
+                &config.bake,
+                &mut images,
+            );
+        }
+    }
+}
+
+/// Regenerating replaces the planet the textures were baked from
+fn remove_textured(
+    mut commands: Commands,
+    textured: Query<Entity, With<TexturedPlanet>>,
+    mut chunks: Query<&mut Visibility, With<ChunkIndex>>,
+) {
+    for entity in &textured {
+        commands.entity(entity).despawn();
+    }
+    for mut visibility in &mut chunks {
+        *visibility = Visibility::Inherited;
+    }
+}
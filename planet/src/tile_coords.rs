@@ -0,0 +1,204 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use bevy::math::{IVec2, Vec3};
+use subsphere::{
+    Face, Sphere, Vertex,
+    proj::{BaseTriProjector, Fuller, Projection},
+};
+use suz_sim::vec_utils;
+
+use crate::hex_sphere::Tile;
+
+/// Axial offsets to the six neighbors of a tile, counter-clockwise starting along the base face U axis
+pub const AXIAL_DIRECTIONS: [IVec2; 6] = [
+    IVec2::new(1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, 0),
+    IVec2::new(0, -1),
+    IVec2::new(1, -1),
+];
+
+/// Lattice offsets between adjacent tile centers, every third kis vertex is a tile center
+const HEX_BASIS: [IVec2; 2] = [IVec2::new(2, -1), IVec2::new(1, 1)];
+
+/// Tiles whose center lies this close to a base face edge are assigned to the lowest indexed face
+const FACE_TIE_EPSILON: f64 = 1e-9;
+
+/// Stable position of a tile, the base icosahedron face containing its center and axial coordinates within that face.
+/// Coordinates only depend on the subdivision count, so they survive saving and reloading a planet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TileCoord {
+    /// Index of the base icosahedron face
+    pub face: u8,
+    /// Axial coordinates of the tile on the hexagonal grid of the base face
+    pub axial: IVec2,
+}
+
+/// The triangular lattice of the kis `TriSphere` within a base face, in the face's local Fuller coordinates.
+/// Mirrors how subsphere places the vertices of a `TriSphere` with parameters `b` and `c`.
+/// Tile centers are the vertices of a sublattice spanned by [HEX_BASIS].
+#[derive(Clone, Copy)]
+struct FaceLattice {
+    origin: [f64; 2],
+    u: [f64; 2],
+    v: [f64; 2],
+}
+
+impl FaceLattice {
+    fn new(b: u32, c: u32) -> Self {
+        let (b, c) = (b as f64, c as f64);
+        let total = b * b + b * c + c * c;
+        let origin = [c * c / total, b * c / total];
+        let u = [(1. - origin[0]) / b, -origin[1] / b];
+        let v = if c > 0. {
+            [origin[0] / c, origin[1] / c]
+        } else {
+            [0., 1. / b]
+        };
+        FaceLattice { origin, u, v }
+    }
+
+    /// Local coordinates of an offset along the lattice
+    fn offset_to_local(self, offset: [f64; 2]) -> [f64; 2] {
+        [
+            self.u[0] * offset[0] + self.v[0] * offset[1],
+            self.u[1] * offset[0] + self.v[1] * offset[1],
+        ]
+    }
+
+    /// The nearest lattice vertex to the local coordinates
+    fn nearest_vertex(self, local: [f64; 2]) -> IVec2 {
+        let x = local[0] - self.origin[0];
+        let y = local[1] - self.origin[1];
+        let det = self.u[0] * self.v[1] - self.v[0] * self.u[1];
+        IVec2::new(
+            ((self.v[1] * x - self.v[0] * y) / det).round() as i32,
+            ((self.u[0] * y - self.u[1] * x) / det).round() as i32,
+        )
+    }
+}
+
+/// Axial coordinates of a tile center given its kis lattice vertex, inverts [HEX_BASIS].
+/// All tile centers in a face share the same remainder, which the euclidean division drops.
+fn kis_to_axial(vertex: IVec2) -> IVec2 {
+    IVec2::new(
+        (vertex.x - vertex.y).div_euclid(3),
+        (vertex.x + 2 * vertex.y).div_euclid(3),
+    )
+}
+
+/// Two way mapping between tile indices and [TileCoord]
+pub struct TileCoords {
+    lattice: FaceLattice,
+    coords: Vec<TileCoord>,
+    indices: HashMap<TileCoord, usize>,
+}
+
+/// The base face whose center is closest to `point`, which is the face containing it
+fn base_face_at(point: [f64; 3]) -> subsphere::basetri::Face {
+    let mut best = None;
+    let mut best_dot = f64::MIN;
+    for face in subsphere::BaseTriSphere::Icosa.faces() {
+        let center = face.center();
+        let dot = center[0] * point[0] + center[1] * point[1] + center[2] * point[2];
+        if dot > best_dot + FACE_TIE_EPSILON {
+            best = Some(face);
+            best_dot = dot;
+        }
+    }
+    best.unwrap()
+}
+
+impl TileCoords {
+    pub fn new(subsphere: &subsphere::HexSphere<Fuller>) -> Self {
+        let kis = subsphere.kis();
+        let lattice = FaceLattice::new(kis.b(), kis.c());
+        let coords: Vec<TileCoord> = subsphere
+            .faces()
+            .map(|face| {
+                let position = face.center().pos();
+                let base_face = base_face_at(position);
+                let local = Fuller.inside(base_face.side(0)).from_sphere(position);
+                TileCoord {
+                    face: base_face.index() as u8,
+                    axial: kis_to_axial(lattice.nearest_vertex(local)),
+                }
+            })
+            .collect();
+        let indices = coords
+            .iter()
+            .enumerate()
+            .map(|(index, coord)| (*coord, index))
+            .collect();
+        TileCoords {
+            lattice,
+            coords,
+            indices,
+        }
+    }
+
+    /// The coordinate of the tile with index `index`
+    pub fn from_index(&self, index: usize) -> TileCoord {
+        self.coords[index]
+    }
+
+    /// The index of the tile at `coord`, if any.
+    /// Tiles on base face edges only have a coordinate in one of the faces sharing the edge.
+    pub fn to_index(&self, coord: TileCoord) -> Option<usize> {
+        self.indices.get(&coord).copied()
+    }
+
+    /// The index of the neighbor of `tiles[index]` in direction [AXIAL_DIRECTIONS]`[direction]`.
+    /// Inside a base face this is an exact lookup, on face edges the adjacent tiles are ranked counter-clockwise
+    /// starting from the one closest to the first direction. Pentagons only have five neighbors, so two directions give the same tile.
+    pub fn neighbor(&self, tiles: &[Tile], index: usize, direction: usize) -> usize {
+        let coord = self.coords[index];
+        let in_face: Vec<Option<usize>> = AXIAL_DIRECTIONS
+            .iter()
+            .map(|offset| {
+                self.to_index(TileCoord {
+                    face: coord.face,
+                    axial: coord.axial + *offset,
+                })
+            })
+            .collect();
+        // Mixing lookups with ranking could give the same neighbor twice, so rank unless every neighbor is in the face
+        if in_face.iter().all(Option::is_some) {
+            return in_face[direction].unwrap();
+        }
+
+        let tile = &tiles[index];
+        let projection = Fuller.inside(
+            subsphere::BaseTriSphere::Icosa
+                .face(coord.face as usize)
+                .side(0),
+        );
+        // Step a short way along the first direction, the neighbor may lie outside this face
+        let local = projection.from_sphere(vec_utils::vec3_to_f64_3(tile.normal));
+        let offset = HEX_BASIS[0].as_dvec2() * 0.01;
+        let step = self.lattice.offset_to_local([offset.x, offset.y]);
+        let towards = Vec3::from(vec_utils::f64_3_to_f32_3(
+            &projection.to_sphere([local[0] + step[0], local[1] + step[1]]),
+        ));
+        let reference = (towards - tile.normal).normalize_or_zero();
+
+        let mut neighbors: Vec<usize> = tile
+            .adjacent
+            .iter()
+            .copied()
+            .filter(|&adjacent| adjacent != index)
+            .collect();
+        let half_slot = PI / neighbors.len() as f32;
+        let angle = |adjacent: usize| {
+            let towards = tiles[adjacent].normal - tile.normal;
+            let angle = reference
+                .cross(towards)
+                .dot(tile.normal)
+                .atan2(reference.dot(towards));
+            (angle + half_slot).rem_euclid(2. * PI)
+        };
+        neighbors.sort_by(|&a, &b| angle(a).total_cmp(&angle(b)));
+        neighbors[direction * neighbors.len() / AXIAL_DIRECTIONS.len()]
+    }
+}
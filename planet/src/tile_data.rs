@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+use suz_sim::{
+    bathymetry::{CrustAge, oceanic_tiles, trench_depths},
+    climate::{tile_precipitation, tile_temperatures},
+    interpolation::{containing_plates, interpolate_tile_values},
+    island_arcs::IslandArcs,
+};
+
+use crate::{
+    coastlines::{Coastlines, extract_coastlines},
+    coloring::recolor,
+    hex_sphere::HexSphere,
+    lakes::detect_lakes,
+    plate_boundaries::BOUNDARY_UPDATE_INTERVAL,
+    sim_resources::{SimPlateBoundaries, SimTectonics},
+    states::SimulationState,
+    strain_rate::StrainRate,
+    tectonics::{TectonicsIteration, TectonicsPluginConfig},
+};
+
+/// Per tile layers that [crate::coloring::MapMode] can color the planet by.
+/// Elevation is kept on the [HexSphere] tiles, boundary types in [SimPlateBoundaries] and strain rates in [StrainRate].
+#[derive(Resource, Default)]
+pub struct TileData {
+    /// Index into [suz_sim::tectonics::Tectonics::plates] of the plate under each tile
+    pub plates: Vec<usize>,
+    /// Color of each plate, in the same order as [suz_sim::tectonics::Tectonics::plates]
+    pub plate_colors: Vec<[f32; 4]>,
+    /// Average absolute strain of the springs near each tile
+    pub spring_stress: Vec<f32>,
+    /// Simulated time since each tile last sat on a divergent boundary, where new crust forms
+    pub crust_age: CrustAge,
+    /// Depth of the oceanic trench under each tile, empty without [suz_sim::bathymetry::BathymetryConfig]
+    pub trench_depths: Vec<f32>,
+    /// Uplift behind ocean-ocean subduction zones, `None` without [suz_sim::island_arcs::IslandArcConfig]
+    pub island_arcs: Option<IslandArcs>,
+    /// Mean annual temperature in °C, only known once the planet is finished
+    pub temperature: Vec<f32>,
+    /// Mean rainfall per moisture step, only known once the planet is finished
+    pub precipitation: Vec<f32>,
+    /// Glacier ice on each tile, only known once the planet is finished
+    pub ice_thickness: Vec<f32>,
+}
+
+pub struct TileDataPlugin;
+impl Plugin for TileDataPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TileData>().add_systems(
+            OnEnter(SimulationState::Erosion),
+            (
+                update_climate.after(extract_coastlines),
+                recolor.after(detect_lakes),
+            ),
+        );
+    }
+}
+
+/// Refreshes the layers on the same iterations as [SimPlateBoundaries], which crust age is derived from
+pub fn update_tile_data(
+    mut tile_data: ResMut<TileData>,
+    hex_sphere: Res<HexSphere>,
+    tectonics: Res<SimTectonics>,
+    strain_rate: Res<StrainRate>,
+    plate_boundaries: Res<SimPlateBoundaries>,
+    tectonics_iteration: Res<TectonicsIteration>,
+    config: Res<TectonicsPluginConfig>,
+) {
+    if tectonics_iteration.0 % BOUNDARY_UPDATE_INTERVAL != 0 {
+        return;
+    }
+    let tile_normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+    tile_data.plates = containing_plates(&tectonics, &tile_normals);
+    tile_data.plate_colors = tectonics.plates.iter().map(|plate| plate.color).collect();
+    tile_data.spring_stress = interpolate_tile_values(
+        &tectonics,
+        &tile_normals,
+        &strain_rate.tracker.point_mass_strains,
+        0.,
+    );
+    tile_data
+        .crust_age
+        .update(&plate_boundaries.tiles, tectonics.simulated_time);
+    tile_data.trench_depths = match &config.bathymetry_config {
+        Some(bathymetry) => trench_depths(
+            bathymetry,
+            &plate_boundaries.0,
+            &oceanic_tiles(&tectonics, &tile_data.plates),
+            &tile_normals,
+            |tile_index| hex_sphere.tiles[tile_index].adjacent.as_slice(),
+        ),
+        None => Vec::new(),
+    };
+    if let Some(island_arc_config) = config.island_arc_config {
+        let tile_data = &mut *tile_data;
+        tile_data
+            .island_arcs
+            .get_or_insert_with(|| IslandArcs::new(island_arc_config))
+            .update(
+                &tectonics,
+                &plate_boundaries.0,
+                &tile_data.plates,
+                &tile_data.crust_age.ages,
+                &tile_normals,
+                |tile_index| hex_sphere.tiles[tile_index].adjacent.as_slice(),
+            );
+    }
+}
+
+/// Climate depends on the final heights and sea level, so it is computed once the planet is finished
+pub fn update_climate(
+    mut tile_data: ResMut<TileData>,
+    hex_sphere: Res<HexSphere>,
+    coastlines: Res<Coastlines>,
+    config: Res<TectonicsPluginConfig>,
+) {
+    let tile_normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+    let tile_heights: Vec<f32> = hex_sphere.tiles.iter().map(|tile| tile.height).collect();
+    tile_data.temperature = tile_temperatures(
+        &config.climate_config,
+        &tile_normals,
+        &tile_heights,
+        coastlines.sea_level,
+    );
+    tile_data.precipitation = tile_precipitation(
+        &config.climate_config,
+        &tile_normals,
+        &tile_heights,
+        coastlines.sea_level,
+        |tile_index| hex_sphere.tiles[tile_index].adjacent.as_slice(),
+    );
+}
@@ -0,0 +1,164 @@
+use bevy::{color::palettes, prelude::*, window::PrimaryWindow};
+
+use crate::{
+    coastlines::Coastlines,
+    hex_sphere::{CurrentMousePick, HexSphere},
+    lakes::Lakes,
+    sim_resources::SimTectonics,
+    tectonics::TectonicsPluginConfig,
+    tile_data::TileData,
+};
+
+/// How many of the closest point masses the inspector lists
+const NEAREST_POINT_MASSES: usize = 3;
+/// Offset of the inspector from the cursor, in logical pixels
+const CURSOR_OFFSET: Vec2 = Vec2::new(16., 16.);
+
+/// Floating panel describing the tile in [CurrentMousePick]
+#[derive(Component)]
+struct TileInspector;
+
+#[derive(Component)]
+struct TileInspectorText;
+
+pub struct TileInspectorPlugin;
+impl Plugin for TileInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup).add_systems(
+            Update,
+            (
+                update_inspector_text
+                    .run_if(resource_exists::<HexSphere>.and(resource_changed::<CurrentMousePick>)),
+                follow_cursor,
+            ),
+        );
+    }
+}
+
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TileInspector,
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(6.)),
+            border: UiRect::all(Val::Px(1.)),
+            display: Display::None,
+            ..default()
+        },
+        BorderColor(LinearRgba::new(0.4, 0.4, 0.4, 1.).into()),
+        BackgroundColor(LinearRgba::new(0.01, 0.01, 0.01, 0.8).into()),
+        children![(
+            Text::default(),
+            TextFont {
+                font: asset_server.load("fonts/FiraMono-Medium.ttf"),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(palettes::css::GOLD.into()),
+            TileInspectorText,
+        )],
+    ));
+}
+
+/// Describes every layer known for the picked tile, layers that are not computed yet are left out
+fn update_inspector_text(
+    hex_sphere: Res<HexSphere>,
+    current_mouse_pick: Res<CurrentMousePick>,
+    tectonics: Option<Res<SimTectonics>>,
+    tile_data: Res<TileData>,
+    lakes: Res<Lakes>,
+    coastlines: Res<Coastlines>,
+    tectonics_plugin_config: Res<TectonicsPluginConfig>,
+    mut inspector_text: Query<&mut Text, With<TileInspectorText>>,
+) {
+    let Some(pick) = &current_mouse_pick.pick else {
+        return;
+    };
+    let tile = &pick.tile;
+    let units = tectonics_plugin_config.tectonics_config.units();
+    let coord = hex_sphere.coords.from_index(tile.index);
+    let mut lines = vec![
+        format!(
+            "Tile {} ({}, {}, {})",
+            tile.index, coord.face, coord.axial.x, coord.axial.y
+        ),
+        format!("Height: {:.4}", tile.height),
+    ];
+    // Sea level is only known once coastlines are extracted
+    if !coastlines.land.is_empty() {
+        lines.push(format!(
+            "Elevation: {:.0} m",
+            units.elevation_m(tile.height, coastlines.sea_level)
+        ));
+    }
+    if let Some(tectonics) = &tectonics {
+        if let Some(&plate_index) = tile_data.plates.get(tile.index) {
+            if let Some(plate) = tectonics.plates.get(plate_index) {
+                lines.push(format!("Plate: {plate_index} ({})", plate.plate_type));
+            }
+        }
+        let mut nearest = tectonics
+            .tree
+            .query_within(tile.normal, tectonics.config.vertex_interpolation_radius);
+        nearest.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+        let distances: Vec<String> = nearest
+            .iter()
+            .take(NEAREST_POINT_MASSES)
+            .map(|(_, distance)| format!("{:.0} km", units.distance_km(*distance)))
+            .collect();
+        lines.push(if distances.is_empty() {
+            "Point masses: none in range".to_string()
+        } else {
+            format!("Point masses: {}", distances.join(", "))
+        });
+    }
+    if let Some(temperature) = tile_data.temperature.get(tile.index) {
+        lines.push(format!("Temperature: {temperature:.1} °C"));
+    }
+    if let Some(precipitation) = tile_data.precipitation.get(tile.index) {
+        lines.push(format!("Precipitation: {precipitation:.3}"));
+    }
+    if let Some(thickness) = tile_data
+        .ice_thickness
+        .get(tile.index)
+        .filter(|thickness| **thickness > 0.)
+    {
+        lines.push(format!("Ice: {thickness:.3}"));
+    }
+    if let Some(Some(lake)) = lakes.tile_to_lake.get(tile.index) {
+        lines.push(format!("Lake: {lake}"));
+    }
+    if current_mouse_pick.pinned {
+        lines.push("Pinned, right click to release".to_string());
+    }
+    **inspector_text.single_mut().unwrap() = lines.join("\n");
+}
+
+/// Keeps the inspector next to the cursor, a pinned inspector stays where it was pinned
+fn follow_cursor(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    current_mouse_pick: Res<CurrentMousePick>,
+    mut inspector: Query<&mut Node, With<TileInspector>>,
+) {
+    let mut node = inspector.single_mut().unwrap();
+    let display = if current_mouse_pick.pick.is_some() {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if node.display != display {
+        node.display = display;
+    }
+    if display == Display::None || current_mouse_pick.pinned {
+        return;
+    }
+    if let Some(cursor_position) = window_query
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position())
+    {
+        let position = cursor_position + CURSOR_OFFSET;
+        node.left = Val::Px(position.x);
+        node.top = Val::Px(position.y);
+    }
+}
@@ -1,137 +1,195 @@
+use crate::chunks::HexSphereChunks;
+use crate::coloring::{ColorRamp, MapMode, color_tiles};
 use crate::hex_sphere::{HexSphere, HexSphereMeshHandle};
-use crate::tectonics::TectonicsIteration;
+use crate::sim_diagnostics::SimulationDiagnosticsPlugin;
+use crate::sim_resources::{
+    SimCompressionField, SimEpochSchedule, SimPlateBoundaries, SimTectonics,
+};
+use crate::strain_rate::StrainRate;
+use crate::tectonics::{SimulationControl, TectonicsIteration, TectonicsPluginConfig};
+use crate::tile_data::TileData;
+use bevy::diagnostic::Diagnostics;
 use bevy::prelude::*;
-use kdtree::KdTree;
-use rayon::prelude::*;
-use suz_sim::tectonics::{CONTINENTAL_HEIGHT, OCEANIC_HEIGHT, Tectonics};
-use suz_sim::vec_utils;
+use bevy::render::mesh::VertexAttributeValues;
+use suz_sim::bathymetry::{apply_bathymetry, oceanic_tiles};
+use suz_sim::flexure::apply_flexure;
+use suz_sim::interpolation::{
+    height_updates, interpolate_tile_heights_with, interpolate_tile_values,
+};
+use suz_sim::island_arcs::apply_island_arcs;
+use suz_sim::plate::PlateType;
+use suz_sim::shelf::apply_shelf;
+use suz_sim::vertex_interpolation::{apply_height_updates, move_tile_vertices};
+
+/// Tiles whose height changed less than this since their last mesh update are left alone
+const HEIGHT_EPSILON: f32 = 1e-4;
+
+/// Moves every tile of `hex_sphere` and its `mesh` to `heights`, for changes made after the tectonic simulation
+pub fn apply_tile_heights(hex_sphere: &mut HexSphere, mesh: &mut Mesh, heights: &[f32]) {
+    for (tile, &height) in hex_sphere.tiles.iter_mut().zip(heights) {
+        tile.height = height;
+    }
+    let all_tiles: Vec<usize> = (0..hex_sphere.tiles.len()).collect();
+    let new_normals = move_tile_vertices(
+        &hex_sphere.tiles,
+        &mut hex_sphere.vertices,
+        &hex_sphere.vertices_to_tiles,
+        &all_tiles,
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, hex_sphere.vertices.clone());
+    if let Some(VertexAttributeValues::Float32x3(normals)) =
+        mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL)
+    {
+        for (vertex_index, normal) in new_normals {
+            normals[vertex_index] = normal.into();
+        }
+    }
+}
 
 pub fn interpolate_vertices(
     mut meshes: ResMut<Assets<Mesh>>,
     mut hex_sphere: ResMut<HexSphere>,
-    tectonics: Res<Tectonics>,
+    mut strain_rate: ResMut<StrainRate>,
+    plate_boundaries: Res<SimPlateBoundaries>,
+    tile_data: Res<TileData>,
+    tectonics: Res<SimTectonics>,
+    mut compression: ResMut<SimCompressionField>,
     tectonics_iteration: Res<TectonicsIteration>,
+    map_mode: Res<MapMode>,
+    color_ramp: Res<ColorRamp>,
+    config: Res<TectonicsPluginConfig>,
+    simulation_control: Res<SimulationControl>,
     mesh_handle: Res<HexSphereMeshHandle>,
+    chunks: Res<HexSphereChunks>,
+    epoch_schedule: Option<Res<SimEpochSchedule>>,
+    mut diagnostics: Diagnostics,
 ) {
-    if tectonics_iteration.0 % 40 == 0 {
-        // 1. For each tile, compute average height from nearby point masses, update tile height and center vertex height
-        let mut kdtree = KdTree::<f32, (_, f32), [f32; 3]>::new(3);
-        for (point_mass, plate_type, spring_compressions) in
-            tectonics.plates.iter().flat_map(|plate| {
-                plate
-                    .shape
-                    .par_iter_point_masses_with_springs()
-                    .map(|(point_mass, springs)| {
-                        (
-                            point_mass,
-                            plate.plate_type,
-                            springs.map(|spring| {
-                                let pm_a = &plate.shape.point_masses[spring.anchor_a];
-                                let pm_b = &plate.shape.point_masses[spring.anchor_b];
-                                let compression: f32 =
-                                    spring.rest_length - pm_a.geodesic_distance(&pm_b);
-                                compression
-                            }),
-                        )
-                    })
-            })
-        {
-            kdtree
-                .add(
-                    point_mass.position.into(),
-                    (plate_type, spring_compressions.sum::<f32>()),
-                )
-                .ok();
+    // Every step is shown when stepping through a paused simulation
+    if tectonics_iteration.0 % 40 == 0 || *simulation_control != SimulationControl::Running {
+        let start = web_time::Instant::now();
+        // 1. For each tile, compute average height from nearby point masses, update tile height and center vertex height if it moved
+        let tile_normals: Vec<Vec3> = hex_sphere.tiles.iter().map(|tile| tile.normal).collect();
+        compression.update(&tectonics.plates);
+        let mut tile_heights =
+            interpolate_tile_heights_with(&tectonics, &compression, &tile_normals);
+        apply_flexure(
+            &config.flexure_config,
+            &mut tile_heights,
+            &tile_normals,
+            |tile_index| hex_sphere.tiles[tile_index].adjacent.as_slice(),
+        );
+        if let Some(bathymetry) = &config.bathymetry_config {
+            apply_bathymetry(
+                bathymetry,
+                tectonics.config.plate_height(PlateType::Oceanic),
+                &mut tile_heights,
+                &oceanic_tiles(&tectonics, &tile_data.plates),
+                &tile_data.crust_age.ages,
+                &tile_data.trench_depths,
+            );
         }
-
-        let tile_results: Vec<_> = hex_sphere
+        if let Some(island_arcs) = &tile_data.island_arcs {
+            apply_island_arcs(&mut tile_heights, &island_arcs.uplift);
+        }
+        if let Some(shelf) = &config.shelf_config {
+            apply_shelf(shelf, &mut tile_heights, &tile_normals, |tile_index| {
+                hex_sphere.tiles[tile_index].adjacent.as_slice()
+            });
+        }
+        if let Some(epoch_schedule) = &epoch_schedule {
+            epoch_schedule.apply_erosion(&mut tile_heights);
+        }
+        let updates = height_updates(&hex_sphere.tiles, &tile_heights, HEIGHT_EPSILON);
+        let moved = apply_height_updates(&mut hex_sphere.tiles, &updates);
+        strain_rate.tiles = interpolate_tile_values(
+            &tectonics,
+            &tile_normals,
+            &strain_rate.tracker.point_mass_rates,
+            0.,
+        );
+        let previous_colors: Vec<[f32; 4]> = hex_sphere
             .tiles
-            .par_iter()
-            .enumerate()
-            .map(|(tile_index, tile)| {
-                let mut weighted_sum = 0.0;
-                let mut weight_total = 0.0;
-                let tile_normal = tile.normal;
-                let tile_center = tile.center;
-                let position: [f32; 3] = tile_normal.into();
-                for (distance, (plate_type, compression)) in kdtree
-                    .within(
-                        &position,
-                        tectonics.config.vertex_interpolation_radius,
-                        &vec_utils::geodesic_distance_arr,
-                    )
-                    .unwrap()
-                {
-                    let weight = 1.0 / (distance + 0.01); // closer = higher weight, avoid div by zero
-                    let plate_height = match plate_type {
-                        suz_sim::plate::PlateType::Oceanic => OCEANIC_HEIGHT,
-                        suz_sim::plate::PlateType::Continental => CONTINENTAL_HEIGHT,
-                    };
-                    weighted_sum += (plate_height + compression) * weight;
-                    weight_total += weight;
-                }
-                let new_height = if weight_total > 0.0 {
-                    weighted_sum / weight_total
-                } else {
-                    OCEANIC_HEIGHT
-                };
-                let color = if new_height < 1.0 {
-                    [0.0, 0.0, 1.0, 1.0] // blue for below 1.0
-                } else {
-                    [0.0, 1.0, 0.0, 1.0] // green for above
-                };
-                (tile_index, new_height, color, tile_center, tile_normal)
+            .iter()
+            .map(|tile| hex_sphere.colors[tile.center])
+            .collect();
+        color_tiles(
+            &mut hex_sphere,
+            *map_mode,
+            &color_ramp,
+            Some(&*strain_rate),
+            Some(&plate_boundaries.0),
+            Some(&*tile_data),
+            None,
+        );
+        let recolored: Vec<usize> = (0..hex_sphere.tiles.len())
+            .filter(|&tile_index| {
+                hex_sphere.colors[hex_sphere.tiles[tile_index].center]
+                    != previous_colors[tile_index]
             })
             .collect();
 
-        // Apply results sequentially to avoid race conditions
-        for (tile_index, new_height, color, tile_center, tile_normal) in tile_results {
-            hex_sphere.tiles[tile_index].height = new_height;
-            hex_sphere.colors[tile_center] = color;
-            hex_sphere.vertices[tile_center] = (tile_normal * new_height).into();
-            for vertex_index in &hex_sphere.tiles[tile_index].vertices.clone() {
-                hex_sphere.colors[*vertex_index] = color;
-            }
-        }
+        // 2. Move the center and corner vertices of moved tiles, corners are interpolated using vertices_to_tiles
+        let hex_sphere_mut = &mut *hex_sphere;
+        let new_normals = move_tile_vertices(
+            &hex_sphere_mut.tiles,
+            &mut hex_sphere_mut.vertices,
+            &hex_sphere_mut.vertices_to_tiles,
+            &moved,
+        );
 
-        // 2. Interpolate corner vertices using vertex_to_tiles (parallel, but collect first)
-        let new_vertex_positions: Vec<_> = (0..hex_sphere.vertices_to_tiles.len())
-            .into_par_iter()
-            .map(|vertex_index| {
-                let tile_indices = &hex_sphere.vertices_to_tiles[vertex_index];
-                // Center vertex has no adjacent tiles, so we skip it
-                if tile_indices.is_empty() {
-                    return hex_sphere.vertices[vertex_index];
-                }
-                let mut sum = Vec3::ZERO;
-                for tile_index in tile_indices {
-                    let tile = &hex_sphere.tiles[*tile_index];
-                    let normal = tile.normal;
-                    let height = tile.height;
-                    sum += normal * height;
+        // 3. Patch only the vertices of moved and recolored tiles in the mesh
+        let Some(mesh) = meshes.get_mut(&mesh_handle.0) else {
+            return;
+        };
+        if hex_sphere.vertices.len() != mesh.count_vertices()
+            || hex_sphere.colors.len() != mesh.count_vertices()
+        {
+            warn!(
+                "Vertex or color array length does not match mesh vertex count: vertices = {}, mesh = {}",
+                hex_sphere.vertices.len(),
+                mesh.count_vertices()
+            );
+            return;
+        }
+        let tile_vertices = |tile_index: usize| {
+            let tile = &hex_sphere.tiles[tile_index];
+            tile.vertices
+                .iter()
+                .copied()
+                .chain(std::iter::once(tile.center))
+        };
+        if let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+        {
+            for &tile_index in &moved {
+                for vertex_index in tile_vertices(tile_index) {
+                    positions[vertex_index] = hex_sphere.vertices[vertex_index];
                 }
-                (sum / 3.).into()
-            })
-            .collect();
-        for (vertex, new_pos) in hex_sphere.vertices.iter_mut().zip(new_vertex_positions) {
-            *vertex = new_pos;
+            }
         }
-
-        // 3. Update mesh
-        if let Some(mesh) = meshes.get_mut(&mesh_handle.0) {
-            if hex_sphere.vertices.len() == mesh.count_vertices()
-                && hex_sphere.colors.len() == mesh.count_vertices()
-            {
-                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, hex_sphere.colors.clone());
-                mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, hex_sphere.vertices.clone());
-                mesh.compute_normals();
-            } else {
-                warn!(
-                    "Vertex or color array length does not match mesh vertex count: vertices = {}, mesh = {}",
-                    hex_sphere.vertices.len(),
-                    mesh.count_vertices()
-                );
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL)
+        {
+            for (vertex_index, normal) in new_normals {
+                normals[vertex_index] = normal.into();
+            }
+        }
+        if let Some(VertexAttributeValues::Float32x4(colors)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR)
+        {
+            for &tile_index in &recolored {
+                for vertex_index in tile_vertices(tile_index) {
+                    colors[vertex_index] = hex_sphere.colors[vertex_index];
+                }
             }
         }
+        chunks.patch(
+            &mut meshes,
+            &mesh_handle.0,
+            moved.iter().chain(&recolored).copied(),
+        );
+        diagnostics.add_measurement(&SimulationDiagnosticsPlugin::MESH_PATCH_TIME, || {
+            start.elapsed().as_secs_f64() * 1000.
+        });
     }
 }
@@ -0,0 +1,193 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
+    math::Affine2,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    coastlines::{Coastlines, extract_coastlines},
+    states::SimulationState,
+    textured::{TexturedPlanet, equirectangular_sphere},
+};
+
+/// Segments of the water sphere around the equator
+const WATER_SEGMENTS: u32 = 128;
+/// Side in pixels of the tiling wave normal map
+const WAVE_TEXTURE_SIZE: u32 = 128;
+/// Times the wave normal map repeats around the equator, half as often from pole to pole
+const WAVE_REPEATS: f32 = 64.;
+/// Wave crests across the wave normal map as (x, y, steepness), whole numbers of crests so the map tiles
+const WAVES: [(f32, f32, f32); 4] = [
+    (3., 1., 0.4),
+    (-2., 3., 0.3),
+    (5., -4., 0.15),
+    (1., 7., 0.1),
+];
+
+/// Draws a translucent sphere at sea level over the terrain once the sea level is picked, so oceans read as water.
+/// K toggles it, it is hidden while the [TexturedPlanet] preview is shown.
+pub struct WaterPlugin {
+    pub color: Color,
+    /// Wave normal map repeats scrolled per second, `None` for a still surface
+    pub wave_speed: Option<f32>,
+}
+impl Plugin for WaterPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WaterConfig {
+            visible: true,
+            color: self.color,
+            wave_speed: self.wave_speed,
+        })
+        .add_systems(
+            OnEnter(SimulationState::Erosion),
+            spawn_water.after(extract_coastlines),
+        )
+        .add_systems(OnExit(SimulationState::Erosion), despawn_water)
+        .add_systems(
+            Update,
+            (
+                toggle_water,
+                sync_water_visibility,
+                animate_waves.run_if(|config: Res<WaterConfig>| config.wave_speed.is_some()),
+            )
+                .chain()
+                .run_if(any_with_component::<Water>),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct WaterConfig {
+    pub visible: bool,
+    pub color: Color,
+    /// Wave normal map repeats scrolled per second, `None` for a still surface
+    pub wave_speed: Option<f32>,
+}
+
+#[derive(Component)]
+pub struct Water;
+
+/// Tiling normal map of a few crossing sine waves
+fn wave_normal_map() -> Image {
+    let size = WAVE_TEXTURE_SIZE;
+    let data = (0..size)
+        .flat_map(|y| (0..size).map(move |x| Vec2::new(x as f32, y as f32) / size as f32))
+        .flat_map(|at| {
+            let slope: Vec2 = WAVES
+                .iter()
+                .map(|&(x, y, steepness)| {
+                    let crests = Vec2::new(x, y);
+                    crests * steepness * (TAU * crests.dot(at)).cos() / crests.length()
+                })
+                .sum();
+            let normal = (-slope).extend(1.).normalize() * 0.5 + Vec3::splat(0.5);
+            let [r, g, b] = (normal * 255.)
+                .round()
+                .to_array()
+                .map(|channel| channel as u8);
+            [r, g, b, u8::MAX]
+        })
+        .collect();
+    let mut image = Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::linear()
+    });
+    image
+}
+
+fn wave_transform(offset: f32) -> Affine2 {
+    Affine2::from_scale_angle_translation(
+        Vec2::new(WAVE_REPEATS, WAVE_REPEATS / 2.),
+        0.,
+        Vec2::splat(offset.rem_euclid(1.)),
+    )
+}
+
+fn spawn_water(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    coastlines: Res<Coastlines>,
+    config: Res<WaterConfig>,
+) {
+    let mut mesh = equirectangular_sphere(WATER_SEGMENTS, coastlines.sea_level);
+    let normal_map_texture = match mesh.generate_tangents() {
+        Ok(()) => config.wave_speed.map(|_| images.add(wave_normal_map())),
+        Err(e) => {
+            warn!("Failed to generate tangents for the water surface, it will have no waves: {e}");
+            None
+        }
+    };
+    commands.spawn((
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: config.color,
+            alpha_mode: AlphaMode::Blend,
+            normal_map_texture,
+            uv_transform: wave_transform(0.),
+            perceptual_roughness: 0.15,
+            reflectance: 0.5,
+            ..Default::default()
+        })),
+        Water,
+    ));
+}
+
+fn despawn_water(mut commands: Commands, water: Query<Entity, With<Water>>) {
+    for entity in &water {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn toggle_water(keys: Res<ButtonInput<KeyCode>>, mut config: ResMut<WaterConfig>) {
+    if keys.just_pressed(KeyCode::KeyK) {
+        config.visible = !config.visible;
+    }
+}
+
+/// The textured preview already draws the oceans, and lies at the same radius as the water
+fn sync_water_visibility(
+    config: Res<WaterConfig>,
+    textured: Query<(), With<TexturedPlanet>>,
+    mut water: Query<&mut Visibility, With<Water>>,
+) {
+    let visibility = if config.visible && textured.is_empty() {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    for mut water_visibility in &mut water {
+        water_visibility.set_if_neq(visibility);
+    }
+}
+
+fn animate_waves(
+    time: Res<Time>,
+    config: Res<WaterConfig>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    water: Query<&MeshMaterial3d<StandardMaterial>, With<Water>>,
+) {
+    let offset = time.elapsed_secs() * config.wave_speed.unwrap_or(0.);
+    for material in &water {
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.uv_transform = wave_transform(offset);
+        }
+    }
+}